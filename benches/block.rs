@@ -2,11 +2,11 @@ use criterion::{criterion_group, criterion_main, Criterion};
 use lsm_tree::{
     coding::Encode,
     segment::{
-        block::{header::Header as BlockHeader, ItemSize},
+        block::{header::Header as BlockHeader, offset::BlockOffset, ItemSize},
         meta::CompressionType,
-        value_block::{BlockOffset, ValueBlock},
+        value_block::ValueBlock,
     },
-    Checksum, InternalValue,
+    Checksum, ChecksumType, InternalValue,
 };
 use std::io::Write;
 
@@ -31,10 +31,12 @@ use std::io::Write;
                 header: BlockHeader {
                     compression: CompressionType::Lz4,
                     checksum: Checksum::from_raw(0),
+                checksum_type: ChecksumType::Xxh3,
                     data_length: 0,
                     previous_block_offset: 0,
                     uncompressed_length: 0,
                 },
+                value_alignment: 1,
             };
 
             b.iter(|| {
@@ -72,10 +74,12 @@ fn value_block_find(c: &mut Criterion) {
             header: BlockHeader {
                 compression: CompressionType::Lz4,
                 checksum: Checksum::from_raw(0),
+                checksum_type: ChecksumType::Xxh3,
                 data_length: 0,
                 previous_block_offset: BlockOffset(0),
                 uncompressed_length: 0,
             },
+            value_alignment: 1,
         };
 
         group.bench_function(format!("{item_count} items (linear)"), |b| {
@@ -134,8 +138,13 @@ fn encode_block(c: &mut Criterion) {
             group.bench_function(format!("{block_size} KiB [{comp_type}]"), |b| {
                 b.iter(|| {
                     // Serialize block
-                    let (mut header, data) =
-                        ValueBlock::to_bytes_compressed(&items, BlockOffset(0), comp_type).unwrap();
+                    let (mut header, data) = ValueBlock::to_bytes_compressed(
+                        &items,
+                        BlockOffset(0),
+                        comp_type,
+                        ChecksumType::Xxh3,
+                    )
+                    .unwrap();
                 });
             });
         }
@@ -175,8 +184,13 @@ fn load_value_block_from_disk(c: &mut Criterion) {
             }
 
             // Serialize block
-            let (mut header, data) =
-                ValueBlock::to_bytes_compressed(&items, BlockOffset(0), comp_type).unwrap();
+            let (mut header, data) = ValueBlock::to_bytes_compressed(
+                &items,
+                BlockOffset(0),
+                comp_type,
+                ChecksumType::Xxh3,
+            )
+            .unwrap();
 
             let mut file = tempfile::tempfile().unwrap();
             header.encode_into(&mut file).unwrap();
@@ -185,6 +199,7 @@ fn load_value_block_from_disk(c: &mut Criterion) {
             let expected_block = ValueBlock {
                 items: items.clone().into_boxed_slice(),
                 header,
+                value_alignment: 1,
             };
 
             group.bench_function(format!("{block_size} KiB [{comp_type}]"), |b| {
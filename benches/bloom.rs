@@ -15,6 +15,41 @@ fn filter_construction(c: &mut Criterion) {
     });
 }
 
+fn filter_construction_batched(c: &mut Criterion) {
+    let hashes = (0..1_000_000u128)
+        .map(|x| BloomFilter::get_hash(&x.to_be_bytes()))
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("bloom filter construction (1M keys)");
+    group.sample_size(10);
+
+    group.bench_function("per key", |b| {
+        b.iter_batched(
+            || BloomFilter::with_fp_rate(hashes.len(), 0.01),
+            |mut filter| {
+                for hash in &hashes {
+                    filter.set_with_hash(*hash);
+                }
+                filter
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    group.bench_function("batched", |b| {
+        b.iter_batched(
+            || BloomFilter::with_fp_rate(hashes.len(), 0.01),
+            |mut filter| {
+                filter.extend_with_hashes(hashes.iter().copied());
+                filter
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    group.finish();
+}
+
 fn filter_contains(c: &mut Criterion) {
     let keys = (0..100_000u128)
         .map(|x| x.to_be_bytes().to_vec())
@@ -51,5 +86,10 @@ fn filter_contains(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, filter_construction, filter_contains,);
+criterion_group!(
+    benches,
+    filter_construction,
+    filter_construction_batched,
+    filter_contains,
+);
 criterion_main!(benches);
@@ -1,34 +1,38 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use lsm_tree::{InternalValue, Memtable};
+use lsm_tree::{InternalValue, Memtable, MemtableKind};
+
+const KINDS: [MemtableKind; 2] = [MemtableKind::SkipList, MemtableKind::BTree];
 use nanoid::nanoid;
 
 fn memtable_get_hit(c: &mut Criterion) {
-    let memtable = Memtable::default();
-
-    memtable.insert(InternalValue::from_components(
-        "abc_w5wa35aw35naw",
-        vec![1, 2, 3],
-        0,
-        lsm_tree::ValueType::Value,
-    ));
+    for kind in KINDS {
+        let memtable = Memtable::new(kind);
 
-    for _ in 0..1_000_000 {
         memtable.insert(InternalValue::from_components(
-            format!("abc_{}", nanoid!()).as_bytes(),
-            vec![],
+            "abc_w5wa35aw35naw",
+            vec![1, 2, 3],
             0,
             lsm_tree::ValueType::Value,
         ));
-    }
 
-    c.bench_function("memtable get", |b| {
-        b.iter(|| {
-            assert_eq!(
-                [1, 2, 3],
-                &*memtable.get(b"abc_w5wa35aw35naw", None).unwrap().value,
-            )
+        for _ in 0..1_000_000 {
+            memtable.insert(InternalValue::from_components(
+                format!("abc_{}", nanoid!()).as_bytes(),
+                vec![],
+                0,
+                lsm_tree::ValueType::Value,
+            ));
+        }
+
+        c.bench_function(&format!("memtable get ({kind})"), |b| {
+            b.iter(|| {
+                assert_eq!(
+                    [1, 2, 3],
+                    &*memtable.get(b"abc_w5wa35aw35naw", None).unwrap().value,
+                )
+            });
         });
-    });
+    }
 }
 
 fn memtable_get_snapshot(c: &mut Criterion) {
@@ -67,20 +71,41 @@ fn memtable_get_snapshot(c: &mut Criterion) {
 }
 
 fn memtable_get_miss(c: &mut Criterion) {
-    let memtable = Memtable::default();
+    for kind in KINDS {
+        let memtable = Memtable::new(kind);
 
-    for _ in 0..1_000_000 {
-        memtable.insert(InternalValue::from_components(
-            format!("abc_{}", nanoid!()).as_bytes(),
-            vec![],
-            0,
-            lsm_tree::ValueType::Value,
-        ));
+        for _ in 0..1_000_000 {
+            memtable.insert(InternalValue::from_components(
+                format!("abc_{}", nanoid!()).as_bytes(),
+                vec![],
+                0,
+                lsm_tree::ValueType::Value,
+            ));
+        }
+
+        c.bench_function(&format!("memtable get miss ({kind})"), |b| {
+            b.iter(|| assert!(memtable.get(b"abc_564321", None).is_none()));
+        });
     }
+}
 
-    c.bench_function("memtable get miss", |b| {
-        b.iter(|| assert!(memtable.get(b"abc_564321", None).is_none()));
-    });
+fn memtable_insert(c: &mut Criterion) {
+    for kind in KINDS {
+        c.bench_function(&format!("memtable insert ({kind})"), |b| {
+            let memtable = Memtable::new(kind);
+            let mut seqno = 0;
+
+            b.iter(|| {
+                memtable.insert(InternalValue::from_components(
+                    format!("abc_{}", nanoid!()).as_bytes(),
+                    vec![],
+                    seqno,
+                    lsm_tree::ValueType::Value,
+                ));
+                seqno += 1;
+            });
+        });
+    }
 }
 
 fn memtable_highest_seqno(c: &mut Criterion) {
@@ -107,6 +132,7 @@ criterion_group!(
     memtable_get_hit,
     memtable_get_snapshot,
     memtable_get_miss,
+    memtable_insert,
     memtable_highest_seqno
 );
 criterion_main!(benches);
@@ -19,6 +19,7 @@ fn tli_find_item(c: &mut Criterion) {
                 items.push(KeyedBlockHandle {
                     end_key: x.to_be_bytes().into(),
                     offset: BlockOffset(x),
+                    start_key: None,
                 });
             }
 
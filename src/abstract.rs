@@ -22,15 +22,18 @@ pub trait AbstractTree {
     ///
     /// Can only be called on a new fresh, empty tree.
     ///
+    /// The items bypass the memtable and are written directly into segments
+    /// (of up to 128 MiB), which are then registered in the last level.
+    ///
     /// # Errors
     ///
-    /// Will return `Err` if an IO error occurs.
+    /// Will return `Err` if an IO error occurs, or [`crate::Error::UnsortedIngest`]
+    /// if the keys are not strictly ascending (which includes duplicate keys).
+    /// In that case, the tree stays empty.
     ///
     /// # Panics
     ///
     /// Panics if the tree is **not** initially empty.
-    ///
-    /// Will panic if the input iterator is not sorted in ascending order.
     #[doc(hidden)]
     fn ingest(&self, iter: impl Iterator<Item = (UserKey, UserValue)>) -> crate::Result<()>;
 
@@ -41,6 +44,47 @@ pub trait AbstractTree {
     /// Will return `Err` if an IO error occurs.
     fn major_compact(&self, target_size: u64, seqno_threshold: SeqNo) -> crate::Result<()>;
 
+    /// Compacts all segments that overlap the key range into the last level,
+    /// blocking the caller until it's done.
+    ///
+    /// This can be used to reclaim space right after deleting a key range,
+    /// because tombstones (and the data they cover) are evicted in the last level.
+    /// Segments that overlap the compacted segments are compacted as well, so the
+    /// compaction may cover more keys than requested. Items that are still in
+    /// memtables are not affected, so the memtables may need to be flushed first.
+    ///
+    /// Like other compactions, versions that an open [`Snapshot`] can read are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    /// tree.remove("a", 1);
+    /// tree.flush_active_memtable(0)?;
+    /// assert_eq!(2, tree.segment_count());
+    ///
+    /// tree.compact_range("a"..="z", u64::MAX, 2)?;
+    /// assert_eq!(0, tree.segment_count());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn compact_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        target_size: u64,
+        seqno_threshold: SeqNo,
+    ) -> crate::Result<()>;
+
     /// Gets the memory usage of all bloom filters in the tree.
     fn bloom_filter_size(&self) -> usize;
 
@@ -64,6 +108,11 @@ pub trait AbstractTree {
 
     /// Atomically registers flushed disk segments into the tree, removing their associated sealed memtables.
     ///
+    /// Sealed memtables are always persisted in the order they were sealed (oldest first),
+    /// so the disk segments always contain a consistent seqno prefix of the data.
+    /// If a memtable is flushed while an older sealed memtable has not been registered yet,
+    /// its segment is held back (and the memtable stays readable) until the older memtable is registered.
+    ///
     /// # Errors
     ///
     /// Will return `Err` if an IO error occurs.
@@ -124,14 +173,90 @@ pub trait AbstractTree {
     /// Seals the active memtable, and returns a reference to it.
     fn rotate_memtable(&self) -> Option<(MemtableId, Arc<Memtable>)>;
 
+    /// Closes the tree.
+    ///
+    /// Running compactions are interrupted and waited for, then all memtables
+    /// are flushed to disk. Afterwards, fallible writes ([`AbstractTree::try_insert`],
+    /// [`AbstractTree::try_remove`]) are rejected with [`crate::Error::TreeClosing`],
+    /// unless [`crate::Config::closing_write_policy`] allows them.
+    ///
+    /// Infallible writes ([`AbstractTree::insert`], [`AbstractTree::remove`]) cannot
+    /// report an error, so they are still accepted into the active memtable,
+    /// and have to be flushed by the next flush (e.g. by calling `close` again).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Error};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.try_insert("a", "abc", 0)?;
+    ///
+    /// tree.close()?;
+    /// assert_eq!(1, tree.segment_count());
+    ///
+    /// assert!(matches!(tree.try_insert("b", "abc", 1), Err(Error::TreeClosing)));
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn close(&self) -> crate::Result<()>;
+
+    /// Returns `true` if the tree is closing (or closed).
+    fn is_closing(&self) -> bool;
+
     /// Returns the amount of disk segments currently in the tree.
     fn segment_count(&self) -> usize;
 
+    /// Estimates the amount of bytes that are stored in the given key range, without scanning it.
+    ///
+    /// The estimate is based on the block indexes of the segments that overlap the range,
+    /// so it is block-granular, and refers to the (possibly compressed) on-disk size.
+    /// Memtables are not counted, and neither are blob files of a key-value separated tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// for x in 0..1_000u64 {
+    ///     tree.insert(x.to_be_bytes(), "abc", x);
+    /// }
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let all = tree.approximate_size_in_range::<&[u8], _>(..)?;
+    /// let half = tree.approximate_size_in_range(..500u64.to_be_bytes())?;
+    /// assert!(half > all / 4 && half < all);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn approximate_size_in_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> crate::Result<u64>;
+
     /// Returns the amount of segments in levels[idx].
     ///
     /// Returns `None` if the level does not exist (if idx >= 7).
     fn level_segment_count(&self, idx: usize) -> Option<usize>;
 
+    /// Returns the amount of segments of every level, starting with L0.
+    ///
+    /// All levels are counted at the same time, so the counts are consistent
+    /// with each other, unlike calling [`AbstractTree::level_segment_count`] per level.
+    fn level_segment_counts(&self) -> Vec<usize>;
+
     /// Returns the amount of disjoint runs in L0.
     ///
     /// Can be used to determine whether to write stall.
@@ -143,9 +268,26 @@ pub trait AbstractTree {
     }
 
     /// Approximates the amount of items in the tree.
+    ///
+    /// This operation has O(1) complexity, but may over-count, because
+    /// overwritten and deleted items are counted as well.
+    ///
+    /// The estimate is the sum of the item counts stored in the segments' metadata,
+    /// plus the sizes of the active and sealed memtables. Versions of the same key are not
+    /// deduplicated (across memtables, segments or levels), so this is an upper bound of
+    /// the exact count returned by [`AbstractTree::len`], which scans the whole tree.
+    /// Both converge once old versions and tombstones are compacted away.
+    #[doc(alias = "key_count_estimate")]
     fn approximate_len(&self) -> usize;
 
     /// Returns the disk space usage.
+    ///
+    /// This is the sum of the sizes of all disk segments (and blob files,
+    /// for key-value separated trees), as stored in their already loaded metadata,
+    /// so no file system calls are made.
+    ///
+    /// Only the data blocks of segments are counted, so their block indexes,
+    /// filters and trailers, as well as manifests, add some more disk space.
     fn disk_space(&self) -> u64;
 
     /// Returns the highest sequence number of the active memtable.
@@ -163,6 +305,13 @@ pub trait AbstractTree {
     /// Never, under any circumstances, use .`len()` == 0 to check
     /// if the tree is empty, use [`Tree::is_empty`] instead.
     ///
+    /// The count is exact for the point-in-time view of the tree
+    /// at the moment the scan is started. Locks are only held while
+    /// setting up the scan, so flushes and compactions are not blocked
+    /// while counting.
+    ///
+    /// For a cheap (but inexact) count, use [`AbstractTree::approximate_len`].
+    ///
     /// # Examples
     ///
     /// ```
@@ -291,6 +440,11 @@ pub trait AbstractTree {
     ///
     /// Avoid using this function, or limit it as otherwise it may scan a lot of items.
     ///
+    /// Like all iterators of the tree, it holds onto the segments that existed when it
+    /// was created. Compactions that finish in the meantime do not change what the iterator
+    /// returns: superseded segments are only deleted from disk once the last iterator
+    /// reading them is dropped.
+    ///
     /// # Examples
     ///
     /// ```
@@ -394,6 +548,15 @@ pub trait AbstractTree {
     ///
     /// Avoid using an empty prefix as it may scan a lot of items (unless limited).
     ///
+    /// Keys are always sorted by their bytes (lexicographically), so all keys
+    /// with the given prefix form a contiguous range, which ends right before
+    /// the prefix's successor (e.g. `abd` for the prefix `abc`).
+    /// A prefix that consists of only `0xFF` bytes has no successor,
+    /// so its range is unbounded at the end.
+    ///
+    /// The iterator is double-ended: use `.rev()` to get the keys
+    /// in descending order, e.g. to get the latest records first.
+    ///
     /// # Examples
     ///
     /// ```
@@ -406,6 +569,9 @@ pub trait AbstractTree {
     /// tree.insert("ab", "abc", 1);
     /// tree.insert("abc", "abc", 2);
     /// assert_eq!(2, tree.prefix("ab", None, None).count());
+    ///
+    /// let (key, _) = tree.prefix("ab", None, None).rev().next().unwrap()?;
+    /// assert_eq!(&*key, b"abc");
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
@@ -579,6 +745,76 @@ pub trait AbstractTree {
     /// Will return `Err` if an IO error occurs.
     fn remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> (u32, u32);
 
+    /// Removes all items in the key range `[start, end)` from the tree.
+    ///
+    /// Returns the added item's size and new size of the memtable.
+    ///
+    /// Unlike [`AbstractTree::delete_prefix`], this does not read the covered keys:
+    /// a single range tombstone is written, which hides every version of a key
+    /// in the range that is older than `seqno`. The covered items are physically
+    /// removed by compactions. If `start` is not lower than `end`, the range is empty,
+    /// so nothing is written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// # use lsm_tree::{AbstractTree, Config, Tree};
+    /// #
+    /// # let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("b", "abc", 1);
+    /// tree.insert("c", "abc", 2);
+    ///
+    /// tree.drop_range("a", "c", 3);
+    ///
+    /// assert!(tree.get("a", None)?.is_none());
+    /// assert!(tree.get("b", None)?.is_none());
+    /// assert!(tree.get("c", None)?.is_some());
+    ///
+    /// // Newer writes are not affected
+    /// tree.insert("b", "def", 4);
+    /// assert_eq!(2, tree.len(None, None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn drop_range<K: Into<UserKey>>(&self, start: K, end: K, seqno: SeqNo) -> (u32, u32);
+
+    /// Removes all items with the given prefix from the tree.
+    ///
+    /// Every version of a key that starts with the prefix and is older than `seqno`
+    /// is deleted. Like [`AbstractTree::drop_range`], this writes a single range tombstone,
+    /// so the covered keys are not read.
+    ///
+    /// Prefixes without an upper bound (the empty prefix, which covers the entire tree,
+    /// and prefixes that consist of `0xFF` bytes only) are deleted by a range tombstone
+    /// that is unbounded at the end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// # use lsm_tree::{AbstractTree, Config, Tree};
+    /// #
+    /// # let tree = Config::new(folder).open()?;
+    /// tree.insert("tenant1#a", "abc", 0);
+    /// tree.insert("tenant1#b", "abc", 1);
+    /// tree.insert("tenant2#a", "abc", 2);
+    ///
+    /// tree.delete_prefix("tenant1#", 3)?;
+    ///
+    /// assert_eq!(0, tree.prefix("tenant1#", None, None).count());
+    /// assert_eq!(1, tree.len(None, None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err(TreeClosing)` if the tree is closing and rejects writes,
+    /// see [`crate::Config::closing_write_policy`].
+    fn delete_prefix<K: AsRef<[u8]>>(&self, prefix: K, seqno: SeqNo) -> crate::Result<()>;
+
     /// Removes an item from the tree.
     ///
     /// The tombstone marker of this delete operation will vanish when it
@@ -612,4 +848,31 @@ pub trait AbstractTree {
     ///
     /// Will return `Err` if an IO error occurs.
     fn remove_weak<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> (u32, u32);
+
+    /// Inserts a key-value pair into the tree, unless the tree is closing.
+    ///
+    /// Returns the added item's size and new size of the memtable.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err(TreeClosing)` if [`AbstractTree::close`] has been called,
+    /// or `Err(DeleteInProgress)` if `seqno` is lower than the seqno of a running
+    /// [`crate::Tree::delete_sorted`].
+    fn try_insert<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        value: V,
+        seqno: SeqNo,
+    ) -> crate::Result<(u32, u32)>;
+
+    /// Removes an item from the tree, unless the tree is closing.
+    ///
+    /// Returns the added item's size and new size of the memtable.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err(TreeClosing)` if [`AbstractTree::close`] has been called,
+    /// or `Err(DeleteInProgress)` if `seqno` is lower than the seqno of a running
+    /// [`crate::Tree::delete_sorted`].
+    fn try_remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> crate::Result<(u32, u32)>;
 }
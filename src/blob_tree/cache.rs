@@ -1,25 +1,29 @@
-use crate::cache::Cache;
+use crate::{cache::Cache, tree::inner::TreeId};
 use std::sync::Arc;
 use value_log::BlobCache;
 
+/// Blob cache of a blob tree
+///
+/// Every value log has its own blob cache, so blobs are keyed by the
+/// index tree's ID, which allows evicting them together with the index blocks.
 #[derive(Clone)]
-pub struct MyBlobCache(pub(crate) Arc<Cache>);
+pub struct MyBlobCache(pub(crate) Arc<Cache>, pub(crate) TreeId);
 
 impl BlobCache for MyBlobCache {
     fn get(
         &self,
-        vlog_id: value_log::ValueLogId,
+        _: value_log::ValueLogId,
         vhandle: &value_log::ValueHandle,
     ) -> Option<value_log::UserValue> {
-        self.0.get_blob(vlog_id, vhandle)
+        self.0.get_blob(self.1, vhandle)
     }
 
     fn insert(
         &self,
-        vlog_id: value_log::ValueLogId,
+        _: value_log::ValueLogId,
         vhandle: &value_log::ValueHandle,
         value: value_log::UserValue,
     ) {
-        self.0.insert_blob(vlog_id, vhandle, value);
+        self.0.insert_blob(self.1, vhandle, value);
     }
 }
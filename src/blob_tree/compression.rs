@@ -24,6 +24,9 @@ impl Compressor for MyCompressor {
 
             #[cfg(feature = "miniz")]
             CompressionType::Miniz(lvl) => miniz_oxide::deflate::compress_to_vec(bytes, lvl),
+
+            #[cfg(feature = "zstd")]
+            CompressionType::Zstd(lvl) => zstd::bulk::compress(bytes, lvl)?,
         })
     }
 
@@ -39,6 +42,11 @@ impl Compressor for MyCompressor {
             #[cfg(feature = "miniz")]
             CompressionType::Miniz(_) => miniz_oxide::inflate::decompress_to_vec(bytes)
                 .map_err(|_| value_log::Error::Decompress),
+
+            #[cfg(feature = "zstd")]
+            CompressionType::Zstd(_) => {
+                zstd::stream::decode_all(bytes).map_err(|_| value_log::Error::Decompress)
+            }
         }
     }
 }
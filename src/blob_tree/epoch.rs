@@ -0,0 +1,136 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, Weak};
+
+/// ID of a blob file
+type BlobFileId = u64;
+
+/// A read epoch
+///
+/// Every epoch keeps its successor alive, so an epoch can only be
+/// freed once it, and all epochs before it, are not pinned anymore.
+#[derive(Default)]
+pub struct Epoch {
+    next: OnceLock<Arc<Self>>,
+}
+
+/// Keeps blob files alive while readers may still be resolving value handles into them
+///
+/// Value log GC relocates blobs and then marks their old blob files as stale.
+/// A reader that looked up a value handle before the relocation may still
+/// access the old blob file, so stale blob files are only deleted once every
+/// reader that started before they became stale has finished.
+#[derive(Default)]
+pub struct ReadEpochs {
+    /// Epoch that new readers pin
+    current: Mutex<Arc<Epoch>>,
+
+    /// Stale blob files that have not been deleted yet, with
+    /// the epoch that needs to expire before they can be deleted
+    stale: Mutex<HashMap<BlobFileId, Weak<Epoch>>>,
+}
+
+impl ReadEpochs {
+    fn lock_current(&self) -> MutexGuard<'_, Arc<Epoch>> {
+        self.current.lock().expect("lock is poisoned")
+    }
+
+    fn lock_stale(&self) -> MutexGuard<'_, HashMap<BlobFileId, Weak<Epoch>>> {
+        self.stale.lock().expect("lock is poisoned")
+    }
+
+    /// Pins the current epoch.
+    ///
+    /// Blob files that become stale while the returned pin is alive are not deleted.
+    pub fn pin(&self) -> Arc<Epoch> {
+        self.lock_current().clone()
+    }
+
+    /// Starts a new epoch, returning the previous one.
+    ///
+    /// The previous epoch expires once no reader has pinned it (or an earlier epoch) anymore.
+    fn advance(&self) -> Weak<Epoch> {
+        let mut current = self.lock_current();
+
+        let next = Arc::new(Epoch::default());
+
+        // NOTE: Only the current epoch is ever advanced, so `next` is not set yet
+        let _ = current.next.set(next.clone());
+
+        let retired = std::mem::replace(&mut *current, next);
+        drop(current);
+
+        Arc::downgrade(&retired)
+    }
+
+    /// Registers the given stale blob files, and returns the IDs of
+    /// all stale blob files that no reader can access anymore.
+    pub fn collect(&self, stale_ids: &[BlobFileId]) -> Vec<BlobFileId> {
+        let mut stale = self.lock_stale();
+
+        // NOTE: Forget about blob files that are gone already
+        stale.retain(|id, _| stale_ids.contains(id));
+
+        if stale_ids.iter().any(|id| !stale.contains_key(id)) {
+            let expiry = self.advance();
+
+            for id in stale_ids {
+                stale.entry(*id).or_insert_with(|| expiry.clone());
+            }
+        }
+
+        stale
+            .iter()
+            .filter(|(_, expiry)| expiry.strong_count() == 0)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Forgets about blob files that have been deleted.
+    pub fn remove(&self, ids: &[BlobFileId]) {
+        let mut stale = self.lock_stale();
+
+        for id in ids {
+            stale.remove(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn read_epochs_no_readers() {
+        let epochs = ReadEpochs::default();
+        assert_eq!(vec![1], epochs.collect(&[1]));
+    }
+
+    #[test]
+    fn read_epochs_pinned() {
+        let epochs = ReadEpochs::default();
+
+        let old_reader = epochs.pin();
+        assert!(epochs.collect(&[1]).is_empty());
+
+        // NOTE: Readers starting after the file became stale do not hold it back
+        let new_reader = epochs.pin();
+
+        let newer_reader = epochs.pin();
+        assert!(epochs.collect(&[1, 2]).is_empty());
+
+        drop(old_reader);
+        assert_eq!(vec![1], epochs.collect(&[1, 2]));
+
+        epochs.remove(&[1]);
+        drop(new_reader);
+        assert!(epochs.collect(&[2]).is_empty());
+
+        drop(newer_reader);
+        assert_eq!(vec![2], epochs.collect(&[2]));
+    }
+}
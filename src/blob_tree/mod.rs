@@ -4,6 +4,7 @@
 
 mod cache;
 mod compression;
+mod epoch;
 mod gc;
 pub mod index;
 pub mod value;
@@ -11,6 +12,7 @@ pub mod value;
 use crate::{
     coding::{Decode, Encode},
     compaction::stream::CompactionStream,
+    export::ExportFormat,
     file::BLOBS_FOLDER,
     r#abstract::{AbstractTree, RangeItem},
     tree::inner::MemtableId,
@@ -19,11 +21,13 @@ use crate::{
 };
 use cache::MyBlobCache;
 use compression::MyCompressor;
+use epoch::ReadEpochs;
 use gc::{reader::GcReader, writer::GcWriter};
 use index::IndexTree;
 use std::{
     io::Cursor,
     ops::{RangeBounds, RangeFull},
+    path::Path,
     sync::{atomic::AtomicUsize, Arc},
 };
 use value::MaybeInlineValue;
@@ -54,6 +58,27 @@ fn resolve_value_handle(vlog: &ValueLog<MyBlobCache, MyCompressor>, item: RangeI
     }
 }
 
+/// Adds dead blobs (count, bytes per blob file) to the stale statistics of their blob files
+///
+/// The next [`BlobTree::gc_scan_stats`] recomputes the exact statistics,
+/// so the stale counts are capped to the blob file size.
+fn mark_blobs_stale(
+    vlog: &ValueLog<MyBlobCache, MyCompressor>,
+    dead_blobs: &crate::HashMap<SegmentId, (u64, u64)>,
+) {
+    for (blob_file_id, (items, bytes)) in dead_blobs {
+        // NOTE: The blob file may have been rewritten by GC in the meantime
+        if let Some(blob_file) = vlog.manifest.get_segment(*blob_file_id) {
+            let stats = &blob_file.gc_stats;
+
+            stats.set_stale_items((stats.stale_items() + items).min(blob_file.meta.item_count));
+            stats.set_stale_bytes(
+                (stats.stale_bytes() + bytes).min(blob_file.meta.total_uncompressed_bytes),
+            );
+        }
+    }
+}
+
 /// A key-value-separated log-structured merge tree
 ///
 /// This tree is a composite structure, consisting of an
@@ -61,6 +86,12 @@ fn resolve_value_handle(vlog: &ValueLog<MyBlobCache, MyCompressor>, item: RangeI
 /// to reduce write amplification.
 ///
 /// See <https://docs.rs/value-log> for more information.
+///
+/// Value log GC may relocate blobs while they are being read. Every read pins
+/// the blob files it may access (iterators for as long as they are alive), so
+/// a relocated blob can always be read from its old blob file until the read is done.
+/// Note that reads at a sequence number below the GC's sequence number should
+/// use a snapshot; the GC watermark has to be respected then, see [`BlobTree::gc_scan_stats`].
 #[derive(Clone)]
 pub struct BlobTree {
     /// Index tree that holds value handles or small inline values
@@ -74,6 +105,9 @@ pub struct BlobTree {
     // TODO: maybe replace this with a nonce system
     #[doc(hidden)]
     pub pending_segments: Arc<AtomicUsize>,
+
+    /// Defers the deletion of stale blob files until no reader can access them anymore
+    epochs: Arc<ReadEpochs>,
 }
 
 impl BlobTree {
@@ -81,17 +115,31 @@ impl BlobTree {
         let path = &config.path;
 
         let vlog_path = path.join(BLOBS_FOLDER);
-        let vlog_cfg =
-            value_log::Config::<MyBlobCache, MyCompressor>::new(MyBlobCache(config.cache.clone()))
-                .segment_size_bytes(config.blob_file_target_size)
-                .compression(MyCompressor(config.blob_compression));
+        let cache = config.cache.clone();
+        let blob_file_target_size = config.blob_file_target_size;
+        let blob_compression = config.blob_compression;
 
         let index: IndexTree = config.open()?.into();
 
+        let vlog_cfg =
+            value_log::Config::<MyBlobCache, MyCompressor>::new(MyBlobCache(cache, index.id))
+                .segment_size_bytes(blob_file_target_size)
+                .compression(MyCompressor(blob_compression));
+
+        let blobs = ValueLog::open(vlog_path, vlog_cfg)?;
+
+        // NOTE: Values that compaction drops because of a range tombstone cannot be read
+        // by anyone anymore, so their blobs are stale
+        let _ = index.on_blobs_dropped.set({
+            let blobs = blobs.clone();
+            Arc::new(move |dropped_blobs| mark_blobs_stale(&blobs, dropped_blobs))
+        });
+
         Ok(Self {
             index,
-            blobs: ValueLog::open(vlog_path, vlog_cfg)?,
+            blobs,
             pending_segments: Arc::new(AtomicUsize::new(0)),
+            epochs: Arc::default(),
         })
     }
 
@@ -203,16 +251,270 @@ impl BlobTree {
         )?;
 
         // NOTE: We still have the memtable lock, can't use gc_drop_stale because recursive locking
-        self.blobs.drop_stale_segments().map_err(Into::into)
+        self.drop_stale_blob_files()
     }
 
     /// Drops all stale blob segment files
+    ///
+    /// Blob files that may still be accessed by running reads are kept
+    /// until a later call, after those reads have finished.
     #[doc(hidden)]
     pub fn gc_drop_stale(&self) -> crate::Result<u64> {
         // IMPORTANT: Write lock memtable to avoid read skew
         let _lock = self.index.lock_active_memtable();
 
-        self.blobs.drop_stale_segments().map_err(Into::into)
+        self.drop_stale_blob_files()
+    }
+
+    /// Returns the value log fragmentation, as of the last [`BlobTree::gc_scan_stats`].
+    ///
+    /// This does not scan the index tree, so blobs that became stale
+    /// since the last scan are not accounted for yet.
+    #[must_use]
+    pub fn gc_stats(&self) -> crate::gc::Report {
+        let segments = self.blobs.manifest.list_segments();
+
+        let mut report = crate::gc::Report {
+            path: self.blobs.path.clone(),
+            segment_count: segments.len(),
+            stale_segment_count: 0,
+            total_bytes: 0,
+            stale_bytes: 0,
+            total_blobs: 0,
+            stale_blobs: 0,
+        };
+
+        for segment in &segments {
+            report.total_bytes += segment.meta.total_uncompressed_bytes;
+            report.total_blobs += segment.meta.item_count;
+            report.stale_bytes += segment.gc_stats.stale_bytes();
+            report.stale_blobs += segment.gc_stats.stale_items();
+
+            if segment.is_stale() {
+                report.stale_segment_count += 1;
+            }
+        }
+
+        report
+    }
+
+    /// Garbage collects the value log.
+    ///
+    /// Scans the index tree for the blobs that are still referenced (see [`BlobTree::gc_scan_stats`]),
+    /// then rewrites every blob file whose ratio of stale blobs exceeds `min_stale_ratio`,
+    /// pointing the index tree to the new blob locations. Fully stale blob files are dropped.
+    ///
+    /// The index tree is read at `seqno`, which is also used to write the updated pointers,
+    /// so it should be a new, unused seqno. Blobs that may still be read by snapshots at or above
+    /// `gc_watermark` are not considered stale.
+    ///
+    /// Returns the amount of freed bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).open_as_blob_tree()?;
+    ///
+    /// tree.insert("a", "a".repeat(10_000), 0);
+    /// tree.insert("b", "b".repeat(10_000), 1);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// // NOTE: Overwrites the blob of "a"
+    /// tree.insert("a", "abc", 2);
+    ///
+    /// tree.gc(0.25, 3, 3)?;
+    /// assert_eq!(0, tree.gc_stats().stale_bytes);
+    /// assert_eq!(&*tree.get("b", None)?.unwrap(), "b".repeat(10_000).as_bytes());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min_stale_ratio` is negative or not finite.
+    pub fn gc(
+        &self,
+        min_stale_ratio: f32,
+        seqno: SeqNo,
+        gc_watermark: SeqNo,
+    ) -> crate::Result<u64> {
+        let strategy = crate::gc::StaleThresholdStrategy::new(min_stale_ratio);
+
+        self.gc_scan_stats(seqno, gc_watermark)?;
+        self.apply_gc_strategy(&strategy, seqno)
+    }
+
+    /// Drops all stale blob files that no reader can access anymore,
+    /// returning the amount of freed bytes.
+    fn drop_stale_blob_files(&self) -> crate::Result<u64> {
+        // IMPORTANT: Only allow 1 rollover or GC at any given time
+        let _guard = self.blobs.rollover_guard.lock().expect("lock is poisoned");
+
+        let stale_ids = self
+            .blobs
+            .manifest
+            .list_segments()
+            .into_iter()
+            .filter(|x| x.is_stale())
+            .map(|x| x.id)
+            .collect::<Vec<_>>();
+
+        let ids = self.epochs.collect(&stale_ids);
+
+        if ids.is_empty() {
+            log::trace!("No blob files to drop");
+            return Ok(0);
+        }
+
+        if ids.len() < stale_ids.len() {
+            log::debug!(
+                "Keeping {} stale blob files alive for running reads",
+                stale_ids.len() - ids.len(),
+            );
+        }
+
+        let segments = self
+            .blobs
+            .manifest
+            .list_segments()
+            .into_iter()
+            .filter(|x| ids.contains(&x.id))
+            .collect::<Vec<_>>();
+
+        let bytes_freed = segments.iter().map(|x| x.meta.compressed_bytes).sum();
+
+        log::info!("Dropping stale blob files: {ids:?}");
+        self.blobs.manifest.drop_segments(&ids)?;
+
+        for segment in segments {
+            std::fs::remove_file(&segment.path)?;
+        }
+
+        self.epochs.remove(&ids);
+
+        Ok(bytes_freed)
+    }
+
+    /// Returns the size of a value if it exists, without reading from the value log.
+    ///
+    /// Separated values store their size in the index tree, so this
+    /// only touches the index tree, which makes it cheap to decide
+    /// whether to buffer or stream a large value before reading it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).open_as_blob_tree()?;
+    /// tree.insert("a", "a".repeat(10_000), 0);
+    /// tree.insert("b", "small", 1);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// assert_eq!(Some(10_000), tree.value_size("a", None)?);
+    /// assert_eq!(Some(5), tree.value_size("b", None)?);
+    /// assert_eq!(None, tree.value_size("c", None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn value_size<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        seqno: Option<SeqNo>,
+    ) -> crate::Result<Option<u32>> {
+        let vhandle = self.index.get_vhandle(key.as_ref(), seqno)?;
+
+        Ok(vhandle.map(|x| match x {
+            // NOTE: Values are 32-bit max
+            #[allow(clippy::cast_possible_truncation)]
+            MaybeInlineValue::Inline(v) => v.len() as u32,
+
+            // NOTE: We skip reading from the value log
+            // because the indirections already store the value size
+            MaybeInlineValue::Indirect { size, .. } => size,
+        }))
+    }
+
+    /// Returns the key at (roughly) rank `n` in sorted order.
+    ///
+    /// See [`Tree::nth_key`](crate::Tree::nth_key) for details.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn nth_key(&self, n: u64) -> crate::Result<Option<UserKey>> {
+        self.index.nth_key(n)
+    }
+
+    /// Exports all live key-value pairs into a single sorted file at `path`.
+    ///
+    /// Blobs are resolved, so the file contains the actual values.
+    ///
+    /// See [`Tree::export_sorted`](crate::Tree::export_sorted) for details.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn export_sorted<P: AsRef<Path>>(
+        &self,
+        path: P,
+        format: ExportFormat,
+    ) -> crate::Result<u64> {
+        crate::export::export_sorted(self.iter(None, None), path.as_ref(), format)
+    }
+
+    /// Runs a major compaction of the index tree, reporting its progress.
+    ///
+    /// See [`Tree::major_compact_with_progress`](crate::Tree::major_compact_with_progress) for details.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn major_compact_with_progress<
+        F: Fn(&crate::compaction::CompactionProgress) + Send + Sync + 'static,
+    >(
+        &self,
+        target_size: u64,
+        seqno_threshold: SeqNo,
+        progress: F,
+    ) -> crate::Result<()> {
+        self.index
+            .major_compact_with_progress(target_size, seqno_threshold, progress)
+    }
+
+    /// Returns the `top_n` most read keys with their estimated read count, most read first.
+    ///
+    /// See [`Tree::hot_keys`](crate::Tree::hot_keys) for details.
+    #[must_use]
+    pub fn hot_keys(&self, top_n: usize) -> Vec<(UserKey, u64)> {
+        self.index.hot_keys(top_n)
+    }
+
+    /// Returns the seqno and type of the newest version of a key.
+    ///
+    /// The value is never read from the value log.
+    /// See [`crate::Tree::latest_seqno`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn latest_seqno<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+    ) -> crate::Result<Option<(SeqNo, crate::ValueType)>> {
+        self.index.latest_seqno(key)
     }
 
     #[doc(hidden)]
@@ -229,6 +531,62 @@ impl BlobTree {
 
         Ok(Some(segment))
     }
+
+    /// Flushes all sealed memtables (oldest first) to segments, and returns the new segments.
+    ///
+    /// See [`crate::Tree::flush_sealed_memtables`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn flush_sealed_memtables(&self, eviction_seqno: SeqNo) -> crate::Result<Vec<Segment>> {
+        let sealed = self
+            .index
+            .sealed_memtables
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut segments = Vec::with_capacity(sealed.len());
+
+        for (segment_id, memtable) in sealed {
+            if let Some(segment) = self.flush_memtable(segment_id, &memtable, eviction_seqno)? {
+                self.register_segments(std::slice::from_ref(&segment))?;
+                segments.push(segment);
+            }
+        }
+
+        Ok(segments)
+    }
+
+    /// Loads the index tree's blocks that overlap with the given range into the cache.
+    ///
+    /// Values stored in blob files are not loaded.
+    /// See [`crate::Tree::warm_cache`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn warm_cache<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> crate::Result<crate::CacheWarmup> {
+        self.index.warm_cache(range)
+    }
+
+    /// Flushes the active memtable, and returns the seqno up to which the tree is durable.
+    ///
+    /// See [`crate::Tree::flush_and_checkpoint`] for the exact guarantee.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn flush_and_checkpoint(&self, eviction_seqno: SeqNo) -> crate::Result<Option<SeqNo>> {
+        self.flush_active_memtable(eviction_seqno)?;
+        Ok(self.get_highest_persisted_seqno())
+    }
 }
 
 impl AbstractTree for BlobTree {
@@ -248,14 +606,13 @@ impl AbstractTree for BlobTree {
 
         let start = Instant::now();
         let mut count = 0;
-        let mut last_key = None;
+        let mut last_key: Option<UserKey> = None;
 
         for (key, value) in iter {
-            if let Some(last_key) = &last_key {
-                assert!(
-                    key > last_key,
-                    "next key in bulk ingest was not greater than last key",
-                );
+            // NOTE: Check before writing the blob, the segment writer checks again
+            if last_key.as_ref().is_some_and(|last_key| key <= *last_key) {
+                segment_writer.discard();
+                return Err(crate::Error::UnsortedIngest);
             }
             last_key = Some(key.clone());
 
@@ -279,8 +636,10 @@ impl AbstractTree for BlobTree {
                 blob_writer.write(&key, value)?;
             } else {
                 // TODO: use Slice::with_size
-                let direct = MaybeInlineValue::Inline(value);
-                let serialized_direct = direct.encode_into_vec();
+                let serialized_direct = MaybeInlineValue::encode_inline(
+                    value,
+                    self.index.config.inline_value_compression,
+                );
                 segment_writer.write(key, serialized_direct.into())?;
             }
 
@@ -299,6 +658,23 @@ impl AbstractTree for BlobTree {
         self.index.major_compact(target_size, seqno_threshold)
     }
 
+    fn approximate_size_in_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> crate::Result<u64> {
+        self.index.approximate_size_in_range(range)
+    }
+
+    fn compact_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        target_size: u64,
+        seqno_threshold: SeqNo,
+    ) -> crate::Result<()> {
+        self.index
+            .compact_range(range, target_size, seqno_threshold)
+    }
+
     fn clear_active_memtable(&self) {
         self.index.clear_active_memtable();
     }
@@ -314,21 +690,34 @@ impl AbstractTree for BlobTree {
     // NOTE: We skip reading from the value log
     // because the vHandles already store the value size
     fn size_of<K: AsRef<[u8]>>(&self, key: K, seqno: Option<SeqNo>) -> crate::Result<Option<u32>> {
-        let vhandle = self.index.get_vhandle(key.as_ref(), seqno)?;
-
-        Ok(vhandle.map(|x| match x {
-            MaybeInlineValue::Inline(v) => v.len() as u32,
-
-            // NOTE: We skip reading from the value log
-            // because the indirections already store the value size
-            MaybeInlineValue::Indirect { size, .. } => size,
-        }))
+        self.value_size(key, seqno)
     }
 
     fn bloom_filter_size(&self) -> usize {
         self.index.bloom_filter_size()
     }
 
+    fn close(&self) -> crate::Result<()> {
+        log::debug!("Closing blob tree {}", self.index.id);
+
+        // NOTE: Interrupts compactions and rejects following fallible writes,
+        // unless the closing write policy buffers them
+        self.index.stop_signal.send();
+
+        // IMPORTANT: Wait for running compactions to wind down
+        let _lock = self.index.lock_major_compaction();
+
+        // NOTE: Seal the active memtable, so all memtables are flushed oldest first
+        self.rotate_memtable();
+        self.flush_sealed_memtables(0)?;
+
+        Ok(())
+    }
+
+    fn is_closing(&self) -> bool {
+        self.index.is_closing()
+    }
+
     fn sealed_memtable_count(&self) -> usize {
         self.index.sealed_memtable_count()
     }
@@ -378,22 +767,44 @@ impl AbstractTree for BlobTree {
             segment_id,
             data_block_size: self.index.config.data_block_size,
             index_block_size: self.index.config.index_block_size,
+            target_item_count: None,
             folder: lsm_segment_folder,
         })?
-        .use_compression(self.index.config.compression);
+        .use_compression(self.index.config.compression_for_level(0))
+        .use_checksum_type(self.index.config.checksum_type)
+        .use_index_start_keys(self.index.config.index_start_keys)
+        .use_value_alignment(self.index.config.value_alignment)
+        .use_prefix_extractor(self.index.config.bloom_prefix_extractor.clone())
+        .use_bloom_hasher(self.index.config.bloom_hasher.clone());
 
-        segment_writer = segment_writer.use_bloom_policy(
-            crate::segment::writer::BloomConstructionPolicy::FpRate(0.0001),
-        );
+        {
+            use crate::segment::writer::BloomConstructionPolicy;
+
+            // NOTE: Memtables are always flushed into L0
+            if self.index.config.use_bloom_filter(0) {
+                segment_writer =
+                    segment_writer.use_bloom_policy(BloomConstructionPolicy::FpRate(0.0001));
+            } else {
+                segment_writer =
+                    segment_writer.use_bloom_policy(BloomConstructionPolicy::BitsPerKey(0));
+            }
+        }
 
         let mut blob_writer = self.blobs.get_writer()?;
 
         let iter = memtable.iter().map(Ok);
-        let compaction_filter = CompactionStream::new(iter, eviction_seqno);
+        let compaction_filter = CompactionStream::new(iter, eviction_seqno)
+            .with_snapshot_watermark(self.index.open_snapshots.oldest());
 
         for item in compaction_filter {
             let item = item?;
 
+            if item.key.is_range_tombstone() {
+                // NOTE: The value of a range tombstone is its end key, which needs to be kept
+                segment_writer.write(item)?;
+                continue;
+            }
+
             if item.is_tombstone() {
                 // NOTE: Still need to add tombstone to index tree
                 // But no blob to blob writer
@@ -442,8 +853,10 @@ impl AbstractTree for BlobTree {
                 blob_writer.write(&item.key.user_key, value)?;
             } else {
                 // TODO: use Slice::with_size
-                let direct = MaybeInlineValue::Inline(value);
-                let serialized_direct = direct.encode_into_vec();
+                let serialized_direct = MaybeInlineValue::encode_inline(
+                    value,
+                    self.index.config.inline_value_compression,
+                );
                 segment_writer.write(InternalValue::new(item.key, serialized_direct))?;
             }
         }
@@ -537,6 +950,10 @@ impl AbstractTree for BlobTree {
         self.index.level_segment_count(idx)
     }
 
+    fn level_segment_counts(&self) -> Vec<usize> {
+        self.index.level_segment_counts()
+    }
+
     fn approximate_len(&self) -> usize {
         self.index.approximate_len()
     }
@@ -554,7 +971,9 @@ impl AbstractTree for BlobTree {
     }
 
     fn disk_space(&self) -> u64 {
-        self.index.disk_space() + self.blobs.manifest.disk_space_used()
+        self.index
+            .disk_space()
+            .saturating_add(self.blobs.manifest.disk_space_used())
     }
 
     fn get_highest_memtable_seqno(&self) -> Option<SeqNo> {
@@ -577,12 +996,20 @@ impl AbstractTree for BlobTree {
         seqno: Option<SeqNo>,
         index: Option<Arc<Memtable>>,
     ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static> {
+        self.index.record_range_read(&range);
+
         let vlog = self.blobs.clone();
+        let pin = self.epochs.pin();
+
         Box::new(
             self.index
                 .0
                 .create_range(&range, seqno, index)
-                .map(move |item| resolve_value_handle(&vlog, item)),
+                .map(move |item| {
+                    // NOTE: Keeps the blob files alive for as long as the iterator lives
+                    let _ = &pin;
+                    resolve_value_handle(&vlog, item)
+                }),
         )
     }
 
@@ -592,12 +1019,20 @@ impl AbstractTree for BlobTree {
         seqno: Option<SeqNo>,
         index: Option<Arc<Memtable>>,
     ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static> {
+        self.index.record_read(prefix.as_ref());
+
         let vlog = self.blobs.clone();
+        let pin = self.epochs.pin();
+
         Box::new(
             self.index
                 .0
                 .create_prefix(prefix, seqno, index)
-                .map(move |item| resolve_value_handle(&vlog, item)),
+                .map(move |item| {
+                    // NOTE: Keeps the blob files alive for as long as the iterator lives
+                    let _ = &pin;
+                    resolve_value_handle(&vlog, item)
+                }),
         )
     }
 
@@ -627,6 +1062,10 @@ impl AbstractTree for BlobTree {
         use value::MaybeInlineValue::{Indirect, Inline};
 
         let key = key.as_ref();
+        self.index.record_read(key);
+
+        // NOTE: Value log GC may relocate the blob after we have read its value handle
+        let _pin = self.epochs.pin();
 
         let Some(value) = self.index.get_vhandle(key, seqno)? else {
             return Ok(None);
@@ -650,6 +1089,33 @@ impl AbstractTree for BlobTree {
         self.index.remove(key, seqno)
     }
 
+    fn drop_range<K: Into<UserKey>>(&self, start: K, end: K, seqno: SeqNo) -> (u32, u32) {
+        // NOTE: The blobs of the removed keys are marked stale when compaction drops the keys
+        self.index.drop_range(start, end, seqno)
+    }
+
+    fn delete_prefix<K: AsRef<[u8]>>(&self, prefix: K, seqno: SeqNo) -> crate::Result<()> {
+        self.index.delete_prefix(prefix, seqno)
+    }
+
+    fn try_insert<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        value: V,
+        seqno: SeqNo,
+    ) -> crate::Result<(u32, u32)> {
+        // NOTE: See insert
+        let item = MaybeInlineValue::Inline(value.into());
+
+        let value = item.encode_into_vec();
+
+        self.index.try_insert(key, value, seqno)
+    }
+
+    fn try_remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> crate::Result<(u32, u32)> {
+        self.index.try_remove(key, seqno)
+    }
+
     fn remove_weak<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> (u32, u32) {
         self.index.remove_weak(key, seqno)
     }
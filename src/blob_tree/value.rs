@@ -2,10 +2,14 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::coding::{Decode, DecodeError, Encode, EncodeError};
+use super::compression::MyCompressor;
+use crate::{
+    coding::{Decode, DecodeError, Encode, EncodeError},
+    CompressionType,
+};
 use byteorder::{ReadBytesExt, WriteBytesExt};
 use std::io::{Cursor, Read, Write};
-use value_log::{Slice, UserValue, ValueHandle};
+use value_log::{Compressor, Slice, UserValue, ValueHandle};
 use varint_rs::{VarintReader, VarintWriter};
 
 /// A value which may or may not be inlined into an index tree
@@ -16,6 +20,10 @@ use varint_rs::{VarintReader, VarintWriter};
 #[allow(clippy::module_name_repetitions)]
 pub enum MaybeInlineValue {
     /// Inlined value (classic LSM-tree)
+    ///
+    /// The value length is stored as a varint, so small values only take
+    /// a single byte of overhead (plus the tag). Like all values in the index tree,
+    /// inline values are limited to 2^32 bytes.
     Inline(UserValue),
 
     /// The value is a handle (pointer) into the value log
@@ -24,8 +32,43 @@ pub enum MaybeInlineValue {
 
 const TAG_INLINE: u8 = 0;
 const TAG_INDIRECT: u8 = 1;
+const TAG_INLINE_COMPRESSED: u8 = 2;
 
 impl MaybeInlineValue {
+    /// Serializes an inlined value.
+    ///
+    /// The value bytes are compressed, unless that does not make them smaller.
+    /// Compressed values are transparently decompressed when decoding.
+    #[must_use]
+    pub fn encode_inline(value: UserValue, compression: CompressionType) -> Vec<u8> {
+        if compression != CompressionType::None {
+            // NOTE: Compressing into a vector cannot fail
+            if let Ok(compressed) = MyCompressor(compression).compress(&value) {
+                // NOTE: The compression type takes 2 more bytes
+                if compressed.len() + 2 < value.len() {
+                    let mut buf = Vec::with_capacity(compressed.len() + 8);
+
+                    // NOTE: Writing into a vector cannot fail
+                    #[allow(clippy::expect_used)]
+                    {
+                        buf.write_u8(TAG_INLINE_COMPRESSED).expect("should write");
+                        compression.encode_into(&mut buf).expect("should write");
+
+                        // NOTE: Values can be up to 2^32 bytes
+                        #[allow(clippy::cast_possible_truncation)]
+                        buf.write_u32_varint(compressed.len() as u32)
+                            .expect("should write");
+                    }
+                    buf.extend_from_slice(&compressed);
+
+                    return buf;
+                }
+            }
+        }
+
+        Self::Inline(value).encode_into_vec()
+    }
+
     pub fn from_slice(bytes: &Slice) -> Result<Self, DecodeError> {
         let mut cursor = Cursor::new(&**bytes);
 
@@ -43,7 +86,7 @@ impl MaybeInlineValue {
                 let slice = bytes.slice((1 + size_len)..);
                 Ok(Self::Inline(slice))
             }
-            TAG_INDIRECT => {
+            TAG_INDIRECT | TAG_INLINE_COMPRESSED => {
                 let mut reader = &**bytes;
                 Self::decode_from(&mut reader)
             }
@@ -89,7 +132,96 @@ impl Decode for MaybeInlineValue {
                 let size = reader.read_u32_varint()?;
                 Ok(Self::Indirect { vhandle, size })
             }
+            TAG_INLINE_COMPRESSED => {
+                let compression = CompressionType::decode_from(reader)?;
+
+                let len = reader.read_u32_varint()? as usize;
+                let mut compressed = vec![0; len];
+                reader.read_exact(&mut compressed)?;
+
+                let bytes = MyCompressor(compression)
+                    .decompress(&compressed)
+                    .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+                Ok(Self::Inline(bytes.into()))
+            }
             x => Err(DecodeError::InvalidTag(("MaybeInlineValue", x))),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    fn roundtrip(value: &[u8], compression: CompressionType) -> Result<Vec<u8>, DecodeError> {
+        let bytes = MaybeInlineValue::encode_inline(value.into(), compression);
+
+        for decoded in [
+            MaybeInlineValue::decode_from(&mut &bytes[..])?,
+            MaybeInlineValue::from_slice(&bytes.clone().into())?,
+        ] {
+            match decoded {
+                MaybeInlineValue::Inline(decoded) => assert_eq!(&*decoded, value),
+                MaybeInlineValue::Indirect { .. } => panic!("should be inline"),
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    #[test]
+    fn inline_value_uncompressed() -> Result<(), DecodeError> {
+        let bytes = roundtrip(&b"abc".repeat(100), CompressionType::None)?;
+        assert_eq!(Some(&TAG_INLINE), bytes.first());
+        Ok(())
+    }
+
+    #[test]
+    fn inline_value_varint_length() -> Result<(), DecodeError> {
+        // NOTE: Tag + 1 byte length for values under 128 bytes
+        let bytes = roundtrip(&[0; 127], CompressionType::None)?;
+        assert_eq!(1 + 1 + 127, bytes.len());
+
+        let bytes = roundtrip(&[0; 128], CompressionType::None)?;
+        assert_eq!(1 + 2 + 128, bytes.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn inline_value_empty() -> Result<(), DecodeError> {
+        // NOTE: Empty values still carry a tag, so they are never confused with tombstones
+        let bytes = roundtrip(b"", CompressionType::None)?;
+        assert!(!bytes.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "lz4")]
+    fn inline_value_compressed_lz4() -> Result<(), DecodeError> {
+        let value = b"abc".repeat(100);
+        let bytes = roundtrip(&value, CompressionType::Lz4)?;
+        assert_eq!(Some(&TAG_INLINE_COMPRESSED), bytes.first());
+        assert!(bytes.len() < value.len());
+
+        // NOTE: Incompressible values are stored as is
+        let bytes = roundtrip(b"abc", CompressionType::Lz4)?;
+        assert_eq!(Some(&TAG_INLINE), bytes.first());
+
+        roundtrip(b"", CompressionType::Lz4)?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "miniz")]
+    fn inline_value_compressed_miniz() -> Result<(), DecodeError> {
+        let value = b"abc".repeat(100);
+        let bytes = roundtrip(&value, CompressionType::Miniz(6))?;
+        assert_eq!(Some(&TAG_INLINE_COMPRESSED), bytes.first());
+        assert!(bytes.len() < value.len());
+        Ok(())
+    }
+}
@@ -18,7 +18,7 @@ fn enable_bit(byte: u8, idx: usize) -> u8 {
 }
 
 /// Fixed-size bit array
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct BitArray(Box<[u8]>);
 
 impl BitArray {
@@ -41,6 +41,12 @@ impl BitArray {
         &self.0
     }
 
+    /// Returns the amount of bits that are `true`.
+    #[must_use]
+    pub fn count_ones(&self) -> usize {
+        self.0.iter().map(|byte| byte.count_ones() as usize).sum()
+    }
+
     /// Sets the i-th bit to `true`.
     pub fn enable(&mut self, idx: usize) {
         let byte_idx = idx / 8;
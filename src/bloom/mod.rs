@@ -5,16 +5,189 @@
 mod bit_array;
 
 use crate::{
-    coding::{Decode, DecodeError, Encode, EncodeError},
-    file::MAGIC_BYTES,
+    coding::{read_format_header, write_format_header, Decode, DecodeError, Encode, EncodeError},
+    config::BloomHasher,
+    file::{FORMAT_VERSION, MAGIC},
 };
 use bit_array::BitArray;
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
-use std::io::{Read, Write};
+use std::{
+    cell::OnceCell,
+    io::{Read, Write},
+};
 
 /// Two hashes that are used for double hashing
 pub type CompositeHash = (u64, u64);
 
+/// Hash type of filters that use the built-in hash, see [`BloomFilter::get_hash`]
+const HASH_TYPE_XXH3: u8 = 0;
+
+/// Hash type of filters that use a custom [`BloomHasher`]
+const HASH_TYPE_CUSTOM: u8 = 1;
+
+/// Hashes of a key, which are computed on first use
+///
+/// A tree may contain filters that were built using the built-in hash,
+/// and filters that were built using a custom [`BloomHasher`], so a point read
+/// needs to check every filter using the hash it was built with.
+/// Each hash is only computed once, and only if some filter needs it.
+pub struct KeyHash<'a> {
+    key: &'a [u8],
+    hasher: Option<&'a BloomHasher>,
+    builtin: OnceCell<CompositeHash>,
+    custom: OnceCell<CompositeHash>,
+}
+
+impl<'a> KeyHash<'a> {
+    /// Prepares the hashes of a key, using the given custom hasher if set.
+    #[must_use]
+    pub fn new(key: &'a [u8], hasher: Option<&'a BloomHasher>) -> Self {
+        Self {
+            key,
+            hasher,
+            builtin: OnceCell::new(),
+            custom: OnceCell::new(),
+        }
+    }
+
+    /// Returns the hash the given filter was built with, if it can be computed.
+    fn for_filter(&self, filter: &BloomFilter) -> Option<CompositeHash> {
+        if filter.custom_hash {
+            let hasher = self.hasher?;
+            Some(*self.custom.get_or_init(|| hasher(self.key)))
+        } else {
+            Some(*self.builtin.get_or_init(|| BloomFilter::get_hash(self.key)))
+        }
+    }
+}
+
+/// Parameters of a bloom filter
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct BloomParams {
+    /// Bit count
+    pub bits: usize,
+
+    /// Number of hash functions
+    pub hashes: usize,
+
+    /// Expected false positive rate
+    pub fp_rate: f32,
+}
+
+/// Returns the bloom filter parameters for `n` items,
+/// if the filter may use `memory_bytes` bytes.
+///
+/// The amount of hash functions is chosen the same way as the
+/// bloom filter constructors do.
+#[must_use]
+pub fn optimal_params(n: usize, memory_bytes: usize) -> BloomParams {
+    use std::f32::consts::LN_2;
+
+    let bits = memory_bytes * 8;
+
+    if n == 0 {
+        return BloomParams {
+            bits,
+            hashes: 1,
+            fp_rate: 0.0,
+        };
+    }
+
+    let bpk = bits / n;
+
+    // NOTE: Bits per key are small, so the conversions are exact
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    let hashes = (((bpk as f32) * LN_2) as usize).max(1);
+
+    BloomParams {
+        bits,
+        hashes,
+        fp_rate: false_positive_rate(n, bits, hashes),
+    }
+}
+
+/// Returns the amount of bits a bloom filter needs to hold `n` items
+/// while maintaining the false positive rate `fpr`.
+///
+/// This is the size [`BloomFilter::with_fp_rate`] allocates.
+#[must_use]
+pub fn bits_for_fp(n: usize, fpr: f32) -> usize {
+    if n == 0 {
+        return 0;
+    }
+
+    // NOTE: Some sensible minimum, see `with_fp_rate`
+    BloomFilter::calculate_m(n, fpr.max(0.000_001))
+}
+
+/// Estimates the false positive rate of a bloom filter with
+/// `m` bits and `k` hash functions holding `n` items.
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn false_positive_rate(n: usize, m: usize, k: usize) -> f32 {
+    if m == 0 {
+        return 1.0;
+    }
+
+    let n = n as f64;
+    let m = m as f64;
+    let k = k as f64;
+
+    (1.0 - (-k * n / m).exp()).powf(k) as f32
+}
+
+/// Statistics of a loaded bloom filter, see [`crate::Segment::bloom_stats`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct BloomStats {
+    /// Bit count
+    pub bits: usize,
+
+    /// Amount of bits that are set
+    pub set_bits: usize,
+
+    /// Number of hash functions
+    pub hash_count: usize,
+
+    /// Amount of keys the filter holds
+    pub key_count: u64,
+
+    /// Expected false positive rate, given the key count
+    pub estimated_fp_rate: f32,
+}
+
+impl BloomStats {
+    /// Fill ratios above this indicate an over-full filter
+    ///
+    /// A filter with an optimal amount of hash functions has about half of its bits set.
+    pub const MAX_HEALTHY_FILL_RATIO: f32 = 0.6;
+
+    /// Returns the fraction of bits that are set.
+    #[must_use]
+    pub fn fill_ratio(&self) -> f32 {
+        if self.bits == 0 {
+            return 1.0;
+        }
+
+        // NOTE: Bit counts are small enough to not lose meaningful precision
+        #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+        let ratio = (self.set_bits as f64 / self.bits as f64) as f32;
+
+        ratio
+    }
+
+    /// Returns `true` if more bits are set than a well-sized filter would have,
+    /// see [`BloomStats::MAX_HEALTHY_FILL_RATIO`].
+    #[must_use]
+    pub fn is_degraded(&self) -> bool {
+        self.fill_ratio() > Self::MAX_HEALTHY_FILL_RATIO
+    }
+}
+
 /// A standard bloom filter
 ///
 /// Allows buffering the key hashes before actual filter construction
@@ -23,7 +196,7 @@ pub type CompositeHash = (u64, u64);
 ///
 /// The filter uses double hashing instead of `k` hash functions, see:
 /// <https://fjall-rs.github.io/post/bloom-filter-hash-sharing>
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 #[allow(clippy::module_name_repetitions)]
 pub struct BloomFilter {
     /// Raw bytes exposed as bit array
@@ -34,18 +207,24 @@ pub struct BloomFilter {
 
     /// Number of hash functions
     k: usize,
+
+    /// Whether the filter was built using a custom [`BloomHasher`]
+    custom_hash: bool,
 }
 
 impl Encode for BloomFilter {
     fn encode_into<W: Write>(&self, writer: &mut W) -> Result<(), EncodeError> {
         // Write header
-        writer.write_all(&MAGIC_BYTES)?;
+        write_format_header(writer, MAGIC, FORMAT_VERSION)?;
 
         // NOTE: Filter type
         writer.write_u8(0)?;
 
-        // NOTE: Hash type (unused)
-        writer.write_u8(0)?;
+        writer.write_u8(if self.custom_hash {
+            HASH_TYPE_CUSTOM
+        } else {
+            HASH_TYPE_XXH3
+        })?;
 
         writer.write_u64::<BigEndian>(self.m as u64)?;
         writer.write_u64::<BigEndian>(self.k as u64)?;
@@ -58,20 +237,17 @@ impl Encode for BloomFilter {
 impl Decode for BloomFilter {
     fn decode_from<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
         // Check header
-        let mut magic = [0u8; MAGIC_BYTES.len()];
-        reader.read_exact(&mut magic)?;
-
-        if magic != MAGIC_BYTES {
-            return Err(DecodeError::InvalidHeader("BloomFilter"));
-        }
+        read_format_header(reader, MAGIC, FORMAT_VERSION, "BloomFilter")?;
 
         // NOTE: Filter type
         let filter_type = reader.read_u8()?;
         assert_eq!(0, filter_type, "Invalid filter type");
 
-        // NOTE: Hash type (unused)
-        let hash_type = reader.read_u8()?;
-        assert_eq!(0, hash_type, "Invalid bloom hash type");
+        let custom_hash = match reader.read_u8()? {
+            HASH_TYPE_XXH3 => false,
+            HASH_TYPE_CUSTOM => true,
+            x => return Err(DecodeError::InvalidTag(("BloomHashType", x))),
+        };
 
         let m = reader.read_u64::<BigEndian>()? as usize;
         let k = reader.read_u64::<BigEndian>()? as usize;
@@ -79,7 +255,10 @@ impl Decode for BloomFilter {
         let mut bytes = vec![0; m / 8];
         reader.read_exact(&mut bytes)?;
 
-        Ok(Self::from_raw(m, k, bytes.into_boxed_slice()))
+        let mut filter = Self::from_raw(m, k, bytes.into_boxed_slice());
+        filter.custom_hash = custom_hash;
+
+        Ok(filter)
     }
 }
 
@@ -102,9 +281,27 @@ impl BloomFilter {
             inner: BitArray::from_bytes(bytes),
             m,
             k,
+            custom_hash: false,
         }
     }
 
+    /// Marks the filter as being built from the hashes of a custom [`BloomHasher`],
+    /// instead of [`BloomFilter::get_hash`].
+    ///
+    /// The marker is persisted, so reads check the filter using the same hasher.
+    /// Filters with a custom hash can only be checked using [`BloomFilter::contains_hash`].
+    #[must_use]
+    pub fn with_custom_hash(mut self) -> Self {
+        self.custom_hash = true;
+        self
+    }
+
+    /// Returns `true` if the filter was built using a custom [`BloomHasher`].
+    #[must_use]
+    pub fn has_custom_hash(&self) -> bool {
+        self.custom_hash
+    }
+
     /// Constructs a bloom filter that can hold `n` items
     /// while maintaining a certain false positive rate `fpr`.
     #[must_use]
@@ -124,6 +321,7 @@ impl BloomFilter {
             inner: BitArray::with_capacity(m / 8),
             m,
             k,
+            custom_hash: false,
         }
     }
 
@@ -150,6 +348,7 @@ impl BloomFilter {
             inner: BitArray::with_capacity(bytes),
             m: bytes * 8,
             k,
+            custom_hash: false,
         }
     }
 
@@ -166,6 +365,71 @@ impl BloomFilter {
         ((m / 8.0).ceil() * 8.0) as usize
     }
 
+    /// Returns statistics of the filter, which holds `key_count` keys.
+    #[must_use]
+    pub fn stats(&self, key_count: u64) -> BloomStats {
+        // NOTE: Key counts of a single segment fit into memory
+        #[allow(clippy::cast_possible_truncation)]
+        let n = key_count as usize;
+
+        BloomStats {
+            bits: self.m,
+            set_bits: self.inner.count_ones(),
+            hash_count: self.k,
+            key_count,
+            estimated_fp_rate: false_positive_rate(n, self.m, self.k),
+        }
+    }
+
+    /// Returns a filter that contains the keys of both filters.
+    ///
+    /// This is much cheaper than rebuilding a filter from the keys, because
+    /// the bit arrays are just OR'ed together. However, the combined filter
+    /// holds more keys in the same amount of bits, so its false positive rate
+    /// is higher, see [`BloomFilter::estimated_fp_rate`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the filters do not use the same bit count,
+    /// amount of hash functions and hash.
+    pub fn union(&self, other: &Self) -> crate::Result<Self> {
+        if self.m != other.m || self.k != other.k || self.custom_hash != other.custom_hash {
+            return Err(crate::Error::IncompatibleBloomFilter);
+        }
+
+        let bytes = self
+            .inner
+            .bytes()
+            .iter()
+            .zip(other.inner.bytes())
+            .map(|(a, b)| a | b)
+            .collect();
+
+        let mut filter = Self::from_raw(self.m, self.k, bytes);
+        filter.custom_hash = self.custom_hash;
+
+        Ok(filter)
+    }
+
+    /// Estimates the false positive rate from the fraction of set bits.
+    ///
+    /// Unlike the rate the filter was constructed for, this also
+    /// reflects the keys that have been added by [`BloomFilter::union`].
+    #[must_use]
+    pub fn estimated_fp_rate(&self) -> f32 {
+        if self.m == 0 {
+            return 1.0;
+        }
+
+        // NOTE: Bit counts are small enough to not lose meaningful precision
+        #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+        let fill = (self.inner.count_ones() as f64 / self.m as f64) as f32;
+
+        // NOTE: k is tiny
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        fill.powi(self.k as i32)
+    }
+
     /// Returns `true` if the hash may be contained.
     ///
     /// Will never have a false negative.
@@ -187,6 +451,16 @@ impl BloomFilter {
         true
     }
 
+    /// Returns `true` if the key may be contained, using the hash the filter was built with.
+    ///
+    /// If the filter was built using a custom hash, but no custom hasher is given,
+    /// the key may be contained.
+    #[must_use]
+    pub fn contains_key_hash(&self, hash: &KeyHash) -> bool {
+        hash.for_filter(self)
+            .map_or(true, |hash| self.contains_hash(hash))
+    }
+
     /// Returns `true` if the item may be contained.
     ///
     /// Will never have a false negative.
@@ -207,6 +481,51 @@ impl BloomFilter {
         }
     }
 
+    /// Adds many keys to the filter.
+    ///
+    /// Results in the same filter as calling [`BloomFilter::set_with_hash`] for every hash,
+    /// but the hashes are processed in small batches, so the bit index calculations
+    /// of different keys do not depend on each other and can be pipelined by the CPU.
+    /// Also, the modulo is computed using multiplications instead of a division.
+    pub fn extend_with_hashes<I: IntoIterator<Item = CompositeHash>>(&mut self, hashes: I) {
+        const LANES: usize = 8;
+
+        let m = self.m as u64;
+        let c = fast_mod_constant(m);
+        let mut hashes = hashes.into_iter();
+        let mut lanes = [(0, 0); LANES];
+
+        loop {
+            // NOTE: Zip polls the lanes first, so no hash is skipped
+            let mut len = 0;
+            for (lane, hash) in lanes.iter_mut().zip(hashes.by_ref()) {
+                *lane = hash;
+                len += 1;
+            }
+
+            let Some(batch) = lanes.get_mut(..len) else {
+                break;
+            };
+
+            for i in 0..(self.k as u64) {
+                for (h1, h2) in batch.iter_mut() {
+                    let idx = fast_mod(*h1, c, m);
+
+                    // NOTE: idx < m, which is a usize
+                    #[allow(clippy::cast_possible_truncation)]
+                    self.inner.enable(idx as usize);
+
+                    *h1 = h1.wrapping_add(*h2);
+                    *h2 = h2.wrapping_add(i);
+                }
+            }
+
+            if len < LANES {
+                break;
+            }
+        }
+    }
+
     /// Returns `true` if the bit at `idx` is `1`.
     fn has_bit(&self, idx: usize) -> bool {
         self.inner.get(idx)
@@ -227,12 +546,187 @@ impl BloomFilter {
     }
 }
 
+/// Precomputes the constant that is used by [`fast_mod`] to compute `_ % d`.
+fn fast_mod_constant(d: u64) -> u128 {
+    (u128::MAX / u128::from(d)).wrapping_add(1)
+}
+
+/// Computes `a % d` without a division, see:
+/// <https://arxiv.org/abs/1902.01961>
+fn fast_mod(a: u64, c: u128, d: u64) -> u64 {
+    let low = c.wrapping_mul(u128::from(a));
+
+    // NOTE: High 64 bits of the 192-bit product low * d
+    #[allow(clippy::cast_possible_truncation)]
+    let bottom = (u128::from(low as u64) * u128::from(d)) >> 64;
+    let top = (low >> 64) * u128::from(d);
+
+    #[allow(clippy::cast_possible_truncation)]
+    let result = ((bottom + top) >> 64) as u64;
+
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs::File;
     use test_log::test;
 
+    #[test]
+    fn bloom_fast_mod() {
+        let divisors = [
+            1,
+            2,
+            3,
+            7,
+            8,
+            1_000,
+            9_585_064,
+            u64::MAX / 3,
+            u64::MAX - 1,
+            u64::MAX,
+        ];
+
+        for d in divisors {
+            let c = fast_mod_constant(d);
+
+            for x in 0..10_000u64 {
+                let (a, _) = BloomFilter::get_hash(&x.to_be_bytes());
+
+                assert_eq!(a % d, fast_mod(a, c, d));
+                assert_eq!(x % d, fast_mod(x, c, d));
+            }
+
+            assert_eq!(u64::MAX % d, fast_mod(u64::MAX, c, d));
+            assert_eq!((d - 1) % d, fast_mod(d - 1, c, d));
+        }
+    }
+
+    #[test]
+    fn bloom_stats() {
+        let mut filter = BloomFilter::with_fp_rate(1_000, 0.01);
+
+        for x in 0..1_000u64 {
+            filter.set_with_hash(BloomFilter::get_hash(&x.to_be_bytes()));
+        }
+
+        let stats = filter.stats(1_000);
+        assert_eq!(filter.m, stats.bits);
+        assert_eq!(filter.k, stats.hash_count);
+        assert!(stats.estimated_fp_rate > 0.005 && stats.estimated_fp_rate < 0.015);
+        assert!(stats.fill_ratio() > 0.4 && stats.fill_ratio() < 0.6);
+        assert!(!stats.is_degraded());
+
+        // NOTE: Way too many keys for the filter's size
+        for x in 1_000..10_000u64 {
+            filter.set_with_hash(BloomFilter::get_hash(&x.to_be_bytes()));
+        }
+
+        let stats = filter.stats(10_000);
+        assert!(stats.estimated_fp_rate > 0.5);
+        assert!(stats.is_degraded());
+    }
+
+    #[test]
+    fn bloom_union() -> crate::Result<()> {
+        let mut a = BloomFilter::with_fp_rate(100, 0.01);
+        let mut b = BloomFilter::with_fp_rate(100, 0.01);
+
+        for x in 0..100u64 {
+            a.set_with_hash(BloomFilter::get_hash(&x.to_be_bytes()));
+            b.set_with_hash(BloomFilter::get_hash(&(x + 100).to_be_bytes()));
+        }
+
+        let union = a.union(&b)?;
+
+        for x in 0..200u64 {
+            assert!(union.contains(&x.to_be_bytes()));
+        }
+        assert!(union.estimated_fp_rate() > a.estimated_fp_rate());
+        assert!(a.estimated_fp_rate() < 0.02);
+
+        assert!(matches!(
+            a.union(&BloomFilter::with_fp_rate(200, 0.01)),
+            Err(crate::Error::IncompatibleBloomFilter)
+        ));
+        assert!(matches!(
+            a.union(&BloomFilter::with_bpk(100, 8)),
+            Err(crate::Error::IncompatibleBloomFilter)
+        ));
+        assert!(matches!(
+            a.union(&BloomFilter::with_fp_rate(100, 0.01).with_custom_hash()),
+            Err(crate::Error::IncompatibleBloomFilter)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn bloom_custom_hash() -> crate::Result<()> {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let calls = Arc::new(AtomicUsize::default());
+
+        let hasher: BloomHasher = {
+            let calls = calls.clone();
+
+            Arc::new(move |key: &[u8]| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                (key.len() as u64, 7)
+            })
+        };
+
+        let mut filter = BloomFilter::with_fp_rate(10, 0.01).with_custom_hash();
+        filter.set_with_hash(hasher(b"abc"));
+
+        // NOTE: Custom hash marker survives serialization
+        let filter = BloomFilter::decode_from(&mut &*filter.encode_into_vec())?;
+        assert!(filter.has_custom_hash());
+
+        let hash = KeyHash::new(b"xyz", Some(&hasher));
+        assert!(filter.contains_key_hash(&hash));
+        assert!(filter.contains_key_hash(&hash));
+        assert!(!filter.contains_key_hash(&KeyHash::new(b"ab", Some(&hasher))));
+        assert_eq!(3, calls.load(Ordering::Relaxed));
+
+        // NOTE: Without the hasher, the filter cannot rule anything out
+        assert!(filter.contains_key_hash(&KeyHash::new(b"ab", None)));
+
+        let mut builtin = BloomFilter::with_fp_rate(10, 0.01);
+        builtin.set_with_hash(BloomFilter::get_hash(b"abc"));
+        assert!(builtin.contains_key_hash(&KeyHash::new(b"abc", Some(&hasher))));
+        assert_eq!(3, calls.load(Ordering::Relaxed));
+
+        Ok(())
+    }
+
+    #[test]
+    fn bloom_extend_with_hashes() {
+        for n in [1, 7, 8, 9, 100] {
+            let hashes = (0..n as u64)
+                .map(|x| BloomFilter::get_hash(&x.to_be_bytes()))
+                .collect::<Vec<_>>();
+
+            let mut expected = BloomFilter::with_fp_rate(n, 0.01);
+            for hash in &hashes {
+                expected.set_with_hash(*hash);
+            }
+
+            let mut filter = BloomFilter::with_fp_rate(n, 0.01);
+            filter.extend_with_hashes(hashes.iter().copied());
+
+            assert_eq!(expected, filter);
+
+            for hash in hashes {
+                assert!(filter.contains_hash(hash));
+            }
+        }
+    }
+
     #[test]
     fn bloom_serde_round_trip() -> crate::Result<()> {
         let dir = tempfile::tempdir()?;
@@ -277,6 +771,48 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn bloom_bits_for_fp() {
+        for n in [1, 10, 100, 1_000, 100_000] {
+            for fpr in [0.5, 0.1, 0.01, 0.001, 0.0001] {
+                let bits = bits_for_fp(n, fpr);
+                let filter = BloomFilter::with_fp_rate(n, fpr);
+                assert_eq!(filter.len() * 8, bits);
+
+                let params = optimal_params(n, bits / 8);
+                assert_eq!(bits, params.bits);
+                assert_eq!(filter.hash_fn_count(), params.hashes);
+
+                // NOTE: The amount of hash functions is rounded down, so allow some slack
+                assert!(params.fp_rate <= fpr * 1.2, "{n} {fpr}: {params:?}");
+            }
+        }
+
+        assert_eq!(0, bits_for_fp(0, 0.01));
+    }
+
+    #[test]
+    fn bloom_optimal_params() {
+        let params = optimal_params(1_000, 1_250);
+        assert_eq!(10_000, params.bits);
+        assert_eq!(
+            BloomFilter::with_bpk(1_000, 10).hash_fn_count(),
+            params.hashes
+        );
+        assert!((params.fp_rate - 0.0084).abs() < 0.0005, "{params:?}");
+
+        // NOTE: More memory means less false positives
+        let mut prev = 1.0;
+        for bytes in (0..=2_000).step_by(100) {
+            let params = optimal_params(1_000, bytes);
+            assert!(params.fp_rate <= prev);
+            prev = params.fp_rate;
+        }
+
+        assert!((optimal_params(1_000, 0).fp_rate - 1.0).abs() < f32::EPSILON);
+        assert!(optimal_params(0, 0).fp_rate.abs() < f32::EPSILON);
+    }
+
     #[test]
     fn bloom_calculate_m() {
         assert_eq!(9_592, BloomFilter::calculate_m(1_000, 0.01));
@@ -5,10 +5,14 @@
 use crate::segment::block::offset::BlockOffset;
 use crate::segment::id::GlobalSegmentId;
 use crate::segment::{block_index::IndexBlock, value_block::ValueBlock};
+use crate::tree::inner::TreeId;
 use crate::UserValue;
 use quick_cache::Weighter;
 use quick_cache::{sync::Cache as QuickCache, Equivalent};
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
 
 const TAG_BLOCK: u8 = 0;
 const TAG_BLOB: u8 = 1;
@@ -49,6 +53,51 @@ impl Weighter<CacheKey, Item> for BlockWeighter {
     }
 }
 
+/// Blocks that were loaded into the cache by [`Tree::warm_cache`](crate::Tree::warm_cache)
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CacheWarmup {
+    /// Amount of data blocks that have been loaded
+    pub blocks: usize,
+
+    /// Uncompressed size of the loaded data blocks in bytes
+    pub bytes: u64,
+}
+
+/// Hit, miss and insert counters of a [`Cache`], see [`Cache::stats`]
+///
+/// Counters include blocks and blobs of all trees that share the cache.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    /// Amount of lookups that found the item in the cache
+    pub hits: u64,
+
+    /// Amount of lookups that did not find the item in the cache
+    pub misses: u64,
+
+    /// Amount of items that have been inserted into the cache
+    pub inserts: u64,
+}
+
+impl CacheStats {
+    /// Returns the ratio of lookups that were served from the cache.
+    ///
+    /// Returns 0.0 if there have not been any lookups yet.
+    #[must_use]
+    pub fn hit_ratio(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+
+        if lookups == 0 {
+            return 0.0;
+        }
+
+        // NOTE: Precision loss is fine for a ratio
+        #[allow(clippy::cast_precision_loss)]
+        let ratio = self.hits as f64 / lookups as f64;
+
+        ratio
+    }
+}
+
 /// Cache, in which blocks or blobs are cached in-memory
 /// after being retrieved from disk
 ///
@@ -80,6 +129,10 @@ pub struct Cache {
 
     /// Capacity in bytes
     capacity: u64,
+
+    hits: AtomicU64,
+    misses: AtomicU64,
+    inserts: AtomicU64,
 }
 
 impl Cache {
@@ -100,7 +153,62 @@ impl Cache {
         Self {
             data: quick_cache,
             capacity: bytes,
+            hits: AtomicU64::default(),
+            misses: AtomicU64::default(),
+            inserts: AtomicU64::default(),
+        }
+    }
+
+    /// Returns the hit, miss and insert counters since the cache was created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Cache, Config};
+    /// use std::sync::Arc;
+    ///
+    /// let cache = Arc::new(Cache::with_capacity_bytes(1_000_000));
+    /// let tree = Config::new(folder).use_cache(cache.clone()).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// tree.get("a", None)?;
+    /// tree.get("a", None)?;
+    ///
+    /// let stats = cache.stats();
+    /// assert!(stats.hits > 0);
+    /// assert!(stats.hit_ratio() > 0.0);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            inserts: self.inserts.load(Ordering::Relaxed),
+        }
+    }
+
+    fn insert(&self, key: CacheKey, item: Item) {
+        if self.capacity > 0 {
+            self.data.insert(key, item);
+            self.inserts.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn get(&self, key: &CacheKey) -> Option<Item> {
+        let item = self.data.get(key);
+
+        if item.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
         }
+
+        item
     }
 
     /// Returns the amount of cached bytes.
@@ -134,12 +242,10 @@ impl Cache {
         offset: BlockOffset,
         value: Arc<ValueBlock>,
     ) {
-        if self.capacity > 0 {
-            self.data.insert(
-                (TAG_BLOCK, id.tree_id(), id.segment_id(), *offset).into(),
-                Item::DataBlock(value),
-            );
-        }
+        self.insert(
+            (TAG_BLOCK, id.tree_id(), id.segment_id(), *offset).into(),
+            Item::DataBlock(value),
+        );
     }
 
     #[doc(hidden)]
@@ -149,12 +255,10 @@ impl Cache {
         offset: BlockOffset,
         value: Arc<IndexBlock>,
     ) {
-        if self.capacity > 0 {
-            self.data.insert(
-                (TAG_BLOCK, id.tree_id(), id.segment_id(), *offset).into(),
-                Item::IndexBlock(value),
-            );
-        }
+        self.insert(
+            (TAG_BLOCK, id.tree_id(), id.segment_id(), *offset).into(),
+            Item::IndexBlock(value),
+        );
     }
 
     #[doc(hidden)]
@@ -166,7 +270,7 @@ impl Cache {
     ) -> Option<Arc<ValueBlock>> {
         let key: CacheKey = (TAG_BLOCK, id.tree_id(), id.segment_id(), *offset).into();
 
-        if let Item::DataBlock(block) = self.data.get(&key)? {
+        if let Item::DataBlock(block) = self.get(&key)? {
             Some(block)
         } else {
             log::warn!("cache item type was unexpected - this is a bug");
@@ -183,7 +287,7 @@ impl Cache {
     ) -> Option<Arc<IndexBlock>> {
         let key: CacheKey = (TAG_BLOCK, id.tree_id(), id.segment_id(), *offset).into();
 
-        if let Item::IndexBlock(block) = self.data.get(&key)? {
+        if let Item::IndexBlock(block) = self.get(&key)? {
             Some(block)
         } else {
             log::warn!("cache item type was unexpected - this is a bug");
@@ -191,31 +295,30 @@ impl Cache {
         }
     }
 
+    /// Evicts all blocks and blobs of the given tree.
+    ///
+    /// This is called when a tree is dropped, so a shared cache does not
+    /// hold onto data that can never be read again.
     #[doc(hidden)]
-    pub fn insert_blob(
-        &self,
-        vlog_id: value_log::ValueLogId,
-        vhandle: &value_log::ValueHandle,
-        value: UserValue,
-    ) {
-        if self.capacity > 0 {
-            self.data.insert(
-                (TAG_BLOB, vlog_id, vhandle.segment_id, vhandle.offset).into(),
-                Item::Blob(value),
-            );
-        }
+    pub fn remove_tree(&self, tree_id: TreeId) {
+        self.data.retain(|key, _| key.1 != tree_id);
+    }
+
+    /// Blobs are namespaced by the ID of the blob tree's index tree
+    #[doc(hidden)]
+    pub fn insert_blob(&self, tree_id: TreeId, vhandle: &value_log::ValueHandle, value: UserValue) {
+        self.insert(
+            (TAG_BLOB, tree_id, vhandle.segment_id, vhandle.offset).into(),
+            Item::Blob(value),
+        );
     }
 
     #[doc(hidden)]
     #[must_use]
-    pub fn get_blob(
-        &self,
-        vlog_id: value_log::ValueLogId,
-        vhandle: &value_log::ValueHandle,
-    ) -> Option<UserValue> {
-        let key: CacheKey = (TAG_BLOB, vlog_id, vhandle.segment_id, vhandle.offset).into();
+    pub fn get_blob(&self, tree_id: TreeId, vhandle: &value_log::ValueHandle) -> Option<UserValue> {
+        let key: CacheKey = (TAG_BLOB, tree_id, vhandle.segment_id, vhandle.offset).into();
 
-        if let Item::Blob(blob) = self.data.get(&key)? {
+        if let Item::Blob(blob) = self.get(&key)? {
             Some(blob)
         } else {
             log::warn!("cache item type was unexpected - this is a bug");
@@ -0,0 +1,101 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+pub use value_log::coding::{Decode, DecodeError, Encode, EncodeError};
+
+use std::io::{Read, Write};
+
+/// Writes a format header, consisting of magic bytes followed by the format version
+///
+/// # Errors
+///
+/// Will return `Err` if an IO error occurs.
+pub(crate) fn write_format_header<W: Write>(
+    writer: &mut W,
+    magic: [u8; 3],
+    version: u8,
+) -> Result<(), EncodeError> {
+    writer.write_all(&magic)?;
+    writer.write_all(&[version])?;
+    Ok(())
+}
+
+/// Reads and checks a format header written by [`write_format_header`]
+///
+/// `name` is used to describe the decoded structure in errors.
+///
+/// # Errors
+///
+/// Will return [`DecodeError::InvalidHeader`] if the magic bytes do not match, and
+/// [`DecodeError::InvalidVersion`] if the format version is not `version`
+/// (e.g. because the data was written by a newer version of the crate).
+pub(crate) fn read_format_header<R: Read>(
+    reader: &mut R,
+    magic: [u8; 3],
+    version: u8,
+    name: &'static str,
+) -> Result<(), DecodeError> {
+    let [a, b, c, actual_version] = {
+        let mut buf = [0; 4];
+        reader.read_exact(&mut buf)?;
+        buf
+    };
+
+    if [a, b, c] != magic {
+        return Err(DecodeError::InvalidHeader(name));
+    }
+
+    if actual_version != version {
+        return Err(DecodeError::InvalidVersion);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn format_header_round_trip() -> crate::Result<()> {
+        let mut bytes = vec![];
+        write_format_header(&mut bytes, *b"LSM", 2)?;
+        assert_eq!(b"LSM\x02", &*bytes);
+
+        read_format_header(&mut &bytes[..], *b"LSM", 2, "Test")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn format_header_invalid_magic() {
+        let bytes = b"XSM\x02";
+
+        assert!(matches!(
+            read_format_header(&mut &bytes[..], *b"LSM", 2, "Test"),
+            Err(DecodeError::InvalidHeader("Test")),
+        ));
+    }
+
+    #[test]
+    fn format_header_unsupported_version() {
+        let bytes = b"LSM\x03";
+
+        assert!(matches!(
+            read_format_header(&mut &bytes[..], *b"LSM", 2, "Test"),
+            Err(DecodeError::InvalidVersion),
+        ));
+    }
+
+    #[test]
+    fn format_header_truncated() {
+        let bytes = b"LS";
+
+        assert!(matches!(
+            read_format_header(&mut &bytes[..], *b"LSM", 2, "Test"),
+            Err(DecodeError::Io(_)),
+        ));
+    }
+}
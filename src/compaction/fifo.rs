@@ -15,6 +15,9 @@ use crate::{config::Config, level_manifest::LevelManifest, time::unix_timestamp,
 ///
 /// Additionally, a (lazy) TTL can be configured to drop old segments.
 ///
+/// Dropping segments only rewrites the level manifest, no segments are written.
+/// Like every strategy, it is passed to [`AbstractTree::compact`](crate::AbstractTree::compact).
+///
 /// ###### Caution
 ///
 /// Only use it for specific workloads where:
@@ -181,6 +184,12 @@ mod tests {
 
             bloom_filter: Some(crate::bloom::BloomFilter::with_fp_rate(1, 0.1)),
 
+            prefix_filter: None,
+
+            range_tombstones: Vec::new(),
+
+            verify_checksums: false,
+
             path: "a".into(),
             is_deleted: AtomicBool::default(),
         }
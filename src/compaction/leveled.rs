@@ -2,7 +2,7 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use super::{Choice, CompactionStrategy, Input as CompactionInput};
+use super::{shares_prefix, Choice, CompactionStrategy, Input as CompactionInput};
 use crate::{
     config::Config,
     level_manifest::{hidden_set::HiddenSet, level::Level, LevelManifest},
@@ -17,12 +17,29 @@ fn aggregate_key_range(segments: &[Segment]) -> KeyRange {
     KeyRange::aggregate(segments.iter().map(|x| &x.metadata.key_range))
 }
 
+/// Returns the segments of a level that overlap a range tombstone of the given segments.
+///
+/// A range tombstone can only be dropped in the last level
+/// if every segment it overlaps is compacted with it.
+fn range_tombstone_overlaps<'a>(
+    segments: &'a [Segment],
+    level: &'a Level,
+) -> impl Iterator<Item = &'a Segment> + 'a {
+    level.iter().filter(|segment| {
+        segments
+            .iter()
+            .flat_map(|x| x.range_tombstones.iter())
+            .any(|rt| rt.overlaps(&segment.metadata.key_range))
+    })
+}
+
 /// Tries to find the most optimal compaction set from
 /// one level into the other.
 fn pick_minimal_compaction(
     curr_level: &Level,
     next_level: &Level,
     hidden_set: &HiddenSet,
+    prefix_len: Option<usize>,
 ) -> Option<(HashSet<SegmentId>, bool)> {
     // assert!(curr_level.is_disjoint, "Lx is not disjoint");
     // assert!(next_level.is_disjoint, "Lx+1 is not disjoint");
@@ -31,8 +48,27 @@ fn pick_minimal_compaction(
         write_amp: f32,
         segment_ids: HashSet<SegmentId>,
         can_trivial_move: bool,
+        crosses_prefix: bool,
+        splits_range_tombstone: bool,
     }
 
+    let crosses_prefix = |key_range: &KeyRange| {
+        prefix_len.is_some_and(|len| !shares_prefix(key_range.min(), key_range.max(), len))
+    };
+
+    // NOTE: See range_tombstone_overlaps
+    let splits_range_tombstone = |window: &[Segment], pull_in: &[&Segment]| {
+        pull_in
+            .iter()
+            .flat_map(|x| x.range_tombstones.iter())
+            .any(|rt| {
+                next_level.iter().any(|segment| {
+                    rt.overlaps(&segment.metadata.key_range)
+                        && !window.iter().any(|x| x.id() == segment.id())
+                })
+            })
+    };
+
     let mut choices = vec![];
 
     let mut add_choice = |choice: Choice| {
@@ -95,22 +131,34 @@ fn pick_minimal_compaction(
         let curr_level_size = curr_level_pull_in
             .iter()
             .map(|x| x.metadata.file_size)
-            .sum::<u64>();
+            .fold(0, u64::saturating_add);
 
         // NOTE: Only consider compactions where we actually reach the amount
         // of bytes we need to merge
         if curr_level_size >= 1 {
-            let next_level_size = window.iter().map(|x| x.metadata.file_size).sum::<u64>();
+            let next_level_size = window
+                .iter()
+                .map(|x| x.metadata.file_size)
+                .fold(0, u64::saturating_add);
 
             let mut segment_ids: HashSet<_> = window.iter().map(Segment::id).collect();
             segment_ids.extend(curr_level_pull_in.iter().map(|x| x.id()));
 
             let write_amp = (next_level_size as f32) / (curr_level_size as f32);
 
+            let merged_key_range = KeyRange::aggregate(
+                window
+                    .iter()
+                    .chain(curr_level_pull_in.iter().copied())
+                    .map(|x| &x.metadata.key_range),
+            );
+
             add_choice(Choice {
                 write_amp,
                 segment_ids,
                 can_trivial_move: false,
+                crosses_prefix: crosses_prefix(&merged_key_range),
+                splits_range_tombstone: splits_range_tombstone(window, &curr_level_pull_in),
             });
         }
     }
@@ -124,15 +172,25 @@ fn pick_minimal_compaction(
                 write_amp: 0.0,
                 segment_ids: window.iter().map(Segment::id).collect(),
                 can_trivial_move: true,
+                // NOTE: Moved segments are not merged with each other
+                crosses_prefix: false,
+                splits_range_tombstone: false,
             });
             break;
         }
     }
 
+    // NOTE: Prefer choices that keep prefixes apart, and range tombstones
+    // together with what they delete, even if they cost more
     let minimum_effort_choice = choices.into_iter().min_by(|a, b| {
-        a.write_amp
-            .partial_cmp(&b.write_amp)
-            .unwrap_or(std::cmp::Ordering::Equal)
+        a.crosses_prefix
+            .cmp(&b.crosses_prefix)
+            .then_with(|| a.splits_range_tombstone.cmp(&b.splits_range_tombstone))
+            .then_with(|| {
+                a.write_amp
+                    .partial_cmp(&b.write_amp)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
     });
 
     minimum_effort_choice.map(|c| (c.segment_ids, c.can_trivial_move))
@@ -172,8 +230,20 @@ pub struct Strategy {
     /// level to the next
     ///
     /// A level target size is: max_memtable_size * level_ratio.pow(#level + 1).
+    ///
+    /// Overridden by [`Config::level_ratio`], if set.
     #[allow(clippy::doc_markdown)]
     pub level_ratio: u8,
+
+    /// Length of the key prefix that segments are partitioned by, e.g. a tenant ID
+    ///
+    /// If set, compactions prefer merging segments of the same prefix,
+    /// and created segments are split at prefix boundaries.
+    /// Once segments are prefix-local, the segments of a prefix can be dropped
+    /// as a whole (see [`Choice::Drop`]) and scans of a prefix touch fewer segments.
+    ///
+    /// Default = None
+    pub prefix_len: Option<usize>,
 }
 
 impl Default for Strategy {
@@ -182,6 +252,7 @@ impl Default for Strategy {
             l0_threshold: 4,
             target_size:/* 64 Mib */ 64 * 1_024 * 1_024,
             level_ratio: 10,
+            prefix_len: None,
         }
     }
 }
@@ -195,189 +266,397 @@ impl Strategy {
     ///
     /// L3 = `level_base_size * ratio * ratio`
     /// ...
-    fn level_target_size(&self, level_idx: u8) -> u64 {
+    ///
+    /// The ratio can be overridden by [`Config::level_ratio`].
+    fn level_target_size(&self, level_idx: u8, config: &Config) -> u64 {
         assert!(level_idx >= 1, "level_target_size does not apply to L0");
 
-        let power = (self.level_ratio as usize).pow(u32::from(level_idx) - 1);
+        if config.level_ratio.is_some() {
+            return config.level_target_size(
+                level_idx,
+                self.level_base_size(),
+                f64::from(self.level_ratio),
+            );
+        }
 
-        (power * (self.level_base_size() as usize)) as u64
+        u64::from(self.level_ratio)
+            .saturating_pow(u32::from(level_idx) - 1)
+            .saturating_mul(self.level_base_size())
     }
 
-    fn level_base_size(&self) -> u64 {
-        u64::from(self.target_size) * u64::from(self.l0_threshold)
+    /// Returns the size target and actual size (in bytes) of every level, starting from L1.
+    ///
+    /// L0 is compacted based on its segment count, so it has no size target.
+    ///
+    /// These are the targets that are used when choosing a compaction,
+    /// so levels that are over their target will be compacted.
+    #[must_use]
+    pub fn level_size_targets(
+        &self,
+        levels: &LevelManifest,
+        config: &Config,
+    ) -> Vec<(u8, u64, u64)> {
+        levels
+            .levels
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(idx, level)| {
+                // NOTE: Level count is 255 max
+                #[allow(clippy::cast_possible_truncation)]
+                let idx = idx as u8;
+
+                (idx, self.level_target_size(idx, config), level.size())
+            })
+            .collect()
     }
-}
 
-impl CompactionStrategy for Strategy {
-    fn get_name(&self) -> &'static str {
-        "LeveledStrategy"
-    }
+    /// Estimates how many bytes need to be compacted to bring every level under its size target.
+    ///
+    /// Once L0 reaches [`Strategy::l0_threshold`], all its bytes are pending.
+    /// Bytes that are pushed out of a level are added to the next level,
+    /// so an overshoot may cascade down the tree. The last level has no target.
+    ///
+    /// Only segment metadata is used, so this is cheap to call.
+    #[must_use]
+    pub fn estimate_compaction_debt(&self, levels: &LevelManifest, config: &Config) -> u64 {
+        // NOTE: Bytes that are pushed into the next level
+        let mut carried = levels
+            .levels
+            .first()
+            .filter(|level| level.len() >= usize::from(self.l0_threshold))
+            .map(|level| level.size())
+            .unwrap_or_default();
+
+        let mut debt = carried;
+
+        // NOTE: Skip the last level
+        for (_, target, actual) in self
+            .level_size_targets(levels, config)
+            .into_iter()
+            .take(levels.levels.len().saturating_sub(2))
+        {
+            let overshoot = actual.saturating_add(carried).saturating_sub(target);
 
-    #[allow(clippy::too_many_lines)]
-    fn choose(&self, levels: &LevelManifest, _: &Config) -> Choice {
-        let view = &levels.levels;
+            debt = u64::saturating_add(debt, overshoot);
+            carried = overshoot;
+        }
 
-        // TODO: look at L1+, if not disjoint
-        // TODO: try to repairing level by rewriting
-        // TODO: abort if any segment is hidden
-        // TODO: then make sure, non-disjoint levels cannot be used in subsequent code below
-        // TODO: add tests
+        debt
+    }
 
-        // L1+ compactions
-        for (curr_level_index, level) in view.iter().enumerate().skip(1).take(view.len() - 2).rev()
-        {
+    fn level_base_size(&self) -> u64 {
+        u64::from(self.target_size) * u64::from(self.l0_threshold)
+    }
+
+    /// Picks the segment with the highest tombstone ratio above `threshold`,
+    /// and pushes it (and conflicting segments) down into the next level.
+    ///
+    /// Segments in the last level are rewritten in place, which drops their tombstones.
+    fn pick_tombstone_compaction(
+        &self,
+        levels: &LevelManifest,
+        threshold: f32,
+    ) -> Option<CompactionInput> {
+        let hidden_set = levels.hidden_set();
+        let last_level_index = levels.last_level_index();
+
+        for (level_index, level) in levels.levels.iter().enumerate() {
             // NOTE: Level count is 255 max
             #[allow(clippy::cast_possible_truncation)]
-            let curr_level_index = curr_level_index as u8;
+            let level_index = level_index as u8;
+
+            let Some(segment) = level
+                .iter()
+                .filter(|x| !hidden_set.is_hidden(x.id()))
+                .filter(|x| x.tombstone_count() > 0 && x.tombstone_ratio() >= threshold)
+                .max_by(|a, b| a.tombstone_ratio().total_cmp(&b.tombstone_ratio()))
+            else {
+                continue;
+            };
 
-            let next_level_index = curr_level_index + 1;
+            // IMPORTANT: L0 segments may overlap, so we cannot move a single segment
+            // below older versions of its keys
+            let mut segment_ids: HashSet<SegmentId> = if level_index == 0 {
+                level.list_ids()
+            } else {
+                std::iter::once(segment.id()).collect()
+            };
 
-            if level.is_empty() {
+            let dest_level = if level_index == last_level_index {
+                level_index
+            } else {
+                let pulled_in: Vec<_> = level
+                    .iter()
+                    .filter(|x| segment_ids.contains(&x.id()))
+                    .cloned()
+                    .collect();
+
+                let key_range = aggregate_key_range(&pulled_in);
+
+                if let Some(next_level) = levels.levels.get(usize::from(level_index) + 1) {
+                    segment_ids
+                        .extend(next_level.overlapping_segments(&key_range).map(Segment::id));
+                    segment_ids
+                        .extend(range_tombstone_overlaps(&pulled_in, next_level).map(Segment::id));
+                }
+
+                level_index + 1
+            };
+
+            if hidden_set.is_blocked(segment_ids.iter().copied()) {
                 continue;
             }
 
-            let level_size: u64 = level
-                .segments
-                .iter()
-                // NOTE: Take bytes that are already being compacted into account,
-                // otherwise we may be overcompensating
-                .filter(|x| !levels.hidden_set().is_hidden(x.id()))
-                .map(|x| x.metadata.file_size)
-                .sum();
+            return Some(CompactionInput {
+                segment_ids,
+                dest_level,
+                target_size: u64::from(self.target_size),
+                prefix_len: self.prefix_len,
+            });
+        }
 
-            let desired_bytes = self.level_target_size(curr_level_index);
+        None
+    }
+    /// Returns `true` if the level is larger than its target size.
+    fn level_overshoots(&self, levels: &LevelManifest, config: &Config, level_index: u8) -> bool {
+        let Some(level) = levels.levels.get(usize::from(level_index)) else {
+            return false;
+        };
 
-            let overshoot = level_size.saturating_sub(desired_bytes);
+        let level_size: u64 = level
+            .segments
+            .iter()
+            // NOTE: Take bytes that are already being compacted into account,
+            // otherwise we may be overcompensating
+            .filter(|x| !levels.hidden_set().is_hidden(x.id()))
+            .map(|x| x.metadata.file_size)
+            .fold(0, u64::saturating_add);
 
-            if overshoot > 0 {
-                let Some(next_level) = &view.get(next_level_index as usize) else {
-                    break;
-                };
+        let desired_bytes = self.level_target_size(level_index, config);
 
-                let Some((segment_ids, can_trivial_move)) =
-                    pick_minimal_compaction(level, next_level, levels.hidden_set())
-                else {
-                    break;
-                };
+        level_size.saturating_sub(desired_bytes) > 0
+    }
 
-                // eprintln!(
-                //     "merge {} segments, L{}->L{next_level_index}: {segment_ids:?}",
-                //     segment_ids.len(),
-                //     next_level_index - 1,
-                // );
+    /// Picks a compaction from an overshooting L1+ level into the next level.
+    fn pick_level_compaction(
+        &self,
+        levels: &LevelManifest,
+        curr_level_index: u8,
+    ) -> Option<Choice> {
+        let view = &levels.levels;
+        let level = view.get(usize::from(curr_level_index))?;
 
-                let choice = CompactionInput {
-                    segment_ids,
-                    dest_level: next_level_index,
-                    target_size: u64::from(self.target_size),
-                };
+        let next_level_index = curr_level_index + 1;
+        let next_level = view.get(usize::from(next_level_index))?;
 
-                // TODO: eventually, this should happen lazily
-                // if a segment file lives for very long, it should get rewritten
-                // Rocks, by default, rewrites files that are 1 month or older
-                //
-                // TODO: 3.0.0 configuration?
-                // NOTE: We purposefully not trivially move segments
-                // if we go from L1 to L2
-                // https://github.com/fjall-rs/lsm-tree/issues/63
-                let goes_into_cold_storage = next_level_index == 2;
-
-                if goes_into_cold_storage {
-                    return Choice::Merge(choice);
-                }
+        let (segment_ids, can_trivial_move) =
+            pick_minimal_compaction(level, next_level, levels.hidden_set(), self.prefix_len)?;
 
-                if can_trivial_move && level.is_disjoint {
-                    return Choice::Move(choice);
-                }
-                return Choice::Merge(choice);
-            }
+        let choice = CompactionInput {
+            segment_ids,
+            dest_level: next_level_index,
+            target_size: u64::from(self.target_size),
+            prefix_len: self.prefix_len,
+        };
+
+        // TODO: eventually, this should happen lazily
+        // if a segment file lives for very long, it should get rewritten
+        // Rocks, by default, rewrites files that are 1 month or older
+        //
+        // TODO: 3.0.0 configuration?
+        // NOTE: We purposefully not trivially move segments
+        // if we go from L1 to L2
+        // https://github.com/fjall-rs/lsm-tree/issues/63
+        let goes_into_cold_storage = next_level_index == 2;
+
+        if goes_into_cold_storage {
+            return Some(Choice::Merge(choice));
         }
 
-        // L0->L1 compactions
-        {
-            let busy_levels = levels.busy_levels();
+        if can_trivial_move && level.is_disjoint {
+            return Some(Choice::Move(choice));
+        }
+        Some(Choice::Merge(choice))
+    }
 
-            let Some(first_level) = view.first() else {
-                return Choice::DoNothing;
-            };
+    /// Picks a compaction of L0, either into L1 or into L0 itself.
+    ///
+    /// Returns [`Choice::DoNothing`] if L0 should not be compacted, and no other
+    /// compaction should be chosen either, or `None` if L0 needs no compaction.
+    fn pick_l0_compaction(&self, levels: &LevelManifest) -> Option<Choice> {
+        let view = &levels.levels;
 
-            if first_level.len() >= self.l0_threshold.into() && !busy_levels.contains(&0) {
-                let first_level_size = first_level.size();
-
-                // NOTE: Special handling for disjoint workloads
-                if levels.is_disjoint() {
-                    if first_level_size < self.target_size.into() {
-                        // TODO: also do this in non-disjoint workloads
-                        // -> intra-L0 compaction
-
-                        // NOTE: Force a merge into L0 itself
-                        // ...we seem to have *very* small flushes
-                        return if first_level.len() >= 32 {
-                            Choice::Merge(CompactionInput {
-                                dest_level: 0,
-                                segment_ids: first_level.list_ids(),
-                                // NOTE: Allow a bit of overshooting
-                                target_size: ((self.target_size as f32) * 1.1) as u64,
-                            })
-                        } else {
-                            Choice::DoNothing
-                        };
-                    }
-
-                    return Choice::Merge(CompactionInput {
-                        dest_level: 1,
-                        segment_ids: first_level.list_ids(),
-                        target_size: ((self.target_size as f32) * 1.1) as u64,
-                    });
-                }
+        let busy_levels = levels.busy_levels();
+
+        let Some(first_level) = view.first() else {
+            return Some(Choice::DoNothing);
+        };
+
+        if first_level.len() >= self.l0_threshold.into() && !busy_levels.contains(&0) {
+            let first_level_size = first_level.size();
 
+            // NOTE: Range tombstones may overlap L1, even if the key ranges are disjoint
+            let splits_range_tombstone = view.get(1).is_some_and(|next_level| {
+                range_tombstone_overlaps(first_level, next_level)
+                    .next()
+                    .is_some()
+            });
+
+            // NOTE: Special handling for disjoint workloads
+            if levels.is_disjoint() && !splits_range_tombstone {
                 if first_level_size < self.target_size.into() {
-                    // NOTE: We reached the threshold, but L0 is still very small
-                    // meaning we have very small segments, so do intra-L0 compaction
-                    return Choice::Merge(CompactionInput {
-                        dest_level: 0,
-                        segment_ids: first_level.list_ids(),
-                        target_size: self.target_size.into(),
+                    // TODO: also do this in non-disjoint workloads
+                    // -> intra-L0 compaction
+
+                    // NOTE: Force a merge into L0 itself
+                    // ...we seem to have *very* small flushes
+                    return Some(if first_level.len() >= 32 {
+                        Choice::Merge(CompactionInput {
+                            dest_level: 0,
+                            segment_ids: first_level.list_ids(),
+                            // NOTE: Allow a bit of overshooting
+                            target_size: ((self.target_size as f32) * 1.1) as u64,
+                            // NOTE: L0 is compacted by segment count, so splitting
+                            // by prefix would just trigger the next compaction
+                            prefix_len: None,
+                        })
+                    } else {
+                        Choice::DoNothing
                     });
                 }
 
-                if !busy_levels.contains(&1) {
-                    let mut level = (**first_level).clone();
-                    level.sort_by_key_range();
+                return Some(Choice::Merge(CompactionInput {
+                    dest_level: 1,
+                    segment_ids: first_level.list_ids(),
+                    target_size: ((self.target_size as f32) * 1.1) as u64,
+                    prefix_len: self.prefix_len,
+                }));
+            }
 
-                    let Some(next_level) = &view.get(1) else {
-                        return Choice::DoNothing;
-                    };
+            if first_level_size < self.target_size.into() {
+                // NOTE: We reached the threshold, but L0 is still very small
+                // meaning we have very small segments, so do intra-L0 compaction
+                return Some(Choice::Merge(CompactionInput {
+                    dest_level: 0,
+                    segment_ids: first_level.list_ids(),
+                    target_size: self.target_size.into(),
+                    // NOTE: See above, L0 is not split by prefix
+                    prefix_len: None,
+                }));
+            }
+
+            if !busy_levels.contains(&1) {
+                let mut level = (**first_level).clone();
+                level.sort_by_key_range();
+
+                let Some(next_level) = &view.get(1) else {
+                    return Some(Choice::DoNothing);
+                };
 
-                    // TODO: list_ids()
-                    let mut segment_ids: HashSet<u64> = level.iter().map(Segment::id).collect();
+                // TODO: list_ids()
+                let mut segment_ids: HashSet<u64> = level.iter().map(Segment::id).collect();
 
-                    // Get overlapping segments in next level
-                    let key_range = aggregate_key_range(&level);
+                // Get overlapping segments in next level
+                let key_range = aggregate_key_range(&level);
 
-                    let next_level_overlapping_segment_ids: Vec<_> = next_level
-                        .overlapping_segments(&key_range)
-                        .map(Segment::id)
-                        .collect();
+                let next_level_overlapping_segment_ids: Vec<_> = next_level
+                    .overlapping_segments(&key_range)
+                    .chain(range_tombstone_overlaps(&level, next_level))
+                    .map(Segment::id)
+                    .collect();
 
-                    segment_ids.extend(&next_level_overlapping_segment_ids);
+                segment_ids.extend(&next_level_overlapping_segment_ids);
 
-                    let choice = CompactionInput {
-                        segment_ids,
-                        dest_level: 1,
-                        target_size: u64::from(self.target_size),
-                    };
+                let choice = CompactionInput {
+                    segment_ids,
+                    dest_level: 1,
+                    target_size: u64::from(self.target_size),
+                    prefix_len: self.prefix_len,
+                };
 
-                    if next_level_overlapping_segment_ids.is_empty() && level.is_disjoint {
-                        return Choice::Move(choice);
-                    }
-                    return Choice::Merge(choice);
+                if next_level_overlapping_segment_ids.is_empty() && level.is_disjoint {
+                    return Some(Choice::Move(choice));
                 }
+                return Some(Choice::Merge(choice));
+            }
+        }
+
+        None
+    }
+}
+
+impl CompactionStrategy for Strategy {
+    fn get_name(&self) -> &'static str {
+        "LeveledStrategy"
+    }
+
+    fn choose(&self, levels: &LevelManifest, config: &Config) -> Choice {
+        // TODO: look at L1+, if not disjoint
+        // TODO: try to repairing level by rewriting
+        // TODO: abort if any segment is hidden
+        // TODO: then make sure, non-disjoint levels cannot be used in subsequent code below
+        // TODO: add tests
+
+        // L1+ compactions
+        for curr_level_index in (1..levels.levels.len().saturating_sub(1)).rev() {
+            // NOTE: Level count is 255 max
+            #[allow(clippy::cast_possible_truncation)]
+            let curr_level_index = curr_level_index as u8;
+
+            if !self.level_overshoots(levels, config, curr_level_index) {
+                continue;
+            }
+
+            let Some(choice) = self.pick_level_compaction(levels, curr_level_index) else {
+                break;
+            };
+
+            return choice;
+        }
+
+        // L0->L1 compactions
+        if let Some(choice) = self.pick_l0_compaction(levels) {
+            return choice;
+        }
+
+        // NOTE: Size targets are not met, but tombstones may still degrade reads
+        if let Some(threshold) = config.tombstone_ratio_trigger {
+            if let Some(choice) = self.pick_tombstone_compaction(levels, threshold) {
+                return Choice::Merge(choice);
             }
         }
 
         Choice::DoNothing
     }
+
+    fn choose_many(&self, levels: &LevelManifest, config: &Config) -> Vec<Choice> {
+        // NOTE: Every overshooting level is compacted into the next one,
+        // jobs that overlap each other are deferred by the compactor
+        let mut choices = (1..levels.levels.len().saturating_sub(1))
+            .rev()
+            .filter_map(|idx| {
+                // NOTE: Level count is 255 max
+                #[allow(clippy::cast_possible_truncation)]
+                let idx = idx as u8;
+
+                if self.level_overshoots(levels, config, idx) {
+                    self.pick_level_compaction(levels, idx)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        choices.extend(self.pick_l0_compaction(levels));
+        choices.retain(|choice| *choice != Choice::DoNothing);
+
+        if choices.is_empty() {
+            choices.push(self.choose(levels, config));
+        }
+
+        choices
+    }
 }
 
 #[cfg(test)]
@@ -461,6 +740,12 @@ mod tests {
 
             bloom_filter: Some(crate::bloom::BloomFilter::with_fp_rate(1, 0.1)),
 
+            prefix_filter: None,
+
+            range_tombstones: Vec::new(),
+
+            verify_checksums: false,
+
             path: "a".into(),
             is_deleted: AtomicBool::default(),
         }
@@ -515,6 +800,96 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn leveled_compaction_debt() -> crate::Result<()> {
+        const MIB: u64 = 1_024 * 1_024;
+
+        let tempdir = tempfile::tempdir()?;
+        let compactor = Strategy::default();
+        let config = Config::default();
+
+        let levels = build_levels(tempdir.path(), vec![vec![], vec![], vec![], vec![]])?;
+        assert_eq!(0, compactor.estimate_compaction_debt(&levels, &config));
+
+        // NOTE: L0 is below its threshold, and the last level has no target
+        #[rustfmt::skip]
+        let levels = build_levels(tempdir.path(), vec![
+            vec![(1, "a", "z", 64), (2, "a", "z", 64), (3, "a", "z", 64)],
+            vec![],
+            vec![],
+            vec![(4, "a", "z", 100_000)],
+        ])?;
+        assert_eq!(0, compactor.estimate_compaction_debt(&levels, &config));
+
+        // NOTE: L1 overshoots by 44 MiB, which are then pushed into the full L2
+        #[rustfmt::skip]
+        let levels = build_levels(tempdir.path(), vec![
+            vec![],
+            vec![(1, "a", "z", 300)],
+            vec![(2, "a", "z", 2_560)],
+            vec![(3, "a", "z", 10_000)],
+        ])?;
+        assert_eq!(
+            88 * MIB,
+            compactor.estimate_compaction_debt(&levels, &config)
+        );
+
+        // NOTE: All of L0 needs to be compacted into L1
+        #[rustfmt::skip]
+        let levels = build_levels(tempdir.path(), vec![
+            vec![(1, "a", "z", 64), (2, "a", "z", 64), (3, "a", "z", 64), (4, "a", "z", 64)],
+            vec![(5, "a", "z", 300)],
+            vec![(6, "a", "z", 2_560)],
+            vec![(7, "a", "z", 10_000)],
+        ])?;
+        assert_eq!(
+            (256 + 300 + 300) * MIB,
+            compactor.estimate_compaction_debt(&levels, &config)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn leveled_huge_segments() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let compactor = Strategy {
+            level_ratio: u8::MAX,
+            ..Default::default()
+        };
+
+        let mut levels = build_levels(tempdir.path(), vec![vec![], vec![], vec![], vec![]])?;
+
+        // NOTE: Synthetic sizes that overflow when summed up
+        levels.insert_into_level(
+            1,
+            fixture_segment(1, string_key_range("a", "g"), u64::MAX - 1, 0.0),
+        );
+        levels.insert_into_level(
+            1,
+            fixture_segment(2, string_key_range("h", "z"), u64::MAX / 2, 0.0),
+        );
+        levels.insert_into_level(
+            2,
+            fixture_segment(3, string_key_range("a", "z"), u64::MAX, 0.0),
+        );
+
+        assert_eq!(u64::MAX, levels.size());
+        assert_eq!(u64::MAX, levels.levels[1].size());
+        assert_eq!(
+            u64::MAX,
+            compactor.level_target_size(u8::MAX, &Config::default())
+        );
+
+        // NOTE: Both levels are way too big, so something needs to be compacted
+        assert_ne!(
+            compactor.choose(&levels, &Config::default()),
+            Choice::DoNothing
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn leveled_default_l0() -> crate::Result<()> {
         let tempdir = tempfile::tempdir()?;
@@ -536,7 +911,8 @@ mod tests {
             Choice::Merge(CompactionInput {
                 dest_level: 1,
                 segment_ids: [1, 2, 3, 4].into_iter().collect::<HashSet<_>>(),
-                target_size: 64 * 1_024 * 1_024
+                target_size: 64 * 1_024 * 1_024,
+                prefix_len: None,
             })
         );
 
@@ -577,6 +953,7 @@ mod tests {
                 dest_level: 0,
                 segment_ids: [1, 2, 3, 4].into_iter().collect::<HashSet<_>>(),
                 target_size: u64::from(compactor.target_size),
+                prefix_len: None,
             })
         );
 
@@ -611,7 +988,8 @@ mod tests {
             Choice::Merge(CompactionInput {
                 dest_level: 1,
                 segment_ids: [1, 2, 3, 4].into_iter().collect::<HashSet<_>>(),
-                target_size: 64 * 1_024 * 1_024
+                target_size: 64 * 1_024 * 1_024,
+                prefix_len: None,
             })
         );
 
@@ -639,7 +1017,8 @@ mod tests {
             Choice::Merge(CompactionInput {
                 dest_level: 1,
                 segment_ids: [1, 2, 3, 4, 5, 6].into_iter().collect::<HashSet<_>>(),
-                target_size: 64 * 1_024 * 1_024
+                target_size: 64 * 1_024 * 1_024,
+                prefix_len: None,
             })
         );
 
@@ -652,6 +1031,48 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn leveled_prefix_locality() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+
+        #[rustfmt::skip]
+        let levels = build_levels(tempdir.path(), vec![
+            vec![],
+            vec![(1, "a1", "a5", 128), (2, "a6", "b3", 128), (3, "b4", "b9", 128)],
+            vec![(10, "a0", "a5", 64), (11, "a6", "b3", 1), (12, "b4", "b9", 64)],
+            vec![],
+        ])?;
+
+        // NOTE: Merging 2 & 11 is the cheapest choice
+        let compactor = Strategy::default();
+        assert_eq!(
+            compactor.choose(&levels, &Config::default()),
+            Choice::Merge(CompactionInput {
+                dest_level: 2,
+                segment_ids: [2, 11].into_iter().collect::<HashSet<_>>(),
+                target_size: 64 * 1_024 * 1_024,
+                prefix_len: None,
+            })
+        );
+
+        // NOTE: ...but it merges prefix "a" with prefix "b"
+        let compactor = Strategy {
+            prefix_len: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            compactor.choose(&levels, &Config::default()),
+            Choice::Merge(CompactionInput {
+                dest_level: 2,
+                segment_ids: [1, 10].into_iter().collect::<HashSet<_>>(),
+                target_size: 64 * 1_024 * 1_024,
+                prefix_len: Some(1),
+            })
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn levelled_from_tiered() -> crate::Result<()> {
         let tempdir = tempfile::tempdir()?;
@@ -674,7 +1095,8 @@ mod tests {
             Choice::Merge(CompactionInput {
                 dest_level: 2,
                 segment_ids: [1, 2, 3, 4, 5, 6].into_iter().collect::<HashSet<_>>(),
-                target_size: 64 * 1_024 * 1_024
+                target_size: 64 * 1_024 * 1_024,
+                prefix_len: None,
             })
         );
 
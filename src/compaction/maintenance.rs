@@ -36,7 +36,12 @@ pub fn choose_least_effort_compaction(segments: &[Segment], n: usize) -> HashSet
     let windows = segments.windows(n);
 
     let window = windows
-        .min_by_key(|window| window.iter().map(|s| s.metadata.file_size).sum::<u64>())
+        .min_by_key(|window| {
+            window
+                .iter()
+                .map(|s| s.metadata.file_size)
+                .fold(0, u64::saturating_add)
+        })
         .expect("should have at least one window");
 
     window.iter().map(Segment::id).collect()
@@ -71,6 +76,7 @@ impl CompactionStrategy for Strategy {
                 dest_level: 0,
                 segment_ids,
                 target_size: u64::MAX,
+                prefix_len: None,
             })
         } else {
             Choice::DoNothing
@@ -144,6 +150,12 @@ mod tests {
 
             bloom_filter: Some(crate::bloom::BloomFilter::with_fp_rate(1, 0.1)),
 
+            prefix_filter: None,
+
+            range_tombstones: Vec::new(),
+
+            verify_checksums: false,
+
             path: "a".into(),
             is_deleted: AtomicBool::default(),
         }
@@ -198,7 +210,8 @@ mod tests {
             Choice::Merge(crate::compaction::Input {
                 dest_level: 0,
                 segment_ids: [0, 1, 2].into_iter().collect::<HashSet<_>>(),
-                target_size: u64::MAX
+                target_size: u64::MAX,
+                prefix_len: None,
             })
         );
 
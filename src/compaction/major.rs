@@ -56,6 +56,7 @@ impl CompactionStrategy for Strategy {
                 segment_ids,
                 dest_level: levels.last_level_index(),
                 target_size: self.target_size,
+                prefix_len: None,
             })
         }
     }
@@ -9,14 +9,22 @@ pub(crate) mod leveled;
 pub(crate) mod maintenance;
 pub(crate) mod major;
 pub(crate) mod movedown;
+pub(crate) mod progress;
 pub(crate) mod pulldown;
+pub(crate) mod range;
+pub(crate) mod retry;
+pub(crate) mod stats;
 pub(crate) mod stream;
 pub(crate) mod tiered;
+pub(crate) mod universal;
 pub(crate) mod worker;
 
 pub use fifo::Strategy as Fifo;
 pub use leveled::Strategy as Leveled;
+pub use progress::{CompactionProgress, ProgressCallback};
+pub use stats::{CompactionListener, CompactionStats};
 pub use tiered::Strategy as SizeTiered;
+pub use universal::Strategy as Universal;
 
 use crate::{config::Config, level_manifest::LevelManifest, segment::meta::SegmentId, HashSet};
 
@@ -46,6 +54,19 @@ pub struct Input {
     /// If a segment compaction reaches the level, a new segment is started.
     /// This results in a sorted "run" of segments
     pub target_size: u64,
+
+    /// Length of the key prefix that segments are partitioned by
+    ///
+    /// If set, a new segment is also started whenever the first `prefix_len` bytes
+    /// of the keys change, so no created segment spans more than one prefix.
+    /// Keys that are shorter than `prefix_len` are their own prefix.
+    pub prefix_len: Option<usize>,
+}
+
+/// Returns `true` if both keys have the same prefix of `prefix_len` bytes,
+/// see [`Input::prefix_len`].
+pub(crate) fn shares_prefix(a: &[u8], b: &[u8], prefix_len: usize) -> bool {
+    a.get(..prefix_len).unwrap_or(a) == b.get(..prefix_len).unwrap_or(b)
 }
 
 /// Describes what to do (compact or not)
@@ -71,6 +92,10 @@ pub enum Choice {
 ///
 /// The strategy receives the levels of the LSM-tree as argument
 /// and emits a choice on what to do.
+///
+/// Per-level statistics are available through [`LevelManifest::level_size`],
+/// [`LevelManifest::level_segment_count`] and [`LevelManifest::level_is_overlapping`],
+/// and size targets of levels through [`Config::level_target_size`].
 #[allow(clippy::module_name_repetitions)]
 pub trait CompactionStrategy {
     // TODO: could be : Display instead
@@ -79,4 +104,16 @@ pub trait CompactionStrategy {
 
     /// Decides on what to do based on the current state of the LSM-tree's levels
     fn choose(&self, _: &LevelManifest, config: &Config) -> Choice;
+
+    /// Decides on multiple compactions that may run at the same time,
+    /// see [`Config::compaction_threads`].
+    ///
+    /// The choices do not need to be independent of each other: choices that touch
+    /// the same segments, or overlapping key ranges of the same levels, are not run
+    /// concurrently. Only the first of them is run, the others are left for the next compaction.
+    ///
+    /// Defaults to the single choice of [`CompactionStrategy::choose`].
+    fn choose_many(&self, levels: &LevelManifest, config: &Config) -> Vec<Choice> {
+        vec![self.choose(levels, config)]
+    }
 }
@@ -33,6 +33,7 @@ impl CompactionStrategy for Strategy {
                 segment_ids,
                 dest_level: self.1,
                 target_size: 64_000_000,
+                prefix_len: None,
             })
         } else {
             Choice::DoNothing
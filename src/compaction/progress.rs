@@ -0,0 +1,128 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::{sync::Arc, time::Duration};
+
+/// Callback that receives progress updates of a running compaction
+pub type ProgressCallback = Arc<dyn Fn(&CompactionProgress) + Send + Sync>;
+
+/// Amount of input items after which a progress update is sent
+pub const PROGRESS_INTERVAL: usize = 100_000;
+
+/// Cumulative progress of a running compaction
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompactionProgress {
+    /// Amount of input items read so far
+    pub items_read: u64,
+
+    /// Total amount of input items
+    pub total_items: u64,
+
+    /// Amount of items written so far
+    ///
+    /// May be lower than `items_read`, because old versions
+    /// and tombstones may be dropped.
+    pub items_written: u64,
+
+    /// Input bytes read so far
+    ///
+    /// This is estimated from `items_read`, as input segments are compressed.
+    pub bytes_read: u64,
+
+    /// Total input bytes (on-disk size of all input segments)
+    pub total_bytes: u64,
+
+    /// Time since the compaction started
+    pub elapsed: Duration,
+}
+
+impl CompactionProgress {
+    pub(crate) fn new(total_items: u64, total_bytes: u64) -> Self {
+        Self {
+            items_read: 0,
+            total_items,
+            items_written: 0,
+            bytes_read: 0,
+            total_bytes,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    pub(crate) fn update(&mut self, items_read: u64, items_written: u64, elapsed: Duration) {
+        self.items_read = items_read;
+        self.items_written = items_written;
+        self.elapsed = elapsed;
+
+        if self.total_items > 0 {
+            let items_read = u128::from(items_read.min(self.total_items));
+
+            // NOTE: Result is <= total_bytes, because items_read <= total_items
+            #[allow(clippy::cast_possible_truncation)]
+            let bytes_read =
+                (u128::from(self.total_bytes) * items_read / u128::from(self.total_items)) as u64;
+
+            self.bytes_read = bytes_read;
+        }
+    }
+
+    /// Returns the completed ratio of the compaction, in [0.0, 1.0].
+    #[must_use]
+    pub fn ratio(&self) -> f64 {
+        if self.total_items == 0 {
+            return 1.0;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let ratio = self.items_read as f64 / self.total_items as f64;
+
+        ratio.min(1.0)
+    }
+
+    /// Estimates the time until the compaction is finished.
+    ///
+    /// Returns `None` if no items have been read yet.
+    #[must_use]
+    pub fn eta(&self) -> Option<Duration> {
+        if self.items_read == 0 {
+            return None;
+        }
+
+        let remaining = self.total_items.saturating_sub(self.items_read);
+
+        let nanos = self.elapsed.as_nanos() * u128::from(remaining) / u128::from(self.items_read);
+
+        // NOTE: Truncation is fine, compactions do not run for 500 years
+        #[allow(clippy::cast_possible_truncation)]
+        Some(Duration::from_nanos(nanos as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn compaction_progress_estimates() {
+        let mut progress = CompactionProgress::new(1_000, 10_000);
+        assert_eq!(None, progress.eta());
+        assert_eq!(0.0, progress.ratio());
+
+        progress.update(250, 200, Duration::from_secs(10));
+        assert_eq!(2_500, progress.bytes_read);
+        assert_eq!(0.25, progress.ratio());
+        assert_eq!(Some(Duration::from_secs(30)), progress.eta());
+
+        progress.update(1_000, 900, Duration::from_secs(40));
+        assert_eq!(10_000, progress.bytes_read);
+        assert_eq!(1.0, progress.ratio());
+        assert_eq!(Some(Duration::ZERO), progress.eta());
+    }
+
+    #[test]
+    fn compaction_progress_empty() {
+        let progress = CompactionProgress::new(0, 0);
+        assert_eq!(1.0, progress.ratio());
+    }
+}
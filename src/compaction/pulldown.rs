@@ -36,6 +36,7 @@ impl CompactionStrategy for Strategy {
             segment_ids,
             dest_level: self.1,
             target_size: 64_000_000,
+            prefix_len: None,
         })
     }
 }
@@ -0,0 +1,93 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{Choice, CompactionStrategy, Input as CompactionInput};
+use crate::{config::Config, level_manifest::LevelManifest, HashSet, KeyRange, Segment, UserKey};
+use std::ops::Bound;
+
+/// Range compaction
+///
+/// Compacts all segments that overlap a key range into the last level.
+pub struct Strategy {
+    bounds: (Bound<UserKey>, Bound<UserKey>),
+    target_size: u64,
+}
+
+impl Strategy {
+    /// Configures a new `RangeCompaction` strategy.
+    #[must_use]
+    pub fn new(bounds: (Bound<UserKey>, Bound<UserKey>), target_size: u64) -> Self {
+        Self {
+            bounds,
+            target_size,
+        }
+    }
+}
+
+impl CompactionStrategy for Strategy {
+    fn get_name(&self) -> &'static str {
+        "RangeCompaction"
+    }
+
+    fn choose(&self, levels: &LevelManifest, _: &Config) -> Choice {
+        let mut segments = levels
+            .iter()
+            .filter(|segment| {
+                segment
+                    .metadata
+                    .key_range
+                    .overlaps_with_bounds(&self.bounds)
+            })
+            .collect::<Vec<_>>();
+
+        if segments.is_empty() {
+            return Choice::DoNothing;
+        }
+
+        // IMPORTANT: The chosen segments may contain keys outside of the range,
+        // which are moved into the last level as well. So every other segment
+        // that overlaps with them needs to be compacted, too, otherwise it would
+        // overlap the created segments in the last level, or an older version
+        // of a key in an upper level would shadow the compacted one.
+        loop {
+            let key_range = KeyRange::aggregate(segments.iter().map(|x| &x.metadata.key_range));
+
+            let overlapping = levels
+                .iter()
+                .filter(|segment| {
+                    segment
+                        .metadata
+                        .key_range
+                        .overlaps_with_key_range(&key_range)
+                })
+                .collect::<Vec<_>>();
+
+            if overlapping.len() == segments.len() {
+                break;
+            }
+
+            segments = overlapping;
+        }
+
+        let segment_ids: HashSet<_> = segments.into_iter().map(Segment::id).collect();
+
+        // NOTE: This should generally not occur because of the
+        // tree-level major compaction lock
+        // But just as a fail-safe...
+        let some_hidden = segment_ids
+            .iter()
+            .any(|&id| levels.hidden_set().is_hidden(id));
+
+        if some_hidden {
+            Choice::DoNothing
+        } else {
+            Choice::Merge(CompactionInput {
+                segment_ids,
+                dest_level: levels.last_level_index(),
+                target_size: self.target_size,
+                prefix_len: None,
+            })
+        }
+    }
+}
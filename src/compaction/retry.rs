@@ -0,0 +1,57 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::stop_signal::StopSignal;
+use std::time::{Duration, Instant};
+
+/// Upper limit of the backoff between compaction retries
+pub const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Interval in which the stop signal is checked while sleeping
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Sleeps for the given duration.
+///
+/// Returns `false` if the stop signal was sent before the duration has passed.
+pub fn sleep_unless_stopped(duration: Duration, stop_signal: &StopSignal) -> bool {
+    let start = Instant::now();
+
+    loop {
+        if stop_signal.is_stopped() {
+            return false;
+        }
+
+        let elapsed = start.elapsed();
+
+        if elapsed >= duration {
+            return true;
+        }
+
+        std::thread::sleep(STOP_CHECK_INTERVAL.min(duration.saturating_sub(elapsed)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn compaction_retry_sleep() {
+        let stop_signal = StopSignal::default();
+
+        let start = Instant::now();
+        assert!(sleep_unless_stopped(
+            Duration::from_millis(50),
+            &stop_signal
+        ));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        stop_signal.send();
+
+        let start = Instant::now();
+        assert!(!sleep_unless_stopped(Duration::from_secs(60), &stop_signal));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
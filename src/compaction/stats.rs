@@ -0,0 +1,35 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::{sync::Arc, time::Duration};
+
+/// Callback that receives the statistics of every completed compaction,
+/// see [`Config::on_compaction_complete`](crate::Config::on_compaction_complete)
+pub type CompactionListener = Arc<dyn Fn(CompactionStats) + Send + Sync>;
+
+/// Callback that receives the blobs of values that were dropped by compaction
+/// because a range tombstone deleted them, as (dead blob count, dead blob bytes) per blob file
+pub type DroppedBlobsListener = Arc<dyn Fn(&crate::HashMap<u64, (u64, u64)>) + Send + Sync>;
+
+/// Statistics of a completed compaction
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CompactionStats {
+    /// Amount of segments that were merged
+    pub input_segment_count: usize,
+
+    /// Total on-disk size of the merged segments
+    pub input_bytes: u64,
+
+    /// Amount of segments that were created
+    pub output_segment_count: usize,
+
+    /// Total on-disk size of the created segments
+    pub output_bytes: u64,
+
+    /// Time it took to run the compaction
+    pub elapsed: Duration,
+
+    /// Level the created segments were put into
+    pub dest_level: u8,
+}
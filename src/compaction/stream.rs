@@ -12,6 +12,7 @@ use std::iter::Peekable;
 pub struct CompactionStream<I: Iterator<Item = crate::Result<InternalValue>>> {
     inner: Peekable<I>,
     gc_seqno_threshold: SeqNo,
+    snapshot_watermark: Option<SeqNo>,
 }
 
 impl<I: Iterator<Item = crate::Result<InternalValue>>> CompactionStream<I> {
@@ -23,9 +24,20 @@ impl<I: Iterator<Item = crate::Result<InternalValue>>> CompactionStream<I> {
         Self {
             inner: iter,
             gc_seqno_threshold,
+            snapshot_watermark: None,
         }
     }
 
+    /// Sets the seqno of the oldest open snapshot.
+    ///
+    /// Older versions of a key are then only dropped if the version shadowing them
+    /// is visible to that snapshot, so no open snapshot loses the version it reads.
+    #[must_use]
+    pub fn with_snapshot_watermark(mut self, seqno: Option<SeqNo>) -> Self {
+        self.snapshot_watermark = seqno;
+        self
+    }
+
     fn drain_key_min(&mut self, key: &UserKey) -> crate::Result<()> {
         loop {
             let Some(next) = self.inner.peek() else {
@@ -42,6 +54,12 @@ impl<I: Iterator<Item = crate::Result<InternalValue>>> CompactionStream<I> {
                     .expect_err("should be error"));
             };
 
+            // IMPORTANT: Range tombstones also delete other keys, so they are never
+            // dropped because of a newer version of their start key
+            if next.key.is_range_tombstone() {
+                return Ok(());
+            }
+
             // Consume version
             if next.key.user_key == key {
                 // NOTE: We know the next value is not empty, because we just peeked it
@@ -77,11 +95,25 @@ impl<I: Iterator<Item = crate::Result<InternalValue>>> Iterator for CompactionSt
                     return Some(Ok(head));
                 }
 
-                if peeked.key.seqno < self.gc_seqno_threshold {
+                // NOTE: Older versions of a range tombstone's start key are deleted by the
+                // range tombstone, and are dropped by compaction just like the rest of its range
+                if head.key.is_range_tombstone() {
+                    return Some(Ok(head));
+                }
+
+                // NOTE: A snapshot reads the newest version below its seqno, so the tail
+                // can only be dropped if the head is visible to the oldest snapshot
+                let visible_to_snapshots = self
+                    .snapshot_watermark
+                    .map_or(true, |watermark| head.key.seqno < watermark);
+
+                if peeked.key.seqno < self.gc_seqno_threshold && visible_to_snapshots {
                     // NOTE: If next item is an actual value, and current value is weak tombstone,
                     // drop the tombstone
-                    let drop_weak_tombstone = peeked.key.value_type == ValueType::Value
-                        && head.key.value_type == ValueType::WeakTombstone;
+                    let drop_weak_tombstone = matches!(
+                        peeked.key.value_type,
+                        ValueType::Value | ValueType::ExpiringValue
+                    ) && head.key.value_type == ValueType::WeakTombstone;
 
                     // NOTE: Next item is expired,
                     // so the tail of this user key is entirely expired, so drain it all
@@ -429,4 +461,62 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn compaction_stream_snapshot_watermark() -> crate::Result<()> {
+        #[rustfmt::skip]
+        let vec = stream![
+          "a", "newnew", "V",
+          "a", "new", "V",
+          "a", "old", "V",
+        ];
+
+        // NOTE: A snapshot at seqno 999 reads a@998, so only a@997 may be dropped
+        let iter = vec.iter().cloned().map(Ok);
+        let mut iter = CompactionStream::new(iter, SeqNo::MAX).with_snapshot_watermark(Some(999));
+
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"newnew", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"new", 998, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        iter_closed!(iter);
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn compaction_stream_keep_range_tombstone() -> crate::Result<()> {
+        let vec = vec![
+            InternalValue::from_components(*b"a", *b"new", 3, ValueType::Value),
+            InternalValue::new_range_tombstone(*b"a", *b"z", 2),
+            InternalValue::from_components(*b"a", *b"old", 1, ValueType::Value),
+        ];
+
+        // NOTE: The range tombstone also deletes other keys, so it is kept,
+        // and the versions below it are left to the range tombstone
+        let iter = vec.iter().cloned().map(Ok);
+        let mut iter = CompactionStream::new(iter, SeqNo::MAX);
+
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"new", 3, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::new_range_tombstone(*b"a", *b"z", 2),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"old", 1, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        iter_closed!(iter);
+
+        Ok(())
+    }
 }
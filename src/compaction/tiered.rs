@@ -5,8 +5,10 @@
 use super::{Choice, CompactionStrategy, Input as CompactionInput};
 use crate::{level_manifest::LevelManifest, Config, HashSet, Segment};
 
-fn desired_level_size_in_bytes(level_idx: u8, ratio: u8, base_size: u32) -> usize {
-    (ratio as usize).pow(u32::from(level_idx + 1)) * (base_size as usize)
+fn desired_level_size_in_bytes(level_idx: u8, ratio: u8, base_size: u32) -> u64 {
+    u64::from(ratio)
+        .saturating_pow(u32::from(level_idx) + 1)
+        .saturating_mul(u64::from(base_size))
 }
 
 /// Size-tiered compaction strategy (STCS)
@@ -80,11 +82,10 @@ impl CompactionStrategy for Strategy {
                 // otherwise we may be overcompensating
                 .filter(|x| !levels.hidden_set().is_hidden(x.id()))
                 .map(|x| x.metadata.file_size)
-                .sum();
+                .fold(0, u64::saturating_add);
 
             let desired_bytes =
-                desired_level_size_in_bytes(curr_level_index, self.level_ratio, self.base_size)
-                    as u64;
+                desired_level_size_in_bytes(curr_level_index, self.level_ratio, self.base_size);
 
             if level_size >= desired_bytes {
                 // NOTE: Take desired_bytes because we are in tiered mode
@@ -130,6 +131,7 @@ impl CompactionStrategy for Strategy {
                     segment_ids,
                     dest_level: next_level_index,
                     target_size: u64::MAX,
+                    prefix_len: None,
                 });
             }
         }
@@ -218,6 +220,12 @@ mod tests {
 
             bloom_filter: Some(BloomFilter::with_fp_rate(1, 0.1)),
 
+            prefix_filter: None,
+
+            range_tombstones: Vec::new(),
+
+            verify_checksums: false,
+
             path: "a".into(),
             is_deleted: AtomicBool::default(),
         }
@@ -272,6 +280,7 @@ mod tests {
                 dest_level: 1,
                 segment_ids: set![1, 2, 3, 4],
                 target_size: u64::MAX,
+                prefix_len: None,
             })
         );
 
@@ -301,6 +310,7 @@ mod tests {
                 dest_level: 1,
                 segment_ids: set![1, 2],
                 target_size: u64::MAX,
+                prefix_len: None,
             })
         );
 
@@ -334,6 +344,7 @@ mod tests {
                 dest_level: 2,
                 segment_ids: set![5, 6, 7, 8],
                 target_size: u64::MAX,
+                prefix_len: None,
             })
         );
 
@@ -362,6 +373,7 @@ mod tests {
                 dest_level: 1,
                 segment_ids: set![1, 2],
                 target_size: u64::MAX,
+                prefix_len: None,
             })
         );
 
@@ -390,6 +402,7 @@ mod tests {
                 dest_level: 2,
                 segment_ids: set![2, 3],
                 target_size: u64::MAX,
+                prefix_len: None,
             })
         );
 
@@ -405,6 +418,7 @@ mod tests {
                 dest_level: 3,
                 segment_ids: set![2, 3],
                 target_size: u64::MAX,
+                prefix_len: None,
             })
         );
 
@@ -0,0 +1,440 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{Choice, CompactionStrategy, Input as CompactionInput};
+use crate::{level_manifest::LevelManifest, Config, HashSet, Segment};
+
+/// Universal (size-tiered) compaction strategy
+///
+/// All segments live in L0 and a single tier level (L1). Segments of similar size
+/// are merged into a larger segment, once there are at least `min_merge_width` of them.
+///
+/// Unlike [`super::SizeTiered`], this does not move data through the levels,
+/// so every item is rewritten roughly once per size tier, which results in little
+/// write amplification, at the cost of higher read and space amplification.
+///
+/// Only segments that are adjacent in age are merged, so newer versions of a key
+/// are always found before older ones. Tombstones are never evicted, because
+/// the tier level is not the last level; use a major compaction to get rid of them.
+///
+/// The strategy expects all other levels to be empty, so it should not be used
+/// on a tree that was compacted with another strategy before.
+#[derive(Clone)]
+pub struct Strategy {
+    /// Lowest size of a segment, relative to the average size of a group, to still be merged with it
+    pub min_size_ratio: f32,
+
+    /// Highest size of a segment, relative to the average size of a group, to still be merged with it
+    pub max_size_ratio: f32,
+
+    /// Minimum amount of segments that are merged at once
+    pub min_merge_width: usize,
+
+    /// Maximum amount of segments that are merged at once
+    ///
+    /// If L0 has more segments than this, its oldest segments are merged
+    /// regardless of their sizes, to limit read amplification.
+    pub max_merge_width: usize,
+}
+
+impl Strategy {
+    /// Creates a new universal compaction strategy.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the ratios are not `0 < min_size_ratio <= 1 <= max_size_ratio`,
+    /// or the merge widths are not `2 <= min_merge_width <= max_merge_width`.
+    #[must_use]
+    pub fn new(
+        min_size_ratio: f32,
+        max_size_ratio: f32,
+        min_merge_width: usize,
+        max_merge_width: usize,
+    ) -> Self {
+        assert!(min_size_ratio > 0.0 && min_size_ratio <= 1.0);
+        assert!(max_size_ratio >= 1.0);
+        assert!(min_merge_width >= 2 && min_merge_width <= max_merge_width);
+
+        Self {
+            min_size_ratio,
+            max_size_ratio,
+            min_merge_width,
+            max_merge_width,
+        }
+    }
+
+    /// Returns `true` if the segment is close enough in size to the group to be merged with it.
+    fn is_similar_size(&self, group_size: u64, group_len: usize, segment_size: u64) -> bool {
+        // NOTE: Precision loss is fine, sizes only need to be roughly similar
+        #[allow(clippy::cast_precision_loss)]
+        let avg_size = group_size as f64 / group_len as f64;
+
+        #[allow(clippy::cast_precision_loss)]
+        let segment_size = segment_size as f64;
+
+        segment_size >= avg_size * f64::from(self.min_size_ratio)
+            && segment_size <= avg_size * f64::from(self.max_size_ratio)
+    }
+}
+
+impl Default for Strategy {
+    fn default() -> Self {
+        Self {
+            min_size_ratio: 0.5,
+            max_size_ratio: 1.5,
+            min_merge_width: 4,
+            max_merge_width: 32,
+        }
+    }
+}
+
+impl CompactionStrategy for Strategy {
+    fn get_name(&self) -> &'static str {
+        "UniversalStrategy"
+    }
+
+    fn choose(&self, levels: &LevelManifest, _: &Config) -> Choice {
+        let by_age = |level_idx: usize| {
+            let mut segments = levels
+                .levels
+                .get(level_idx)
+                .map(|level| level.segments.clone())
+                .unwrap_or_default();
+
+            // NOTE: A disjoint level is sorted by key range, so sort it from newest to oldest
+            segments.sort_by_key(|segment| std::cmp::Reverse(segment.metadata.seqnos.1));
+            segments
+        };
+
+        let first_level = by_age(0);
+        let tier_level = by_age(1);
+
+        // NOTE: All segments from newest to oldest, the tier level is older than L0
+        let runs = first_level.iter().chain(&tier_level).collect::<Vec<_>>();
+        let is_hidden = |segment: &Segment| levels.hidden_set().is_hidden(segment.id());
+
+        let merge = |window: &[&Segment]| {
+            // IMPORTANT: Only move segments into the tier level if there is no older
+            // segment left in L0, or the older segment would shadow the merged ones
+            let oldest_in_first_level = first_level.last().map(Segment::id);
+
+            let dest_level = u8::from(window.iter().any(|segment| {
+                Some(segment.id()) == oldest_in_first_level
+                    || tier_level.iter().any(|x| x.id() == segment.id())
+            }));
+
+            Choice::Merge(CompactionInput {
+                segment_ids: window.iter().map(|x| x.id()).collect::<HashSet<_>>(),
+                dest_level,
+                target_size: u64::MAX,
+                prefix_len: None,
+            })
+        };
+
+        for start in 0..runs.len() {
+            let Some(first) = runs.get(start) else {
+                break;
+            };
+
+            if is_hidden(first) {
+                continue;
+            }
+
+            let mut group_size = first.metadata.file_size;
+            let mut end = start + 1;
+
+            while let Some(segment) = runs.get(end) {
+                let group_len = end - start;
+
+                if group_len >= self.max_merge_width
+                    || is_hidden(segment)
+                    || !self.is_similar_size(group_size, group_len, segment.metadata.file_size)
+                {
+                    break;
+                }
+
+                group_size += segment.metadata.file_size;
+                end += 1;
+            }
+
+            if let Some(window) = runs.get(start..end) {
+                if window.len() >= self.min_merge_width {
+                    return merge(window);
+                }
+            }
+        }
+
+        // NOTE: Too many segments of different sizes pile up in L0,
+        // so merge its oldest segments into the tier level
+        if first_level.len() > self.max_merge_width {
+            let oldest = first_level
+                .iter()
+                .rev()
+                .take_while(|x| !is_hidden(x))
+                .take(self.max_merge_width)
+                .collect::<Vec<_>>();
+
+            if oldest.len() >= self.min_merge_width {
+                return merge(&oldest);
+            }
+        }
+
+        Choice::DoNothing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Strategy;
+    use crate::{
+        bloom::BloomFilter,
+        cache::Cache,
+        compaction::{Choice, CompactionStrategy, Input as CompactionInput},
+        config::Config,
+        descriptor_table::FileDescriptorTable,
+        file::LEVELS_MANIFEST_FILE,
+        level_manifest::LevelManifest,
+        segment::{
+            block::offset::BlockOffset,
+            block_index::{two_level_index::TwoLevelBlockIndex, BlockIndexImpl},
+            file_offsets::FileOffsets,
+            meta::{Metadata, SegmentId},
+            Segment, SegmentInner,
+        },
+        HashSet, KeyRange, SeqNo,
+    };
+    use std::sync::{atomic::AtomicBool, Arc};
+    use test_log::test;
+
+    #[allow(clippy::expect_used)]
+    fn fixture_segment(id: SegmentId, size_mib: u64, max_seqno: SeqNo) -> Segment {
+        let cache = Arc::new(Cache::with_capacity_bytes(10 * 1_024 * 1_024));
+
+        let block_index = TwoLevelBlockIndex::new((0, id).into(), cache.clone());
+        let block_index = Arc::new(BlockIndexImpl::TwoLevel(block_index));
+
+        SegmentInner {
+            tree_id: 0,
+            descriptor_table: Arc::new(FileDescriptorTable::new(512, 1)),
+            block_index,
+
+            offsets: FileOffsets {
+                bloom_ptr: BlockOffset(0),
+                range_filter_ptr: BlockOffset(0),
+                index_block_ptr: BlockOffset(0),
+                metadata_ptr: BlockOffset(0),
+                range_tombstones_ptr: BlockOffset(0),
+                tli_ptr: BlockOffset(0),
+                pfx_ptr: BlockOffset(0),
+            },
+
+            metadata: Metadata {
+                data_block_count: 0,
+                index_block_count: 0,
+                data_block_size: 4_096,
+                index_block_size: 4_096,
+                created_at: 0,
+                id,
+                file_size: size_mib * 1_024 * 1_024,
+                compression: crate::segment::meta::CompressionType::None,
+                table_type: crate::segment::meta::TableType::Block,
+                item_count: 0,
+                key_count: 0,
+                key_range: KeyRange::new((vec![].into(), vec![].into())),
+                tombstone_count: 0,
+                range_tombstone_count: 0,
+                uncompressed_size: size_mib * 1_024 * 1_024,
+                seqnos: (0, max_seqno),
+            },
+            cache,
+
+            bloom_filter: Some(BloomFilter::with_fp_rate(1, 0.1)),
+
+            prefix_filter: None,
+
+            range_tombstones: Vec::new(),
+
+            verify_checksums: false,
+
+            path: "a".into(),
+            is_deleted: AtomicBool::default(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn universal_empty_levels() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+
+        let compactor = Strategy::default();
+        let levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
+
+        assert_eq!(
+            compactor.choose(&levels, &Config::default()),
+            Choice::DoNothing
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn universal_similar_sizes() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+
+        let compactor = Strategy::default();
+        let config = Config::default();
+
+        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
+
+        levels.add(fixture_segment(1, 8, 5));
+        levels.add(fixture_segment(2, 10, 6));
+        levels.add(fixture_segment(3, 7, 7));
+        assert_eq!(compactor.choose(&levels, &config), Choice::DoNothing);
+
+        levels.add(fixture_segment(4, 9, 8));
+
+        assert_eq!(
+            compactor.choose(&levels, &config),
+            Choice::Merge(CompactionInput {
+                dest_level: 1,
+                segment_ids: set![1, 2, 3, 4],
+                target_size: u64::MAX,
+                prefix_len: None,
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn universal_dissimilar_sizes() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+
+        let compactor = Strategy::default();
+        let config = Config::default();
+
+        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
+
+        levels.add(fixture_segment(1, 512, 5));
+        levels.add(fixture_segment(2, 64, 6));
+        levels.add(fixture_segment(3, 8, 7));
+        levels.add(fixture_segment(4, 1, 8));
+
+        assert_eq!(compactor.choose(&levels, &config), Choice::DoNothing);
+
+        Ok(())
+    }
+
+    #[test]
+    fn universal_newer_group_stays_in_l0() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+
+        let compactor = Strategy::default();
+        let config = Config::default();
+
+        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
+
+        levels.add(fixture_segment(1, 512, 5));
+        levels.add(fixture_segment(2, 8, 6));
+        levels.add(fixture_segment(3, 8, 7));
+        levels.add(fixture_segment(4, 8, 8));
+        levels.add(fixture_segment(5, 8, 9));
+
+        assert_eq!(
+            compactor.choose(&levels, &config),
+            Choice::Merge(CompactionInput {
+                dest_level: 0,
+                segment_ids: set![2, 3, 4, 5],
+                target_size: u64::MAX,
+                prefix_len: None,
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn universal_merge_with_tier_level() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+
+        let compactor = Strategy::default();
+        let config = Config::default();
+
+        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
+
+        levels.insert_into_level(1, fixture_segment(1, 8, 1));
+        levels.insert_into_level(1, fixture_segment(2, 8, 2));
+        levels.insert_into_level(1, fixture_segment(3, 8, 3));
+        assert_eq!(compactor.choose(&levels, &config), Choice::DoNothing);
+
+        levels.add(fixture_segment(4, 8, 4));
+
+        assert_eq!(
+            compactor.choose(&levels, &config),
+            Choice::Merge(CompactionInput {
+                dest_level: 1,
+                segment_ids: set![1, 2, 3, 4],
+                target_size: u64::MAX,
+                prefix_len: None,
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn universal_max_merge_width() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+
+        let compactor = Strategy::new(0.5, 1.5, 2, 3);
+        let config = Config::default();
+
+        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
+
+        levels.add(fixture_segment(1, 8, 5));
+        levels.add(fixture_segment(2, 8, 6));
+        levels.add(fixture_segment(3, 8, 7));
+        levels.add(fixture_segment(4, 8, 8));
+        levels.add(fixture_segment(5, 8, 9));
+
+        assert_eq!(
+            compactor.choose(&levels, &config),
+            Choice::Merge(CompactionInput {
+                dest_level: 0,
+                segment_ids: set![3, 4, 5],
+                target_size: u64::MAX,
+                prefix_len: None,
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn universal_too_many_l0_segments() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+
+        let compactor = Strategy::new(0.9, 1.1, 2, 3);
+        let config = Config::default();
+
+        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
+
+        levels.add(fixture_segment(1, 1, 5));
+        levels.add(fixture_segment(2, 4, 6));
+        levels.add(fixture_segment(3, 16, 7));
+        levels.add(fixture_segment(4, 64, 8));
+
+        assert_eq!(
+            compactor.choose(&levels, &config),
+            Choice::Merge(CompactionInput {
+                dest_level: 1,
+                segment_ids: set![1, 2, 3],
+                target_size: u64::MAX,
+                prefix_len: None,
+            })
+        );
+
+        Ok(())
+    }
+}
@@ -4,11 +4,18 @@
 
 use super::{CompactionStrategy, Input as CompactionPayload};
 use crate::{
-    compaction::{stream::CompactionStream, Choice},
+    compaction::{
+        progress::{CompactionProgress, ProgressCallback, PROGRESS_INTERVAL},
+        retry,
+        stats::{CompactionListener, CompactionStats, DroppedBlobsListener},
+        stream::CompactionStream,
+        Choice,
+    },
     file::SEGMENTS_FOLDER,
     level_manifest::LevelManifest,
     level_scanner::LevelScanner,
     merge::Merger,
+    range_tombstone::{is_covered, RangeTombstone},
     segment::{
         block_index::{
             full_index::FullBlockIndex, two_level_index::TwoLevelBlockIndex, BlockIndexImpl,
@@ -18,15 +25,16 @@ use crate::{
         scanner::CompactionReader,
         Segment, SegmentInner,
     },
+    snapshot::SnapshotTracker,
     stop_signal::StopSignal,
     tree::inner::TreeId,
-    Config, SegmentId, SeqNo,
+    Config, HashSet, InternalValue, KeyRange, SegmentId, SeqNo,
 };
 use std::{
     path::Path,
     sync::{
         atomic::{AtomicBool, AtomicU64},
-        Arc, RwLock, RwLockWriteGuard,
+        Arc, Mutex, RwLock, RwLockWriteGuard,
     },
     time::Instant,
 };
@@ -52,6 +60,18 @@ pub struct Options {
 
     /// Evicts items that are older than this seqno (MVCC GC).
     pub eviction_seqno: u64,
+
+    /// Open snapshots, whose versions are kept regardless of `eviction_seqno`.
+    pub open_snapshots: SnapshotTracker,
+
+    /// Receives progress updates while segments are merged.
+    pub progress: Option<ProgressCallback>,
+
+    /// Receives the statistics of the compaction once it has completed.
+    pub on_compaction_complete: Option<CompactionListener>,
+
+    /// Receives the blobs of range-deleted values that were dropped (blob trees only).
+    pub on_blobs_dropped: Option<DroppedBlobsListener>,
 }
 
 impl Options {
@@ -64,6 +84,10 @@ impl Options {
             stop_signal: tree.stop_signal.clone(),
             strategy,
             eviction_seqno: 0,
+            open_snapshots: tree.open_snapshots.clone(),
+            progress: None,
+            on_compaction_complete: tree.config.on_compaction_complete.clone(),
+            on_blobs_dropped: tree.on_blobs_dropped.get().cloned(),
         }
     }
 }
@@ -75,6 +99,10 @@ pub fn do_compaction(opts: &Options) -> crate::Result<()> {
     log::trace!("compactor: acquiring levels manifest lock");
     let original_levels = opts.levels.write().expect("lock is poisoned");
 
+    if opts.config.compaction_threads > 1 {
+        return do_concurrent_compaction(original_levels, opts);
+    }
+
     log::trace!(
         "compactor: consulting compaction strategy {:?}",
         opts.strategy.get_name(),
@@ -83,6 +111,14 @@ pub fn do_compaction(opts: &Options) -> crate::Result<()> {
 
     log::debug!("compactor: choice: {choice:?}");
 
+    run_choice(original_levels, opts, choice)
+}
+
+fn run_choice(
+    original_levels: RwLockWriteGuard<'_, LevelManifest>,
+    opts: &Options,
+    choice: Choice,
+) -> crate::Result<()> {
     match choice {
         Choice::Merge(payload) => merge_segments(original_levels, opts, &payload),
         Choice::Move(payload) => move_segments(original_levels, opts, payload),
@@ -101,11 +137,224 @@ pub fn do_compaction(opts: &Options) -> crate::Result<()> {
     }
 }
 
+/// Stand-in for the compaction strategy of a job that runs on another thread
+///
+/// Strategies are not required to be `Send`, but once a job is chosen,
+/// only the strategy's name is needed anymore.
+struct ChosenBy(&'static str);
+
+impl CompactionStrategy for ChosenBy {
+    fn get_name(&self) -> &'static str {
+        self.0
+    }
+
+    fn choose(&self, _: &LevelManifest, _: &Config) -> Choice {
+        Choice::DoNothing
+    }
+}
+
+/// Segments, levels and key range that a compaction job touches
+struct Footprint {
+    segment_ids: HashSet<SegmentId>,
+    levels: HashSet<u8>,
+    key_range: KeyRange,
+}
+
+impl Footprint {
+    fn new(levels: &LevelManifest, choice: &Choice) -> Option<Self> {
+        let (segment_ids, dest_level) = match choice {
+            Choice::Merge(payload) | Choice::Move(payload) => {
+                (&payload.segment_ids, Some(payload.dest_level))
+            }
+            Choice::Drop(segment_ids) => (segment_ids, None),
+            Choice::DoNothing => return None,
+        };
+
+        let mut touched_levels = HashSet::default();
+        touched_levels.extend(dest_level);
+
+        let mut key_ranges = vec![];
+
+        for (idx, level) in levels.levels.iter().enumerate() {
+            for segment in level.iter().filter(|x| segment_ids.contains(&x.id())) {
+                // NOTE: Level count is u8
+                #[allow(clippy::cast_possible_truncation)]
+                touched_levels.insert(idx as u8);
+
+                key_ranges.push(&segment.metadata.key_range);
+            }
+        }
+
+        Some(Self {
+            segment_ids: segment_ids.clone(),
+            levels: touched_levels,
+            key_range: KeyRange::aggregate(key_ranges.into_iter()),
+        })
+    }
+
+    /// Returns `true` if both jobs may not run at the same time.
+    ///
+    /// Jobs that write into the same key range of a level could create overlapping
+    /// segments in a disjoint level, or move older versions of a key above newer ones.
+    fn conflicts_with(&self, other: &Self) -> bool {
+        !self.segment_ids.is_disjoint(&other.segment_ids)
+            || (!self.levels.is_disjoint(&other.levels)
+                && self.key_range.overlaps_with_key_range(&other.key_range))
+    }
+}
+
+/// Runs the independent compaction jobs chosen by the strategy in parallel,
+/// see [`Config::compaction_threads`].
+///
+/// The level manifest is only locked while choosing the jobs; every job
+/// locks it again to hide (and later replace) its segments.
+fn do_concurrent_compaction(
+    original_levels: RwLockWriteGuard<'_, LevelManifest>,
+    opts: &Options,
+) -> crate::Result<()> {
+    log::trace!(
+        "compactor: consulting compaction strategy {:?} for concurrent jobs",
+        opts.strategy.get_name(),
+    );
+    let choices = opts.strategy.choose_many(&original_levels, &opts.config);
+
+    // IMPORTANT: Overlapping jobs are serialized, by leaving them to the next compaction
+    let mut jobs: Vec<(Footprint, Choice)> = vec![];
+
+    for choice in choices {
+        let Some(footprint) = Footprint::new(&original_levels, &choice) else {
+            continue;
+        };
+
+        if jobs
+            .iter()
+            .any(|(other, _)| other.conflicts_with(&footprint))
+        {
+            log::debug!("compactor: deferring conflicting job: {choice:?}");
+            continue;
+        }
+
+        jobs.push((footprint, choice));
+    }
+
+    drop(original_levels);
+
+    log::debug!(
+        "compactor: running {} jobs: {:?}",
+        jobs.len(),
+        jobs.iter().map(|(_, choice)| choice).collect::<Vec<_>>(),
+    );
+
+    if jobs.is_empty() {
+        log::trace!("Compactor chose to do nothing");
+        return Ok(());
+    }
+
+    let strategy_name = opts.strategy.get_name();
+    let thread_count = opts.config.compaction_threads.min(jobs.len());
+    let queue = Mutex::new(
+        jobs.into_iter()
+            .map(|(footprint, choice)| (footprint.segment_ids, choice)),
+    );
+
+    let results = std::thread::scope(|scope| {
+        let handles = (0..thread_count)
+            .map(|_| {
+                let job_opts = JobOptions::from(opts);
+                let queue = &queue;
+
+                scope.spawn(move || -> crate::Result<()> {
+                    let opts = job_opts.into_options(strategy_name);
+
+                    loop {
+                        let Some((segment_ids, choice)) =
+                            queue.lock().expect("lock is poisoned").next()
+                        else {
+                            return Ok(());
+                        };
+
+                        let levels = opts.levels.write().expect("lock is poisoned");
+
+                        // NOTE: Another compaction may have taken the segments
+                        // after the manifest lock was released
+                        if levels.should_decline_compaction(segment_ids.iter().copied()) {
+                            log::debug!(
+                                "compactor: segments of job were taken by another compaction, skipping it"
+                            );
+                            continue;
+                        }
+
+                        run_choice(levels, &opts, choice)?;
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("compaction thread should not panic"))
+            .collect::<Vec<_>>()
+    });
+
+    results.into_iter().collect()
+}
+
+/// Compaction options without the strategy, which can be sent to another thread
+struct JobOptions {
+    tree_id: TreeId,
+    segment_id_generator: Arc<AtomicU64>,
+    config: Config,
+    levels: Arc<RwLock<LevelManifest>>,
+    stop_signal: StopSignal,
+    eviction_seqno: u64,
+    open_snapshots: SnapshotTracker,
+    progress: Option<ProgressCallback>,
+    on_compaction_complete: Option<CompactionListener>,
+    on_blobs_dropped: Option<DroppedBlobsListener>,
+}
+
+impl From<&Options> for JobOptions {
+    fn from(opts: &Options) -> Self {
+        Self {
+            tree_id: opts.tree_id,
+            segment_id_generator: opts.segment_id_generator.clone(),
+            config: opts.config.clone(),
+            levels: opts.levels.clone(),
+            stop_signal: opts.stop_signal.clone(),
+            eviction_seqno: opts.eviction_seqno,
+            open_snapshots: opts.open_snapshots.clone(),
+            progress: opts.progress.clone(),
+            on_compaction_complete: opts.on_compaction_complete.clone(),
+            on_blobs_dropped: opts.on_blobs_dropped.clone(),
+        }
+    }
+}
+
+impl JobOptions {
+    fn into_options(self, strategy_name: &'static str) -> Options {
+        Options {
+            tree_id: self.tree_id,
+            segment_id_generator: self.segment_id_generator,
+            config: self.config,
+            levels: self.levels,
+            strategy: Arc::new(ChosenBy(strategy_name)),
+            stop_signal: self.stop_signal,
+            eviction_seqno: self.eviction_seqno,
+            open_snapshots: self.open_snapshots,
+            progress: self.progress,
+            on_compaction_complete: self.on_compaction_complete,
+            on_blobs_dropped: self.on_blobs_dropped,
+        }
+    }
+}
+
 fn create_compaction_stream<'a>(
     segment_base_folder: &Path,
     levels: &LevelManifest,
     to_compact: &[SegmentId],
     eviction_seqno: SeqNo,
+    snapshot_watermark: Option<SeqNo>,
+    items_read: Option<&Arc<AtomicU64>>,
 ) -> crate::Result<Option<CompactionStream<Merger<CompactionReader<'a>>>>> {
     let mut readers: Vec<CompactionReader<'_>> = vec![];
     let mut found = 0;
@@ -155,8 +404,25 @@ fn create_compaction_stream<'a>(
         }
     }
 
+    // NOTE: Count input items for progress reporting
+    if let Some(items_read) = items_read {
+        readers = readers
+            .into_iter()
+            .map(|reader| {
+                let items_read = items_read.clone();
+
+                Box::new(reader.inspect(move |_| {
+                    items_read.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                })) as CompactionReader<'a>
+            })
+            .collect();
+    }
+
     Ok(if found == to_compact.len() {
-        Some(CompactionStream::new(Merger::new(readers), eviction_seqno))
+        Some(
+            CompactionStream::new(Merger::new(readers), eviction_seqno)
+                .with_snapshot_watermark(snapshot_watermark),
+        )
     } else {
         None
     })
@@ -190,15 +456,57 @@ fn move_segments(
     })
 }
 
-#[allow(clippy::too_many_lines)]
+/// Merges segments, retrying transient IO errors, see [`Config::compaction_retries`].
+///
+/// Compactions that fail otherwise are logged and given up on, the input
+/// segments stay in place.
 fn merge_segments(
-    mut levels: RwLockWriteGuard<'_, LevelManifest>,
+    levels: RwLockWriteGuard<'_, LevelManifest>,
     opts: &Options,
     payload: &CompactionPayload,
 ) -> crate::Result<()> {
+    let mut levels = levels;
+    let mut backoff = opts.config.compaction_retry_backoff;
+    let mut attempt = 0;
+
+    loop {
+        let (error, propagate) = match try_merge_segments(levels, opts, payload) {
+            Ok(None) => return Ok(()),
+            Ok(Some(e)) => (e, false),
+            Err(e) => (e, true),
+        };
+
+        if attempt >= opts.config.compaction_max_retries || !error.is_transient() {
+            log::error!("Compaction failed: {error:?}");
+            return if propagate { Err(error) } else { Ok(()) };
+        }
+
+        log::warn!(
+            "Compaction failed with transient error, retrying in {backoff:?} ({}/{}): {error:?}",
+            attempt + 1,
+            opts.config.compaction_max_retries,
+        );
+
+        if !retry::sleep_unless_stopped(backoff, &opts.stop_signal) {
+            log::debug!("Stopping compaction retries because of stop signal");
+            return Ok(());
+        }
+        attempt += 1;
+        backoff = backoff.saturating_mul(2).min(retry::MAX_BACKOFF);
+
+        levels = opts.levels.write().expect("lock is poisoned");
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+fn try_merge_segments(
+    mut levels: RwLockWriteGuard<'_, LevelManifest>,
+    opts: &Options,
+    payload: &CompactionPayload,
+) -> crate::Result<Option<crate::Error>> {
     if opts.stop_signal.is_stopped() {
         log::debug!("Stopping before compaction because of stop signal");
-        return Ok(());
+        return Ok(None);
     }
 
     // Fail-safe for buggy compaction strategies
@@ -207,7 +515,7 @@ fn merge_segments(
             "Compaction task created by {:?} contained hidden segments, declining to run it - please report this at https://github.com/fjall-rs/lsm-tree/issues/new?template=bug_report.md",
             opts.strategy.get_name(),
         );
-        return Ok(());
+        return Ok(None);
     }
 
     let Some(segments) = payload
@@ -220,7 +528,7 @@ fn merge_segments(
             "Compaction task created by {:?} contained segments not referenced in the level manifest",
             opts.strategy.get_name(),
         );
-        return Ok(());
+        return Ok(None);
     };
 
     let segments_base_folder = opts.config.path.join(SEGMENTS_FOLDER);
@@ -229,38 +537,95 @@ fn merge_segments(
         "Compacting segments {:?} into L{}, compression={}, mvcc_gc_watermark={}",
         payload.segment_ids,
         payload.dest_level,
-        opts.config.compression,
+        opts.config.compression_for_level(payload.dest_level),
         opts.eviction_seqno,
     );
 
+    let items_read = opts
+        .progress
+        .as_ref()
+        .map(|_| Arc::new(AtomicU64::default()));
+
     let Some(merge_iter) = create_compaction_stream(
         &segments_base_folder,
         &levels,
         &payload.segment_ids.iter().copied().collect::<Vec<_>>(),
         opts.eviction_seqno,
+        opts.open_snapshots.oldest(),
+        items_read.as_ref(),
     )?
     else {
         log::warn!(
             "Compaction task tried to compact segments that do not exist, declining to run it"
         );
-        return Ok(());
+        return Ok(None);
     };
 
     let last_level = levels.last_level_index();
 
+    // NOTE: Only evict tombstones when reaching the last level,
+    // That way we don't resurrect data beneath the tombstone
+    let is_last_level = payload.dest_level == last_level;
+
+    let snapshot_watermark = opts.open_snapshots.oldest();
+    let visible_to_snapshots =
+        |seqno: SeqNo| snapshot_watermark.map_or(true, |watermark| seqno < watermark);
+
+    // NOTE: Range tombstones may delete items of any other segment,
+    // so they can only be dropped when compacting into the last level,
+    // and no segment outside of the compaction holds older items in their range
+    let stale_range_tombstones = if is_last_level {
+        segments
+            .iter()
+            .flat_map(|segment| segment.range_tombstones.iter())
+            .filter(|rt| rt.seqno < opts.eviction_seqno && visible_to_snapshots(rt.seqno))
+            .filter(|rt| {
+                !levels.iter().any(|segment| {
+                    !payload.segment_ids.contains(&segment.id())
+                        && segment.metadata.seqnos.0 < rt.seqno
+                        && rt.overlaps(&segment.metadata.key_range)
+                })
+            })
+            .cloned()
+            .collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+
     levels.hide_segments(payload.segment_ids.iter().copied());
 
     // IMPORTANT: Free lock so the compaction (which may go on for a while)
     // does not block possible other compactions and reads
     drop(levels);
 
-    // NOTE: Only evict tombstones when reaching the last level,
-    // That way we don't resurrect data beneath the tombstone
-    let is_last_level = payload.dest_level == last_level;
-
     let start = Instant::now();
 
-    let Ok(segment_writer) = MultiWriter::new(
+    let input_bytes = segments
+        .iter()
+        .map(|x| x.metadata.file_size)
+        .fold(0, u64::saturating_add);
+
+    let mut progress = CompactionProgress::new(
+        segments
+            .iter()
+            .map(|x| x.metadata.item_count)
+            .fold(0, u64::saturating_add),
+        input_bytes,
+    );
+    let mut items_written = 0;
+
+    let report_progress = |progress: &mut CompactionProgress, items_written: u64| {
+        if let (Some(callback), Some(items_read)) = (&opts.progress, &items_read) {
+            progress.update(
+                items_read.load(std::sync::atomic::Ordering::Relaxed),
+                items_written,
+                start.elapsed(),
+            );
+            callback(progress);
+        }
+    };
+
+    let segment_writer = match MultiWriter::new(
         opts.segment_id_generator.clone(),
         payload.target_size,
         crate::segment::writer::Options {
@@ -268,25 +633,34 @@ fn merge_segments(
             segment_id: 0, // TODO: this is never used in MultiWriter
             data_block_size: opts.config.data_block_size,
             index_block_size: opts.config.index_block_size,
+            target_item_count: None,
         },
-    ) else {
-        log::error!("Compaction failed");
-
-        // IMPORTANT: Show the segments again, because compaction failed
-        opts.levels
-            .write()
-            .expect("lock is poisoned")
-            .show_segments(payload.segment_ids.iter().copied());
+    ) {
+        Ok(writer) => writer,
+        Err(e) => {
+            // IMPORTANT: Show the segments again, because compaction failed
+            opts.levels
+                .write()
+                .expect("lock is poisoned")
+                .show_segments(payload.segment_ids.iter().copied());
 
-        return Ok(());
+            return Ok(Some(e));
+        }
     };
 
-    let mut segment_writer = segment_writer.use_compression(opts.config.compression);
+    let mut segment_writer = segment_writer
+        .use_compression(opts.config.compression_for_level(payload.dest_level))
+        .use_checksum_type(opts.config.checksum_type)
+        .use_index_start_keys(opts.config.index_start_keys)
+        .use_value_alignment(opts.config.value_alignment)
+        .use_prefix_extractor(opts.config.bloom_prefix_extractor.clone())
+        .use_bloom_hasher(opts.config.bloom_hasher.clone())
+        .use_prefix_len(payload.prefix_len.or(opts.config.partition_prefix_len));
 
     {
         use crate::segment::writer::BloomConstructionPolicy;
 
-        if opts.config.bloom_bits_per_key >= 0 {
+        if opts.config.use_bloom_filter(payload.dest_level) {
             // NOTE: Apply some MONKEY to have very high FPR on small levels
             // because it's cheap
             //
@@ -300,58 +674,129 @@ fn merge_segments(
             };
 
             segment_writer = segment_writer.use_bloom_policy(bloom_policy);
+
+            // NOTE: If all input filters are compatible, combining them is much cheaper
+            // than rebuilding the filter, the writer decides if the result is good enough
+            let mut filters = segments.iter().map(|x| x.bloom_filter.as_ref());
+            let bloom_union = filters.next().flatten().and_then(|first| {
+                filters.try_fold(first.clone(), |acc, filter| acc.union(filter?).ok())
+            });
+
+            segment_writer = segment_writer.use_bloom_union(bloom_union);
         } else {
             segment_writer =
                 segment_writer.use_bloom_policy(BloomConstructionPolicy::BitsPerKey(0));
         }
     }
 
-    for (idx, item) in merge_iter.enumerate() {
-        let Ok(item) = item else {
-            log::error!("Compaction failed");
+    // NOTE: Only range tombstones that every read can see may drop items,
+    // otherwise reads below the tombstone's seqno would lose the deleted versions
+    let range_tombstones = segments
+        .iter()
+        .flat_map(|segment| segment.range_tombstones.iter())
+        .filter(|rt| rt.seqno < opts.eviction_seqno && visible_to_snapshots(rt.seqno))
+        .cloned()
+        .collect::<Vec<_>>();
 
-            // IMPORTANT: Show the segments again, because compaction failed
-            opts.levels
-                .write()
-                .expect("lock is poisoned")
-                .show_segments(payload.segment_ids.iter().copied());
+    let mut pending_tombstone: Option<InternalValue> = None;
 
-            return Ok(());
+    // Stores the dead blob count and bytes of every blob file
+    let mut dropped_blobs = crate::HashMap::<u64, (u64, u64)>::default();
+
+    let now = crate::time::unix_timestamp();
+
+    for (idx, item) in merge_iter.enumerate() {
+        let item = match item {
+            Ok(item) => item,
+            Err(e) => {
+                segment_writer.discard();
+
+                // IMPORTANT: Show the segments again, because compaction failed
+                opts.levels
+                    .write()
+                    .expect("lock is poisoned")
+                    .show_segments(payload.segment_ids.iter().copied());
+
+                return Ok(Some(e));
+            }
         };
 
-        // IMPORTANT: We can only drop tombstones when writing into last level
-        if is_last_level && item.is_tombstone() {
+        // NOTE: Expired values become tombstones, instead of being dropped,
+        // so they keep shadowing older versions in deeper levels
+        let item = item.expire(now);
+
+        // NOTE: Items deleted by a range tombstone are dropped,
+        // once no snapshot can read them anymore
+        let is_range_deleted =
+            item.key.seqno < opts.eviction_seqno && is_covered(&range_tombstones, &item);
+
+        // NOTE: When everything a range tombstone deletes has been dropped, it can be dropped as well
+        let is_stale_range_tombstone =
+            RangeTombstone::from_item(&item).is_some_and(|rt| stale_range_tombstones.contains(&rt));
+
+        if is_range_deleted || is_stale_range_tombstone {
+            if is_range_deleted && opts.on_blobs_dropped.is_some() && !item.is_tombstone() {
+                collect_dropped_blob(&mut dropped_blobs, &item);
+            }
+
             continue;
         }
 
-        if segment_writer.write(item).is_err() {
-            log::error!("Compaction failed");
+        // IMPORTANT: We can only drop tombstones when writing into last level
+        //
+        // Also, older versions of the key may be kept (because of the GC watermark),
+        // so the tombstone can only be dropped if it is the oldest version,
+        // otherwise the older versions would be resurrected
+        let kept_tombstone = pending_tombstone
+            .take()
+            .filter(|tombstone| tombstone.key.user_key == item.key.user_key);
+
+        let item = if is_last_level && item.is_tombstone() && !item.key.is_range_tombstone() {
+            pending_tombstone = Some(item);
+            None
+        } else {
+            Some(item)
+        };
 
-            // IMPORTANT: Show the segments again, because compaction failed
-            opts.levels
-                .write()
-                .expect("lock is poisoned")
-                .show_segments(payload.segment_ids.iter().copied());
+        for item in kept_tombstone.into_iter().chain(item) {
+            if let Err(e) = segment_writer.write(item) {
+                segment_writer.discard();
 
-            return Ok(());
-        };
+                // IMPORTANT: Show the segments again, because compaction failed
+                opts.levels
+                    .write()
+                    .expect("lock is poisoned")
+                    .show_segments(payload.segment_ids.iter().copied());
+
+                return Ok(Some(e));
+            }
+
+            items_written += 1;
+        }
+
+        if (idx + 1) % PROGRESS_INTERVAL == 0 {
+            report_progress(&mut progress, items_written);
+        }
 
         if idx % 1_000_000 == 0 && opts.stop_signal.is_stopped() {
             log::debug!("compactor: stopping amidst compaction because of stop signal");
-            return Ok(());
+            return Ok(None);
         }
     }
 
-    let Ok(writer_results) = segment_writer.finish() else {
-        log::error!("Compaction failed");
+    report_progress(&mut progress, items_written);
 
-        // IMPORTANT: Show the segments again, because compaction failed
-        opts.levels
-            .write()
-            .expect("lock is poisoned")
-            .show_segments(payload.segment_ids.iter().copied());
+    let writer_results = match segment_writer.finish() {
+        Ok(results) => results,
+        Err(e) => {
+            // IMPORTANT: Show the segments again, because compaction failed
+            opts.levels
+                .write()
+                .expect("lock is poisoned")
+                .show_segments(payload.segment_ids.iter().copied());
 
-        return Ok(());
+            return Ok(Some(e));
+        }
     };
 
     log::debug!(
@@ -360,35 +805,32 @@ fn merge_segments(
         writer_results.len(),
     );
 
-    let Ok(created_segments) = writer_results
+    let created_segments = match writer_results
         .into_iter()
         .map(|trailer| -> crate::Result<Segment> {
             let segment_id = trailer.metadata.id;
             let segment_file_path = segments_base_folder.join(segment_id.to_string());
 
-            let block_index = match payload.dest_level {
-                0 | 1 => {
-                    let block_index = FullBlockIndex::from_file(
-                        &segment_file_path,
-                        &trailer.metadata,
-                        &trailer.offsets,
-                    )?;
-                    BlockIndexImpl::Full(block_index)
-                }
-                _ => {
-                    // NOTE: Need to allow because of false positive in Clippy
-                    // because of "bloom" feature
-                    #[allow(clippy::needless_borrows_for_generic_args)]
-                    let block_index = TwoLevelBlockIndex::from_file(
-                        &segment_file_path,
-                        &trailer.metadata,
-                        trailer.offsets.tli_ptr,
-                        (opts.tree_id, segment_id).into(),
-                        opts.config.descriptor_table.clone(),
-                        opts.config.cache.clone(),
-                    )?;
-                    BlockIndexImpl::TwoLevel(block_index)
-                }
+            let block_index = if opts.config.preloads_block_index(payload.dest_level) {
+                let block_index = FullBlockIndex::from_file(
+                    &segment_file_path,
+                    &trailer.metadata,
+                    &trailer.offsets,
+                )?;
+                BlockIndexImpl::Full(block_index)
+            } else {
+                // NOTE: Need to allow because of false positive in Clippy
+                // because of "bloom" feature
+                #[allow(clippy::needless_borrows_for_generic_args)]
+                let block_index = TwoLevelBlockIndex::from_file(
+                    &segment_file_path,
+                    &trailer.metadata,
+                    trailer.offsets.tli_ptr,
+                    (opts.tree_id, segment_id).into(),
+                    opts.config.descriptor_table.clone(),
+                    opts.config.cache.clone(),
+                )?;
+                BlockIndexImpl::TwoLevel(block_index)
             };
             let block_index = Arc::new(block_index);
 
@@ -407,22 +849,31 @@ fn merge_segments(
                 block_index,
 
                 bloom_filter: Segment::load_bloom(&segment_file_path, trailer.offsets.bloom_ptr)?,
+                prefix_filter: Segment::load_bloom(&segment_file_path, trailer.offsets.pfx_ptr)?,
+
+                range_tombstones: Segment::load_range_tombstones(
+                    &segment_file_path,
+                    trailer.offsets.range_tombstones_ptr,
+                )?,
+
+                verify_checksums: opts.config.verify_checksums,
 
                 is_deleted: AtomicBool::default(),
             }
             .into())
         })
         .collect::<crate::Result<Vec<_>>>()
-    else {
-        log::error!("Compaction failed");
-
-        // IMPORTANT: Show the segments again, because compaction failed
-        opts.levels
-            .write()
-            .expect("lock is poisoned")
-            .show_segments(payload.segment_ids.iter().copied());
+    {
+        Ok(segments) => segments,
+        Err(e) => {
+            // IMPORTANT: Show the segments again, because compaction failed
+            opts.levels
+                .write()
+                .expect("lock is poisoned")
+                .show_segments(payload.segment_ids.iter().copied());
 
-        return Ok(());
+            return Ok(Some(e));
+        }
     };
 
     // NOTE: Mind lock order L -> M -> S
@@ -468,7 +919,7 @@ fn merge_segments(
     // NOTE: If the application were to crash >here< it's fine
     // The segments are not referenced anymore, and will be
     // cleaned up upon recovery
-    for segment in segments {
+    for segment in &segments {
         segment.mark_as_deleted();
     }
 
@@ -477,7 +928,42 @@ fn merge_segments(
 
     log::trace!("Compaction successful");
 
-    Ok(())
+    if let Some(callback) = &opts.on_blobs_dropped {
+        if !dropped_blobs.is_empty() {
+            callback(&dropped_blobs);
+        }
+    }
+
+    if let Some(callback) = &opts.on_compaction_complete {
+        callback(CompactionStats {
+            input_segment_count: segments.len(),
+            input_bytes,
+            output_segment_count: created_segments.len(),
+            output_bytes: created_segments
+                .iter()
+                .map(|x| x.metadata.file_size)
+                .fold(0, u64::saturating_add),
+            elapsed: start.elapsed(),
+            dest_level: payload.dest_level,
+        });
+    }
+
+    Ok(None)
+}
+
+/// Adds the blob of a dropped index tree value to the dead blobs of its blob file
+fn collect_dropped_blob(dropped_blobs: &mut crate::HashMap<u64, (u64, u64)>, item: &InternalValue) {
+    use crate::{blob_tree::value::MaybeInlineValue, coding::Decode};
+
+    let mut reader = &item.value[..];
+
+    if let Ok(MaybeInlineValue::Indirect { vhandle, size }) =
+        MaybeInlineValue::decode_from(&mut reader)
+    {
+        let entry = dropped_blobs.entry(vhandle.segment_id).or_default();
+        entry.0 += 1;
+        entry.1 += u64::from(size);
+    }
 }
 
 fn drop_segments(
@@ -524,7 +1010,7 @@ fn drop_segments(
     // NOTE: If the application were to crash >here< it's fine
     // The segments are not referenced anymore, and will be
     // cleaned up upon recovery
-    for segment in segments {
+    for segment in &segments {
         segment.mark_as_deleted();
     }
 
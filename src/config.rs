@@ -3,17 +3,32 @@
 // (found in the LICENSE-* files in the repository)
 
 use crate::{
+    bloom::CompositeHash,
     cache::Cache,
+    compaction::CompactionListener,
     descriptor_table::FileDescriptorTable,
+    flush_policy::FlushPolicy,
+    memtable::MemtableKind,
     path::absolute_path,
-    segment::meta::{CompressionType, TableType},
-    BlobTree, Tree,
+    segment::{
+        block::MAX_VALUE_ALIGNMENT,
+        meta::{CompressionType, TableType},
+    },
+    BlobTree, ChecksumType, Tree,
 };
 use std::{
+    ops::RangeInclusive,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
+/// Extracts the prefix of a key, see [`Config::bloom_prefix_extractor`]
+pub type PrefixExtractor = Arc<dyn Fn(&[u8]) -> &[u8] + Send + Sync>;
+
+/// Hashes a key for the Bloom filters, see [`Config::bloom_hasher`]
+pub type BloomHasher = Arc<dyn Fn(&[u8]) -> CompositeHash + Send + Sync>;
+
 /// LSM-tree type
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TreeType {
@@ -45,6 +60,29 @@ impl TryFrom<u8> for TreeType {
     }
 }
 
+/// When data in memtables becomes visible to reads, see [`Config::sealed_memtable_visibility`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SealedMemtableVisibility {
+    /// Reads see the active and sealed memtables
+    #[default]
+    Immediate,
+
+    /// Reads only see segments, so data becomes visible once it has been flushed
+    AfterFlush,
+}
+
+/// What happens to writes into a closing tree, see [`Config::closing_write_policy`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ClosingWritePolicy {
+    /// Fallible writes (e.g. [`crate::AbstractTree::try_insert`]) return [`crate::Error::TreeClosing`]
+    #[default]
+    Reject,
+
+    /// Fallible writes are buffered in the active memtable, and are persisted by the next flush
+    /// (e.g. by calling [`crate::AbstractTree::close`] again)
+    Buffer,
+}
+
 const DEFAULT_FILE_FOLDER: &str = ".lsm.data";
 
 #[derive(Clone)]
@@ -61,9 +99,21 @@ pub struct Config {
     /// What type of compression is used
     pub compression: CompressionType,
 
+    /// What type of compression is used per level, overriding `compression`
+    pub compression_per_level: Option<Vec<CompressionType>>,
+
     /// What type of compression is used for blobs
     pub blob_compression: CompressionType,
 
+    /// What type of compression is used for values that are inlined into a blob tree's index
+    pub inline_value_compression: CompressionType,
+
+    /// What type of checksum is used for blocks
+    pub checksum_type: ChecksumType,
+
+    /// Whether the checksums of data blocks are verified when they are read from disk
+    pub verify_checksums: bool,
+
     /// Table type (unused)
     #[allow(unused)]
     pub(crate) table_type: TableType,
@@ -74,15 +124,36 @@ pub struct Config {
     /// Block size of index blocks
     pub index_block_size: u32,
 
+    /// Whether block handles in the block index store the first key of their block
+    pub index_start_keys: bool,
+
+    /// Alignment of values within data blocks
+    pub value_alignment: u32,
+
     /// Amount of levels of the LSM tree (depth of tree)
     pub level_count: u8,
 
+    /// Amount of top levels whose segments load their full block index into memory
+    pub preload_block_index_levels: u8,
+
     /// Bits per key for levels that are not L0, L1, L2
     // NOTE: bloom_bits_per_key is not conditionally compiled,
     // because that would change the file format
     #[doc(hidden)]
     pub bloom_bits_per_key: i8,
 
+    /// Levels whose segments get bloom filters
+    pub bloom_levels: RangeInclusive<u8>,
+
+    /// Extracts the key prefix that is stored in each segment's prefix filter
+    pub bloom_prefix_extractor: Option<PrefixExtractor>,
+
+    /// Hashes the keys that are stored in each segment's Bloom filter
+    pub bloom_hasher: Option<BloomHasher>,
+
+    /// Length of the key prefix that denotes a logical partition
+    pub partition_prefix_len: Option<usize>,
+
     /// Block cache to use
     #[doc(hidden)]
     pub cache: Arc<Cache>,
@@ -98,6 +169,60 @@ pub struct Config {
     /// Descriptor table to use
     #[doc(hidden)]
     pub descriptor_table: Arc<FileDescriptorTable>,
+
+    /// Amount of data blocks to read ahead during sequential scans
+    pub scan_readahead_blocks: usize,
+
+    /// Backing data structure of memtables
+    pub memtable_kind: MemtableKind,
+
+    /// Whether reads see memtables that have not been flushed yet
+    pub sealed_memtable_visibility: SealedMemtableVisibility,
+
+    /// What happens to writes into a closing tree
+    pub closing_write_policy: ClosingWritePolicy,
+
+    /// Whether memtables only keep the latest version of every key
+    pub memtable_coalesce_overwrites: bool,
+
+    /// Approximate size in bytes at which the active memtable is sealed
+    pub max_memtable_size: Option<u32>,
+
+    /// If `true`, sealed memtables are flushed on a background thread
+    pub background_flush: bool,
+
+    /// Amount of items at which the active memtable is sealed
+    pub max_memtable_entries: Option<usize>,
+
+    /// Decides when the active memtable is sealed, overriding the memtable limits
+    pub flush_policy: Option<Arc<dyn FlushPolicy>>,
+
+    /// Tombstone ratio at which a segment is compacted, regardless of level sizes
+    pub tombstone_ratio_trigger: Option<f32>,
+
+    /// Size ratio between levels, overriding the leveled compaction strategy's ratio
+    pub level_ratio: Option<f64>,
+
+    /// Ratio of reads that are sampled for hot key tracking
+    pub hot_key_sample_rate: Option<f32>,
+
+    /// Maximum amount of compactions that may run at the same time
+    pub max_concurrent_compactions: Option<usize>,
+
+    /// Amount of threads a single compaction may run its jobs on
+    pub compaction_threads: usize,
+
+    /// Receives the statistics of every completed compaction
+    pub on_compaction_complete: Option<CompactionListener>,
+
+    /// Amount of times a compaction is retried after a transient IO error
+    pub compaction_max_retries: u32,
+
+    /// Time to wait before the first retry of a compaction, doubled for every further retry
+    pub compaction_retry_backoff: Duration,
+
+    /// Total memory budget in bytes
+    pub memory_budget: Option<u64>,
 }
 
 impl Default for Config {
@@ -110,21 +235,59 @@ impl Default for Config {
 
             data_block_size: /* 4 KiB */ 4_096,
             index_block_size: /* 4 KiB */ 4_096,
+            index_start_keys: false,
+            value_alignment: 1,
             level_count: 7,
+            preload_block_index_levels: 2,
             tree_type: TreeType::Standard,
             table_type: TableType::Block,
             compression: CompressionType::None,
+            compression_per_level: None,
             blob_compression: CompressionType::None,
+            inline_value_compression: CompressionType::None,
+            checksum_type: ChecksumType::Xxh3,
+            verify_checksums: true,
             bloom_bits_per_key: 10,
+            bloom_levels: 0..=u8::MAX,
+            bloom_prefix_extractor: None,
+            bloom_hasher: None,
+            partition_prefix_len: None,
 
             blob_file_target_size: /* 64 MiB */ 64 * 1_024 * 1_024,
             blob_file_separation_threshold: /* 4 KiB */ 4 * 1_024,
+
+            scan_readahead_blocks: 0,
+
+            memtable_kind: MemtableKind::SkipList,
+            sealed_memtable_visibility: SealedMemtableVisibility::Immediate,
+            closing_write_policy: ClosingWritePolicy::Reject,
+            memtable_coalesce_overwrites: false,
+            max_memtable_size: None,
+            background_flush: false,
+            max_memtable_entries: None,
+            flush_policy: None,
+
+            tombstone_ratio_trigger: None,
+            level_ratio: None,
+
+            hot_key_sample_rate: None,
+
+            max_concurrent_compactions: None,
+            compaction_threads: 1,
+            on_compaction_complete: None,
+            compaction_max_retries: 0,
+            compaction_retry_backoff: Duration::from_millis(100),
+
+            memory_budget: None,
         }
     }
 }
 
 impl Config {
     /// Initializes a new config
+    ///
+    /// The tree's files never refer to the path, so a closed tree
+    /// can be moved to another folder and be reopened from there.
     pub fn new<P: AsRef<Path>>(path: P) -> Self {
         Self {
             path: absolute_path(path.as_ref()),
@@ -150,6 +313,194 @@ impl Config {
         self
     }
 
+    /// Sets the levels whose segments get bloom filters.
+    ///
+    /// Bloom filters are most valuable in the upper levels,
+    /// while deep levels are large (so they need the most memory for bloom filters),
+    /// and rarely serve point reads. Skipping bloom filters in those levels
+    /// can save a lot of memory, at the cost of performing an index lookup
+    /// for every point read that reaches the level.
+    ///
+    /// Only affects newly written segments.
+    ///
+    /// Defaults to all levels.
+    #[must_use]
+    pub fn bloom_levels(mut self, levels: RangeInclusive<u8>) -> Self {
+        self.bloom_levels = levels;
+        self
+    }
+
+    /// Sets a function that extracts the prefix of a key, e.g. a tenant ID.
+    ///
+    /// Newly written segments then get a second Bloom filter, containing the
+    /// extracted prefixes of their keys. Prefix scans use that filter to skip
+    /// segments that do not contain the scanned prefix.
+    ///
+    /// The prefix filter is separate from the regular Bloom filter,
+    /// so point reads still check the full key, and are not affected.
+    /// Prefix filters are only built for levels that get Bloom filters,
+    /// see [`Config::bloom_levels`].
+    ///
+    /// A prefix scan only consults the filter if the extractor returns a non-empty
+    /// prefix for the scanned prefix. So, the extractor needs to be *consistent*:
+    /// if it returns `p` for a scanned prefix, it must return `p` for every key
+    /// that starts with the scanned prefix. Keys that have no prefix (e.g. they are
+    /// shorter than a fixed prefix length) should return an empty slice.
+    /// Also, the extractor should not change for an existing tree, because prefixes of
+    /// existing segments are not extracted again.
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    /// use std::sync::Arc;
+    ///
+    /// // NOTE: Keys look like `tenant_id|timestamp`
+    /// fn tenant(key: &[u8]) -> &[u8] {
+    ///     match key.iter().position(|&byte| byte == b'|') {
+    ///         Some(idx) => &key[..=idx],
+    ///         None => &[],
+    ///     }
+    /// }
+    ///
+    /// let tree = Config::new(folder)
+    ///     .bloom_prefix_extractor(Arc::new(tenant))
+    ///     .open()?;
+    ///
+    /// tree.insert("tenant1|1", "a", 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// assert_eq!(0, tree.prefix("tenant2|", None, None).count());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// Defaults to no prefix filters.
+    #[must_use]
+    pub fn bloom_prefix_extractor(mut self, extractor: PrefixExtractor) -> Self {
+        self.bloom_prefix_extractor = Some(extractor);
+        self
+    }
+
+    /// Sets a custom hash function for the keys of the Bloom filters, instead of XXH3.
+    ///
+    /// This can skip hashing keys that already are (or contain) a good hash,
+    /// e.g. random 128-bit IDs. The two returned hashes are used for double hashing,
+    /// so they should be independent of each other.
+    ///
+    /// Every filter stores whether it was built using a custom hash, so existing
+    /// segments keep working. The hasher should not be changed for an existing tree,
+    /// otherwise filters built with the previous hasher return false negatives.
+    /// Prefix filters always use XXH3.
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    /// use std::sync::Arc;
+    ///
+    /// // NOTE: Keys are random 128-bit IDs
+    /// fn id_hash(key: &[u8]) -> (u64, u64) {
+    ///     let mut buf = [0; 16];
+    ///     buf.copy_from_slice(key);
+    ///     let id = u128::from_be_bytes(buf);
+    ///     ((id >> 64) as u64, id as u64)
+    /// }
+    ///
+    /// let tree = Config::new(folder)
+    ///     .bloom_hasher(Arc::new(id_hash))
+    ///     .open()?;
+    ///
+    /// tree.insert(0x2a4e_91c2_7d3b_8f06_55e1_c9a0_3b7d_12f4_u128.to_be_bytes(), "a", 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// assert!(tree.contains_key(0x2a4e_91c2_7d3b_8f06_55e1_c9a0_3b7d_12f4_u128.to_be_bytes(), None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// Defaults to XXH3.
+    #[must_use]
+    pub fn bloom_hasher(mut self, hasher: BloomHasher) -> Self {
+        self.bloom_hasher = Some(hasher);
+        self
+    }
+
+    /// Splits the tree into logical partitions, denoted by the first `n` bytes of every key.
+    ///
+    /// Partitions share everything (memtables, segments, compaction and caches),
+    /// but can be scanned and dropped on their own, see [`Tree::scan_partition`],
+    /// [`Tree::drop_partition`] and [`Tree::partition_stats`].
+    /// Compactions never create segments that span more than one partition,
+    /// so the space of a dropped partition is reclaimed as its segments are compacted.
+    ///
+    /// Every key needs to start with its partition ID of exactly `n` bytes
+    /// (e.g. a big-endian `u32`), followed by the key within the partition.
+    /// Keys that are shorter than `n` bytes do not belong to any partition.
+    /// The prefix length should not change for an existing tree.
+    ///
+    /// To also skip segments of other partitions when scanning a partition, set
+    /// a [`Config::bloom_prefix_extractor`] that returns the first `n` bytes.
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).partition_prefix_len(2).open()?;
+    ///
+    /// tree.insert("p1a", "abc", 0);
+    /// tree.insert("p1b", "abc", 1);
+    /// tree.insert("p2a", "abc", 2);
+    ///
+    /// assert_eq!(2, tree.scan_partition("p1", None).count());
+    ///
+    /// tree.drop_partition("p1", 3)?;
+    /// assert_eq!(0, tree.scan_partition("p1", None).count());
+    /// assert_eq!(1, tree.len(None, None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// Defaults to no partitions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    #[must_use]
+    pub fn partition_prefix_len(mut self, n: usize) -> Self {
+        assert!(n > 0, "partition prefix length must be greater than 0");
+
+        self.partition_prefix_len = Some(n);
+        self
+    }
+
+    /// Returns `true` if segments in the given level should get bloom filters.
+    pub(crate) fn use_bloom_filter(&self, level: u8) -> bool {
+        self.bloom_bits_per_key >= 0 && self.bloom_levels.contains(&level)
+    }
+
+    /// Sets the checksum algorithm of newly written blocks.
+    ///
+    /// Every block stores its checksum type, so changing it does
+    /// not affect existing segments.
+    ///
+    /// Defaults to XXH3.
+    #[must_use]
+    pub fn checksum_type(mut self, checksum_type: ChecksumType) -> Self {
+        self.checksum_type = checksum_type;
+        self
+    }
+
+    /// If `true`, the checksum of every data block that is read from disk is verified,
+    /// returning [`crate::Error::ChecksumMismatch`] if the block is corrupted.
+    ///
+    /// Blocks that are served from the block cache are not verified again.
+    ///
+    /// Defaults to `true`.
+    #[must_use]
+    pub fn verify_checksums(mut self, verify_checksums: bool) -> Self {
+        self.verify_checksums = verify_checksums;
+        self
+    }
+
     /// Sets the compression method.
     ///
     /// Using some compression is recommended.
@@ -161,6 +512,45 @@ impl Config {
         self
     }
 
+    /// Sets the compression method per level.
+    ///
+    /// The compression at index N is used for segments written into level N,
+    /// the last compression is used for all deeper levels. For example, the upper levels
+    /// can be left uncompressed for fast flushes and compactions, while the deep
+    /// levels, which hold most of the data, are compressed heavily to save space.
+    ///
+    /// Every block stores its compression type, so changing it does
+    /// not affect existing segments.
+    ///
+    /// Defaults to using [`Config::compression`] for all levels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `compression` is empty.
+    #[must_use]
+    pub fn compression_per_level(mut self, compression: Vec<CompressionType>) -> Self {
+        assert!(
+            !compression.is_empty(),
+            "compression_per_level may not be empty"
+        );
+
+        self.compression_per_level = Some(compression);
+        self
+    }
+
+    /// Returns the compression to use for segments in the given level.
+    pub(crate) fn compression_for_level(&self, level: u8) -> CompressionType {
+        let Some(compression) = &self.compression_per_level else {
+            return self.compression;
+        };
+
+        compression
+            .get(usize::from(level))
+            .or_else(|| compression.last())
+            .copied()
+            .unwrap_or(self.compression)
+    }
+
     /// Sets the compression method.
     ///
     /// Using some compression is recommended.
@@ -172,6 +562,22 @@ impl Config {
         self
     }
 
+    /// Sets the compression method of values that are not separated
+    /// into blob files, but inlined into the index tree.
+    ///
+    /// Values that do not become smaller are stored uncompressed.
+    /// Compressing inline values shrinks the index tree, so
+    /// the separation threshold can be raised.
+    ///
+    /// Default = None
+    ///
+    /// This option has no effect when not used for opening a blob tree.
+    #[must_use]
+    pub fn inline_value_compression(mut self, compression: CompressionType) -> Self {
+        self.inline_value_compression = compression;
+        self
+    }
+
     /// Sets the amount of levels of the LSM tree (depth of tree).
     ///
     /// Defaults to 7, like `LevelDB` and `RocksDB`.
@@ -189,10 +595,39 @@ impl Config {
         self
     }
 
+    /// Sets the amount of top levels (starting from L0) whose segments load
+    /// their full block index into memory when they are opened, which is on tree open,
+    /// and after flushes and compactions.
+    ///
+    /// Reads into these segments never need to load index blocks from disk, so the first
+    /// reads after opening the tree do not pay for it. Segments in deeper levels use a partitioned
+    /// block index, whose index blocks are loaded lazily (and cached in the block cache).
+    ///
+    /// Segments that are moved into a deeper level without being rewritten
+    /// keep their block index.
+    ///
+    /// Defaults to 2 (L0 and L1).
+    #[must_use]
+    pub fn preload_block_index_levels(mut self, n: u8) -> Self {
+        self.preload_block_index_levels = n;
+        self
+    }
+
+    /// Returns `true` if segments in the given level load their full block index,
+    /// see [`Config::preload_block_index_levels`].
+    pub(crate) fn preloads_block_index(&self, level_idx: u8) -> bool {
+        level_idx < self.preload_block_index_levels
+    }
+
     /// Sets the data block size.
     ///
     /// Defaults to 4 KiB (4096 bytes).
     ///
+    /// The block size refers to the uncompressed size of the items:
+    /// a block is cut once its items reach the block size, no matter how well
+    /// they compress. So a block exceeds the block size by at most one item,
+    /// which bounds the buffer that is allocated when decompressing it.
+    ///
     /// For point read heavy workloads (get) a sensible default is
     /// somewhere between 4 - 8 KiB, depending on the average value size.
     ///
@@ -235,6 +670,51 @@ impl Config {
         self
     }
 
+    /// If `true`, the block index stores the first key of every block,
+    /// in addition to its last key.
+    ///
+    /// This allows point reads to answer negative lookups of keys that
+    /// fall in between two blocks without loading any data block,
+    /// which helps most when blocks only contain few keys.
+    /// In return, the block index becomes larger.
+    ///
+    /// Only affects newly written segments.
+    ///
+    /// Defaults to `false`.
+    #[must_use]
+    pub fn index_start_keys(mut self, index_start_keys: bool) -> Self {
+        self.index_start_keys = index_start_keys;
+        self
+    }
+
+    /// Pads values within data blocks, so every value starts at a multiple of
+    /// `alignment` bytes, relative to the start of the uncompressed block data.
+    ///
+    /// This allows casting fixed-size values to typed structs when reading
+    /// the raw block data, at the expense of some padding per value.
+    /// The alignment is stored in every block, so segments written with
+    /// different alignments stay readable.
+    ///
+    /// For key-value separated trees, only the index tree's values are padded.
+    ///
+    /// Only affects newly written segments.
+    ///
+    /// Defaults to 1 (no padding).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the alignment is not a power of two, or larger than 4096.
+    #[must_use]
+    pub fn value_alignment(mut self, alignment: u32) -> Self {
+        assert!(
+            alignment.is_power_of_two() && alignment <= MAX_VALUE_ALIGNMENT,
+            "invalid value alignment",
+        );
+
+        self.value_alignment = alignment;
+        self
+    }
+
     /// Sets the global cache.
     ///
     /// You can create a global [`Cache`] and share it between multiple
@@ -244,6 +724,27 @@ impl Config {
     #[must_use]
     pub fn use_cache(mut self, cache: Arc<Cache>) -> Self {
         self.cache = cache;
+        self.memory_budget = None;
+        self
+    }
+
+    /// Sets the total amount of memory the tree may use.
+    ///
+    /// The budget is split up between the block cache (75%),
+    /// Bloom filters and memtables (the rest).
+    /// This replaces the cache set by [`Config::use_cache`] (and vice versa).
+    ///
+    /// Bloom filters are always kept in memory, so as they grow,
+    /// less memory is left for memtables.
+    /// Memtables are flushed by the caller, so see
+    /// [`Tree::effective_config`](crate::Tree::effective_config) for the
+    /// resulting memtable size the caller should flush at.
+    #[must_use]
+    pub fn memory_budget(mut self, bytes: u64) -> Self {
+        use crate::memory_budget::block_cache_capacity;
+
+        self.cache = Arc::new(Cache::with_capacity_bytes(block_cache_capacity(bytes)));
+        self.memory_budget = Some(bytes);
         self
     }
 
@@ -255,6 +756,11 @@ impl Config {
     /// Larger blob files decrease the number of files on disk and maintenance
     /// overhead.
     ///
+    /// Garbage collection works on whole blob files: a blob file can only be
+    /// dropped once all its blobs are stale, otherwise its live blobs need to
+    /// be rewritten. A blob file is rolled over once it reaches the target size,
+    /// so it may be larger by up to one blob.
+    ///
     /// Defaults to 64 MiB.
     ///
     /// This option has no effect when not used for opening a blob tree.
@@ -266,6 +772,11 @@ impl Config {
 
     /// Sets the key-value separation threshold in bytes.
     ///
+    /// When a memtable is flushed, values that are smaller than the threshold
+    /// stay inline in the index tree, so reading them takes no extra lookup.
+    /// Values of at least `bytes` are written to the value log instead,
+    /// and the index tree only stores a handle to them.
+    ///
     /// Smaller value will reduce compaction overhead and thus write amplification,
     /// at the cost of lower read performance.
     ///
@@ -273,11 +784,380 @@ impl Config {
     ///
     /// This option has no effect when not used for opening a blob tree.
     #[must_use]
+    #[doc(alias = "blob_inline_threshold")]
     pub fn blob_file_separation_threshold(mut self, bytes: u32) -> Self {
         self.blob_file_separation_threshold = bytes;
         self
     }
 
+    /// Sets the amount of data blocks to read ahead during sequential scans.
+    ///
+    /// When a forward scan (iter, range, prefix) moves on to the next data block,
+    /// the following `n` blocks are read in a single pass, so the scan does not
+    /// stall on every block boundary.
+    ///
+    /// Read-ahead blocks are held by the iterator until consumed, so at most
+    /// `n` blocks per segment reader are buffered, and the block cache is not
+    /// polluted with blocks that are never read.
+    ///
+    /// Defaults to 0 (no read-ahead).
+    #[must_use]
+    pub fn scan_readahead_blocks(mut self, n: usize) -> Self {
+        self.scan_readahead_blocks = n;
+        self
+    }
+
+    /// Sets the data structure that backs memtables.
+    ///
+    /// A skiplist allows concurrent writers to proceed without blocking each other,
+    /// while a B-tree tends to be faster for reads that hit the memtable.
+    ///
+    /// Only affects memtables that are created after opening the tree,
+    /// so memtables passed into [`crate::AbstractTree::set_active_memtable`]
+    /// keep their own kind.
+    ///
+    /// Defaults to [`MemtableKind::SkipList`].
+    #[must_use]
+    pub fn memtable_kind(mut self, kind: MemtableKind) -> Self {
+        self.memtable_kind = kind;
+        self
+    }
+
+    /// Sets whether reads see data that has not been flushed yet.
+    ///
+    /// With [`SealedMemtableVisibility::AfterFlush`], point reads and iterators skip
+    /// the sealed memtables, and the active memtable as well (because it is sealed
+    /// before being flushed), so only data in segments is visible. This allows
+    /// staging writes that are published by flushing them.
+    ///
+    /// [`crate::Tree::latest_seqno`] always sees memtables, so conflict checks
+    /// (e.g. [`crate::Tree::compare_and_swap`]) are not affected.
+    ///
+    /// Defaults to [`SealedMemtableVisibility::Immediate`].
+    #[must_use]
+    pub fn sealed_memtable_visibility(mut self, visibility: SealedMemtableVisibility) -> Self {
+        self.sealed_memtable_visibility = visibility;
+        self
+    }
+
+    /// Sets what happens to writes once [`crate::AbstractTree::close`] has been called.
+    ///
+    /// With [`ClosingWritePolicy::Reject`], fallible writes return [`crate::Error::TreeClosing`],
+    /// so they cannot end up in a memtable that is never flushed.
+    ///
+    /// With [`ClosingWritePolicy::Buffer`], fallible writes are still accepted into the active memtable,
+    /// and have to be flushed explicitly, e.g. by calling [`crate::AbstractTree::close`] again.
+    ///
+    /// Infallible writes (e.g. [`crate::AbstractTree::insert`]) cannot report an error,
+    /// so they are always buffered, regardless of the policy.
+    ///
+    /// Defaults to [`ClosingWritePolicy::Reject`].
+    #[must_use]
+    pub fn closing_write_policy(mut self, policy: ClosingWritePolicy) -> Self {
+        self.closing_write_policy = policy;
+        self
+    }
+
+    /// If `true`, an insert into the memtable drops all older versions of its key,
+    /// so a key that is overwritten many times only takes up memory once.
+    ///
+    /// # Caution
+    ///
+    /// This mode is incompatible with snapshots (or reads at an explicit sequence number)
+    /// that are taken while the memtable is being written to: such a read cannot find the
+    /// intermediate versions anymore, and may see an older version from disk instead,
+    /// or no version at all.
+    /// Only enable it if reads always observe the latest data.
+    ///
+    /// Versions that are covered by a weak tombstone are kept.
+    ///
+    /// Defaults to `false`.
+    #[must_use]
+    pub fn memtable_coalesce_overwrites(mut self, enabled: bool) -> Self {
+        self.memtable_coalesce_overwrites = enabled;
+        self
+    }
+
+    /// Sets the approximate size in bytes at which the active memtable is sealed.
+    ///
+    /// Once a write makes the active memtable reach the limit, it is sealed
+    /// and a new active memtable is started. Sealed memtables still need to be
+    /// flushed by the caller, see [`AbstractTree::sealed_memtable_count`](crate::AbstractTree::sealed_memtable_count),
+    /// unless [`Config::background_flush`] is enabled.
+    ///
+    /// Can be combined with [`Config::max_memtable_entries`],
+    /// in which case the memtable is sealed by whichever limit is hit first.
+    ///
+    /// Unlimited by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is 0.
+    #[must_use]
+    pub fn max_memtable_size(mut self, bytes: u32) -> Self {
+        assert!(bytes > 0, "max_memtable_size may not be 0");
+
+        self.max_memtable_size = Some(bytes);
+        self
+    }
+
+    /// If enabled, a write that seals the active memtable (see [`Config::max_memtable_size`],
+    /// [`Config::max_memtable_entries`] and [`Config::flush_policy`]) starts a background
+    /// thread, which flushes all sealed memtables into L0.
+    ///
+    /// Reads keep seeing sealed memtables until their segments are registered.
+    /// Compactions are not started automatically.
+    ///
+    /// Only applies to standard trees: blob trees separate values while flushing,
+    /// so their sealed memtables still need to be flushed using
+    /// [`BlobTree::flush_sealed_memtables`](crate::BlobTree::flush_sealed_memtables).
+    ///
+    /// Disabled by default.
+    #[must_use]
+    pub fn background_flush(mut self, enabled: bool) -> Self {
+        self.background_flush = enabled;
+        self
+    }
+
+    /// Sets the amount of items at which the active memtable is sealed.
+    ///
+    /// For small, uniform values, a size limit results in memtables
+    /// with huge amounts of items, which slows down memtable operations
+    /// and makes flushes less predictable.
+    ///
+    /// Can be combined with [`Config::max_memtable_size`],
+    /// in which case the memtable is sealed by whichever limit is hit first.
+    ///
+    /// Unlimited by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    #[must_use]
+    pub fn max_memtable_entries(mut self, n: usize) -> Self {
+        assert!(n > 0, "max_memtable_entries may not be 0");
+
+        self.max_memtable_entries = Some(n);
+        self
+    }
+
+    /// Sets a custom policy that decides when the active memtable is sealed.
+    ///
+    /// The policy is consulted after every write, and replaces
+    /// [`Config::max_memtable_size`] and [`Config::max_memtable_entries`].
+    /// To keep these limits, wrap a [`DefaultFlushPolicy`](crate::DefaultFlushPolicy).
+    ///
+    /// Defaults to sealing by size and item count.
+    #[must_use]
+    pub fn flush_policy(mut self, policy: Arc<dyn FlushPolicy>) -> Self {
+        self.flush_policy = Some(policy);
+        self
+    }
+
+    /// Compacts segments whose ratio of tombstones to items exceeds `ratio`,
+    /// even if no level exceeds its size target.
+    ///
+    /// In delete-heavy workloads, tombstones are small, so they may pile up
+    /// (and slow down reads) without ever triggering a size-based compaction.
+    /// Such segments are pushed down towards the last level,
+    /// where the tombstones are finally dropped.
+    ///
+    /// Tombstones that are still visible to a snapshot cannot be dropped,
+    /// in which case the segment may be picked again by the next compaction.
+    ///
+    /// Currently only applies to the leveled compaction strategy.
+    ///
+    /// Disabled by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ratio is not in (0.0, 1.0].
+    #[must_use]
+    pub fn tombstone_ratio_trigger(mut self, ratio: f32) -> Self {
+        assert!(ratio > 0.0 && ratio <= 1.0, "invalid tombstone ratio");
+
+        self.tombstone_ratio_trigger = Some(ratio);
+        self
+    }
+
+    /// Sets the size ratio between levels (a.k.a fanout, growth rate).
+    ///
+    /// Every level (starting from L2) may be `ratio` times as big as the level before.
+    /// See [`Tree::level_size_targets`](crate::Tree::level_size_targets)
+    /// for inspecting the resulting level size targets.
+    ///
+    /// Currently only applies to the leveled compaction strategy,
+    /// where it overrides [`Strategy::level_ratio`](crate::compaction::Leveled::level_ratio).
+    ///
+    /// Defaults to the compaction strategy's ratio.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ratio is below 1.0.
+    #[must_use]
+    pub fn level_ratio(mut self, ratio: f64) -> Self {
+        assert!(ratio >= 1.0, "level ratio should be at least 1.0");
+
+        self.level_ratio = Some(ratio);
+        self
+    }
+
+    /// Returns the size target of a level for size-based compaction strategies.
+    ///
+    /// L1 = `base_size`
+    ///
+    /// L2 = `base_size * ratio`
+    ///
+    /// L3 = `base_size * ratio * ratio`
+    /// ...
+    ///
+    /// The ratio is [`Config::level_ratio`], if set, otherwise `default_ratio`.
+    /// Custom strategies may use this in [`CompactionStrategy::choose`](crate::compaction::CompactionStrategy::choose)
+    /// together with [`LevelManifest::level_size`](crate::level_manifest::LevelManifest::level_size).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `level_idx` is 0, because L0 has no size target.
+    #[must_use]
+    pub fn level_target_size(&self, level_idx: u8, base_size: u64, default_ratio: f64) -> u64 {
+        assert!(level_idx >= 1, "level_target_size does not apply to L0");
+
+        let ratio = self.level_ratio.unwrap_or(default_ratio);
+
+        // NOTE: Float to int casts saturate, so huge targets become u64::MAX
+        #[allow(
+            clippy::cast_precision_loss,
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss
+        )]
+        let target = (ratio.powi(i32::from(level_idx) - 1) * base_size as f64) as u64;
+
+        target
+    }
+
+    /// Tracks the most read keys, see [`Tree::hot_keys`](crate::Tree::hot_keys).
+    ///
+    /// Only the given ratio of point reads and range/prefix scans is sampled
+    /// (for scans, the start key is counted). Counts are kept in a fixed-size
+    /// count-min sketch, so memory usage is bounded regardless of the key space.
+    ///
+    /// Disabled by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sample rate is not in (0.0, 1.0].
+    #[must_use]
+    pub fn track_hot_keys(mut self, sample_rate: f32) -> Self {
+        assert!(
+            sample_rate > 0.0 && sample_rate <= 1.0,
+            "invalid hot key sample rate"
+        );
+
+        self.hot_key_sample_rate = Some(sample_rate);
+        self
+    }
+
+    /// Sets the maximum amount of compactions that may run at the same time.
+    ///
+    /// Compactions only ever pick segments that are not being compacted already,
+    /// so multiple compaction workers can work on different levels concurrently.
+    /// If the limit is reached, further compactions return immediately without doing anything.
+    ///
+    /// Major compactions always run exclusively.
+    ///
+    /// Unlimited by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    #[must_use]
+    pub fn max_concurrent_compactions(mut self, n: usize) -> Self {
+        assert!(n > 0, "max_concurrent_compactions may not be 0");
+
+        self.max_concurrent_compactions = Some(n);
+        self
+    }
+
+    /// Sets the amount of threads a compaction may use.
+    ///
+    /// If greater than 1, the compaction strategy may choose multiple jobs
+    /// (see [`CompactionStrategy::choose_many`](crate::compaction::CompactionStrategy::choose_many)),
+    /// which are run in parallel if they do not overlap. The level manifest is only locked
+    /// while choosing the jobs, and while each job hides and replaces its segments.
+    ///
+    /// The jobs of a compaction count as a single compaction towards
+    /// [`Config::max_concurrent_compactions`].
+    ///
+    /// Defaults to 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    #[must_use]
+    pub fn compaction_threads(mut self, n: usize) -> Self {
+        assert!(n > 0, "compaction_threads may not be 0");
+
+        self.compaction_threads = n;
+        self
+    }
+
+    /// Sets a callback that is called after every compaction that merged segments.
+    ///
+    /// The statistics can be used to track write amplification,
+    /// for example by exporting them to a metrics system.
+    /// Moving or dropping segments does not call the callback.
+    ///
+    /// The callback runs on the compacting thread, after the created segments
+    /// have been registered, so it should return quickly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    /// use std::sync::{atomic::{AtomicU64, Ordering}, Arc};
+    ///
+    /// let bytes_written = Arc::new(AtomicU64::default());
+    /// let counter = bytes_written.clone();
+    ///
+    /// let tree = Config::new(folder)
+    ///     .on_compaction_complete(Arc::new(move |stats| {
+    ///         counter.fetch_add(stats.output_bytes, Ordering::Relaxed);
+    ///     }))
+    ///     .open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    /// tree.major_compact(u64::MAX, 1)?;
+    ///
+    /// assert!(bytes_written.load(Ordering::Relaxed) > 0);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn on_compaction_complete(mut self, callback: CompactionListener) -> Self {
+        self.on_compaction_complete = Some(callback);
+        self
+    }
+
+    /// Retries compactions that failed because of a transient IO error
+    /// (for example a full disk, or a timed out request to network storage)
+    /// up to `max_retries` times.
+    ///
+    /// Before every retry, the compaction waits for `backoff`, which is doubled
+    /// for every further retry (up to 30 seconds). Retries are logged,
+    /// and stop once the tree is dropped.
+    ///
+    /// Other errors (like corruption) are never retried.
+    ///
+    /// Defaults to no retries.
+    #[must_use]
+    pub fn compaction_retries(mut self, max_retries: u32, backoff: Duration) -> Self {
+        self.compaction_max_retries = max_retries;
+        self.compaction_retry_backoff = backoff;
+        self
+    }
+
     #[must_use]
     #[doc(hidden)]
     pub fn descriptor_table(mut self, descriptor_table: Arc<FileDescriptorTable>) -> Self {
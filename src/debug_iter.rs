@@ -0,0 +1,121 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    memtable::Memtable,
+    merge::{BoxedIterator, Merger},
+    segment::value_block::CachePolicy,
+    tree::inner::MemtableId,
+    InternalValue, Segment, SegmentId,
+};
+use self_cell::self_cell;
+use std::sync::Arc;
+
+/// Where an item returned by [`DebugMergeIter`] is stored
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Source {
+    /// The active memtable
+    ActiveMemtable,
+
+    /// A sealed memtable that has not been flushed yet
+    SealedMemtable(MemtableId),
+
+    /// A disk segment
+    Segment {
+        /// Level the segment is in
+        level: u8,
+
+        /// ID of the segment
+        id: SegmentId,
+    },
+}
+
+/// The memtables that are referenced while the iterator is open
+struct DebugIterState {
+    active: Arc<Memtable>,
+    sealed: Vec<(MemtableId, Arc<Memtable>)>,
+}
+
+type BoxedMerge<'a> = Box<Merger<BoxedIterator<'a>>>;
+
+self_cell!(
+    struct DebugIterInner {
+        owner: DebugIterState,
+
+        #[covariant]
+        dependent: BoxedMerge,
+    }
+);
+
+/// Iterates over all items of a tree, in merge order, tagged with their source
+///
+/// Unlike regular iterators, no MVCC collapsing takes place:
+/// every version of every key is returned, including tombstones
+/// and items that are shadowed by newer versions.
+///
+/// Items with the same key and seqno may be returned in any order.
+#[allow(clippy::module_name_repetitions)]
+pub struct DebugMergeIter {
+    sources: Vec<Source>,
+    inner: DebugIterInner,
+}
+
+impl DebugMergeIter {
+    pub(crate) fn new(
+        active: Arc<Memtable>,
+        sealed: Vec<(MemtableId, Arc<Memtable>)>,
+        segments: &[(u8, Segment)],
+    ) -> Self {
+        let mut sources = Vec::with_capacity(1 + sealed.len() + segments.len());
+
+        sources.push(Source::ActiveMemtable);
+        sources.extend(sealed.iter().map(|(id, _)| Source::SealedMemtable(*id)));
+        sources.extend(segments.iter().map(|(level, segment)| Source::Segment {
+            level: *level,
+            id: segment.id(),
+        }));
+
+        // NOTE: Inspecting the whole tree should not evict the working set
+        //
+        // Segment readers pin their segment, so they do not need to be referenced
+        let segment_iters = segments
+            .iter()
+            .map(|(_, segment)| segment.iter().cache_policy(CachePolicy::Read))
+            .collect::<Vec<_>>();
+
+        let state = DebugIterState { active, sealed };
+
+        let inner = DebugIterInner::new(state, |state| {
+            let mut iters: Vec<BoxedIterator<'_>> = Vec::with_capacity(sources.len());
+
+            iters.push(Box::new(state.active.iter().map(Ok)));
+
+            for (_, memtable) in &state.sealed {
+                iters.push(Box::new(memtable.iter().map(Ok)));
+            }
+
+            for iter in segment_iters {
+                iters.push(Box::new(iter));
+            }
+
+            Box::new(Merger::new(iters))
+        });
+
+        Self { sources, inner }
+    }
+}
+
+impl Iterator for DebugMergeIter {
+    type Item = crate::Result<(Source, InternalValue)>;
+
+    #[allow(clippy::indexing_slicing)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (idx, item) = fail_iter!(self
+            .inner
+            .with_dependent_mut(|_, iter| iter.next_with_source())?);
+
+        // NOTE: Every iterator has a source, see constructor
+        Some(Ok((self.sources[idx], item)))
+    }
+}
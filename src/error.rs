@@ -5,7 +5,7 @@
 use crate::{
     coding::{DecodeError, EncodeError},
     version::Version,
-    Checksum, CompressionType,
+    Checksum, CompressionType, SegmentId,
 };
 
 /// Represents errors that can occur in the LSM-tree
@@ -33,8 +33,45 @@ pub enum Error {
     /// Invalid checksum value (got, expected)
     InvalidChecksum((Checksum, Checksum)),
 
+    /// The checksum of a block read from disk does not match its stored checksum,
+    /// see [`crate::Config::verify_checksums`]
+    ChecksumMismatch {
+        /// Segment that contains the corrupted block
+        segment_id: SegmentId,
+
+        /// File offset of the corrupted block
+        block_offset: u64,
+    },
+
     /// Value log errors
     ValueLog(value_log::Error),
+
+    /// The tree is closing (or closed), so writes are rejected
+    TreeClosing,
+
+    /// An imported segment is not sorted, exceeds its declared key range,
+    /// or overlaps with other segments of its (disjoint) destination level
+    OverlapViolation(SegmentId),
+
+    /// A segment was about to be registered in the level manifest more than once
+    ///
+    /// This indicates a bug, and the level manifest was left unchanged.
+    DuplicateSegment(SegmentId),
+
+    /// Two Bloom filters cannot be combined, because their bit count,
+    /// amount of hash functions or hash differ, see [`crate::bloom::BloomFilter::union`]
+    IncompatibleBloomFilter,
+
+    /// A key of a bulk ingestion was not greater than the previous key,
+    /// see [`crate::AbstractTree::ingest`] and [`crate::Tree::delete_sorted`]
+    UnsortedIngest,
+
+    /// The given level does not exist in the tree
+    InvalidLevel(u8),
+
+    /// A write was rejected, because its seqno is lower than the seqno
+    /// of a running [`crate::Tree::delete_sorted`]
+    DeleteInProgress(crate::SeqNo),
 }
 
 impl std::fmt::Display for Error {
@@ -53,8 +90,53 @@ impl std::error::Error for Error {
             Self::Decompress(_)
             | Self::InvalidVersion(_)
             | Self::Unrecoverable
-            | Self::InvalidChecksum(_) => None,
+            | Self::InvalidChecksum(_)
+            | Self::ChecksumMismatch { .. }
+            | Self::TreeClosing
+            | Self::OverlapViolation(_)
+            | Self::DuplicateSegment(_)
+            | Self::IncompatibleBloomFilter
+            | Self::UnsortedIngest
+            | Self::InvalidLevel(_)
+            | Self::DeleteInProgress(_) => None,
+        }
+    }
+}
+
+impl Error {
+    /// Returns `true` if the error is an I/O error that may go away by itself,
+    /// for example a full disk or a timed out request to network storage.
+    ///
+    /// Corruption is never transient.
+    pub(crate) fn is_transient(&self) -> bool {
+        let (Self::Io(e) | Self::Encode(EncodeError::Io(e)) | Self::Decode(DecodeError::Io(e))) =
+            self
+        else {
+            return false;
+        };
+
+        if matches!(
+            e.kind(),
+            std::io::ErrorKind::WouldBlock
+                | std::io::ErrorKind::Interrupted
+                | std::io::ErrorKind::TimedOut
+        ) {
+            return true;
         }
+
+        // NOTE: ErrorKind::StorageFull requires Rust 1.83, so check the OS error codes
+        // of a full disk (ENOSPC, ERROR_HANDLE_DISK_FULL and ERROR_DISK_FULL)
+        #[cfg(unix)]
+        let disk_full_codes = [28];
+
+        #[cfg(windows)]
+        let disk_full_codes = [39, 112];
+
+        #[cfg(not(any(unix, windows)))]
+        let disk_full_codes: [i32; 0] = [];
+
+        e.raw_os_error()
+            .is_some_and(|code| disk_full_codes.contains(&code))
     }
 }
 
@@ -84,3 +166,28 @@ impl From<value_log::Error> for Error {
 
 /// Tree result
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn error_is_transient() {
+        use std::io::{Error as IoError, ErrorKind};
+
+        assert!(Error::Io(IoError::from(ErrorKind::WouldBlock)).is_transient());
+        assert!(Error::Io(IoError::from(ErrorKind::TimedOut)).is_transient());
+        assert!(
+            Error::Encode(EncodeError::Io(IoError::from(ErrorKind::Interrupted))).is_transient()
+        );
+
+        #[cfg(unix)]
+        assert!(Error::Io(IoError::from_raw_os_error(28)).is_transient());
+
+        assert!(!Error::Io(IoError::from(ErrorKind::PermissionDenied)).is_transient());
+        assert!(!Error::Io(IoError::from(ErrorKind::UnexpectedEof)).is_transient());
+        assert!(!Error::Decode(DecodeError::InvalidHeader("Block")).is_transient());
+        assert!(!Error::Unrecoverable.is_transient());
+    }
+}
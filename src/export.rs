@@ -0,0 +1,142 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{file::temp_file_path, KvPair};
+use byteorder::{BigEndian, WriteBytesExt};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+use xxhash_rust::xxh3::Xxh3;
+
+/// Magic bytes of an export file in the [`ExportFormat::Native`] format
+pub const EXPORT_MAGIC_BYTES: [u8; 4] = [b'L', b'S', b'M', b'X'];
+
+/// File format of a sorted export
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ExportFormat {
+    /// The crate's own, simple length-prefixed format
+    ///
+    /// All integers are big endian.
+    ///
+    /// ```text
+    /// [magic: "LSMX"] [version: u8 = 1]
+    /// [key len: u16] [key] [value len: u32] [value]   (repeated, in key order)
+    /// [item count: u64] [xxh3 checksum of all previous bytes: u64]
+    /// ```
+    #[default]
+    Native,
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Native => "native",
+            }
+        )
+    }
+}
+
+/// Writer that hashes everything that passes through it
+struct ChecksummedWriter<W: Write> {
+    inner: W,
+    hasher: Xxh3,
+}
+
+impl<W: Write> Write for ChecksummedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+
+        #[allow(clippy::indexing_slicing)]
+        self.hasher.update(&buf[..written]);
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writes the key-value pairs of `iter` into a single file at `path`.
+///
+/// The file is written to a temporary file first, so `path`
+/// is either fully written or not touched at all.
+///
+/// Returns the amount of exported items.
+pub fn export_sorted<I: Iterator<Item = crate::Result<KvPair>>>(
+    iter: I,
+    path: &Path,
+    format: ExportFormat,
+) -> crate::Result<u64> {
+    log::debug!("Exporting tree to {} ({format})", path.display());
+
+    let temp_path = temp_file_path(path);
+
+    let mut writer = ChecksummedWriter {
+        inner: BufWriter::new(File::create(&temp_path)?),
+        hasher: Xxh3::new(),
+    };
+
+    let item_count = match format {
+        ExportFormat::Native => write_native(iter, &mut writer),
+    };
+
+    let item_count = match item_count {
+        Ok(item_count) => item_count,
+        Err(e) => {
+            drop(writer);
+            std::fs::remove_file(&temp_path)?;
+            return Err(e);
+        }
+    };
+
+    let checksum = writer.hasher.digest();
+    let mut file = writer.inner;
+    file.write_u64::<BigEndian>(checksum)?;
+    file.flush()?;
+    file.get_mut().sync_all()?;
+    drop(file);
+
+    std::fs::rename(&temp_path, path)?;
+
+    log::debug!("Exported {item_count} items to {}", path.display());
+
+    Ok(item_count)
+}
+
+fn write_native<I: Iterator<Item = crate::Result<KvPair>>, W: Write>(
+    iter: I,
+    writer: &mut W,
+) -> crate::Result<u64> {
+    writer.write_all(&EXPORT_MAGIC_BYTES)?;
+    writer.write_u8(1)?;
+
+    let mut item_count = 0;
+
+    for kv in iter {
+        let (key, value) = kv?;
+
+        // NOTE: Truncation is okay, keys are limited to 16-bit length
+        #[allow(clippy::cast_possible_truncation)]
+        writer.write_u16::<BigEndian>(key.len() as u16)?;
+        writer.write_all(&key)?;
+
+        // NOTE: Truncation is okay, values are limited to 32-bit length
+        #[allow(clippy::cast_possible_truncation)]
+        writer.write_u32::<BigEndian>(value.len() as u32)?;
+        writer.write_all(&value)?;
+
+        item_count += 1;
+    }
+
+    writer.write_u64::<BigEndian>(item_count)?;
+
+    Ok(item_count)
+}
@@ -2,26 +2,54 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use std::{io::Write, path::Path};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
 
-pub const MAGIC_BYTES: [u8; 4] = [b'L', b'S', b'M', 2];
+/// Magic bytes that start every format header
+pub const MAGIC: [u8; 3] = [b'L', b'S', b'M'];
+
+/// Current disk format version
+pub const FORMAT_VERSION: u8 = 2;
+
+pub const MAGIC_BYTES: [u8; 4] = [b'L', b'S', b'M', FORMAT_VERSION];
 
 pub const MANIFEST_FILE: &str = "manifest";
 pub const SEGMENTS_FOLDER: &str = "segments";
 pub const LEVELS_MANIFEST_FILE: &str = "levels";
 pub const BLOBS_FOLDER: &str = "blobs";
 
-/// Atomically rewrites a file
-pub fn rewrite_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+/// Returns the path of the temporary file that is used to rewrite `path`
+///
+/// The name is deterministic, so a leftover file can be found after a crash.
+#[must_use]
+pub fn temp_file_path(path: &Path) -> PathBuf {
     // NOTE: Nothing we can do
     #[allow(clippy::expect_used)]
-    let folder = path.parent().expect("should have a parent");
+    let mut file_name = path
+        .file_name()
+        .expect("should have a file name")
+        .to_os_string();
+
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+/// Atomically rewrites a file
+///
+/// The content is written to [`temp_file_path`] first, which is then renamed over `path`.
+pub fn rewrite_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let temp_path = temp_file_path(path);
+
+    {
+        let mut temp_file = std::fs::File::create(&temp_path)?;
+        temp_file.write_all(content)?;
+        temp_file.flush()?;
+        temp_file.sync_all()?;
+    }
 
-    let mut temp_file = tempfile::NamedTempFile::new_in(folder)?;
-    temp_file.write_all(content)?;
-    temp_file.flush()?;
-    temp_file.as_file_mut().sync_all()?;
-    temp_file.persist(path)?;
+    std::fs::rename(&temp_path, path)?;
 
     // TODO: not sure why it fails on Windows...
     #[cfg(not(target_os = "windows"))]
@@ -70,6 +98,7 @@ mod tests {
 
         let content = std::fs::read_to_string(&path)?;
         assert_eq!("newcontent", content);
+        assert!(!temp_file_path(&path).try_exists()?);
 
         Ok(())
     }
@@ -0,0 +1,87 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+/// State of the active memtable after a write, see [`FlushPolicy`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct MemtableStats<'a> {
+    /// Approximate size of the memtable in bytes
+    pub size: u32,
+
+    /// Amount of items in the memtable
+    pub len: usize,
+
+    /// User key of the item that was just written
+    pub key: &'a [u8],
+}
+
+/// Decides when the active memtable is sealed, see [`Config::flush_policy`](crate::Config::flush_policy)
+///
+/// The policy is consulted after every write to the active memtable.
+/// Sealed memtables still need to be flushed by the caller,
+/// see [`Tree::flush_sealed_memtables`](crate::Tree::flush_sealed_memtables).
+pub trait FlushPolicy: Send + Sync {
+    /// Returns `true` if the active memtable should be sealed.
+    ///
+    /// This is called in the write path, so it should be cheap.
+    fn should_flush(&self, stats: &MemtableStats) -> bool;
+}
+
+/// Seals the active memtable once it reaches a size or item count limit
+///
+/// This is the policy that is used if no other policy is configured,
+/// using [`Config::max_memtable_size`](crate::Config::max_memtable_size)
+/// and [`Config::max_memtable_entries`](crate::Config::max_memtable_entries).
+/// Custom policies can wrap it to keep the built-in limits.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct DefaultFlushPolicy {
+    /// Approximate size in bytes at which the memtable is sealed
+    pub max_size: Option<u32>,
+
+    /// Amount of items at which the memtable is sealed
+    pub max_entries: Option<usize>,
+}
+
+impl FlushPolicy for DefaultFlushPolicy {
+    fn should_flush(&self, stats: &MemtableStats) -> bool {
+        let size_limit_hit = self.max_size.is_some_and(|limit| stats.size >= limit);
+        let entry_limit_hit = self.max_entries.is_some_and(|limit| stats.len >= limit);
+
+        size_limit_hit || entry_limit_hit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn default_flush_policy() {
+        let stats = MemtableStats {
+            size: 1_000,
+            len: 10,
+            key: b"a",
+        };
+
+        assert!(!DefaultFlushPolicy::default().should_flush(&stats));
+
+        let policy = DefaultFlushPolicy {
+            max_size: Some(1_000),
+            max_entries: None,
+        };
+        assert!(policy.should_flush(&stats));
+
+        let policy = DefaultFlushPolicy {
+            max_size: Some(1_001),
+            max_entries: Some(11),
+        };
+        assert!(!policy.should_flush(&stats));
+
+        let policy = DefaultFlushPolicy {
+            max_size: Some(u32::MAX),
+            max_entries: Some(10),
+        };
+        assert!(policy.should_flush(&stats));
+    }
+}
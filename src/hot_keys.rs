@@ -0,0 +1,186 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{HashMap, UserKey};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex, MutexGuard,
+};
+
+/// Amount of rows (hash functions) of the count-min sketch
+const SKETCH_DEPTH: usize = 4;
+
+/// Amount of counters per row of the count-min sketch
+const SKETCH_WIDTH: usize = 1_024;
+
+/// Maximum amount of candidate keys that are tracked
+pub const HOT_KEY_CAPACITY: usize = 128;
+
+/// Tracks frequently read keys
+///
+/// Reads are sampled, and every sampled key is counted in a count-min sketch,
+/// which uses a fixed amount of memory regardless of the key space.
+/// The heaviest keys (according to the sketch) are kept in a small candidate set,
+/// so they can be reported without the sketch having to store keys.
+pub struct HotKeys {
+    /// Every n-th read is sampled
+    sample_interval: u64,
+
+    /// Amount of reads seen so far
+    reads: AtomicU64,
+
+    /// Count-min sketch, consisting of `SKETCH_DEPTH` rows
+    sketch: Box<[AtomicU64]>,
+
+    /// Heaviest keys with their estimated (sampled) count
+    candidates: Mutex<HashMap<UserKey, u64>>,
+}
+
+impl HotKeys {
+    /// Creates a new tracker that samples the given ratio of reads.
+    pub fn new(sample_rate: f32) -> Self {
+        // NOTE: Sample rate is in (0.0, 1.0], so the interval is >= 1
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let sample_interval = (1.0 / sample_rate).round().max(1.0) as u64;
+
+        Self {
+            sample_interval,
+            reads: AtomicU64::default(),
+            sketch: (0..(SKETCH_DEPTH * SKETCH_WIDTH))
+                .map(|_| AtomicU64::default())
+                .collect(),
+            candidates: Mutex::default(),
+        }
+    }
+
+    fn lock_candidates(&self) -> MutexGuard<'_, HashMap<UserKey, u64>> {
+        self.candidates.lock().expect("lock is poisoned")
+    }
+
+    /// Returns the counter indexes of a key, one per row.
+    fn slots(key: &[u8]) -> impl Iterator<Item = usize> {
+        let h = xxhash_rust::xxh3::xxh3_128(key);
+
+        // NOTE: Double hashing, see bloom filter
+        #[allow(clippy::cast_possible_truncation)]
+        let (h1, h2) = ((h >> 64) as u64, h as u64);
+
+        (0..SKETCH_DEPTH).map(move |row| {
+            let h = h1.wrapping_add((row as u64).wrapping_mul(h2));
+
+            // NOTE: Result is < SKETCH_WIDTH, so it fits into a usize
+            #[allow(clippy::cast_possible_truncation)]
+            let idx = (h % SKETCH_WIDTH as u64) as usize;
+
+            row * SKETCH_WIDTH + idx
+        })
+    }
+
+    /// Records a read of `key`, if it is sampled.
+    pub fn record(&self, key: &[u8]) {
+        if self.reads.fetch_add(1, Ordering::Relaxed) % self.sample_interval != 0 {
+            return;
+        }
+
+        let estimate = Self::slots(key)
+            .filter_map(|idx| self.sketch.get(idx))
+            .map(|counter| counter.fetch_add(1, Ordering::Relaxed) + 1)
+            .min()
+            .unwrap_or_default();
+
+        let mut candidates = self.lock_candidates();
+
+        if let Some(count) = candidates.get_mut(key) {
+            *count = estimate;
+            return;
+        }
+
+        if candidates.len() < HOT_KEY_CAPACITY {
+            candidates.insert(key.into(), estimate);
+            return;
+        }
+
+        // NOTE: Replace the lightest candidate, if this key is heavier
+        let lightest = candidates
+            .iter()
+            .min_by_key(|(_, count)| **count)
+            .map(|(key, count)| (key.clone(), *count));
+
+        if let Some((lightest_key, lightest_count)) = lightest {
+            if estimate > lightest_count {
+                candidates.remove(&lightest_key);
+                candidates.insert(key.into(), estimate);
+            }
+        }
+    }
+
+    /// Returns the `top_n` most read keys, with their estimated read count,
+    /// heaviest first.
+    pub fn top(&self, top_n: usize) -> Vec<(UserKey, u64)> {
+        let mut keys: Vec<_> = self
+            .lock_candidates()
+            .iter()
+            .map(|(key, count)| (key.clone(), count * self.sample_interval))
+            .collect();
+
+        keys.sort_by(|(a_key, a), (b_key, b)| b.cmp(a).then_with(|| a_key.cmp(b_key)));
+        keys.truncate(top_n);
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn hot_keys_skew() {
+        let hot_keys = HotKeys::new(1.0);
+
+        for x in 0..10_000u64 {
+            hot_keys.record(&x.to_be_bytes());
+
+            if x % 2 == 0 {
+                hot_keys.record(b"hot");
+            }
+            if x % 4 == 0 {
+                hot_keys.record(b"warm");
+            }
+        }
+
+        let top = hot_keys.top(2);
+        assert_eq!(2, top.len());
+
+        assert_eq!(&*top[0].0, b"hot");
+        assert!(top[0].1 >= 5_000);
+
+        assert_eq!(&*top[1].0, b"warm");
+        assert!(top[1].1 >= 2_500);
+    }
+
+    #[test]
+    fn hot_keys_sampling() {
+        let hot_keys = HotKeys::new(0.1);
+
+        for _ in 0..1_000 {
+            hot_keys.record(b"a");
+        }
+
+        let top = hot_keys.top(10);
+        assert_eq!(1, top.len());
+        assert_eq!(1_000, top[0].1);
+    }
+
+    #[test]
+    fn hot_keys_bounded() {
+        let hot_keys = HotKeys::new(1.0);
+
+        for x in 0..(HOT_KEY_CAPACITY as u64 * 10) {
+            hot_keys.record(&x.to_be_bytes());
+        }
+
+        assert_eq!(HOT_KEY_CAPACITY, hot_keys.top(usize::MAX).len());
+    }
+}
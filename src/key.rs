@@ -32,6 +32,8 @@ impl std::fmt::Debug for InternalKey {
                 ValueType::Value => "V",
                 ValueType::Tombstone => "T",
                 ValueType::WeakTombstone => "W",
+                ValueType::ExpiringValue => "E",
+                ValueType::RangeTombstone => "R",
             },
         )
     }
@@ -54,7 +56,21 @@ impl InternalKey {
     }
 
     pub fn is_tombstone(&self) -> bool {
-        self.value_type == ValueType::Tombstone || self.value_type == ValueType::WeakTombstone
+        matches!(
+            self.value_type,
+            ValueType::Tombstone | ValueType::WeakTombstone | ValueType::RangeTombstone
+        )
+    }
+
+    pub fn is_range_tombstone(&self) -> bool {
+        self.value_type == ValueType::RangeTombstone
+    }
+
+    /// Returns `true` if a value is stored with the key.
+    ///
+    /// Tombstones have no value, except range tombstones, which store their end key.
+    pub fn has_value(&self) -> bool {
+        !self.is_tombstone() || self.is_range_tombstone()
     }
 }
 
@@ -122,7 +122,10 @@ impl Level {
 
     /// Returns the level size in bytes.
     pub fn size(&self) -> u64 {
-        self.segments.iter().map(|x| x.metadata.file_size).sum()
+        self.segments
+            .iter()
+            .map(|x| x.metadata.file_size)
+            .fold(0, u64::saturating_add)
     }
 
     pub(crate) fn compute_is_disjoint(&self) -> bool {
@@ -304,6 +307,12 @@ mod tests {
 
             bloom_filter: Some(crate::bloom::BloomFilter::with_fp_rate(1, 0.1)),
 
+            prefix_filter: None,
+
+            range_tombstones: Vec::new(),
+
+            verify_checksums: false,
+
             path: "a".into(),
             is_deleted: AtomicBool::default(),
         }
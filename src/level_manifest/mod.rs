@@ -4,12 +4,14 @@
 
 pub(crate) mod hidden_set;
 pub(crate) mod level;
+pub(crate) mod snapshot;
 
 use crate::{
     coding::{DecodeError, Encode, EncodeError},
-    file::{rewrite_atomic, MAGIC_BYTES},
+    file::{fsync_directory, rewrite_atomic, temp_file_path, MAGIC_BYTES},
+    range_tombstone::RangeTombstone,
     segment::{meta::SegmentId, Segment},
-    HashMap, HashSet, KeyRange,
+    Checksum, HashMap, HashSet, KeyRange,
 };
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use hidden_set::HiddenSet;
@@ -38,6 +40,12 @@ pub struct LevelManifest {
     hidden_set: HiddenSet,
 
     is_disjoint: bool,
+
+    /// Range tombstones of all segments, sorted by start key.
+    ///
+    /// Rebuilt whenever the segments change, so reads don't need to
+    /// visit every segment to find range tombstones.
+    range_tombstones: Arc<[RangeTombstone]>,
 }
 
 impl std::fmt::Display for LevelManifest {
@@ -125,6 +133,7 @@ impl LevelManifest {
             levels,
             hidden_set: HiddenSet::default(),
             is_disjoint: true,
+            range_tombstones: Arc::default(),
         };
         Self::write_to_disk(&manifest.path, &manifest.deep_clone())?;
 
@@ -144,7 +153,16 @@ impl LevelManifest {
     }
 
     pub(crate) fn load_level_manifest(path: &Path) -> crate::Result<Vec<Vec<SegmentId>>> {
-        let mut level_manifest = Cursor::new(std::fs::read(path)?);
+        // NOTE: Level manifests written by older versions have no checksum trailer
+        Self::decode(&std::fs::read(path)?, true)
+    }
+
+    /// Decodes a serialized level manifest.
+    ///
+    /// The checksum trailer acts as the completeness marker of the file.
+    /// If `allow_legacy` is set, a missing trailer is tolerated.
+    fn decode(bytes: &[u8], allow_legacy: bool) -> crate::Result<Vec<Vec<SegmentId>>> {
+        let mut level_manifest = Cursor::new(bytes);
 
         // Check header
         let mut magic = [0u8; MAGIC_BYTES.len()];
@@ -172,9 +190,79 @@ impl LevelManifest {
             levels.push(level);
         }
 
+        // NOTE: Cursor position is always inside the buffer
+        #[allow(clippy::cast_possible_truncation)]
+        let payload_len = level_manifest.position() as usize;
+
+        if payload_len == bytes.len() && allow_legacy {
+            return Ok(levels);
+        }
+
+        let expected = Checksum::from_raw(level_manifest.read_u64::<BigEndian>()?);
+
+        #[allow(clippy::indexing_slicing)]
+        let got = Checksum::from_bytes(&bytes[..payload_len]);
+
+        if got != expected {
+            return Err(crate::Error::InvalidChecksum((got, expected)));
+        }
+
+        if payload_len + std::mem::size_of::<u64>() != bytes.len() {
+            return Err(crate::Error::Decode(DecodeError::InvalidTrailer));
+        }
+
         Ok(levels)
     }
 
+    /// Reconciles a level manifest write that was interrupted by a crash.
+    ///
+    /// If a temporary level manifest is left over, it is either completed
+    /// (renamed over the level manifest), if it is intact and all of its segments
+    /// exist, or discarded otherwise.
+    pub(crate) fn reconcile_interrupted_write(
+        path: &Path,
+        segment_folder: &Path,
+    ) -> crate::Result<()> {
+        let temp_path = temp_file_path(path);
+
+        if !temp_path.try_exists()? {
+            return Ok(());
+        }
+
+        match Self::decode(&std::fs::read(&temp_path)?, false) {
+            Ok(levels) => {
+                let mut segment_ids = levels.iter().flatten();
+
+                if segment_ids.all(|id| segment_folder.join(id.to_string()).exists()) {
+                    log::info!(
+                        "Completing interrupted level manifest write at {}",
+                        temp_path.display(),
+                    );
+                    std::fs::rename(&temp_path, path)?;
+                } else {
+                    log::warn!(
+                        "Discarding level manifest at {} that references missing segments",
+                        temp_path.display(),
+                    );
+                    std::fs::remove_file(&temp_path)?;
+                }
+            }
+            Err(e) => {
+                log::warn!(
+                    "Discarding incomplete level manifest at {}: {e:?}",
+                    temp_path.display(),
+                );
+                std::fs::remove_file(&temp_path)?;
+            }
+        }
+
+        // NOTE: Nothing we can do
+        #[allow(clippy::expect_used)]
+        fsync_directory(path.parent().expect("should have parent folder"))?;
+
+        Ok(())
+    }
+
     pub(crate) fn recover_ids(
         path: &Path,
     ) -> crate::Result<crate::HashMap<SegmentId, u8 /* Level index */>> {
@@ -232,6 +320,7 @@ impl LevelManifest {
             hidden_set: HiddenSet::default(),
             path,
             is_disjoint: false,
+            range_tombstones: Arc::default(),
         };
         manifest.set_disjoint_flag();
 
@@ -241,7 +330,12 @@ impl LevelManifest {
     pub(crate) fn write_to_disk(path: &Path, levels: &[Level]) -> crate::Result<()> {
         log::trace!("Writing level manifest to {path:?}");
 
-        let serialized = Runs(levels).encode_into_vec();
+        let mut serialized = Runs(levels).encode_into_vec();
+
+        // NOTE: The checksum trailer marks the file as complete,
+        // see `reconcile_interrupted_write`
+        let checksum = Checksum::from_bytes(&serialized);
+        serialized.write_u64::<BigEndian>(*checksum)?;
 
         // NOTE: Compaction threads don't have concurrent access to the level manifest
         // because it is behind a mutex
@@ -266,6 +360,23 @@ impl LevelManifest {
             .collect()
     }
 
+    /// Makes sure every segment is only registered once, across all levels.
+    fn check_duplicates(levels: &[Level]) -> crate::Result<()> {
+        let mut ids = HashSet::with_capacity_and_hasher(
+            levels.iter().map(Level::len).sum(),
+            xxhash_rust::xxh3::Xxh3Builder::new(),
+        );
+
+        for id in levels.iter().flat_map(Level::ids) {
+            if !ids.insert(id) {
+                log::error!("Segment {id} is registered more than once - this is a bug");
+                return Err(crate::Error::DuplicateSegment(id));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Modifies the level manifest atomically.
     pub(crate) fn atomic_swap<F: FnOnce(&mut Vec<Level>)>(&mut self, f: F) -> crate::Result<()> {
         // NOTE: Copy-on-write...
@@ -278,6 +389,8 @@ impl LevelManifest {
 
         f(&mut working_copy);
 
+        Self::check_duplicates(&working_copy)?;
+
         Self::write_to_disk(&self.path, &working_copy)?;
         self.levels = working_copy.into_iter().map(Arc::new).collect();
         self.update_metadata();
@@ -300,6 +413,19 @@ impl LevelManifest {
                 .expect("could not get mutable Arc - this is a bug")
                 .update_metadata();
         }
+
+        let mut range_tombstones = self
+            .iter()
+            .flat_map(|segment| segment.range_tombstones.iter().cloned())
+            .collect::<Vec<_>>();
+        range_tombstones.sort_by(|a, b| a.start.cmp(&b.start));
+
+        self.range_tombstones = range_tombstones.into();
+    }
+
+    /// Returns the range tombstones of all segments, sorted by start key.
+    pub(crate) fn range_tombstones(&self) -> Arc<[RangeTombstone]> {
+        self.range_tombstones.clone()
     }
 
     #[allow(unused)]
@@ -344,6 +470,38 @@ impl LevelManifest {
         self.levels.first().map(|lvl| lvl.len()).unwrap_or_default()
     }
 
+    /// Returns the (compressed) size of all segments in the given level.
+    ///
+    /// Returns 0 if the level does not exist.
+    #[must_use]
+    pub fn level_size(&self, idx: u8) -> u64 {
+        self.levels
+            .get(usize::from(idx))
+            .map(|lvl| lvl.size())
+            .unwrap_or_default()
+    }
+
+    /// Returns the amount of segments in the given level.
+    ///
+    /// Returns 0 if the level does not exist.
+    #[must_use]
+    pub fn level_segment_count(&self, idx: u8) -> usize {
+        self.levels
+            .get(usize::from(idx))
+            .map(|lvl| lvl.len())
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if the key ranges of some segments in the given level overlap.
+    ///
+    /// Returns `false` if the level does not exist.
+    #[must_use]
+    pub fn level_is_overlapping(&self, idx: u8) -> bool {
+        self.levels
+            .get(usize::from(idx))
+            .is_some_and(|lvl| !lvl.is_disjoint)
+    }
+
     /// Returns the amount of levels in the tree
     #[must_use]
     pub fn last_level_index(&self) -> u8 {
@@ -359,7 +517,9 @@ impl LevelManifest {
     /// Returns the (compressed) size of all segments
     #[must_use]
     pub fn size(&self) -> u64 {
-        self.iter().map(|s| s.metadata.file_size).sum()
+        self.iter()
+            .map(|s| s.metadata.file_size)
+            .fold(0, u64::saturating_add)
     }
 
     #[must_use]
@@ -515,6 +675,65 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn level_manifest_level_stats() -> crate::Result<()> {
+        let folder = tempfile::tempdir()?;
+
+        let tree = crate::Config::new(folder).open()?;
+
+        tree.insert("a", "a", 0);
+        tree.insert("c", "c", 1);
+        tree.flush_active_memtable(0)?;
+        tree.insert("b", "b", 2);
+        tree.flush_active_memtable(0)?;
+
+        let levels = tree.levels.read().expect("lock is poisoned");
+
+        assert_eq!(2, levels.level_segment_count(0));
+        assert_eq!(levels.size(), levels.level_size(0));
+        assert!(levels.level_is_overlapping(0));
+
+        assert_eq!(0, levels.level_segment_count(1));
+        assert_eq!(0, levels.level_size(1));
+        assert!(!levels.level_is_overlapping(1));
+
+        // NOTE: Levels that do not exist are empty
+        assert_eq!(0, levels.level_segment_count(u8::MAX));
+        assert_eq!(0, levels.level_size(u8::MAX));
+        assert!(!levels.level_is_overlapping(u8::MAX));
+
+        Ok(())
+    }
+
+    #[test]
+    fn level_manifest_duplicate_segment() -> crate::Result<()> {
+        let folder = tempfile::tempdir()?;
+
+        let tree = crate::Config::new(folder).open()?;
+
+        tree.insert("a", "a", 0);
+        let segment = tree.flush_active_memtable(0)?.expect("should flush");
+
+        let result = tree
+            .levels
+            .write()
+            .expect("lock is poisoned")
+            .atomic_swap(|levels| levels[1].insert(segment.clone()));
+
+        assert!(matches!(
+            result,
+            Err(crate::Error::DuplicateSegment(id)) if id == segment.id(),
+        ));
+
+        // NOTE: The level manifest is unchanged
+        assert_eq!(1, tree.segment_count());
+        assert_eq!(Some(1), tree.level_segment_count(0));
+        assert_eq!(Some(0), tree.level_segment_count(1));
+        assert_eq!(&*tree.get("a", None)?.expect("should exist"), b"a");
+
+        Ok(())
+    }
+
     #[test]
     fn level_manifest_raw_empty() -> crate::Result<()> {
         let manifest = LevelManifest {
@@ -522,6 +741,7 @@ mod tests {
             levels: Vec::default(),
             path: "a".into(),
             is_disjoint: false,
+            range_tombstones: std::sync::Arc::default(),
         };
 
         let bytes = Runs(&manifest.deep_clone()).encode_into_vec();
@@ -0,0 +1,119 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::LevelManifest;
+use crate::{segment::meta::SegmentId, CompressionType, KeyRange, SeqNo};
+
+/// Metadata of a single segment, see [`ManifestSnapshot`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SegmentSnapshot {
+    /// Segment ID
+    pub id: SegmentId,
+
+    /// Key range
+    pub key_range: KeyRange,
+
+    /// Sequence number range
+    pub seqnos: (SeqNo, SeqNo),
+
+    /// Compressed size in bytes (on disk)
+    pub file_size: u64,
+
+    /// Number of KV-pairs in the segment
+    ///
+    /// This may include tombstones and multiple versions of the same key
+    pub item_count: u64,
+
+    /// Number of tombstones
+    pub tombstone_count: u64,
+
+    /// What type of compression is used
+    pub compression: CompressionType,
+
+    /// `true` if the segment is currently being compacted
+    pub is_compacting: bool,
+}
+
+/// Segments of a single level, see [`ManifestSnapshot`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct LevelSnapshot {
+    /// `true` if the key ranges of the level's segments do not overlap
+    pub is_disjoint: bool,
+
+    /// Segments of the level, in the order they are stored in
+    pub segments: Vec<SegmentSnapshot>,
+}
+
+impl LevelSnapshot {
+    /// Returns the (compressed) size of all segments in the level.
+    #[must_use]
+    pub fn size(&self) -> u64 {
+        self.segments
+            .iter()
+            .map(|s| s.file_size)
+            .fold(0, u64::saturating_add)
+    }
+}
+
+/// Owned copy of the level layout of a tree, see [`Tree::manifest_snapshot`](crate::Tree::manifest_snapshot)
+///
+/// The snapshot does not keep any segments alive, so it may be
+/// held onto (or serialized) without affecting the tree.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct ManifestSnapshot {
+    /// Levels, starting with L0
+    pub levels: Vec<LevelSnapshot>,
+}
+
+impl ManifestSnapshot {
+    /// Returns the amount of segments, summed over all levels.
+    #[must_use]
+    pub fn segment_count(&self) -> usize {
+        self.levels.iter().map(|lvl| lvl.segments.len()).sum()
+    }
+
+    /// Returns the (compressed) size of all segments.
+    #[must_use]
+    pub fn size(&self) -> u64 {
+        self.levels
+            .iter()
+            .map(LevelSnapshot::size)
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Returns an iterator over all segments, starting with L0.
+    pub fn iter(&self) -> impl Iterator<Item = &SegmentSnapshot> + '_ {
+        self.levels.iter().flat_map(|lvl| &lvl.segments)
+    }
+}
+
+impl From<&LevelManifest> for ManifestSnapshot {
+    fn from(manifest: &LevelManifest) -> Self {
+        let levels = manifest
+            .levels
+            .iter()
+            .map(|level| LevelSnapshot {
+                is_disjoint: level.is_disjoint,
+                segments: level
+                    .iter()
+                    .map(|segment| SegmentSnapshot {
+                        id: segment.id(),
+                        key_range: segment.metadata.key_range.clone(),
+                        seqnos: segment.metadata.seqnos,
+                        file_size: segment.metadata.file_size,
+                        item_count: segment.metadata.item_count,
+                        tombstone_count: segment.metadata.tombstone_count,
+                        compression: segment.metadata.compression,
+                        is_compacting: manifest.hidden_set.is_hidden(segment.id()),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self { levels }
+    }
+}
@@ -17,6 +17,7 @@ pub struct LevelReader {
     lo_reader: Option<Range>,
     hi_reader: Option<Range>,
     cache_policy: CachePolicy,
+    readahead_blocks: usize,
 }
 
 impl LevelReader {
@@ -69,8 +70,18 @@ impl LevelReader {
             lo_reader: Some(lo_reader),
             hi_reader,
             cache_policy,
+            readahead_blocks: 0,
         }
     }
+
+    /// Sets the amount of blocks to read ahead when scanning forwards.
+    #[must_use]
+    pub fn readahead(mut self, blocks: usize) -> Self {
+        self.readahead_blocks = blocks;
+        self.lo_reader = self.lo_reader.map(|x| x.readahead(blocks));
+        self.hi_reader = self.hi_reader.map(|x| x.readahead(blocks));
+        self
+    }
 }
 
 impl Iterator for LevelReader {
@@ -93,7 +104,8 @@ impl Iterator for LevelReader {
                             .get(self.lo)
                             .expect("should exist")
                             .iter()
-                            .cache_policy(self.cache_policy),
+                            .cache_policy(self.cache_policy)
+                            .readahead(self.readahead_blocks),
                     );
                 }
             } else if let Some(hi_reader) = &mut self.hi_reader {
@@ -126,7 +138,8 @@ impl DoubleEndedIterator for LevelReader {
                             .get(self.hi)
                             .expect("should exist")
                             .iter()
-                            .cache_policy(self.cache_policy),
+                            .cache_policy(self.cache_policy)
+                            .readahead(self.readahead_blocks),
                     );
                 }
             } else if let Some(lo_reader) = &mut self.lo_reader {
@@ -137,12 +137,15 @@ pub mod bloom;
 
 pub mod compaction;
 mod config;
+mod debug_iter;
 
 #[doc(hidden)]
 pub mod descriptor_table;
 
 mod error;
-// mod export;
+mod export;
+mod flush_policy;
+mod hot_keys;
 
 #[doc(hidden)]
 pub mod file;
@@ -156,6 +159,7 @@ mod level_reader;
 mod level_scanner;
 
 mod manifest;
+mod memory_budget;
 mod memtable;
 
 #[doc(hidden)]
@@ -171,6 +175,10 @@ mod path;
 #[doc(hidden)]
 pub mod range;
 
+mod range_tombstone;
+
+mod read_stats;
+
 #[doc(hidden)]
 pub mod segment;
 
@@ -193,12 +201,11 @@ pub type KvPair = (UserKey, UserValue);
 pub use value_log::KeyRange;
 
 #[doc(hidden)]
-pub mod coding {
-    pub use value_log::coding::{Decode, DecodeError, Encode, EncodeError};
-}
+pub mod coding;
 
 #[doc(hidden)]
 pub use {
+    memtable::MemtableStore,
     merge::BoxedIterator,
     segment::{block::checksum::Checksum, id::GlobalSegmentId, meta::SegmentId},
     tree::inner::TreeId,
@@ -206,16 +213,27 @@ pub use {
 };
 
 pub use {
-    cache::Cache,
+    bloom::BloomStats,
+    cache::{Cache, CacheStats, CacheWarmup},
     coding::{DecodeError, EncodeError},
-    config::{Config, TreeType},
+    config::{
+        BloomHasher, ClosingWritePolicy, Config, PrefixExtractor, SealedMemtableVisibility,
+        TreeType,
+    },
     error::{Error, Result},
-    memtable::Memtable,
+    export::ExportFormat,
+    flush_policy::{DefaultFlushPolicy, FlushPolicy, MemtableStats},
+    level_manifest::snapshot::{LevelSnapshot, ManifestSnapshot, SegmentSnapshot},
+    memory_budget::MemoryBudget,
+    memtable::{Memtable, MemtableKind},
     r#abstract::AbstractTree,
-    segment::{meta::CompressionType, Segment},
+    read_stats::ReadLevelHistogram,
+    segment::{
+        block::checksum::ChecksumType, meta::CompressionType, value_block::CachePolicy, Segment,
+    },
     seqno::SequenceNumberCounter,
     snapshot::Snapshot,
-    tree::Tree,
+    tree::{batch::Batch, import::ImportValidation, partition::PartitionStats, Tree},
     value::{SeqNo, UserKey, UserValue, ValueType},
     version::Version,
 };
@@ -226,6 +244,11 @@ pub use blob_tree::BlobTree;
 
 pub use value_log::{BlobCache, Slice};
 
+/// Inspection utilities for repair tools
+pub mod debug {
+    pub use crate::debug_iter::{DebugMergeIter, Source};
+}
+
 /// Blob garbage collection utilities
 pub mod gc {
     pub use value_log::{
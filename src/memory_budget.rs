@@ -0,0 +1,64 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+/// Share of the memory budget that is given to the block cache, in percent
+const BLOCK_CACHE_SHARE: u64 = 75;
+
+/// Returns the block cache capacity for the given memory budget.
+pub fn block_cache_capacity(budget: u64) -> u64 {
+    budget / 100 * BLOCK_CACHE_SHARE + budget % 100 * BLOCK_CACHE_SHARE / 100
+}
+
+/// How a tree's memory budget is split up, see [`Config::memory_budget`](crate::Config::memory_budget)
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct MemoryBudget {
+    /// Total memory budget in bytes
+    pub total: u64,
+
+    /// Capacity of the block cache in bytes
+    pub block_cache: u64,
+
+    /// Memory used by the (always resident) Bloom filters of all segments in bytes
+    pub bloom_filters: u64,
+
+    /// Memory that is left for memtables in bytes
+    ///
+    /// Memtables are flushed by the caller, so the active memtable
+    /// should be flushed once the tree's memtables exceed this size.
+    pub write_buffer: u64,
+}
+
+impl MemoryBudget {
+    pub(crate) fn new(total: u64, block_cache: u64, bloom_filters: u64) -> Self {
+        Self {
+            total,
+            block_cache,
+            bloom_filters,
+            write_buffer: total
+                .saturating_sub(block_cache)
+                .saturating_sub(bloom_filters),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn memory_budget_partition() {
+        assert_eq!(0, block_cache_capacity(0));
+        assert_eq!(75, block_cache_capacity(100));
+        assert_eq!(768, block_cache_capacity(1_024));
+        assert!(block_cache_capacity(u64::MAX) < u64::MAX);
+
+        let budget = MemoryBudget::new(1_024, 768, 56);
+        assert_eq!(200, budget.write_buffer);
+
+        // NOTE: Bloom filters may grow past the budget, leaving no write buffer
+        let budget = MemoryBudget::new(1_024, 768, 1_000);
+        assert_eq!(0, budget.write_buffer);
+    }
+}
@@ -2,21 +2,37 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
+mod store;
+
 use crate::key::InternalKey;
+use crate::range_tombstone::RangeTombstone;
 use crate::segment::block::ItemSize;
-use crate::value::{InternalValue, SeqNo, UserValue, ValueType};
-use crossbeam_skiplist::SkipMap;
+use crate::value::{InternalValue, SeqNo, UserKey, ValueType};
+use crate::Config;
 use std::ops::RangeBounds;
 use std::sync::atomic::{AtomicU32, AtomicU64};
+use std::sync::{RwLock, RwLockReadGuard};
+
+pub use store::{MemtableKind, MemtableStore};
 
 /// The memtable serves as an intermediary, ephemeral, sorted storage for new items
 ///
 /// When the Memtable exceeds some size, it should be flushed to a disk segment.
-#[derive(Default)]
 pub struct Memtable {
-    /// The actual content, stored in a lock-free skiplist.
+    /// The actual content, stored in the data structure chosen by [`MemtableKind`].
     #[doc(hidden)]
-    pub items: SkipMap<InternalKey, UserValue>,
+    pub items: Box<dyn MemtableStore>,
+
+    kind: MemtableKind,
+
+    /// If `true`, only the latest version of every key is kept
+    coalesce_overwrites: bool,
+
+    /// Range tombstones in the memtable
+    ///
+    /// They are also stored in `items`, but need to be found by reads of any key.
+    /// Sorted by start key.
+    range_tombstones: RwLock<Vec<RangeTombstone>>,
 
     /// Approximate active memtable size.
     ///
@@ -29,10 +45,56 @@ pub struct Memtable {
     pub(crate) highest_seqno: AtomicU64,
 }
 
+impl Default for Memtable {
+    fn default() -> Self {
+        Self::new(MemtableKind::default())
+    }
+}
+
 impl Memtable {
+    /// Creates an empty memtable, backed by the given data structure.
+    #[must_use]
+    pub fn new(kind: MemtableKind) -> Self {
+        Self {
+            items: kind.create_store(),
+            kind,
+            coalesce_overwrites: false,
+            range_tombstones: RwLock::default(),
+            approximate_size: AtomicU32::default(),
+            highest_seqno: AtomicU64::default(),
+        }
+    }
+
+    /// Creates an empty memtable, as configured for the tree.
+    pub(crate) fn from_config(config: &Config) -> Self {
+        Self::new(config.memtable_kind).coalesce_overwrites(config.memtable_coalesce_overwrites)
+    }
+
+    /// If `true`, inserts drop all older versions of the inserted key, see
+    /// [`Config::memtable_coalesce_overwrites`].
+    #[must_use]
+    pub fn coalesce_overwrites(mut self, enabled: bool) -> Self {
+        self.coalesce_overwrites = enabled;
+        self
+    }
+
+    /// Returns the backing data structure of the memtable.
+    #[must_use]
+    pub fn kind(&self) -> MemtableKind {
+        self.kind
+    }
+
     /// Clears the memtable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range tombstone lock is poisoned.
     pub fn clear(&mut self) {
         self.items.clear();
+        self.range_tombstones
+            .get_mut()
+            .expect("lock is poisoned")
+            .clear();
         self.highest_seqno = AtomicU64::new(0);
         self.approximate_size
             .store(0, std::sync::atomic::Ordering::Release);
@@ -40,21 +102,95 @@ impl Memtable {
 
     /// Creates an iterator over all items.
     pub fn iter(&self) -> impl DoubleEndedIterator<Item = InternalValue> + '_ {
-        self.items.iter().map(|entry| InternalValue {
-            key: entry.key().clone(),
-            value: entry.value().clone(),
-        })
+        self.items
+            .range((std::ops::Bound::Unbounded, std::ops::Bound::Unbounded))
     }
 
     /// Creates an iterator over a range of items.
-    pub(crate) fn range<'a, R: RangeBounds<InternalKey> + 'a>(
+    pub(crate) fn range_internal<'a, R: RangeBounds<InternalKey> + 'a>(
         &'a self,
         range: R,
     ) -> impl DoubleEndedIterator<Item = InternalValue> + 'a {
-        self.items.range(range).map(|entry| InternalValue {
-            key: entry.key().clone(),
-            value: entry.value().clone(),
-        })
+        self.items
+            .range((range.start_bound().cloned(), range.end_bound().cloned()))
+    }
+
+    /// Creates an iterator over all items (including all versions and tombstones)
+    /// whose keys are in the given key range.
+    ///
+    /// Items are yielded in sorted order (by key, then newest version first),
+    /// like the readers of disk segments, so the iterator can be merged with other sources.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lsm_tree::{InternalValue, Memtable, ValueType};
+    ///
+    /// let memtable = Memtable::default();
+    /// memtable.insert(InternalValue::from_components("a", "abc", 0, ValueType::Value));
+    /// memtable.insert(InternalValue::from_components("b", "abc", 0, ValueType::Value));
+    /// memtable.insert(InternalValue::from_components("b", "def", 1, ValueType::Value));
+    /// memtable.insert(InternalValue::from_components("c", "abc", 0, ValueType::Value));
+    ///
+    /// assert_eq!(3, memtable.range("b"..).count());
+    /// assert_eq!(b"c", &*memtable.range("b"..).next_back().unwrap().key.user_key);
+    /// ```
+    pub fn range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = InternalValue> + '_ {
+        use std::ops::Bound::{self, Excluded, Included, Unbounded};
+
+        let lo: Bound<UserKey> = match range.start_bound() {
+            Included(x) => Included(x.as_ref().into()),
+            Excluded(x) => Excluded(x.as_ref().into()),
+            Unbounded => Unbounded,
+        };
+
+        let hi: Bound<UserKey> = match range.end_bound() {
+            Included(x) => Included(x.as_ref().into()),
+            Excluded(x) => Excluded(x.as_ref().into()),
+            Unbounded => Unbounded,
+        };
+
+        self.range_internal(crate::range::to_internal_bounds(&(lo, hi)))
+    }
+
+    /// Creates an iterator over all items (including all versions and tombstones)
+    /// whose keys start with the given prefix.
+    ///
+    /// See [`Memtable::range`].
+    pub fn prefix<K: AsRef<[u8]>>(
+        &self,
+        prefix: K,
+    ) -> impl DoubleEndedIterator<Item = InternalValue> + '_ {
+        let bounds = crate::range::prefix_to_range(prefix.as_ref());
+        self.range_internal(crate::range::to_internal_bounds(&bounds))
+    }
+
+    /// Returns the range tombstones in the memtable.
+    pub(crate) fn range_tombstones(&self) -> Vec<RangeTombstone> {
+        self.read_range_tombstones().clone()
+    }
+
+    fn read_range_tombstones(&self) -> RwLockReadGuard<'_, Vec<RangeTombstone>> {
+        self.range_tombstones.read().expect("lock is poisoned")
+    }
+
+    /// Returns the seqno of the newest range tombstone in the memtable that is
+    /// visible at `read_seqno` and deletes the given version of a key, if any.
+    pub(crate) fn covering_range_tombstone(
+        &self,
+        key: &[u8],
+        seqno: SeqNo,
+        read_seqno: Option<SeqNo>,
+    ) -> Option<SeqNo> {
+        crate::range_tombstone::newest_covering(
+            &self.read_range_tombstones(),
+            key,
+            seqno,
+            read_seqno,
+        )
     }
 
     /// Returns the item by key if it exists.
@@ -91,15 +227,9 @@ impl Memtable {
             ValueType::Value,
         );
 
-        let mut iter = self
-            .items
-            .range(lower_bound..)
-            .take_while(|entry| &*entry.key().user_key == key);
-
-        iter.next().map(|entry| InternalValue {
-            key: entry.key().clone(),
-            value: entry.value().clone(),
-        })
+        self.items
+            .lower_bound(&lower_bound)
+            .filter(|item| &*item.key.user_key == key)
     }
 
     /// Gets approximate size of memtable in bytes.
@@ -130,13 +260,76 @@ impl Memtable {
             .approximate_size
             .fetch_add(item_size, std::sync::atomic::Ordering::AcqRel);
 
+        if let Some(rt) = RangeTombstone::from_item(&item) {
+            crate::range_tombstone::insert_sorted(
+                &mut self.range_tombstones.write().expect("lock is poisoned"),
+                rt,
+            );
+        }
+
         let key = InternalKey::new(item.key.user_key, item.key.seqno, item.key.value_type);
+
+        let user_key = self.coalesce_overwrites.then(|| key.user_key.clone());
+
         self.items.insert(key, item.value);
 
         self.highest_seqno
             .fetch_max(item.key.seqno, std::sync::atomic::Ordering::AcqRel);
 
-        (item_size, size_before + item_size)
+        let size_after = match user_key {
+            Some(user_key) => self.drop_old_versions(&user_key),
+            None => size_before + item_size,
+        };
+
+        (item_size, size_after)
+    }
+
+    /// Removes all versions of a key, but the latest one.
+    ///
+    /// Returns the approximate memtable size afterwards.
+    fn drop_old_versions(&self, user_key: &[u8]) -> u32 {
+        use std::ops::Bound::Included;
+
+        let lo = InternalKey::new(user_key, SeqNo::MAX, ValueType::Value);
+        let hi = InternalKey::new(user_key, 0, ValueType::Value);
+
+        let mut versions = self.items.range((Included(lo), Included(hi)));
+
+        // NOTE: A weak tombstone only cancels the version below it,
+        // so that version needs to be kept
+        let Some(latest) = versions.next() else {
+            return self.size();
+        };
+
+        if latest.key.value_type == ValueType::WeakTombstone {
+            return self.size();
+        }
+
+        let mut freed = 0;
+
+        for version in versions {
+            // IMPORTANT: A range tombstone also deletes other keys, so it needs to be kept
+            if version.key.is_range_tombstone() {
+                continue;
+            }
+
+            // NOTE: Another writer of the same key may have removed the version already
+            if self.items.remove(&version.key) {
+                // NOTE: We know values are limited to 32-bit length
+                #[allow(clippy::cast_possible_truncation)]
+                let version_size = version.size() as u32;
+
+                freed += version_size;
+            }
+        }
+
+        if freed == 0 {
+            return self.size();
+        }
+
+        self.approximate_size
+            .fetch_sub(freed, std::sync::atomic::Ordering::AcqRel)
+            - freed
     }
 
     /// Returns the highest sequence number in the memtable.
@@ -351,4 +544,217 @@ mod tests {
             memtable.get(b"abc", Some(50))
         );
     }
+
+    #[test]
+    fn memtable_coalesce_overwrites() {
+        for kind in [MemtableKind::SkipList, MemtableKind::BTree] {
+            let memtable = Memtable::new(kind).coalesce_overwrites(true);
+
+            for seqno in 0..100 {
+                memtable.insert(InternalValue::from_components(
+                    *b"hot",
+                    seqno.to_string(),
+                    seqno,
+                    ValueType::Value,
+                ));
+            }
+            memtable.insert(InternalValue::from_components(
+                *b"cold",
+                *b"cold",
+                100,
+                ValueType::Value,
+            ));
+
+            assert_eq!(2, memtable.len());
+            assert_eq!(&*memtable.get(b"hot", None).unwrap().value, b"99");
+            assert_eq!(None, memtable.get(b"hot", Some(99)));
+
+            let item = InternalValue::from_components(*b"hot", *b"99", 99, ValueType::Value);
+            let cold = InternalValue::from_components(*b"cold", *b"cold", 100, ValueType::Value);
+            assert_eq!((item.size() + cold.size()) as u32, memtable.size());
+
+            // NOTE: Out-of-order inserts do not replace the latest version
+            memtable.insert(InternalValue::from_components(
+                *b"hot",
+                *b"old",
+                5,
+                ValueType::Value,
+            ));
+            assert_eq!(2, memtable.len());
+            assert_eq!(&*memtable.get(b"hot", None).unwrap().value, b"99");
+
+            memtable.insert(InternalValue::new_tombstone(*b"hot", 101));
+            assert_eq!(2, memtable.len());
+            assert!(memtable.get(b"hot", None).unwrap().is_tombstone());
+        }
+    }
+
+    #[test]
+    fn memtable_coalesce_overwrites_weak_tombstone() {
+        let memtable = Memtable::default().coalesce_overwrites(true);
+
+        memtable.insert(InternalValue::from_components(
+            *b"a",
+            *b"old",
+            0,
+            ValueType::Value,
+        ));
+        memtable.insert(InternalValue::from_components(
+            *b"a",
+            *b"new",
+            1,
+            ValueType::Value,
+        ));
+
+        // NOTE: The weak tombstone needs the version it cancels
+        memtable.insert(InternalValue::new_weak_tombstone(*b"a", 2));
+        assert_eq!(2, memtable.len());
+
+        memtable.insert(InternalValue::from_components(
+            *b"a",
+            *b"newer",
+            3,
+            ValueType::Value,
+        ));
+        assert_eq!(1, memtable.len());
+        assert_eq!(&*memtable.get(b"a", None).unwrap().value, b"newer");
+    }
+
+    #[test]
+    fn memtable_btree_range() {
+        let memtable = Memtable::new(MemtableKind::BTree);
+
+        for key in [b"a", b"b", b"c", b"d", b"e"] {
+            memtable.insert(InternalValue::from_components(
+                *key,
+                *key,
+                0,
+                ValueType::Value,
+            ));
+        }
+
+        let lo = InternalKey::new(*b"b", SeqNo::MAX, ValueType::Value);
+        let hi = InternalKey::new(*b"d", 0, ValueType::Tombstone);
+
+        let keys = memtable
+            .range_internal(lo.clone()..=hi.clone())
+            .map(|item| item.key.user_key)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, [&b"b"[..], b"c", b"d"]);
+
+        let keys = memtable
+            .range_internal(lo.clone()..=hi.clone())
+            .rev()
+            .map(|item| item.key.user_key)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, [&b"d"[..], b"c", b"b"]);
+
+        // NOTE: Both ends meet in the middle without yielding an item twice
+        let mut iter = memtable.range_internal(lo..=hi);
+        assert!(iter.next().is_some());
+        assert!(iter.next_back().is_some());
+        assert!(iter.next().is_some());
+        assert!(iter.next_back().is_none());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn memtable_range_user_keys() {
+        for kind in [MemtableKind::SkipList, MemtableKind::BTree] {
+            let memtable = Memtable::new(kind);
+
+            for (key, seqno) in [("a", 0), ("ab", 1), ("ab", 2), ("abc", 3), ("b", 4)] {
+                memtable.insert(InternalValue::from_components(
+                    key,
+                    key,
+                    seqno,
+                    ValueType::Value,
+                ));
+            }
+            memtable.insert(InternalValue::new_tombstone("ab", 5));
+
+            let items = memtable
+                .range("ab"..="abc")
+                .map(|item| (item.key.user_key, item.key.seqno))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                items,
+                [
+                    (b"ab".into(), 5),
+                    (b"ab".into(), 2),
+                    (b"ab".into(), 1),
+                    (b"abc".into(), 3),
+                ],
+            );
+
+            let keys = memtable
+                .range("a".."b")
+                .rev()
+                .map(|item| item.key.user_key)
+                .collect::<Vec<_>>();
+            assert_eq!(keys, [&b"abc"[..], b"ab", b"ab", b"ab", b"a"]);
+
+            assert_eq!(0, memtable.range("ab".."ab").count());
+            assert_eq!(6, memtable.range::<&str, _>(..).count());
+        }
+    }
+
+    #[test]
+    fn memtable_prefix() {
+        for kind in [MemtableKind::SkipList, MemtableKind::BTree] {
+            let memtable = Memtable::new(kind);
+
+            for (key, seqno) in [("a", 0), ("ab", 1), ("ab", 2), ("abc", 3), ("b", 4)] {
+                memtable.insert(InternalValue::from_components(
+                    key,
+                    key,
+                    seqno,
+                    ValueType::Value,
+                ));
+            }
+
+            let keys = memtable
+                .prefix("ab")
+                .map(|item| item.key.user_key)
+                .collect::<Vec<_>>();
+            assert_eq!(keys, [&b"ab"[..], b"ab", b"abc"]);
+
+            let keys = memtable
+                .prefix("ab")
+                .rev()
+                .map(|item| item.key.user_key)
+                .collect::<Vec<_>>();
+            assert_eq!(keys, [&b"abc"[..], b"ab", b"ab"]);
+
+            assert_eq!(5, memtable.prefix("").count());
+            assert_eq!(0, memtable.prefix("c").count());
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn memtable_btree_iter_sees_inserts() {
+        let memtable = Memtable::new(MemtableKind::BTree);
+
+        memtable.insert(InternalValue::from_components(
+            *b"a",
+            *b"a",
+            0,
+            ValueType::Value,
+        ));
+
+        let mut iter = memtable.iter();
+        assert_eq!(&*iter.next().unwrap().key.user_key, b"a");
+
+        // NOTE: Iterators do not hold the lock, so writers are not blocked
+        memtable.insert(InternalValue::from_components(
+            *b"b",
+            *b"b",
+            1,
+            ValueType::Value,
+        ));
+
+        assert_eq!(&*iter.next().unwrap().key.user_key, b"b");
+        assert!(iter.next().is_none());
+    }
 }
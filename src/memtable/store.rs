@@ -0,0 +1,247 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::key::InternalKey;
+use crate::value::{InternalValue, UserValue};
+use crossbeam_skiplist::SkipMap;
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Bounds of a memtable range read
+pub type StoreBounds = (Bound<InternalKey>, Bound<InternalKey>);
+
+/// Boxed iterator over memtable entries
+pub type StoreIter<'a> = Box<dyn DoubleEndedIterator<Item = InternalValue> + 'a>;
+
+/// Backing data structure of a memtable
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum MemtableKind {
+    /// Lock-free skiplist
+    ///
+    /// Writers never block each other, which makes it the best choice for
+    /// write-heavy workloads.
+    #[default]
+    SkipList,
+
+    /// B-tree guarded by a read-write lock
+    ///
+    /// Point reads and scans are more cache-friendly than in a skiplist,
+    /// but concurrent writers are serialized.
+    BTree,
+}
+
+impl std::fmt::Display for MemtableKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::SkipList => "skiplist",
+                Self::BTree => "btree",
+            }
+        )
+    }
+}
+
+/// Sorted, concurrent storage that backs a [`crate::Memtable`]
+///
+/// Implementations need to allow inserting through a shared reference,
+/// and iterators need to stay valid while other threads insert.
+pub trait MemtableStore: Send + Sync {
+    /// Inserts an entry, replacing an existing entry with the same key.
+    fn insert(&self, key: InternalKey, value: UserValue);
+
+    /// Removes the entry with the given key, returning `true` if it existed.
+    fn remove(&self, key: &InternalKey) -> bool;
+
+    /// Creates an iterator over a range of entries, in key order.
+    fn range(&self, bounds: StoreBounds) -> StoreIter<'_>;
+
+    /// Returns the lowest entry that is greater or equal to `key`.
+    fn lower_bound(&self, key: &InternalKey) -> Option<InternalValue> {
+        self.range((Bound::Included(key.clone()), Bound::Unbounded))
+            .next()
+    }
+
+    /// Counts the amount of entries.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if there are no entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all entries.
+    fn clear(&mut self);
+}
+
+impl MemtableStore for SkipMap<InternalKey, UserValue> {
+    fn insert(&self, key: InternalKey, value: UserValue) {
+        Self::insert(self, key, value);
+    }
+
+    fn remove(&self, key: &InternalKey) -> bool {
+        Self::remove(self, key).is_some()
+    }
+
+    fn range(&self, bounds: StoreBounds) -> StoreIter<'_> {
+        Box::new(Self::range(self, bounds).map(|entry| InternalValue {
+            key: entry.key().clone(),
+            value: entry.value().clone(),
+        }))
+    }
+
+    fn lower_bound(&self, key: &InternalKey) -> Option<InternalValue> {
+        Self::lower_bound(self, Bound::Included(key)).map(|entry| InternalValue {
+            key: entry.key().clone(),
+            value: entry.value().clone(),
+        })
+    }
+
+    fn len(&self) -> usize {
+        Self::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        Self::is_empty(self)
+    }
+
+    fn clear(&mut self) {
+        Self::clear(self);
+    }
+}
+
+/// B-tree backed memtable storage
+///
+/// # Panics
+///
+/// All operations panic if the lock is poisoned.
+#[derive(Default)]
+pub struct BTreeStore(RwLock<BTreeMap<InternalKey, UserValue>>);
+
+impl BTreeStore {
+    fn read(&self) -> RwLockReadGuard<'_, BTreeMap<InternalKey, UserValue>> {
+        self.0.read().expect("lock is poisoned")
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, BTreeMap<InternalKey, UserValue>> {
+        self.0.write().expect("lock is poisoned")
+    }
+}
+
+impl MemtableStore for BTreeStore {
+    fn insert(&self, key: InternalKey, value: UserValue) {
+        self.write().insert(key, value);
+    }
+
+    fn remove(&self, key: &InternalKey) -> bool {
+        self.write().remove(key).is_some()
+    }
+
+    fn range(&self, (lo, hi): StoreBounds) -> StoreIter<'_> {
+        Box::new(BTreeCursor {
+            store: self,
+            lo,
+            hi,
+        })
+    }
+
+    fn lower_bound(&self, key: &InternalKey) -> Option<InternalValue> {
+        self.read()
+            .range(key..)
+            .next()
+            .map(|(key, value)| InternalValue {
+                key: key.clone(),
+                value: value.clone(),
+            })
+    }
+
+    fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    fn clear(&mut self) {
+        self.0.get_mut().expect("lock is poisoned").clear();
+    }
+}
+
+/// Iterator over a [`BTreeStore`]
+///
+/// The read lock cannot be held across calls to `next`, as that would block
+/// writers for as long as the iterator lives. Instead, every step re-seeks
+/// from the last returned key, so the cursor behaves like a skiplist iterator
+/// and observes concurrent inserts past its position.
+struct BTreeCursor<'a> {
+    store: &'a BTreeStore,
+    lo: Bound<InternalKey>,
+    hi: Bound<InternalKey>,
+}
+
+impl BTreeCursor<'_> {
+    fn is_exhausted(&self) -> bool {
+        use Bound::{Excluded, Included};
+
+        match (&self.lo, &self.hi) {
+            (Included(lo), Included(hi)) => lo > hi,
+            (Included(lo) | Excluded(lo), Included(hi) | Excluded(hi)) => lo >= hi,
+            _ => false,
+        }
+    }
+
+    fn bounds(&self) -> StoreBounds {
+        (self.lo.clone(), self.hi.clone())
+    }
+}
+
+impl Iterator for BTreeCursor<'_> {
+    type Item = InternalValue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // NOTE: BTreeMap::range panics on empty excluded ranges
+        if self.is_exhausted() {
+            return None;
+        }
+
+        let (key, value) = self
+            .store
+            .read()
+            .range(self.bounds())
+            .next()
+            .map(|(key, value)| (key.clone(), value.clone()))?;
+
+        self.lo = Bound::Excluded(key.clone());
+
+        Some(InternalValue { key, value })
+    }
+}
+
+impl DoubleEndedIterator for BTreeCursor<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        // NOTE: BTreeMap::range panics on empty excluded ranges
+        if self.is_exhausted() {
+            return None;
+        }
+
+        let (key, value) = self
+            .store
+            .read()
+            .range(self.bounds())
+            .next_back()
+            .map(|(key, value)| (key.clone(), value.clone()))?;
+
+        self.hi = Bound::Excluded(key.clone());
+
+        Some(InternalValue { key, value })
+    }
+}
+
+impl MemtableKind {
+    pub(crate) fn create_store(self) -> Box<dyn MemtableStore> {
+        match self {
+            Self::SkipList => Box::<SkipMap<InternalKey, UserValue>>::default(),
+            Self::BTree => Box::<BTreeStore>::default(),
+        }
+    }
+}
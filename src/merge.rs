@@ -9,6 +9,23 @@ type IterItem = crate::Result<InternalValue>;
 
 pub type BoxedIterator<'a> = Box<dyn DoubleEndedIterator<Item = IterItem> + 'a>;
 
+/// Iterator that can skip ahead to a key, see [`Merger::seek`]
+pub trait SeekIterator: DoubleEndedIterator<Item = IterItem> {
+    /// Skips ahead, so the next item is the first item whose key is `>= key`.
+    ///
+    /// Seeking never moves backwards, so seeking to a key
+    /// that was already passed does nothing.
+    fn seek(&mut self, key: &[u8]) -> crate::Result<()>;
+}
+
+impl<T: SeekIterator + ?Sized> SeekIterator for Box<T> {
+    fn seek(&mut self, key: &[u8]) -> crate::Result<()> {
+        (**self).seek(key)
+    }
+}
+
+pub type BoxedSeekIterator<'a> = Box<dyn SeekIterator + 'a>;
+
 #[derive(Eq)]
 struct HeapItem(usize, InternalValue);
 
@@ -80,11 +97,10 @@ impl<I: DoubleEndedIterator<Item = IterItem>> Merger<I> {
     }
 }
 
-impl<I: Iterator<Item = IterItem>> Iterator for Merger<I> {
-    type Item = IterItem;
-
+impl<I: Iterator<Item = IterItem>> Merger<I> {
+    /// Returns the next item, together with the index of the iterator it came from.
     #[allow(clippy::indexing_slicing)]
-    fn next(&mut self) -> Option<Self::Item> {
+    pub(crate) fn next_with_source(&mut self) -> Option<crate::Result<(usize, InternalValue)>> {
         if !self.initialized_lo {
             fail_iter!(self.initialize_lo());
         }
@@ -96,7 +112,65 @@ impl<I: Iterator<Item = IterItem>> Iterator for Merger<I> {
             self.heap.push(HeapItem(min_item.0, next_item));
         }
 
-        Some(Ok(min_item.1))
+        Some(Ok((min_item.0, min_item.1)))
+    }
+}
+
+impl<I: SeekIterator> Merger<I> {
+    /// Skips ahead, so the next item is the first item whose key is `>= key`.
+    ///
+    /// Every iterator seeks on its own before its items are merged,
+    /// so segment ranges can look up the key in their block index,
+    /// instead of reading and discarding the skipped blocks.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the merger was already read from the back.
+    #[allow(clippy::indexing_slicing)]
+    pub fn seek(&mut self, key: &[u8]) -> crate::Result<()> {
+        assert!(
+            !self.initialized_hi,
+            "cannot seek after reading from the back"
+        );
+
+        if !self.initialized_lo {
+            for iter in &mut self.iterators {
+                iter.seek(key)?;
+            }
+            return Ok(());
+        }
+
+        // NOTE: The heap contains the next item of every iterator,
+        // so only the iterators whose next item is before the key need to seek
+        while self
+            .heap
+            .min()
+            .is_some_and(|item| &*item.1.key.user_key < key)
+        {
+            let Some(HeapItem(idx, _)) = self.heap.pop_min() else {
+                break;
+            };
+
+            self.iterators[idx].seek(key)?;
+
+            if let Some(next_item) = self.iterators[idx].next() {
+                self.heap.push(HeapItem(idx, next_item?));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<I: Iterator<Item = IterItem>> Iterator for Merger<I> {
+    type Item = IterItem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_with_source()?.map(|(_, item)| item))
     }
 }
 
@@ -2,18 +2,164 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::InternalValue;
-use std::collections::VecDeque;
+use crate::{InternalValue, KeyRange, UserKey};
+use std::{collections::VecDeque, ops::Bound};
+
+/// Key bounds a reader of a [`MultiReader`] is limited to
+pub type ReaderBounds = (Bound<UserKey>, Bound<UserKey>);
+
+/// Returns `true` if the key is below the lower bound.
+fn is_below(bounds: &ReaderBounds, key: &[u8]) -> bool {
+    match &bounds.0 {
+        Bound::Included(lo) => key < &**lo,
+        Bound::Excluded(lo) => key <= &**lo,
+        Bound::Unbounded => false,
+    }
+}
+
+/// Returns `true` if the key is above the upper bound.
+fn is_above(bounds: &ReaderBounds, key: &[u8]) -> bool {
+    match &bounds.1 {
+        Bound::Included(hi) => key > &**hi,
+        Bound::Excluded(hi) => key >= &**hi,
+        Bound::Unbounded => false,
+    }
+}
+
+/// Checks that the keys emitted in one direction are ordered,
+/// and that consecutive readers are disjoint
+#[cfg(debug_assertions)]
+#[derive(Default)]
+struct OrderCheck {
+    last_key: Option<UserKey>,
+    switched_reader: bool,
+}
+
+#[cfg(debug_assertions)]
+impl OrderCheck {
+    fn observe(&mut self, key: &UserKey, reverse: bool) {
+        if let Some(last_key) = &self.last_key {
+            let ordering = if reverse {
+                last_key.cmp(key)
+            } else {
+                key.cmp(last_key)
+            };
+
+            if self.switched_reader {
+                debug_assert!(
+                    ordering.is_gt(),
+                    "readers are not disjoint: {last_key:?} is followed by {key:?}",
+                );
+            } else {
+                debug_assert!(
+                    ordering.is_ge(),
+                    "reader is not sorted: {last_key:?} is followed by {key:?}",
+                );
+            }
+        }
+
+        self.last_key = Some(key.clone());
+        self.switched_reader = false;
+    }
+}
+
+struct BoundedReader<I> {
+    reader: I,
+    bounds: ReaderBounds,
+}
 
 /// Reads through a disjoint, sorted set of readers
+///
+/// Every reader may be given key bounds. Items outside of the bounds are skipped,
+/// so a reader that over-reads cannot emit items that belong to its neighbours.
 pub struct MultiReader<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> {
-    readers: VecDeque<I>,
+    readers: VecDeque<BoundedReader<I>>,
+
+    #[cfg(debug_assertions)]
+    front_check: OrderCheck,
+
+    #[cfg(debug_assertions)]
+    back_check: OrderCheck,
 }
 
 impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> MultiReader<I> {
+    /// Creates a multi reader over readers that are not bounded.
+    #[allow(dead_code)]
     #[must_use]
     pub fn new(readers: VecDeque<I>) -> Self {
-        Self { readers }
+        Self::with_bounds(
+            readers
+                .into_iter()
+                .map(|reader| (reader, (Bound::Unbounded, Bound::Unbounded)))
+                .collect(),
+        )
+    }
+
+    /// Creates a multi reader, limiting every reader to its key bounds.
+    #[must_use]
+    pub fn with_bounds(readers: VecDeque<(I, ReaderBounds)>) -> Self {
+        Self {
+            readers: readers
+                .into_iter()
+                .map(|(reader, bounds)| BoundedReader { reader, bounds })
+                .collect(),
+
+            #[cfg(debug_assertions)]
+            front_check: OrderCheck::default(),
+
+            #[cfg(debug_assertions)]
+            back_check: OrderCheck::default(),
+        }
+    }
+
+    /// Creates a multi reader over readers that cover the given key ranges,
+    /// limited to the range of `lo` and `hi`.
+    ///
+    /// Readers that do not overlap with the range are dropped up front, so they are never polled.
+    /// Only readers that cross one of the range's bounds are limited to the range,
+    /// readers that lie fully inside the range need not check their items.
+    #[must_use]
+    pub fn with_range(
+        readers: VecDeque<(I, KeyRange)>,
+        lo: Bound<UserKey>,
+        hi: Bound<UserKey>,
+    ) -> Self {
+        let bounds = (lo, hi);
+
+        Self::with_bounds(
+            readers
+                .into_iter()
+                .filter(|(_, key_range)| key_range.overlaps_with_bounds(&bounds))
+                .map(|(reader, key_range)| {
+                    let is_contained =
+                        !is_below(&bounds, key_range.min()) && !is_above(&bounds, key_range.max());
+
+                    if is_contained {
+                        (reader, (Bound::Unbounded, Bound::Unbounded))
+                    } else {
+                        (reader, bounds.clone())
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    fn pop_front(&mut self) {
+        self.readers.pop_front();
+
+        #[cfg(debug_assertions)]
+        {
+            self.front_check.switched_reader = true;
+        }
+    }
+
+    fn pop_back(&mut self) {
+        self.readers.pop_back();
+
+        #[cfg(debug_assertions)]
+        {
+            self.back_check.switched_reader = true;
+        }
     }
 }
 
@@ -22,12 +168,30 @@ impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> Iterator for M
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            if let Some(item) = self.readers.front_mut()?.next() {
-                return Some(item);
+            let front = self.readers.front_mut()?;
+
+            let Some(item) = front.reader.next() else {
+                // NOTE: Current reader has no more items, load next reader if it exists and try again
+                self.pop_front();
+                continue;
+            };
+
+            let item = fail_iter!(item);
+
+            // NOTE: The reader over-read, so the rest of it is out of bounds as well
+            if is_above(&front.bounds, &item.key.user_key) {
+                self.pop_front();
+                continue;
             }
 
-            // NOTE: Current reader has no more items, load next reader if it exists and try again
-            self.readers.pop_front();
+            if is_below(&front.bounds, &item.key.user_key) {
+                continue;
+            }
+
+            #[cfg(debug_assertions)]
+            self.front_check.observe(&item.key.user_key, false);
+
+            return Some(Ok(item));
         }
     }
 }
@@ -37,12 +201,30 @@ impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> DoubleEndedIte
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         loop {
-            if let Some(item) = self.readers.back_mut()?.next_back() {
-                return Some(item);
+            let back = self.readers.back_mut()?;
+
+            let Some(item) = back.reader.next_back() else {
+                // NOTE: Current reader has no more items, load next reader if it exists and try again
+                self.pop_back();
+                continue;
+            };
+
+            let item = fail_iter!(item);
+
+            // NOTE: The reader over-read, so the rest of it is out of bounds as well
+            if is_below(&back.bounds, &item.key.user_key) {
+                self.pop_back();
+                continue;
+            }
+
+            if is_above(&back.bounds, &item.key.user_key) {
+                continue;
             }
 
-            // NOTE: Current reader has no more items, load next reader if it exists and try again
-            self.readers.pop_back();
+            #[cfg(debug_assertions)]
+            self.back_check.observe(&item.key.user_key, true);
+
+            return Some(Ok(item));
         }
     }
 }
@@ -163,4 +345,157 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn segment_multi_reader_bounds() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let tree = crate::Config::new(&tempdir).open()?;
+
+        let ids = [["a", "b", "c"], ["d", "e", "f"], ["g", "h", "i"]];
+
+        for batch in ids {
+            for id in batch {
+                tree.insert(id, vec![], 0);
+            }
+            tree.flush_active_memtable(0)?;
+        }
+
+        let segments = tree
+            .levels
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // NOTE: Every reader reads its whole segment, so the bounds need to clip it
+        let bounds: [ReaderBounds; 3] = [
+            (Bound::Excluded(Slice::from(*b"a")), Bound::Unbounded),
+            (Bound::Unbounded, Bound::Included(Slice::from(*b"e"))),
+            (
+                Bound::Included(Slice::from(*b"h")),
+                Bound::Excluded(Slice::from(*b"i")),
+            ),
+        ];
+
+        let create_reader = || {
+            MultiReader::with_bounds(
+                segments
+                    .iter()
+                    .zip(bounds.iter().cloned())
+                    .map(|(segment, bounds)| (segment.iter(), bounds))
+                    .collect(),
+            )
+        };
+
+        let keys = create_reader()
+            .map(|item| item.map(|item| item.key.user_key))
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(
+            keys,
+            [*b"b", *b"c", *b"d", *b"e", *b"h"]
+                .map(Slice::from)
+                .to_vec(),
+        );
+
+        let keys = create_reader()
+            .rev()
+            .map(|item| item.map(|item| item.key.user_key))
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(
+            keys,
+            [*b"h", *b"e", *b"d", *b"c", *b"b"]
+                .map(Slice::from)
+                .to_vec(),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn segment_multi_reader_range() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let tree = crate::Config::new(&tempdir).open()?;
+
+        let ids = [
+            ["a", "b", "c"],
+            ["d", "e", "f"],
+            ["g", "h", "i"],
+            ["j", "k", "l"],
+        ];
+
+        for batch in ids {
+            for id in batch {
+                tree.insert(id, vec![], 0);
+            }
+            tree.flush_active_memtable(0)?;
+        }
+
+        let segments = tree
+            .levels
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let create_reader = || {
+            MultiReader::with_range(
+                segments
+                    .iter()
+                    .map(|segment| (segment.iter(), segment.metadata.key_range.clone()))
+                    .collect(),
+                Bound::Excluded(Slice::from(*b"d")),
+                Bound::Included(Slice::from(*b"j")),
+            )
+        };
+
+        // NOTE: The first segment is out of range, only the boundary readers are limited
+        let reader = create_reader();
+        assert_eq!(3, reader.readers.len());
+        assert!(reader.readers.iter().map(|x| &x.bounds).eq(&[
+            (
+                Bound::Excluded(Slice::from(*b"d")),
+                Bound::Included(Slice::from(*b"j"))
+            ),
+            (Bound::Unbounded, Bound::Unbounded),
+            (
+                Bound::Excluded(Slice::from(*b"d")),
+                Bound::Included(Slice::from(*b"j"))
+            ),
+        ]));
+
+        let keys = create_reader()
+            .map(|item| item.map(|item| item.key.user_key))
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(
+            keys,
+            [*b"e", *b"f", *b"g", *b"h", *b"i", *b"j"]
+                .map(Slice::from)
+                .to_vec(),
+        );
+
+        let keys = create_reader()
+            .rev()
+            .map(|item| item.map(|item| item.key.user_key))
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(
+            keys,
+            [*b"j", *b"i", *b"h", *b"g", *b"f", *b"e"]
+                .map(Slice::from)
+                .to_vec(),
+        );
+
+        let reader = MultiReader::with_range(
+            segments
+                .iter()
+                .map(|segment| (segment.iter(), segment.metadata.key_range.clone()))
+                .collect(),
+            Bound::Excluded(Slice::from(*b"l")),
+            Bound::Unbounded,
+        );
+        assert_eq!(0, reader.count());
+
+        Ok(())
+    }
 }
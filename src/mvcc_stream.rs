@@ -2,7 +2,10 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::{InternalValue, UserKey};
+use crate::{
+    range_tombstone::{is_covered, RangeTombstone},
+    InternalValue, UserKey,
+};
 use double_ended_peekable::{DoubleEndedPeekable, DoubleEndedPeekableExt};
 
 /// Consumes a stream of KVs and emits a new stream according to MVCC and tombstone rules
@@ -11,6 +14,12 @@ use double_ended_peekable::{DoubleEndedPeekable, DoubleEndedPeekableExt};
 #[allow(clippy::module_name_repetitions)]
 pub struct MvccStream<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> {
     inner: DoubleEndedPeekable<I>,
+
+    /// If `true`, older versions are not collapsed into the newest one
+    all_versions: bool,
+
+    /// Range tombstones that are visible to the read
+    range_tombstones: Vec<RangeTombstone>,
 }
 
 impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> MvccStream<I> {
@@ -18,7 +27,46 @@ impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> MvccStream<I>
     #[must_use]
     pub fn new(iter: I) -> Self {
         let iter = iter.double_ended_peekable();
-        Self { inner: iter }
+
+        Self {
+            inner: iter,
+            all_versions: false,
+            range_tombstones: Vec::new(),
+        }
+    }
+
+    /// Initializes a new merge iterator that emits every version of every key
+    ///
+    /// Versions are emitted in MVCC order (keys ascending, and per key, newest version first),
+    /// including tombstones and shadowed versions. This is useful for change data capture
+    /// and debugging.
+    #[must_use]
+    pub fn new_all_versions(iter: I) -> Self {
+        let iter = iter.double_ended_peekable();
+
+        Self {
+            inner: iter,
+            all_versions: true,
+            range_tombstones: Vec::new(),
+        }
+    }
+
+    /// Sets the range tombstones that are visible to the read.
+    ///
+    /// Versions that are deleted by a range tombstone are emitted as tombstones.
+    #[must_use]
+    pub(crate) fn with_range_tombstones(mut self, range_tombstones: Vec<RangeTombstone>) -> Self {
+        self.range_tombstones = range_tombstones;
+        self
+    }
+
+    /// Turns the item into a tombstone, if it is deleted by a range tombstone.
+    fn mask(&self, item: InternalValue) -> InternalValue {
+        if is_covered(&self.range_tombstones, &item) {
+            InternalValue::new_tombstone(item.key.user_key, item.key.seqno)
+        } else {
+            item
+        }
     }
 
     fn drain_key_min(&mut self, key: &UserKey) -> crate::Result<()> {
@@ -54,6 +102,11 @@ impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> Iterator for M
 
     fn next(&mut self) -> Option<Self::Item> {
         let head = fail_iter!(self.inner.next()?);
+        let head = self.mask(head);
+
+        if self.all_versions {
+            return Some(Ok(head));
+        }
 
         // As long as items are the same key, ignore them
         fail_iter!(self.drain_key_min(&head.key.user_key));
@@ -66,6 +119,11 @@ impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> DoubleEndedIte
     for MvccStream<I>
 {
     fn next_back(&mut self) -> Option<Self::Item> {
+        if self.all_versions {
+            let item = fail_iter!(self.inner.next_back()?);
+            return Some(Ok(self.mask(item)));
+        }
+
         loop {
             let tail = fail_iter!(self.inner.next_back()?);
 
@@ -81,12 +139,12 @@ impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> DoubleEndedIte
                         .expect_err("should be error")));
                 }
                 None => {
-                    return Some(Ok(tail));
+                    return Some(Ok(self.mask(tail)));
                 }
             };
 
             if prev.key.user_key < tail.key.user_key {
-                return Some(Ok(tail));
+                return Some(Ok(self.mask(tail)));
             }
         }
     }
@@ -145,6 +203,17 @@ mod tests {
             let backwards = iter.rev().flatten().collect::<Vec<_>>();
 
             assert_eq!(forwards, backwards);
+
+            let iter = Box::new($v.iter().cloned().map(Ok));
+            let iter = MvccStream::new_all_versions(iter);
+            let mut forwards = iter.flatten().collect::<Vec<_>>();
+            forwards.reverse();
+
+            let iter = Box::new($v.iter().cloned().map(Ok));
+            let iter = MvccStream::new_all_versions(iter);
+            let backwards = iter.rev().flatten().collect::<Vec<_>>();
+
+            assert_eq!(forwards, backwards);
         };
     }
 
@@ -434,6 +503,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn mvcc_stream_empty_value() -> crate::Result<()> {
+        #[rustfmt::skip]
+        let vec = stream![
+          "a", "", "V",
+          "a", "old", "V",
+          "b", "", "T",
+          "b", "", "V",
+        ];
+
+        let iter = Box::new(vec.iter().cloned().map(Ok));
+
+        let mut iter = MvccStream::new(iter);
+
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"b", *b"", 999, ValueType::Tombstone),
+            iter.next().unwrap()?,
+        );
+        iter_closed!(iter);
+
+        test_reverse!(vec);
+
+        Ok(())
+    }
+
     #[test]
     #[allow(clippy::unwrap_used)]
     fn mvcc_stream_simple_multi_keys() -> crate::Result<()> {
@@ -642,4 +741,191 @@ mod tests {
 
         Ok(())
     }
+
+    /// Returns every sequence of value types of length `len`.
+    fn value_type_combinations(len: usize) -> Vec<Vec<ValueType>> {
+        let mut combinations = vec![vec![]];
+
+        for _ in 0..len {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|prefix| {
+                    [
+                        ValueType::Value,
+                        ValueType::Tombstone,
+                        ValueType::WeakTombstone,
+                    ]
+                    .into_iter()
+                    .map(move |value_type| {
+                        let mut combination = prefix.clone();
+                        combination.push(value_type);
+                        combination
+                    })
+                })
+                .collect();
+        }
+
+        combinations
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn mvcc_stream_value_type_combinations() -> crate::Result<()> {
+        for len in 1..=4 {
+            for combination in value_type_combinations(len) {
+                // NOTE: Versions are sorted by descending seqno
+                let vec = combination
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, value_type)| {
+                        InternalValue::from_components("a", "", (len - idx) as u64, *value_type)
+                    })
+                    .collect::<Vec<_>>();
+
+                let mut iter = MvccStream::new(Box::new(vec.iter().cloned().map(Ok)));
+                assert_eq!(vec[0], iter.next().unwrap()?, "{combination:?}");
+                iter_closed!(iter);
+
+                let mut iter = MvccStream::new(Box::new(vec.iter().cloned().map(Ok)));
+                assert_eq!(vec[0], iter.next_back().unwrap()?, "{combination:?}");
+                iter_closed!(iter);
+
+                test_reverse!(vec);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn mvcc_stream_value_type_combinations_multi_keys() -> crate::Result<()> {
+        for combination in value_type_combinations(3) {
+            let mut vec = vec![InternalValue::from_components(
+                "a",
+                "a",
+                0,
+                ValueType::Value,
+            )];
+
+            vec.extend(combination.iter().enumerate().map(|(idx, value_type)| {
+                InternalValue::from_components("b", "", (3 - idx) as u64, *value_type)
+            }));
+
+            vec.push(InternalValue::from_components(
+                "c",
+                "c",
+                0,
+                ValueType::Value,
+            ));
+
+            let mut iter = MvccStream::new(Box::new(vec.iter().cloned().map(Ok)));
+            assert_eq!(vec[4], iter.next_back().unwrap()?, "{combination:?}");
+            assert_eq!(vec[1], iter.next_back().unwrap()?, "{combination:?}");
+            assert_eq!(vec[0], iter.next_back().unwrap()?, "{combination:?}");
+            iter_closed!(iter);
+
+            // NOTE: Mixed directions meet in the middle
+            let mut iter = MvccStream::new(Box::new(vec.iter().cloned().map(Ok)));
+            assert_eq!(vec[0], iter.next().unwrap()?, "{combination:?}");
+            assert_eq!(vec[4], iter.next_back().unwrap()?, "{combination:?}");
+            assert_eq!(vec[1], iter.next_back().unwrap()?, "{combination:?}");
+            iter_closed!(iter);
+
+            test_reverse!(vec);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn mvcc_stream_all_versions() -> crate::Result<()> {
+        #[rustfmt::skip]
+        let vec = stream![
+          "a", "new", "V",
+          "a", "", "T",
+          "a", "old", "V",
+          "b", "", "W",
+          "b", "old", "V",
+          "c", "c", "V",
+        ];
+
+        let iter = MvccStream::new_all_versions(Box::new(vec.iter().cloned().map(Ok)));
+        assert_eq!(vec, iter.collect::<crate::Result<Vec<_>>>()?);
+
+        // NOTE: Mixed directions meet in the middle, even within the versions of a key
+        let mut iter = MvccStream::new_all_versions(Box::new(vec.iter().cloned().map(Ok)));
+        assert_eq!(vec[0], iter.next().unwrap()?);
+        assert_eq!(vec[5], iter.next_back().unwrap()?);
+        assert_eq!(vec[4], iter.next_back().unwrap()?);
+        assert_eq!(vec[1], iter.next().unwrap()?);
+        assert_eq!(vec[2], iter.next().unwrap()?);
+        assert_eq!(vec[3], iter.next_back().unwrap()?);
+        iter_closed!(iter);
+
+        test_reverse!(vec);
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn mvcc_stream_range_tombstones() -> crate::Result<()> {
+        #[rustfmt::skip]
+        let vec = stream![
+          "a", "old", "V",
+          "b", "new", "V",
+          "b", "old", "V",
+          "c", "old", "V",
+        ];
+
+        let range_tombstones = vec![RangeTombstone {
+            start: "b".into(),
+            end: Some("c".into()),
+            seqno: 999,
+        }];
+
+        // NOTE: b@999 is not older than the range tombstone, so it is kept
+        let iter = Box::new(vec.iter().cloned().map(Ok));
+        let mut iter = MvccStream::new(iter).with_range_tombstones(range_tombstones.clone());
+
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"old", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"b", *b"new", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"c", *b"old", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        iter_closed!(iter);
+
+        let range_tombstones = vec![RangeTombstone {
+            seqno: 1_000,
+            ..range_tombstones[0].clone()
+        }];
+
+        let iter = Box::new(vec.iter().cloned().map(Ok));
+        let mut iter = MvccStream::new(iter).with_range_tombstones(range_tombstones);
+
+        assert_eq!(
+            InternalValue::from_components(*b"c", *b"old", 999, ValueType::Value),
+            iter.next_back().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::new_tombstone(*b"b", 999),
+            iter.next_back().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"old", 999, ValueType::Value),
+            iter.next_back().unwrap()?,
+        );
+        iter_closed!(iter);
+
+        Ok(())
+    }
 }
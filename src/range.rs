@@ -3,6 +3,7 @@
 // (found in the LICENSE-* files in the repository)
 
 use crate::{
+    bloom::CompositeHash,
     key::InternalKey,
     level_manifest::{level::Level, LevelManifest},
     level_reader::LevelReader,
@@ -12,7 +13,7 @@ use crate::{
     mvcc_stream::MvccStream,
     segment::value_block::CachePolicy,
     value::{SeqNo, UserKey},
-    InternalValue,
+    InternalValue, KeyRange,
 };
 use guardian::ArcRwLockReadGuardian;
 use self_cell::self_cell;
@@ -23,6 +24,12 @@ pub fn seqno_filter(item_seqno: SeqNo, seqno: SeqNo) -> bool {
     item_seqno < seqno
 }
 
+/// Returns the key range that contains exactly the keys starting with `prefix`.
+///
+/// The upper bound is the prefix's successor: the prefix with its last byte
+/// incremented (skipping trailing `0xFF` bytes, which cannot be incremented).
+/// This relies on keys being ordered lexicographically by their bytes,
+/// which is the only key order the tree supports.
 #[must_use]
 #[allow(clippy::module_name_repetitions)]
 pub fn prefix_to_range(prefix: &[u8]) -> (Bound<UserKey>, Bound<UserKey>) {
@@ -48,20 +55,69 @@ pub fn prefix_to_range(prefix: &[u8]) -> (Bound<UserKey>, Bound<UserKey>) {
     (Included(prefix.into()), Unbounded)
 }
 
+/// Converts user key bounds into bounds of internal keys that include
+/// all versions of the keys in the range.
+#[must_use]
+pub(crate) fn to_internal_bounds(
+    bounds: &(Bound<UserKey>, Bound<UserKey>),
+) -> (Bound<InternalKey>, Bound<InternalKey>) {
+    let lo = match &bounds.0 {
+        // NOTE: See memtable.rs for range explanation
+        Bound::Included(key) => Bound::Included(InternalKey::new(
+            key.clone(),
+            SeqNo::MAX,
+            crate::value::ValueType::Tombstone,
+        )),
+        Bound::Excluded(key) => Bound::Excluded(InternalKey::new(
+            key.clone(),
+            0,
+            crate::value::ValueType::Tombstone,
+        )),
+        Bound::Unbounded => Bound::Unbounded,
+    };
+
+    let hi = match &bounds.1 {
+        // NOTE: See memtable.rs for range explanation, this is the reverse case
+        // where we need to go all the way to the last seqno of an item
+        //
+        // Example: We search for (Unbounded..Excluded(abdef))
+        //
+        // key -> seqno
+        //
+        // a   -> 7 <<< This is the lowest key that matches the range
+        // abc -> 5
+        // abc -> 4
+        // abc -> 3 <<< This is the highest key that matches the range
+        // abcdef -> 6
+        // abcdef -> 5
+        //
+        Bound::Included(key) => Bound::Included(InternalKey::new(
+            key.clone(),
+            0,
+            crate::value::ValueType::Value,
+        )),
+        Bound::Excluded(key) => Bound::Excluded(InternalKey::new(
+            key.clone(),
+            SeqNo::MAX,
+            crate::value::ValueType::Value,
+        )),
+        Bound::Unbounded => Bound::Unbounded,
+    };
+
+    (lo, hi)
+}
+
 /// The iter state references the memtables used while the range is open
 ///
 /// Because of Rust rules, the state is referenced using `self_cell`, see below.
+///
+/// Segments do not need to be referenced here: every segment reader pins the
+/// segment(s) it reads from, so the iterator keeps reading a consistent view,
+/// even if a compaction swaps out (and deletes) those segments in the meantime.
 pub struct IterState {
     pub(crate) active: Arc<Memtable>,
     pub(crate) sealed: Vec<Arc<Memtable>>,
     pub(crate) ephemeral: Option<Arc<Memtable>>,
-
-    // NOTE: Monkey patch to keep segments referenced until range read drops
-    // Otherwise segment files can get deleted too early
-    // (because once we create the range iterator, it does not hold onto segments normally)
-    // TODO: we need a Version system
-    #[allow(unused)]
-    pub(crate) levels: Vec<Arc<Level>>,
 }
 
 type BoxedMerge<'a> = Box<dyn DoubleEndedIterator<Item = crate::Result<InternalValue>> + 'a>;
@@ -89,14 +145,30 @@ impl DoubleEndedIterator for TreeIter {
     }
 }
 
+/// Removes the segments whose prefix filter rules out the prefix from the levels
+fn prune_levels(levels: &[Arc<Level>], prefix_hash: CompositeHash) -> Vec<Arc<Level>> {
+    levels
+        .iter()
+        .map(|level| {
+            Arc::new(Level {
+                segments: level
+                    .iter()
+                    .filter(|x| x.may_contain_prefix_hash(prefix_hash))
+                    .cloned()
+                    .collect(),
+                is_disjoint: level.is_disjoint,
+            })
+        })
+        .collect()
+}
+
+/// Reads the levels of a disjoint tree, see [`LevelManifest::is_disjoint`]
 fn collect_disjoint_tree_with_range(
-    level_manifest: &LevelManifest,
+    levels: &[Arc<Level>],
     bounds: &(Bound<UserKey>, Bound<UserKey>),
+    readahead_blocks: usize,
 ) -> MultiReader<LevelReader> {
-    debug_assert!(level_manifest.is_disjoint());
-
-    let mut levels = level_manifest
-        .levels
+    let mut levels = levels
         .iter()
         .filter(|x| !x.is_empty())
         .cloned()
@@ -129,10 +201,15 @@ fn collect_disjoint_tree_with_range(
 
     let readers = levels
         .into_iter()
-        .filter_map(|lvl| LevelReader::new(lvl, bounds, CachePolicy::Write))
+        .filter_map(|lvl| {
+            let key_range = KeyRange::aggregate(lvl.segments.iter().map(|x| &x.metadata.key_range));
+
+            LevelReader::new(lvl, bounds, CachePolicy::Write)
+                .map(|reader| (reader.readahead(readahead_blocks), key_range))
+        })
         .collect();
 
-    MultiReader::new(readers)
+    MultiReader::with_range(readers, bounds.0.clone(), bounds.1.clone())
 }
 
 impl TreeIter {
@@ -143,58 +220,23 @@ impl TreeIter {
         bounds: (Bound<UserKey>, Bound<UserKey>),
         seqno: Option<SeqNo>,
         level_manifest: ArcRwLockReadGuardian<LevelManifest>,
+        readahead_blocks: usize,
+        prefix_hash: Option<CompositeHash>,
     ) -> Self {
         Self::new(guard, |lock| {
-            let lo = match &bounds.0 {
-                // NOTE: See memtable.rs for range explanation
-                Bound::Included(key) => Bound::Included(InternalKey::new(
-                    key.clone(),
-                    SeqNo::MAX,
-                    crate::value::ValueType::Tombstone,
-                )),
-                Bound::Excluded(key) => Bound::Excluded(InternalKey::new(
-                    key.clone(),
-                    0,
-                    crate::value::ValueType::Tombstone,
-                )),
-                Bound::Unbounded => Bound::Unbounded,
-            };
-
-            let hi = match &bounds.1 {
-                // NOTE: See memtable.rs for range explanation, this is the reverse case
-                // where we need to go all the way to the last seqno of an item
-                //
-                // Example: We search for (Unbounded..Excluded(abdef))
-                //
-                // key -> seqno
-                //
-                // a   -> 7 <<< This is the lowest key that matches the range
-                // abc -> 5
-                // abc -> 4
-                // abc -> 3 <<< This is the highest key that matches the range
-                // abcdef -> 6
-                // abcdef -> 5
-                //
-                Bound::Included(key) => Bound::Included(InternalKey::new(
-                    key.clone(),
-                    0,
-                    crate::value::ValueType::Value,
-                )),
-                Bound::Excluded(key) => Bound::Excluded(InternalKey::new(
-                    key.clone(),
-                    SeqNo::MAX,
-                    crate::value::ValueType::Value,
-                )),
-                Bound::Unbounded => Bound::Unbounded,
-            };
-
-            let range = (lo, hi);
+            let range = to_internal_bounds(&bounds);
 
             let mut iters: Vec<BoxedIterator<'_>> = Vec::with_capacity(5);
 
+            // NOTE: Removing segments keeps levels disjoint
+            let levels = match prefix_hash {
+                Some(hash) => prune_levels(&level_manifest.levels, hash),
+                None => level_manifest.levels.clone(),
+            };
+
             // NOTE: Optimize disjoint trees (e.g. timeseries) to only use a single MultiReader.
             if level_manifest.is_disjoint() {
-                let reader = collect_disjoint_tree_with_range(&level_manifest, &bounds);
+                let reader = collect_disjoint_tree_with_range(&levels, &bounds, readahead_blocks);
 
                 if let Some(seqno) = seqno {
                     iters.push(Box::new(reader.filter(move |item| match item {
@@ -205,11 +247,12 @@ impl TreeIter {
                     iters.push(Box::new(reader));
                 }
             } else {
-                for level in &level_manifest.levels {
+                for level in &levels {
                     if level.is_disjoint {
                         if !level.is_empty() {
                             if let Some(reader) =
                                 LevelReader::new(level.clone(), &bounds, CachePolicy::Write)
+                                    .map(|reader| reader.readahead(readahead_blocks))
                             {
                                 if let Some(seqno) = seqno {
                                     iters.push(Box::new(reader.filter(move |item| match item {
@@ -224,7 +267,8 @@ impl TreeIter {
                     } else {
                         for segment in &level.segments {
                             if segment.check_key_range_overlap(&bounds) {
-                                let reader = segment.range(bounds.clone());
+                                let reader =
+                                    segment.range(bounds.clone()).readahead(readahead_blocks);
 
                                 if let Some(seqno) = seqno {
                                     iters.push(Box::new(reader.filter(move |item| match item {
@@ -240,11 +284,27 @@ impl TreeIter {
                 }
             };
 
+            // NOTE: Range tombstones may start before the range, so they are
+            // collected from all segments and memtables
+            let memtables = lock
+                .sealed
+                .iter()
+                .chain(std::iter::once(&lock.active))
+                .chain(lock.ephemeral.as_ref());
+
+            let range_tombstones = level_manifest
+                .range_tombstones()
+                .iter()
+                .cloned()
+                .chain(memtables.flat_map(|memtable| memtable.range_tombstones()))
+                .filter(|rt| rt.is_visible(seqno))
+                .collect();
+
             drop(level_manifest);
 
             // Sealed memtables
             for memtable in lock.sealed.iter() {
-                let iter = memtable.range(range.clone());
+                let iter = memtable.range_internal(range.clone());
 
                 if let Some(seqno) = seqno {
                     iters.push(Box::new(
@@ -258,7 +318,7 @@ impl TreeIter {
 
             // Active memtable
             {
-                let iter = lock.active.range(range.clone());
+                let iter = lock.active.range_internal(range.clone());
 
                 if let Some(seqno) = seqno {
                     iters.push(Box::new(
@@ -271,16 +331,20 @@ impl TreeIter {
             }
 
             if let Some(index) = &lock.ephemeral {
-                let iter = Box::new(index.range(range).map(Ok));
+                let iter = Box::new(index.range_internal(range).map(Ok));
                 iters.push(iter);
             }
 
             let merged = Merger::new(iters);
-            let iter = MvccStream::new(merged);
+            let iter = MvccStream::new(merged).with_range_tombstones(range_tombstones);
+
+            // NOTE: Expired values only become invisible after resolving MVCC,
+            // so they still shadow older versions of their key
+            let now = crate::time::unix_timestamp();
 
-            Box::new(iter.filter(|x| match x {
-                Ok(value) => !value.key.is_tombstone(),
-                Err(_) => true,
+            Box::new(iter.filter_map(move |x| match x {
+                Ok(value) => value.into_visible(now).map(Ok),
+                Err(e) => Some(Err(e)),
             }))
         })
     }
@@ -342,6 +406,11 @@ mod tests {
         test_prefix(&[255, 255, 255], Unbounded);
     }
 
+    #[test]
+    fn prefix_to_range_single_char_max() {
+        test_prefix(&[255], Unbounded);
+    }
+
     #[test]
     fn prefix_to_range_char_max() {
         test_prefix(&[0, 255], Excluded(&[1]));
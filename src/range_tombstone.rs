@@ -0,0 +1,287 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    coding::{Decode, DecodeError, Encode, EncodeError},
+    InternalValue, KeyRange, SeqNo, UserKey,
+};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::{
+    io::{Read, Write},
+    ops::Bound,
+};
+use varint_rs::{VarintReader, VarintWriter};
+
+/// Deletion of all keys in `[start, end)` that are older than the tombstone,
+/// see [`crate::AbstractTree::drop_range`]
+///
+/// The range may be unbounded at the end, see [`crate::AbstractTree::delete_prefix`].
+///
+/// Range tombstones are stored as regular items (of type [`crate::ValueType::RangeTombstone`]),
+/// keyed by their start key. Because they affect keys other than their own,
+/// memtables and segments additionally keep a list of their range tombstones,
+/// which is consulted by every read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeTombstone {
+    /// Inclusive start of the deleted range
+    pub start: UserKey,
+
+    /// Exclusive end of the deleted range, or `None` if the range is unbounded
+    pub end: Option<UserKey>,
+
+    /// Sequence number of the deletion
+    pub seqno: SeqNo,
+}
+
+impl RangeTombstone {
+    /// Returns the range tombstone that is encoded in the given item, if any.
+    #[must_use]
+    pub fn from_item(item: &InternalValue) -> Option<Self> {
+        item.key.is_range_tombstone().then(|| Self {
+            start: item.key.user_key.clone(),
+            end: decode_end(item.value.clone()),
+            seqno: item.key.seqno,
+        })
+    }
+
+    /// Returns `true` if the tombstone is visible to a read at the given seqno.
+    #[must_use]
+    pub fn is_visible(&self, seqno: Option<SeqNo>) -> bool {
+        seqno.map_or(true, |seqno| self.seqno < seqno)
+    }
+
+    /// Returns `true` if the tombstone may delete keys in the given key range.
+    #[must_use]
+    pub fn overlaps(&self, key_range: &KeyRange) -> bool {
+        key_range.overlaps_with_bounds(&(
+            Bound::Included(self.start.clone()),
+            self.end.clone().map_or(Bound::Unbounded, Bound::Excluded),
+        ))
+    }
+
+    /// Returns `true` if the tombstone deletes the given version of a key.
+    #[must_use]
+    pub fn covers(&self, key: &[u8], seqno: SeqNo) -> bool {
+        seqno < self.seqno
+            && *self.start <= *key
+            && self.end.as_ref().map_or(true, |end| *key < **end)
+    }
+}
+
+/// Converts the stored end key of a range tombstone back into its bound.
+///
+/// An empty range cannot be deleted, so an empty end key is
+/// used to store that the range is unbounded.
+fn decode_end(end: UserKey) -> Option<UserKey> {
+    (!end.is_empty()).then_some(end)
+}
+
+/// Inserts a range tombstone into a list that is sorted by start key.
+pub fn insert_sorted(range_tombstones: &mut Vec<RangeTombstone>, rt: RangeTombstone) {
+    let idx = range_tombstones.partition_point(|x| x.start <= rt.start);
+    range_tombstones.insert(idx, rt);
+}
+
+/// Returns the seqno of the newest range tombstone that is visible at `read_seqno`
+/// and deletes the given version of a key, if any.
+///
+/// The list needs to be sorted by start key, so tombstones that start
+/// after the key do not need to be checked.
+#[must_use]
+pub fn newest_covering(
+    range_tombstones: &[RangeTombstone],
+    key: &[u8],
+    seqno: SeqNo,
+    read_seqno: Option<SeqNo>,
+) -> Option<SeqNo> {
+    let candidates = range_tombstones.partition_point(|rt| *rt.start <= *key);
+
+    range_tombstones
+        .iter()
+        .take(candidates)
+        .filter(|rt| rt.is_visible(read_seqno) && rt.covers(key, seqno))
+        .map(|rt| rt.seqno)
+        .max()
+}
+
+/// Returns `true` if any of the range tombstones deletes the item.
+///
+/// Range tombstones themselves are never deleted, as they may cover
+/// more keys than the range tombstone that shadows them.
+#[must_use]
+pub fn is_covered(range_tombstones: &[RangeTombstone], item: &InternalValue) -> bool {
+    !item.key.is_range_tombstone()
+        && range_tombstones
+            .iter()
+            .any(|rt| rt.covers(&item.key.user_key, item.key.seqno))
+}
+
+impl Encode for RangeTombstone {
+    fn encode_into<W: Write>(&self, writer: &mut W) -> Result<(), EncodeError> {
+        writer.write_u64_varint(self.seqno)?;
+
+        // NOTE: Truncation is okay, keys are limited to 16-bit length
+        #[allow(clippy::cast_possible_truncation)]
+        writer.write_u16_varint(self.start.len() as u16)?;
+        writer.write_all(&self.start)?;
+
+        // NOTE: An unbounded end is written as an empty key, see decode_end
+        let end = self.end.as_deref().unwrap_or_default();
+
+        // NOTE: Truncation is okay, keys are limited to 16-bit length
+        #[allow(clippy::cast_possible_truncation)]
+        writer.write_u16_varint(end.len() as u16)?;
+        writer.write_all(end)?;
+
+        Ok(())
+    }
+}
+
+impl Decode for RangeTombstone {
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let seqno = reader.read_u64_varint()?;
+
+        let start_len = reader.read_u16_varint()?;
+        let start = UserKey::from_reader(reader, start_len.into())?;
+
+        let end_len = reader.read_u16_varint()?;
+        let end = decode_end(UserKey::from_reader(reader, end_len.into())?);
+
+        Ok(Self { start, end, seqno })
+    }
+}
+
+/// Writes a list of range tombstones, as stored in a segment file.
+pub fn encode_list<W: Write>(
+    writer: &mut W,
+    range_tombstones: &[RangeTombstone],
+) -> Result<(), EncodeError> {
+    // NOTE: Truncation is okay, there are never 4 billion range tombstones in a segment
+    #[allow(clippy::cast_possible_truncation)]
+    writer.write_u32::<BigEndian>(range_tombstones.len() as u32)?;
+
+    for rt in range_tombstones {
+        rt.encode_into(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Reads a list of range tombstones, as stored in a segment file.
+pub fn decode_list<R: Read>(reader: &mut R) -> Result<Vec<RangeTombstone>, DecodeError> {
+    let len = reader.read_u32::<BigEndian>()?;
+
+    (0..len)
+        .map(|_| RangeTombstone::decode_from(reader))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn range_tombstone_covers() {
+        let rt = RangeTombstone {
+            start: "b".into(),
+            end: Some("d".into()),
+            seqno: 5,
+        };
+
+        assert!(rt.covers(b"b", 4));
+        assert!(rt.covers(b"c", 0));
+        assert!(rt.covers(b"cz", 4));
+
+        assert!(!rt.covers(b"a", 4));
+        assert!(!rt.covers(b"d", 4));
+        assert!(!rt.covers(b"b", 5));
+        assert!(!rt.covers(b"c", 6));
+    }
+
+    #[test]
+    fn range_tombstone_covers_unbounded() {
+        let rt = RangeTombstone {
+            start: "b".into(),
+            end: None,
+            seqno: 5,
+        };
+
+        assert!(rt.covers(b"b", 4));
+        assert!(rt.covers(&[255; 16], 4));
+
+        assert!(!rt.covers(b"a", 4));
+        assert!(!rt.covers(&[255; 16], 5));
+    }
+
+    #[test]
+    fn range_tombstone_newest_covering() {
+        let mut rts = vec![];
+
+        for (start, end, seqno) in [("m", Some("p"), 3), ("a", None, 2), ("c", Some("f"), 8)] {
+            insert_sorted(
+                &mut rts,
+                RangeTombstone {
+                    start: start.into(),
+                    end: end.map(Into::into),
+                    seqno,
+                },
+            );
+        }
+
+        let starts = rts.iter().map(|rt| &*rt.start).collect::<Vec<_>>();
+        assert_eq!(vec![b"a", b"c", b"m"], starts);
+
+        assert_eq!(Some(8), newest_covering(&rts, b"d", 1, None));
+        assert_eq!(Some(2), newest_covering(&rts, b"d", 1, Some(8)));
+        assert_eq!(Some(3), newest_covering(&rts, b"n", 1, None));
+        assert_eq!(Some(2), newest_covering(&rts, b"z", 1, None));
+        assert_eq!(None, newest_covering(&rts, b"z", 2, None));
+        assert_eq!(None, newest_covering(&rts, b"d", 1, Some(2)));
+    }
+
+    #[test]
+    fn range_tombstone_is_covered_skips_range_tombstones() {
+        let rts = [RangeTombstone {
+            start: "a".into(),
+            end: Some("z".into()),
+            seqno: 10,
+        }];
+
+        assert!(is_covered(&rts, &InternalValue::new_tombstone("b", 5)));
+        assert!(!is_covered(
+            &rts,
+            &InternalValue::new_range_tombstone("b", "c", 5)
+        ));
+    }
+
+    #[test]
+    fn range_tombstone_serde_roundtrip() -> crate::Result<()> {
+        let rts = vec![
+            RangeTombstone {
+                start: "a".into(),
+                end: Some("c".into()),
+                seqno: 5,
+            },
+            RangeTombstone {
+                start: "x".into(),
+                end: Some("yz".into()),
+                seqno: 7,
+            },
+            RangeTombstone {
+                start: "z".into(),
+                end: None,
+                seqno: 9,
+            },
+        ];
+
+        let mut bytes = vec![];
+        encode_list(&mut bytes, &rts)?;
+
+        let decoded = decode_list(&mut &bytes[..])?;
+        assert_eq!(rts, decoded);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Where point reads have been answered, see [`Tree::read_level_histogram`](crate::Tree::read_level_histogram)
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReadLevelHistogram {
+    /// Reads answered by the active or a sealed memtable
+    pub memtable: u64,
+
+    /// Reads answered by a segment, per level
+    pub levels: Vec<u64>,
+
+    /// Reads of keys that do not exist anywhere in the tree
+    pub misses: u64,
+}
+
+impl ReadLevelHistogram {
+    /// Returns the total amount of reads.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.levels.iter().copied().fold(
+            self.memtable.saturating_add(self.misses),
+            u64::saturating_add,
+        )
+    }
+}
+
+/// Counts where point reads are answered
+pub struct ReadLevelStats {
+    memtable: AtomicU64,
+    levels: Box<[AtomicU64]>,
+    misses: AtomicU64,
+}
+
+impl ReadLevelStats {
+    pub fn new(level_count: u8) -> Self {
+        Self {
+            memtable: AtomicU64::default(),
+            levels: (0..level_count).map(|_| AtomicU64::default()).collect(),
+            misses: AtomicU64::default(),
+        }
+    }
+
+    pub fn record_memtable(&self) {
+        self.memtable.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_level(&self, level_idx: usize) {
+        if let Some(counter) = self.levels.get(level_idx) {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn histogram(&self) -> ReadLevelHistogram {
+        ReadLevelHistogram {
+            memtable: self.memtable.load(Ordering::Relaxed),
+            levels: self
+                .levels
+                .iter()
+                .map(|x| x.load(Ordering::Relaxed))
+                .collect(),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -4,7 +4,104 @@
 
 use xxhash_rust::xxh3::xxh3_64;
 
-/// A checksum based on xxh3
+/// Checksum algorithm used for blocks
+///
+/// Every block header stores the algorithm its checksum was created with,
+/// so blocks using different algorithms can be mixed in a tree.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum ChecksumType {
+    /// 64-bit XXH3
+    ///
+    /// Fast and wider than CRC32, good default.
+    #[default]
+    Xxh3,
+
+    /// 32-bit CRC (IEEE)
+    Crc32,
+}
+
+impl From<ChecksumType> for u8 {
+    fn from(value: ChecksumType) -> Self {
+        match value {
+            ChecksumType::Xxh3 => 0,
+            ChecksumType::Crc32 => 1,
+        }
+    }
+}
+
+impl TryFrom<u8> for ChecksumType {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Xxh3),
+            1 => Ok(Self::Crc32),
+            _ => Err(()),
+        }
+    }
+}
+
+impl std::fmt::Display for ChecksumType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Xxh3 => "xxh3",
+                Self::Crc32 => "crc32",
+            }
+        )
+    }
+}
+
+/// Lookup table for the reflected IEEE polynomial
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut idx = 0;
+
+    while idx < 256 {
+        // NOTE: idx < 256
+        #[allow(clippy::cast_possible_truncation)]
+        let mut crc = idx as u32;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+
+        #[allow(clippy::indexing_slicing)]
+        {
+            table[idx] = crc;
+        }
+        idx += 1;
+    }
+
+    table
+};
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for &byte in bytes {
+        let idx = (crc ^ u32::from(byte)) & 0xFF;
+
+        // NOTE: idx is masked to 8 bits, so it is in bounds
+        #[allow(clippy::indexing_slicing)]
+        let entry = CRC32_TABLE[idx as usize];
+
+        crc = (crc >> 8) ^ entry;
+    }
+
+    !crc
+}
+
+/// A checksum based on xxh3 (or CRC32, see [`ChecksumType`])
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Checksum(u64);
 
@@ -22,9 +119,45 @@ impl Checksum {
         Self(value)
     }
 
-    /// Calculates a checksum.
+    /// Calculates a xxh3 checksum.
     #[must_use]
     pub fn from_bytes(bytes: &[u8]) -> Self {
         Self(xxh3_64(bytes))
     }
+
+    /// Calculates a checksum using the given algorithm.
+    #[must_use]
+    pub fn from_bytes_with(checksum_type: ChecksumType, bytes: &[u8]) -> Self {
+        match checksum_type {
+            ChecksumType::Xxh3 => Self::from_bytes(bytes),
+            ChecksumType::Crc32 => Self(crc32(bytes).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn checksum_crc32_known_values() {
+        assert_eq!(0, crc32(b""));
+        assert_eq!(0xCBF4_3926, crc32(b"123456789"));
+        assert_eq!(
+            0x414F_A339,
+            crc32(b"The quick brown fox jumps over the lazy dog")
+        );
+    }
+
+    #[test]
+    fn checksum_type_tag_round_trip() {
+        for checksum_type in [ChecksumType::Xxh3, ChecksumType::Crc32] {
+            assert_eq!(
+                Ok(checksum_type),
+                ChecksumType::try_from(u8::from(checksum_type))
+            );
+        }
+        assert_eq!(Err(()), ChecksumType::try_from(2));
+    }
 }
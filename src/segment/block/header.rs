@@ -2,7 +2,10 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use super::{checksum::Checksum, offset::BlockOffset};
+use super::{
+    checksum::{Checksum, ChecksumType},
+    offset::BlockOffset,
+};
 use crate::{
     coding::{Decode, DecodeError, Encode, EncodeError},
     file::MAGIC_BYTES,
@@ -20,6 +23,9 @@ pub struct Header {
     /// Checksum value to verify integrity of data
     pub checksum: Checksum,
 
+    /// Checksum algorithm used
+    pub checksum_type: ChecksumType,
+
     /// File offset of previous block - only used for data blocks
     pub previous_block_offset: BlockOffset,
 
@@ -53,7 +59,15 @@ impl Encode for Header {
         // Write header
         writer.write_all(&MAGIC_BYTES)?;
 
-        self.compression.encode_into(writer)?;
+        // NOTE: The upper nibble of the compression tag stores the checksum type,
+        // so blocks of older versions (which are always XXH3, tag 0) stay readable
+        let mut compression = self.compression.encode_into_vec();
+
+        if let Some(tag) = compression.first_mut() {
+            *tag |= u8::from(self.checksum_type) << 4;
+        }
+
+        writer.write_all(&compression)?;
 
         // Write checksum
         writer.write_u64::<BigEndian>(*self.checksum)?;
@@ -81,7 +95,13 @@ impl Decode for Header {
             return Err(DecodeError::InvalidHeader("Block"));
         }
 
-        let compression = CompressionType::decode_from(reader)?;
+        // NOTE: See encode_into
+        let tag = reader.read_u8()?;
+        let checksum_type = ChecksumType::try_from(tag >> 4)
+            .map_err(|()| DecodeError::InvalidTag(("ChecksumType", tag >> 4)))?;
+
+        let level = reader.read_u8()?;
+        let compression = CompressionType::decode_from(&mut &[tag & 0x0F, level][..])?;
 
         // Read checksum
         let checksum = reader.read_u64::<BigEndian>()?;
@@ -98,6 +118,7 @@ impl Decode for Header {
         Ok(Self {
             compression,
             checksum: Checksum::from_raw(checksum),
+            checksum_type,
             previous_block_offset: BlockOffset(previous_block_offset),
             data_length,
             uncompressed_length,
@@ -116,6 +137,7 @@ mod tests {
         let header = Header {
             compression: CompressionType::None,
             checksum: Checksum::from_raw(4),
+            checksum_type: ChecksumType::Xxh3,
             previous_block_offset: BlockOffset(2),
             data_length: 15,
             uncompressed_length: 15,
@@ -152,4 +174,27 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn block_header_checksum_type() -> crate::Result<()> {
+        let header = Header {
+            compression: CompressionType::None,
+            checksum: Checksum::from_raw(4),
+            checksum_type: ChecksumType::Crc32,
+            previous_block_offset: BlockOffset(2),
+            data_length: 15,
+            uncompressed_length: 15,
+        };
+
+        let bytes = header.encode_into_vec();
+
+        // NOTE: Checksum type is stored in the upper nibble of the compression tag
+        assert_eq!(Some(&0x10), bytes.get(MAGIC_BYTES.len()));
+        assert_eq!(Header::serialized_len(), bytes.len());
+
+        let deserialized = Header::decode_from(&mut Cursor::new(bytes))?;
+        assert_eq!(header, deserialized);
+
+        Ok(())
+    }
 }
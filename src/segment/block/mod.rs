@@ -6,14 +6,21 @@ pub mod checksum;
 pub mod header;
 pub mod offset;
 
-use super::meta::CompressionType;
-use crate::coding::{Decode, Encode};
+use super::meta::{CompressionType, SegmentId};
+use crate::coding::{Decode, DecodeError, Encode, EncodeError};
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
-use checksum::Checksum;
+use checksum::{Checksum, ChecksumType};
 use header::Header as BlockHeader;
 use offset::BlockOffset;
 use std::io::{Cursor, Read};
 
+/// Maximum alignment of values within a block, see [`Block::value_alignment`]
+pub const MAX_VALUE_ALIGNMENT: u32 = 4_096;
+
+/// A block cannot contain 2^24 items, so the upper byte of the item count
+/// stores the value alignment (as a power of two)
+const ALIGNMENT_SHIFT: u32 = 24;
+
 // TODO: better name
 pub trait ItemSize {
     fn size(&self) -> usize;
@@ -25,6 +32,24 @@ impl<T: ItemSize> ItemSize for [T] {
     }
 }
 
+/// Items that may have their value aligned within a block
+///
+/// By default, items are not padded at all.
+pub trait AlignedItem: Encode + Decode + Sized {
+    /// Serializes the item, padding its value so it starts at a multiple of `alignment`,
+    /// relative to the start of the (uncompressed) block data.
+    fn encode_aligned(&self, buf: &mut Vec<u8>, alignment: usize) -> Result<(), EncodeError> {
+        let _ = alignment;
+        self.encode_into(buf)
+    }
+
+    /// Deserializes an item that was serialized using [`AlignedItem::encode_aligned`].
+    fn decode_aligned(reader: &mut Cursor<Vec<u8>>, alignment: usize) -> Result<Self, DecodeError> {
+        let _ = alignment;
+        Self::decode_from(reader)
+    }
+}
+
 /// A disk-based block
 ///
 /// A block is split into its header and a blob of data.
@@ -35,13 +60,56 @@ impl<T: ItemSize> ItemSize for [T] {
 ///
 /// The integrity of a block can be checked using the checksum value that is saved in its header.
 #[derive(Clone, Debug)]
-pub struct Block<T: Clone + Encode + Decode + ItemSize> {
+pub struct Block<T: Clone + AlignedItem + ItemSize> {
     pub header: BlockHeader,
     pub items: Box<[T]>,
+
+    /// Alignment of the values within the uncompressed block data, 1 if unaligned
+    ///
+    /// The alignment is stored in the block itself, so blocks of different
+    /// alignments can be read regardless of the current configuration.
+    pub value_alignment: u32,
 }
 
-impl<T: Clone + Encode + Decode + ItemSize> Block<T> {
+impl<T: Clone + AlignedItem + ItemSize> Block<T> {
     pub fn from_reader<R: Read>(reader: &mut R) -> crate::Result<Self> {
+        let (header, bytes) = Self::read_raw(reader)?;
+        Self::from_raw(header, bytes)
+    }
+
+    /// Like [`Block::from_reader`], but compares the checksum stored in the block header
+    /// against the checksum of the (possibly compressed) data that was read.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error::ChecksumMismatch`] if the block is corrupted.
+    pub fn from_reader_verified<R: Read>(
+        reader: &mut R,
+        segment_id: SegmentId,
+        offset: BlockOffset,
+    ) -> crate::Result<Self> {
+        let (header, bytes) = Self::read_raw(reader)?;
+
+        let checksum = Checksum::from_bytes_with(header.checksum_type, &bytes);
+
+        if checksum != header.checksum {
+            log::error!(
+                "Checksum mismatch of block {segment_id}/{offset:?}, got={}, expected={}",
+                *checksum,
+                *header.checksum,
+            );
+
+            return Err(crate::Error::ChecksumMismatch {
+                segment_id,
+                block_offset: *offset,
+            });
+        }
+
+        Self::from_raw(header, bytes)
+    }
+
+    /// Reads the block header and the (possibly compressed) data.
+    fn read_raw<R: Read>(reader: &mut R) -> crate::Result<(BlockHeader, Vec<u8>)> {
         // Read block header
         let header = BlockHeader::decode_from(reader)?;
         log::trace!("Got block header: {header:?}");
@@ -50,6 +118,10 @@ impl<T: Clone + Encode + Decode + ItemSize> Block<T> {
         let mut bytes = vec![0u8; header.data_length as usize];
         reader.read_exact(&mut bytes)?;
 
+        Ok((header, bytes))
+    }
+
+    fn from_raw(header: BlockHeader, bytes: Vec<u8>) -> crate::Result<Self> {
         // TODO: 3.0.0 when header.compressed is reliable
         // can we preallocate a vector to stream the compression into?
         // -> saves reallocation costs
@@ -65,22 +137,45 @@ impl<T: Clone + Encode + Decode + ItemSize> Block<T> {
                 miniz_oxide::inflate::decompress_to_vec(&bytes)
                     .map_err(|_| crate::Error::Decompress(header.compression))?
             }
+
+            #[cfg(feature = "zstd")]
+            super::meta::CompressionType::Zstd(_) => zstd::stream::decode_all(&*bytes)
+                .map_err(|_| crate::Error::Decompress(header.compression))?,
         };
         let mut bytes = Cursor::new(bytes);
 
         // TODO: 3.0.0 varint?
         // Read number of items
-        let item_count = bytes.read_u32::<BigEndian>()? as usize;
+        let item_count = bytes.read_u32::<BigEndian>()?;
+
+        // NOTE: See ALIGNMENT_SHIFT
+        #[allow(clippy::cast_possible_truncation)]
+        let alignment_exponent = (item_count >> ALIGNMENT_SHIFT) as u8;
+
+        let value_alignment = 1u32
+            .checked_shl(alignment_exponent.into())
+            .filter(|&x| x <= MAX_VALUE_ALIGNMENT)
+            .ok_or(DecodeError::InvalidTag((
+                "ValueAlignment",
+                alignment_exponent,
+            )))?;
+
+        let item_count = (item_count & ((1 << ALIGNMENT_SHIFT) - 1)) as usize;
 
         // Deserialize each value
         let mut items = Vec::with_capacity(item_count);
         for _ in 0..item_count {
-            items.push(T::decode_from(&mut bytes)?);
+            items.push(if value_alignment > 1 {
+                T::decode_aligned(&mut bytes, value_alignment as usize)?
+            } else {
+                T::decode_from(&mut bytes)?
+            });
         }
 
         Ok(Self {
             header,
             items: items.into_boxed_slice(),
+            value_alignment,
         })
     }
 
@@ -92,16 +187,55 @@ impl<T: Clone + Encode + Decode + ItemSize> Block<T> {
         Self::from_reader(reader)
     }
 
+    /// Like [`Block::from_file`], but verifies the block's checksum, see [`Block::from_reader_verified`].
+    pub fn from_file_verified<R: std::io::Read + std::io::Seek>(
+        reader: &mut R,
+        segment_id: SegmentId,
+        offset: BlockOffset,
+    ) -> crate::Result<Self> {
+        reader.seek(std::io::SeekFrom::Start(*offset))?;
+        Self::from_reader_verified(reader, segment_id, offset)
+    }
+
     pub fn to_bytes_compressed(
         items: &[T],
         previous_block_offset: BlockOffset,
         compression: CompressionType,
+        checksum_type: ChecksumType,
+    ) -> crate::Result<(BlockHeader, Vec<u8>)> {
+        Self::to_bytes_compressed_aligned(
+            items,
+            previous_block_offset,
+            compression,
+            checksum_type,
+            1,
+        )
+    }
+
+    /// Like [`Block::to_bytes_compressed`], but aligns the items' values to
+    /// `value_alignment` bytes, see [`Block::value_alignment`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the alignment is not a power of two, or exceeds [`MAX_VALUE_ALIGNMENT`].
+    pub fn to_bytes_compressed_aligned(
+        items: &[T],
+        previous_block_offset: BlockOffset,
+        compression: CompressionType,
+        checksum_type: ChecksumType,
+        value_alignment: u32,
     ) -> crate::Result<(BlockHeader, Vec<u8>)> {
-        let packed = Self::pack_items(items, compression)?;
-        let checksum = Checksum::from_bytes(&packed);
+        assert!(
+            value_alignment.is_power_of_two() && value_alignment <= MAX_VALUE_ALIGNMENT,
+            "invalid value alignment",
+        );
+
+        let packed = Self::pack_items(items, compression, value_alignment)?;
+        let checksum = Checksum::from_bytes_with(checksum_type, &packed);
 
         let header = BlockHeader {
             checksum,
+            checksum_type,
             compression,
             previous_block_offset,
 
@@ -120,16 +254,28 @@ impl<T: Clone + Encode + Decode + ItemSize> Block<T> {
         Ok((header, packed))
     }
 
-    fn pack_items(items: &[T], compression: CompressionType) -> crate::Result<Vec<u8>> {
+    fn pack_items(
+        items: &[T],
+        compression: CompressionType,
+        value_alignment: u32,
+    ) -> crate::Result<Vec<u8>> {
         let mut buf = Vec::with_capacity(u16::MAX.into());
 
-        // NOTE: There cannot be 4 billion items in a block
+        // NOTE: There cannot be 16 million items in a block
         #[allow(clippy::cast_possible_truncation)]
-        buf.write_u32::<BigEndian>(items.len() as u32)?;
+        let item_count = items.len() as u32;
+
+        buf.write_u32::<BigEndian>(
+            item_count | (value_alignment.trailing_zeros() << ALIGNMENT_SHIFT),
+        )?;
 
         // Serialize each value
         for value in items {
-            value.encode_into(&mut buf)?;
+            if value_alignment > 1 {
+                value.encode_aligned(&mut buf, value_alignment as usize)?;
+            } else {
+                value.encode_into(&mut buf)?;
+            }
         }
 
         // TODO: 3.0.0 return buf.len() - 4 as uncompressed size
@@ -142,6 +288,9 @@ impl<T: Clone + Encode + Decode + ItemSize> Block<T> {
 
             #[cfg(feature = "miniz")]
             CompressionType::Miniz(level) => miniz_oxide::deflate::compress_to_vec(&buf, level),
+
+            #[cfg(feature = "zstd")]
+            CompressionType::Zstd(level) => zstd::bulk::compress(&buf, level)?,
         })
     }
 }
@@ -168,8 +317,12 @@ mod tests {
         // Serialize to bytes
         let mut serialized = Vec::new();
 
-        let (header, data) =
-            ValueBlock::to_bytes_compressed(&items, BlockOffset(0), CompressionType::None)?;
+        let (header, data) = ValueBlock::to_bytes_compressed(
+            &items,
+            BlockOffset(0),
+            CompressionType::None,
+            ChecksumType::Xxh3,
+        )?;
 
         header.encode_into(&mut serialized)?;
         serialized.write_all(&data)?;
@@ -189,8 +342,9 @@ mod tests {
                 &block.items,
                 block.header.previous_block_offset,
                 block.header.compression,
+                block.header.checksum_type,
             )?;
-            Checksum::from_bytes(&data)
+            Checksum::from_bytes_with(block.header.checksum_type, &data)
         };
         assert_eq!(block.header.checksum, checksum);
 
@@ -209,8 +363,12 @@ mod tests {
         // Serialize to bytes
         let mut serialized = Vec::new();
 
-        let (header, data) =
-            ValueBlock::to_bytes_compressed(&items, BlockOffset(0), CompressionType::None)?;
+        let (header, data) = ValueBlock::to_bytes_compressed(
+            &items,
+            BlockOffset(0),
+            CompressionType::None,
+            ChecksumType::Xxh3,
+        )?;
 
         header.encode_into(&mut serialized)?;
         serialized.write_all(&data)?;
@@ -224,11 +382,128 @@ mod tests {
                 &block.items,
                 block.header.previous_block_offset,
                 block.header.compression,
+                block.header.checksum_type,
             )?;
-            Checksum::from_bytes(&data)
+            Checksum::from_bytes_with(block.header.checksum_type, &data)
         };
         assert_eq!(block.header.checksum, checksum);
 
         Ok(())
     }
+
+    #[test]
+    fn disk_block_aligned_values() -> crate::Result<()> {
+        let items = vec![
+            InternalValue::from_components(*b"a", *b"12345678", 5, ValueType::Value),
+            InternalValue::from_components(*b"abc", *b"", 4, ValueType::Tombstone),
+            InternalValue::from_components(*b"abcdefg", *b"abcdefgh", 3, ValueType::Value),
+            InternalValue::from_components(*b"b", *b"", 2, ValueType::Value),
+            InternalValue::from_components(*b"bb", *b"ABCDEFGHIJKLMNOP", 1, ValueType::Value),
+        ];
+
+        for alignment in [1, 2, 8, 64, MAX_VALUE_ALIGNMENT] {
+            let (header, data) = ValueBlock::to_bytes_compressed_aligned(
+                &items,
+                BlockOffset(0),
+                CompressionType::None,
+                ChecksumType::Xxh3,
+                alignment,
+            )?;
+
+            // NOTE: Values are found at aligned offsets of the uncompressed data
+            for value in [&b"12345678"[..], b"abcdefgh", b"ABCDEFGHIJKLMNOP"] {
+                let offset = data
+                    .windows(value.len())
+                    .position(|x| x == value)
+                    .expect("value should exist");
+
+                assert_eq!(0, offset % alignment as usize);
+            }
+
+            let mut serialized = header.encode_into_vec();
+            serialized.extend_from_slice(&data);
+
+            let block = ValueBlock::from_reader(&mut Cursor::new(serialized))?;
+            assert_eq!(alignment, block.value_alignment);
+            assert_eq!(&*items, &*block.items);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn disk_block_invalid_alignment() -> crate::Result<()> {
+        let items = vec![InternalValue::from_components(
+            *b"a",
+            *b"a",
+            0,
+            ValueType::Value,
+        )];
+
+        let (header, mut data) = ValueBlock::to_bytes_compressed(
+            &items,
+            BlockOffset(0),
+            CompressionType::None,
+            ChecksumType::Xxh3,
+        )?;
+
+        // NOTE: Larger than the maximum alignment
+        if let Some(byte) = data.first_mut() {
+            *byte = 13;
+        }
+
+        let mut serialized = header.encode_into_vec();
+        serialized.extend_from_slice(&data);
+
+        assert!(matches!(
+            ValueBlock::from_reader(&mut Cursor::new(serialized)),
+            Err(crate::Error::Decode(DecodeError::InvalidTag((
+                "ValueAlignment",
+                13
+            ))))
+        ));
+
+        Ok(())
+    }
+    #[test]
+    fn disk_block_verified_checksum_mismatch() -> crate::Result<()> {
+        let items = vec![InternalValue::from_components(
+            *b"a",
+            *b"abc",
+            0,
+            ValueType::Value,
+        )];
+
+        let (header, data) = ValueBlock::to_bytes_compressed(
+            &items,
+            BlockOffset(0),
+            CompressionType::None,
+            ChecksumType::Xxh3,
+        )?;
+
+        let mut serialized = header.encode_into_vec();
+        serialized.extend_from_slice(&data);
+
+        let block =
+            ValueBlock::from_reader_verified(&mut Cursor::new(&serialized), 7, BlockOffset(0))?;
+        assert_eq!(&*items, &*block.items);
+
+        // NOTE: Flip a bit of the value
+        if let Some(byte) = serialized.last_mut() {
+            *byte ^= 1;
+        }
+
+        assert!(matches!(
+            ValueBlock::from_reader_verified(&mut Cursor::new(&serialized), 7, BlockOffset(0)),
+            Err(crate::Error::ChecksumMismatch {
+                segment_id: 7,
+                block_offset: 0,
+            })
+        ));
+
+        // NOTE: Unverified reads do not notice the corruption
+        assert!(ValueBlock::from_reader(&mut Cursor::new(&serialized)).is_ok());
+
+        Ok(())
+    }
 }
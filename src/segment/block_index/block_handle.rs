@@ -4,7 +4,7 @@
 
 use crate::{
     coding::{Decode, DecodeError, Encode, EncodeError},
-    segment::block::{offset::BlockOffset, ItemSize},
+    segment::block::{offset::BlockOffset, AlignedItem, ItemSize},
     value::UserKey,
     Slice,
 };
@@ -20,6 +20,11 @@ pub struct KeyedBlockHandle {
 
     /// Position of block in file
     pub offset: BlockOffset,
+
+    /// Key of first item in block, if stored in the index
+    ///
+    /// Allows point reads to skip blocks that cannot contain the searched key.
+    pub start_key: Option<UserKey>,
 }
 
 impl KeyedBlockHandle {
@@ -28,16 +33,36 @@ impl KeyedBlockHandle {
         Self {
             end_key: end_key.into(),
             offset,
+            start_key: None,
         }
     }
+
+    #[must_use]
+    pub fn with_start_key<K: Into<Slice>>(mut self, start_key: K) -> Self {
+        self.start_key = Some(start_key.into());
+        self
+    }
+
+    /// Returns `false` if the block definitely does not contain the key,
+    /// because the key is lower than the block's first key.
+    #[must_use]
+    pub fn may_contain_key(&self, key: &[u8]) -> bool {
+        self.start_key
+            .as_ref()
+            .map_or(true, |start_key| &**start_key <= key)
+    }
 }
 
 impl ItemSize for KeyedBlockHandle {
     fn size(&self) -> usize {
-        std::mem::size_of::<BlockOffset>() + self.end_key.len()
+        std::mem::size_of::<BlockOffset>()
+            + self.end_key.len()
+            + self.start_key.as_ref().map_or(0, |x| x.len())
     }
 }
 
+impl AlignedItem for KeyedBlockHandle {}
+
 impl PartialEq for KeyedBlockHandle {
     fn eq(&self, other: &Self) -> bool {
         self.offset == other.offset
@@ -67,6 +92,19 @@ impl Encode for KeyedBlockHandle {
     fn encode_into<W: Write>(&self, writer: &mut W) -> Result<(), EncodeError> {
         writer.write_u64_varint(*self.offset)?;
 
+        // NOTE: Keys may not be empty, so an empty key marks
+        // a handle that is followed by its start key
+        //
+        // This keeps handles without start keys in the old format
+        if let Some(start_key) = &self.start_key {
+            writer.write_u16_varint(0)?;
+
+            // NOTE: Truncation is okay and actually needed
+            #[allow(clippy::cast_possible_truncation)]
+            writer.write_u16_varint(start_key.len() as u16)?;
+            writer.write_all(start_key)?;
+        }
+
         // NOTE: Truncation is okay and actually needed
         #[allow(clippy::cast_possible_truncation)]
         writer.write_u16_varint(self.end_key.len() as u16)?;
@@ -83,13 +121,25 @@ impl Decode for KeyedBlockHandle {
     {
         let offset = reader.read_u64_varint()?;
 
-        let key_len = reader.read_u16_varint()?;
+        let mut key_len = reader.read_u16_varint()?;
+        let mut start_key = None;
+
+        if key_len == 0 {
+            let start_key_len = reader.read_u16_varint()?;
+            let mut key = vec![0; start_key_len.into()];
+            reader.read_exact(&mut key)?;
+            start_key = Some(UserKey::from(key));
+
+            key_len = reader.read_u16_varint()?;
+        }
+
         let mut key = vec![0; key_len.into()];
         reader.read_exact(&mut key)?;
 
         Ok(Self {
             offset: BlockOffset(offset),
             end_key: UserKey::from(key),
+            start_key,
         })
     }
 }
@@ -107,4 +157,32 @@ mod tests {
         ];
         assert_eq!(26, items.size());
     }
+
+    #[test]
+    fn block_handle_serde_round_trip() -> crate::Result<()> {
+        let handle = KeyedBlockHandle::new("abcd", BlockOffset(5));
+        let bytes = handle.encode_into_vec();
+
+        // NOTE: Handles without start keys keep the old format
+        assert_eq!(6, bytes.len());
+
+        let decoded = KeyedBlockHandle::decode_from(&mut &bytes[..])?;
+        assert_eq!(handle.end_key, decoded.end_key);
+        assert_eq!(handle.offset, decoded.offset);
+        assert_eq!(None, decoded.start_key);
+
+        let handle = KeyedBlockHandle::new("efgh", BlockOffset(10)).with_start_key("ab");
+        let bytes = handle.encode_into_vec();
+
+        let decoded = KeyedBlockHandle::decode_from(&mut &bytes[..])?;
+        assert_eq!(handle.end_key, decoded.end_key);
+        assert_eq!(handle.offset, decoded.offset);
+        assert_eq!(handle.start_key, decoded.start_key);
+
+        assert!(!decoded.may_contain_key(b"a"));
+        assert!(decoded.may_contain_key(b"ab"));
+        assert!(decoded.may_contain_key(b"c"));
+
+        Ok(())
+    }
 }
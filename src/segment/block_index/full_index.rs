@@ -75,6 +75,18 @@ impl BlockIndex for FullBlockIndex {
             .map(|x| x.map(|x| x.offset))
     }
 
+    fn get_point_read_block(
+        &self,
+        key: &[u8],
+        _: CachePolicy,
+    ) -> crate::Result<Option<BlockOffset>> {
+        use super::KeyedBlockIndex;
+
+        self.0
+            .get_point_read_block(key, CachePolicy::Read)
+            .map(|x| x.map(|x| x.offset))
+    }
+
     fn get_last_block_handle(&self, _: CachePolicy) -> crate::Result<BlockOffset> {
         use super::KeyedBlockIndex;
 
@@ -37,6 +37,20 @@ pub trait KeyedBlockIndex {
 
     /// Returns a handle to the last block
     fn get_last_block_handle(&self, cache_policy: CachePolicy) -> crate::Result<&KeyedBlockHandle>;
+
+    /// Gets the block handle that may contain the given key for a point read
+    ///
+    /// Unlike [`KeyedBlockIndex::get_lowest_block_containing_key`], this returns `None`
+    /// if the block's start key shows the key falls in between two blocks.
+    fn get_point_read_block(
+        &self,
+        key: &[u8],
+        cache_policy: CachePolicy,
+    ) -> crate::Result<Option<&KeyedBlockHandle>> {
+        Ok(self
+            .get_lowest_block_containing_key(key, cache_policy)?
+            .filter(|handle| handle.may_contain_key(key)))
+    }
 }
 
 impl KeyedBlockIndex for [KeyedBlockHandle] {
@@ -99,6 +113,15 @@ pub trait BlockIndex {
 
     /// Returns a handle to the last block
     fn get_last_block_handle(&self, cache_policy: CachePolicy) -> crate::Result<BlockOffset>;
+
+    /// Gets the block handle that may contain the given key for a point read
+    ///
+    /// Returns `None` if the index alone shows that the key does not exist.
+    fn get_point_read_block(
+        &self,
+        key: &[u8],
+        cache_policy: CachePolicy,
+    ) -> crate::Result<Option<BlockOffset>>;
 }
 
 /// The block index stores references to the positions of blocks on a file and their size
@@ -137,6 +160,7 @@ mod tests {
         KeyedBlockHandle {
             end_key: end_key.into(),
             offset,
+            start_key: None,
         }
     }
 
@@ -392,4 +416,36 @@ mod tests {
             assert_eq!(handle.offset, BlockOffset(60));
         }
     }
+
+    #[test]
+    fn block_handle_array_point_read_start_keys() {
+        let index = [
+            bh(*b"c", BlockOffset(0)).with_start_key(*b"a"),
+            bh(*b"g", BlockOffset(10)).with_start_key(*b"e"),
+            bh(*b"l", BlockOffset(20)).with_start_key(*b"g"),
+        ];
+
+        for (key, offset) in [
+            (&b"a"[..], Some(BlockOffset(0))),
+            (b"c", Some(BlockOffset(0))),
+            (b"d", None),
+            (b"e", Some(BlockOffset(10))),
+            (b"g", Some(BlockOffset(10))),
+            (b"h", Some(BlockOffset(20))),
+            (b"z", None),
+        ] {
+            let handle = index
+                .get_point_read_block(key, CachePolicy::Read)
+                .expect("cannot fail");
+
+            assert_eq!(offset, handle.map(|x| x.offset), "{key:?}");
+        }
+
+        // NOTE: Seeks still land in the next block
+        let handle = index
+            .get_lowest_block_containing_key(b"d", CachePolicy::Read)
+            .expect("cannot fail")
+            .expect("should exist");
+        assert_eq!(handle.offset, BlockOffset(10));
+    }
 }
@@ -76,6 +76,30 @@ impl BlockIndex for TwoLevelBlockIndex {
     ) -> crate::Result<Option<BlockOffset>> {
         self.get_last_data_block_handle_containing_item(key, cache_policy)
     }
+
+    fn get_point_read_block(
+        &self,
+        key: &[u8],
+        cache_policy: CachePolicy,
+    ) -> crate::Result<Option<BlockOffset>> {
+        use super::KeyedBlockIndex;
+
+        // NOTE: If the TLI knows the start key of the index block,
+        // we may not even need to load the index block
+        let Some(index_block_handle) = self
+            .top_level_index
+            .get_point_read_block(key, cache_policy)?
+        else {
+            return Ok(None);
+        };
+
+        let index_block = self.load_index_block(index_block_handle.offset, cache_policy)?;
+
+        Ok(index_block
+            .items
+            .get_point_read_block(key, cache_policy)?
+            .map(|x| x.offset))
+    }
 }
 
 impl TwoLevelBlockIndex {
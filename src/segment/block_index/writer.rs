@@ -6,7 +6,7 @@ use super::{IndexBlock, KeyedBlockHandle};
 use crate::{
     coding::Encode,
     segment::{
-        block::{header::Header as BlockHeader, offset::BlockOffset},
+        block::{checksum::ChecksumType, header::Header as BlockHeader, offset::BlockOffset},
         meta::CompressionType,
     },
     value::UserKey,
@@ -25,12 +25,16 @@ pub struct Writer {
 
     block_size: u32,
     compression: CompressionType,
+    checksum_type: ChecksumType,
 
     buffer_size: u32,
 
     block_handles: Vec<KeyedBlockHandle>,
     tli_pointers: Vec<KeyedBlockHandle>,
 
+    /// Whether block handles store the start key of their block
+    use_start_keys: bool,
+
     pub block_count: usize,
 }
 
@@ -43,8 +47,10 @@ impl Writer {
             buffer_size: 0,
             block_size,
             compression: CompressionType::None,
+            checksum_type: ChecksumType::default(),
             block_handles: Vec::new(),
             tli_pointers: Vec::new(),
+            use_start_keys: false,
             block_count: 0,
         })
     }
@@ -55,12 +61,25 @@ impl Writer {
         self
     }
 
+    #[must_use]
+    pub fn use_checksum_type(mut self, checksum_type: ChecksumType) -> Self {
+        self.checksum_type = checksum_type;
+        self
+    }
+
+    #[must_use]
+    pub fn use_start_keys(mut self, use_start_keys: bool) -> Self {
+        self.use_start_keys = use_start_keys;
+        self
+    }
+
     fn write_block(&mut self) -> crate::Result<()> {
         // Write to file
         let (header, data) = IndexBlock::to_bytes_compressed(
             &self.block_handles,
             self.prev_pos.0,
             self.compression,
+            self.checksum_type,
         )?;
 
         header.encode_into(&mut self.write_buffer)?;
@@ -80,9 +99,18 @@ impl Writer {
         #[allow(clippy::expect_used)]
         let last = self.block_handles.pop().expect("Chunk should not be empty");
 
+        // NOTE: The index block starts where its first data block starts
+        let start_key = self
+            .block_handles
+            .first()
+            .unwrap_or(&last)
+            .start_key
+            .clone();
+
         let index_block_handle = KeyedBlockHandle {
             end_key: last.end_key,
             offset: self.file_pos,
+            start_key,
         };
 
         self.tli_pointers.push(index_block_handle);
@@ -102,12 +130,25 @@ impl Writer {
         Ok(())
     }
 
-    pub fn register_block(&mut self, end_key: UserKey, offset: BlockOffset) -> crate::Result<()> {
+    pub fn register_block(
+        &mut self,
+        start_key: UserKey,
+        end_key: UserKey,
+        offset: BlockOffset,
+    ) -> crate::Result<()> {
+        let start_key = self.use_start_keys.then_some(start_key);
+
         // NOTE: Truncation is OK, because a key is bound by 65535 bytes, so can never exceed u32s
         #[allow(clippy::cast_possible_truncation)]
-        let block_handle_size = (end_key.len() + std::mem::size_of::<KeyedBlockHandle>()) as u32;
-
-        let block_handle = KeyedBlockHandle { end_key, offset };
+        let block_handle_size = (end_key.len()
+            + start_key.as_ref().map_or(0, |x| x.len())
+            + std::mem::size_of::<KeyedBlockHandle>()) as u32;
+
+        let block_handle = KeyedBlockHandle {
+            end_key,
+            offset,
+            start_key,
+        };
 
         self.block_handles.push(block_handle);
 
@@ -135,8 +176,12 @@ impl Writer {
         }
 
         // Write to file
-        let (header, data) =
-            IndexBlock::to_bytes_compressed(&self.tli_pointers, BlockOffset(0), self.compression)?;
+        let (header, data) = IndexBlock::to_bytes_compressed(
+            &self.tli_pointers,
+            BlockOffset(0),
+            self.compression,
+            self.checksum_type,
+        )?;
 
         header.encode_into(block_file_writer)?;
         block_file_writer.write_all(&data)?;
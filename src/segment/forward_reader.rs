@@ -27,6 +27,9 @@ pub struct ForwardReader<'a> {
     pub(crate) lo_initialized: bool,
 
     cache_policy: CachePolicy,
+
+    /// Whether blocks read from disk are verified using their checksum
+    verify_checksums: bool,
 }
 
 impl<'a> ForwardReader<'a> {
@@ -51,9 +54,24 @@ impl<'a> ForwardReader<'a> {
             lo_initialized: false,
 
             cache_policy: CachePolicy::Write,
+            verify_checksums: false,
         }
     }
 
+    /// Sets the cache policy
+    #[must_use]
+    pub fn cache_policy(mut self, policy: CachePolicy) -> Self {
+        self.cache_policy = policy;
+        self
+    }
+
+    /// Sets whether blocks read from disk are verified using their checksum.
+    #[must_use]
+    pub fn verify_checksums(mut self, verify_checksums: bool) -> Self {
+        self.verify_checksums = verify_checksums;
+        self
+    }
+
     fn load_data_block(
         &self,
         offset: BlockOffset,
@@ -64,6 +82,7 @@ impl<'a> ForwardReader<'a> {
             self.segment_id,
             offset,
             self.cache_policy,
+            self.verify_checksums,
         )?;
 
         // Truncate as many items as possible
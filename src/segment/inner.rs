@@ -3,7 +3,10 @@
 // (found in the LICENSE-* files in the repository)
 
 use super::{block_index::BlockIndexImpl, file_offsets::FileOffsets, meta::Metadata};
-use crate::{cache::Cache, descriptor_table::FileDescriptorTable, tree::inner::TreeId};
+use crate::{
+    cache::Cache, descriptor_table::FileDescriptorTable, range_tombstone::RangeTombstone,
+    tree::inner::TreeId,
+};
 use std::{
     path::PathBuf,
     sync::{atomic::AtomicBool, Arc},
@@ -37,6 +40,16 @@ pub struct Inner {
     #[doc(hidden)]
     pub bloom_filter: Option<crate::bloom::BloomFilter>,
 
+    /// Bloom filter of key prefixes, see [`crate::Config::bloom_prefix_extractor`]
+    #[doc(hidden)]
+    pub prefix_filter: Option<crate::bloom::BloomFilter>,
+
+    /// Range tombstones in the segment, see [`crate::AbstractTree::drop_range`]
+    pub(crate) range_tombstones: Vec<RangeTombstone>,
+
+    /// Whether data blocks are verified when they are read from disk, see [`crate::Config::verify_checksums`]
+    pub(crate) verify_checksums: bool,
+
     pub is_deleted: AtomicBool,
 }
 
@@ -51,9 +64,12 @@ impl Drop for Inner {
                     self.path,
                 );
             }
-
-            log::trace!("Closing file handles of deleted segment file {global_id:?}");
-            self.descriptor_table.remove(global_id);
         }
+
+        // NOTE: Nothing can read from the segment anymore, so also close its file handles
+        // if it was not deleted (e.g. the tree was dropped), so they do not occupy
+        // a (possibly shared) descriptor table
+        log::trace!("Closing file handles of segment file {global_id:?}");
+        self.descriptor_table.remove(global_id);
     }
 }
@@ -8,6 +8,7 @@ use std::io::{Read, Write};
 
 /// Compression algorithm to use.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[allow(clippy::module_name_repetitions)]
 pub enum CompressionType {
     /// No compression
@@ -33,6 +34,19 @@ pub enum CompressionType {
     /// - 10 may save even more space than 9, but the speed trade off may not be worth it
     #[cfg(feature = "miniz")]
     Miniz(u8),
+
+    /// Zstandard compression
+    ///
+    /// Compression level (-128-22) can be adjusted.
+    ///
+    /// - Negative levels optimize for speed
+    /// - 3 is a good default
+    /// - Higher levels optimize for space, at the cost of write speed
+    ///
+    /// Recommended for cold data that should take up as little space as possible,
+    /// decompression speed stays roughly the same across levels.
+    #[cfg(feature = "zstd")]
+    Zstd(i32),
 }
 
 impl Encode for CompressionType {
@@ -56,6 +70,20 @@ impl Encode for CompressionType {
                 writer.write_u8(2)?;
                 writer.write_u8(*level)?;
             }
+
+            #[cfg(feature = "zstd")]
+            Self::Zstd(level) => {
+                assert!(
+                    (-128..=22).contains(level),
+                    "invalid zstd compression level",
+                );
+
+                writer.write_u8(3)?;
+
+                // NOTE: Truncation is OK because the level was checked above
+                #[allow(clippy::cast_possible_truncation)]
+                writer.write_i8(*level as i8)?;
+            }
         };
 
         Ok(())
@@ -87,6 +115,15 @@ impl Decode for CompressionType {
                 Ok(Self::Miniz(level))
             }
 
+            #[cfg(feature = "zstd")]
+            3 => {
+                let level = reader.read_i8()?;
+
+                assert!(level <= 22, "invalid zstd compression level");
+
+                Ok(Self::Zstd(level.into()))
+            }
+
             tag => Err(DecodeError::InvalidTag(("CompressionType", tag))),
         }
     }
@@ -105,6 +142,9 @@ impl std::fmt::Display for CompressionType {
 
                 #[cfg(feature = "miniz")]
                 Self::Miniz(_) => "miniz",
+
+                #[cfg(feature = "zstd")]
+                Self::Zstd(_) => "zstd",
             }
         )
     }
@@ -146,4 +186,29 @@ mod tests {
             }
         }
     }
+
+    #[cfg(feature = "zstd")]
+    mod zstd {
+        use super::*;
+        use test_log::test;
+
+        #[test]
+        fn compression_serialize_zstd() -> Result<(), DecodeError> {
+            for lvl in [-128, -1, 0, 1, 3, 9, 22] {
+                let serialized = CompressionType::Zstd(lvl).encode_into_vec();
+                assert_eq!(2, serialized.len());
+
+                let deserialized = CompressionType::decode_from(&mut &serialized[..])?;
+                assert_eq!(CompressionType::Zstd(lvl), deserialized);
+            }
+
+            Ok(())
+        }
+
+        #[test]
+        #[should_panic = "invalid zstd compression level"]
+        fn compression_serialize_zstd_invalid_level() {
+            let _ = CompressionType::Zstd(23).encode_into_vec();
+        }
+    }
 }
@@ -7,8 +7,8 @@ mod table_type;
 
 use super::writer::Writer;
 use crate::{
-    coding::{Decode, DecodeError, Encode, EncodeError},
-    file::MAGIC_BYTES,
+    coding::{read_format_header, write_format_header, Decode, DecodeError, Encode, EncodeError},
+    file::{FORMAT_VERSION, MAGIC},
     time::unix_timestamp,
     value::SeqNo,
     KeyRange,
@@ -80,7 +80,7 @@ pub struct Metadata {
 impl Encode for Metadata {
     fn encode_into<W: Write>(&self, writer: &mut W) -> Result<(), EncodeError> {
         // Write header
-        writer.write_all(&MAGIC_BYTES)?;
+        write_format_header(writer, MAGIC, FORMAT_VERSION)?;
 
         writer.write_u64::<BigEndian>(self.id)?;
 
@@ -116,12 +116,7 @@ impl Encode for Metadata {
 impl Decode for Metadata {
     fn decode_from<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
         // Check header
-        let mut magic = [0u8; MAGIC_BYTES.len()];
-        reader.read_exact(&mut magic)?;
-
-        if magic != MAGIC_BYTES {
-            return Err(DecodeError::InvalidHeader("SegmentMetadata"));
-        }
+        read_format_header(reader, MAGIC, FORMAT_VERSION, "SegmentMetadata")?;
 
         let id = reader.read_u64::<BigEndian>()?;
 
@@ -192,7 +187,7 @@ impl Metadata {
             // But because millis already returns u128, might as well use micros :)
             created_at: unix_timestamp().as_micros(),
 
-            compression: CompressionType::None,
+            compression: writer.compression,
             table_type: TableType::Block,
 
             // NOTE: Truncation is OK - even with the smallest block size (1 KiB), 4 billion blocks would be 4 TB
@@ -230,8 +225,7 @@ impl Metadata {
 
             tombstone_count: writer.meta.tombstone_count as u64,
 
-            // TODO: #2 https://github.com/fjall-rs/lsm-tree/issues/2
-            range_tombstone_count: 0,
+            range_tombstone_count: writer.range_tombstone_count() as u64,
         })
     }
 
@@ -279,4 +273,35 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn segment_metadata_unsupported_version() {
+        let metadata = Metadata {
+            data_block_count: 0,
+            index_block_count: 0,
+            data_block_size: 4_096,
+            index_block_size: 4_096,
+            created_at: 5,
+            id: 632_632,
+            file_size: 1,
+            compression: CompressionType::None,
+            table_type: TableType::Block,
+            item_count: 0,
+            key_count: 0,
+            key_range: KeyRange::new((vec![2].into(), vec![5].into())),
+            tombstone_count: 0,
+            range_tombstone_count: 0,
+            uncompressed_size: 0,
+            seqnos: (0, 5),
+        };
+
+        // NOTE: Simulate metadata written by a newer format version
+        let mut bytes = metadata.encode_into_vec();
+        bytes[MAGIC.len()] = FORMAT_VERSION + 1;
+
+        assert!(matches!(
+            Metadata::decode_from(&mut Cursor::new(bytes)),
+            Err(DecodeError::InvalidVersion),
+        ));
+    }
 }
@@ -19,9 +19,10 @@ pub mod value_block_consumer;
 pub mod writer;
 
 use crate::{
-    bloom::{BloomFilter, CompositeHash},
+    bloom::{BloomFilter, CompositeHash, KeyHash},
     cache::Cache,
     descriptor_table::FileDescriptorTable,
+    range_tombstone::RangeTombstone,
     time::unix_timestamp,
     tree::inner::TreeId,
     value::{InternalValue, SeqNo, UserKey},
@@ -106,8 +107,37 @@ impl Segment {
         self.metadata.id
     }
 
-    pub(crate) fn verify(&self) -> crate::Result<usize> {
-        use block::checksum::Checksum;
+    /// Walks through all data blocks of the segment, verifying their checksums.
+    ///
+    /// Blocks are always read from disk, so cached blocks are verified as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error::ChecksumMismatch`] for the first corrupted data block,
+    /// or another error if a block cannot be read at all.
+    pub fn verify(&self) -> crate::Result<()> {
+        use value_block::{CachePolicy, ValueBlock};
+
+        let handles = self.data_block_handles(CachePolicy::Read)?;
+
+        let guard = self
+            .descriptor_table
+            .access(&self.global_id())?
+            .expect("should have gotten file");
+
+        let mut file = guard.file.lock().expect("lock is poisoned");
+
+        for handle in handles {
+            ValueBlock::from_file_verified(&mut *file, self.id(), handle.offset)?;
+        }
+
+        drop(file);
+
+        Ok(())
+    }
+
+    /// Walks through all blocks of the segment, returning the amount of corrupted blocks.
+    pub(crate) fn count_corrupted_blocks(&self) -> crate::Result<usize> {
         use block_index::IndexBlock;
         use value_block::ValueBlock;
 
@@ -125,27 +155,12 @@ impl Segment {
         match &*self.block_index {
             BlockIndexImpl::Full(block_index) => {
                 for handle in block_index.iter() {
-                    let value_block = match ValueBlock::from_file(&mut *file, handle.offset) {
-                        Ok(v) => v,
-                        Err(e) => {
-                            log::error!(
-                     "data block {handle:?} could not be loaded, it is probably corrupted: {e:?}"
-                 );
-                            broken_count += 1;
-                            data_block_count += 1;
-                            continue;
-                        }
-                    };
-
-                    let (_, data) = ValueBlock::to_bytes_compressed(
-                        &value_block.items,
-                        value_block.header.previous_block_offset,
-                        value_block.header.compression,
-                    )?;
-                    let actual_checksum = Checksum::from_bytes(&data);
-
-                    if value_block.header.checksum != actual_checksum {
-                        log::error!("{handle:?} is corrupted, invalid checksum value");
+                    if let Err(e) =
+                        ValueBlock::from_file_verified(&mut *file, self.id(), handle.offset)
+                    {
+                        log::error!(
+                            "data block {handle:?} could not be loaded, it is probably corrupted: {e:?}"
+                        );
                         broken_count += 1;
                     }
 
@@ -172,27 +187,12 @@ impl Segment {
                     };
 
                     for handle in &*block.items {
-                        let value_block = match ValueBlock::from_file(&mut *file, handle.offset) {
-                            Ok(v) => v,
-                            Err(e) => {
-                                log::error!(
-                     "data block {handle:?} could not be loaded, it is probably corrupted: {e:?}"
-                 );
-                                broken_count += 1;
-                                data_block_count += 1;
-                                continue;
-                            }
-                        };
-
-                        let (_, data) = ValueBlock::to_bytes_compressed(
-                            &value_block.items,
-                            value_block.header.previous_block_offset,
-                            value_block.header.compression,
-                        )?;
-                        let actual_checksum = Checksum::from_bytes(&data);
-
-                        if value_block.header.checksum != actual_checksum {
-                            log::error!("{handle:?} is corrupted, invalid checksum value");
+                        if let Err(e) =
+                            ValueBlock::from_file_verified(&mut *file, self.id(), handle.offset)
+                        {
+                            log::error!(
+                                "data block {handle:?} could not be loaded, it is probably corrupted: {e:?}"
+                            );
                             broken_count += 1;
                         }
 
@@ -236,6 +236,24 @@ impl Segment {
         })
     }
 
+    pub(crate) fn load_range_tombstones(
+        path: &Path,
+        ptr: block::offset::BlockOffset,
+    ) -> crate::Result<Vec<RangeTombstone>> {
+        Ok(if *ptr > 0 {
+            use std::{
+                fs::File,
+                io::{BufReader, Seek, SeekFrom},
+            };
+
+            let mut reader = File::open(path)?;
+            reader.seek(SeekFrom::Start(*ptr))?;
+            crate::range_tombstone::decode_list(&mut BufReader::new(reader))?
+        } else {
+            Vec::new()
+        })
+    }
+
     /// Tries to recover a segment from a file.
     pub(crate) fn recover(
         file_path: &Path,
@@ -243,6 +261,7 @@ impl Segment {
         cache: Arc<Cache>,
         descriptor_table: Arc<FileDescriptorTable>,
         use_full_block_index: bool,
+        verify_checksums: bool,
     ) -> crate::Result<Self> {
         use block_index::{full_index::FullBlockIndex, two_level_index::TwoLevelBlockIndex};
         use trailer::SegmentFileTrailer;
@@ -250,11 +269,6 @@ impl Segment {
         log::debug!("Recovering segment from file {file_path:?}");
         let trailer = SegmentFileTrailer::from_file(file_path)?;
 
-        assert_eq!(
-            0, *trailer.offsets.range_tombstones_ptr,
-            "Range tombstones not supported"
-        );
-
         log::debug!(
             "Creating block index, with tli_ptr={}",
             trailer.offsets.tli_ptr
@@ -292,6 +306,14 @@ impl Segment {
             cache,
 
             bloom_filter: Self::load_bloom(file_path, bloom_ptr)?,
+            prefix_filter: Self::load_bloom(file_path, trailer.offsets.pfx_ptr)?,
+
+            range_tombstones: Self::load_range_tombstones(
+                file_path,
+                trailer.offsets.range_tombstones_ptr,
+            )?,
+
+            verify_checksums,
 
             is_deleted: AtomicBool::default(),
         })))
@@ -312,11 +334,81 @@ impl Segment {
             .unwrap_or_default()
     }
 
+    /// Returns statistics of the segment's bloom filter, if it has one.
+    #[must_use]
+    pub fn bloom_stats(&self) -> Option<crate::bloom::BloomStats> {
+        self.bloom_filter
+            .as_ref()
+            .map(|filter| filter.stats(self.metadata.key_count))
+    }
+
+    /// Estimates the size of the data blocks that may contain keys in the given range, in bytes.
+    ///
+    /// The estimate is based on the block index, so it is block-granular,
+    /// and refers to the (possibly compressed) on-disk size.
+    pub(crate) fn approximate_size_in_range(
+        &self,
+        bounds: &(Bound<UserKey>, Bound<UserKey>),
+    ) -> crate::Result<u64> {
+        use block_index::BlockIndex;
+        use std::ops::Bound::{Excluded, Included, Unbounded};
+
+        if !self.metadata.key_range.overlaps_with_bounds(bounds) {
+            return Ok(0);
+        }
+
+        let data_end = *self.offsets.index_block_ptr;
+
+        let start = match &bounds.0 {
+            Included(key) | Excluded(key) => {
+                let Some(offset) = self
+                    .block_index
+                    .get_lowest_block_containing_key(key, value_block::CachePolicy::Read)?
+                else {
+                    return Ok(0);
+                };
+                *offset
+            }
+            Unbounded => 0,
+        };
+
+        let end = match &bounds.1 {
+            Included(key) | Excluded(key) => {
+                match self
+                    .block_index
+                    .get_last_block_containing_key(key, value_block::CachePolicy::Read)?
+                {
+                    // NOTE: The block's size is only known after reading its header,
+                    // so assume it has the average size
+                    Some(offset) => {
+                        let avg_block_size =
+                            data_end / u64::from(self.metadata.data_block_count.max(1));
+                        (*offset + avg_block_size).min(data_end)
+                    }
+                    None => data_end,
+                }
+            }
+            Unbounded => data_end,
+        };
+
+        Ok(end.saturating_sub(start))
+    }
+
+    /// Returns `false` if the segment's prefix filter rules out the prefix.
+    ///
+    /// Segments without a prefix filter may contain any prefix.
+    pub(crate) fn may_contain_prefix_hash(&self, hash: CompositeHash) -> bool {
+        self.prefix_filter
+            .as_ref()
+            .map_or(true, |filter| filter.contains_hash(hash))
+    }
+
     pub fn get(
         &self,
         key: &[u8],
         seqno: Option<SeqNo>,
-        hash: CompositeHash,
+        hash: &KeyHash,
+        cache_policy: value_block::CachePolicy,
     ) -> crate::Result<Option<InternalValue>> {
         if let Some(seqno) = seqno {
             if self.metadata.seqnos.0 >= seqno {
@@ -325,22 +417,63 @@ impl Segment {
         }
 
         if let Some(bf) = &self.bloom_filter {
-            if !bf.contains_hash(hash) {
+            if !bf.contains_key_hash(hash) {
                 return Ok(None);
             }
         }
 
-        self.point_read(key, seqno)
+        self.point_read(key, seqno, cache_policy)
+    }
+
+    /// Lists the handles of all data blocks, in key order.
+    ///
+    /// For a two-level index, this needs to read all index blocks.
+    pub(crate) fn data_block_handles(
+        &self,
+        cache_policy: value_block::CachePolicy,
+    ) -> crate::Result<Vec<block_index::block_handle::KeyedBlockHandle>> {
+        match &*self.block_index {
+            BlockIndexImpl::Full(index) => Ok(index.to_vec()),
+            BlockIndexImpl::TwoLevel(index) => {
+                let mut handles = Vec::new();
+
+                for handle in index.top_level_index.iter() {
+                    let block = index.load_index_block(handle.offset, cache_policy)?;
+                    handles.extend(block.items.iter().cloned());
+                }
+
+                Ok(handles)
+            }
+        }
+    }
+
+    /// Loads the data block at the given offset.
+    pub(crate) fn load_data_block(
+        &self,
+        offset: block::offset::BlockOffset,
+        cache_policy: value_block::CachePolicy,
+    ) -> crate::Result<Option<Arc<value_block::ValueBlock>>> {
+        value_block::ValueBlock::load_by_block_handle(
+            &self.descriptor_table,
+            &self.cache,
+            self.global_id(),
+            offset,
+            cache_policy,
+            self.verify_checksums,
+        )
     }
 
-    fn point_read(&self, key: &[u8], seqno: Option<SeqNo>) -> crate::Result<Option<InternalValue>> {
+    fn point_read(
+        &self,
+        key: &[u8],
+        seqno: Option<SeqNo>,
+        cache_policy: value_block::CachePolicy,
+    ) -> crate::Result<Option<InternalValue>> {
         use block_index::BlockIndex;
-        use value_block::{CachePolicy, ValueBlock};
+        use value_block::ValueBlock;
         use value_block_consumer::ValueBlockConsumer;
 
-        let Some(first_block_handle) = self
-            .block_index
-            .get_lowest_block_containing_key(key, CachePolicy::Write)?
+        let Some(first_block_handle) = self.block_index.get_point_read_block(key, cache_policy)?
         else {
             return Ok(None);
         };
@@ -350,7 +483,8 @@ impl Segment {
             &self.cache,
             self.global_id(),
             first_block_handle,
-            CachePolicy::Write,
+            cache_policy,
+            self.verify_checksums,
         )?
         else {
             return Ok(None);
@@ -371,7 +505,9 @@ impl Segment {
             self.global_id(),
             &self.cache,
             first_block_handle,
-        );
+        )
+        .cache_policy(cache_policy)
+        .verify_checksums(self.verify_checksums);
         reader.lo_block_size = block.header.data_length.into();
         reader.lo_block_items = Some(ValueBlockConsumer::with_bounds(block, Some(key), None));
         reader.lo_initialized = true;
@@ -443,6 +579,44 @@ impl Segment {
         Scanner::new(&segment_file_path, block_count)
     }
 
+    /// Reads all items of the segment in storage order, block by block.
+    ///
+    /// Unlike reads through the tree, items are not merged by MVCC: every version
+    /// of a key (including tombstones) is returned with its seqno and value type.
+    /// Blocks are read directly from the segment file, bypassing the block cache.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, ValueType};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "old", 0);
+    /// tree.insert("a", "new", 1);
+    /// tree.remove("b", 2);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let segment = tree.levels.read().unwrap().iter().next().cloned().unwrap();
+    ///
+    /// let items = segment.raw_iter()?.collect::<lsm_tree::Result<Vec<_>>>()?;
+    /// assert_eq!(3, items.len());
+    /// assert_eq!(1, items[0].key.seqno);
+    /// assert_eq!(0, items[1].key.seqno);
+    /// assert_eq!(ValueType::Tombstone, items[2].key.value_type);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn raw_iter(&self) -> crate::Result<Scanner> {
+        let block_count = self.metadata.data_block_count.try_into().expect("oops");
+        Scanner::new(&self.path, block_count)
+    }
+
     /// Creates a ranged iterator over the `Segment`.
     ///
     /// # Errors
@@ -458,6 +632,8 @@ impl Segment {
             self.block_index.clone(),
             range,
         )
+        .verify_checksums(self.verify_checksums)
+        .pin(self.clone())
     }
 
     /// Returns the highest sequence number in the segment.
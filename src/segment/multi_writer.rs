@@ -4,14 +4,23 @@
 
 use super::{
     trailer::SegmentFileTrailer,
-    writer::{BloomConstructionPolicy, Options, Writer},
+    writer::{BloomCheckpoint, BloomConstructionPolicy, Options, Writer},
+};
+use crate::{
+    bloom::BloomFilter,
+    compaction::shares_prefix,
+    config::{BloomHasher, PrefixExtractor},
+    value::InternalValue,
+    ChecksumType, CompressionType, UserKey,
 };
-use crate::{value::InternalValue, CompressionType, UserKey};
 use std::sync::{atomic::AtomicU64, Arc};
 
-/// Like `Writer` but will rotate to a new segment, once a segment grows larger than `target_size`
+/// Like `Writer` but will rotate to a new segment, once a segment grows larger than `target_size`,
+/// or holds more than [`Options::target_item_count`] items
 ///
 /// This results in a sorted "run" of segments
+///
+/// If a prefix length is set, a new segment is also started when the key prefix changes.
 #[allow(clippy::module_name_repetitions)]
 pub struct MultiWriter {
     /// Target size of segments in bytes
@@ -30,8 +39,21 @@ pub struct MultiWriter {
 
     pub compression: CompressionType,
 
+    pub checksum_type: ChecksumType,
+
     bloom_policy: BloomConstructionPolicy,
 
+    index_start_keys: bool,
+
+    value_alignment: u32,
+
+    prefix_extractor: Option<PrefixExtractor>,
+
+    bloom_hasher: Option<BloomHasher>,
+
+    /// Length of the key prefix that segments are split on, see [`crate::compaction::Input::prefix_len`]
+    prefix_len: Option<usize>,
+
     current_key: Option<UserKey>,
 }
 
@@ -50,6 +72,7 @@ impl MultiWriter {
             folder: opts.folder.clone(),
             data_block_size: opts.data_block_size,
             index_block_size: opts.index_block_size,
+            target_item_count: opts.target_item_count,
         })?;
 
         Ok(Self {
@@ -62,8 +85,20 @@ impl MultiWriter {
 
             compression: CompressionType::None,
 
+            checksum_type: ChecksumType::default(),
+
             bloom_policy: BloomConstructionPolicy::default(),
 
+            index_start_keys: false,
+
+            value_alignment: 1,
+
+            prefix_extractor: None,
+
+            bloom_hasher: None,
+
+            prefix_len: None,
+
             current_key: None,
         })
     }
@@ -75,6 +110,13 @@ impl MultiWriter {
         self
     }
 
+    #[must_use]
+    pub fn use_checksum_type(mut self, checksum_type: ChecksumType) -> Self {
+        self.checksum_type = checksum_type;
+        self.writer = self.writer.use_checksum_type(checksum_type);
+        self
+    }
+
     #[must_use]
     pub fn use_bloom_policy(mut self, bloom_policy: BloomConstructionPolicy) -> Self {
         self.bloom_policy = bloom_policy;
@@ -82,6 +124,62 @@ impl MultiWriter {
         self
     }
 
+    #[must_use]
+    pub fn use_index_start_keys(mut self, index_start_keys: bool) -> Self {
+        self.index_start_keys = index_start_keys;
+        self.writer = self.writer.use_index_start_keys(index_start_keys);
+        self
+    }
+
+    #[must_use]
+    pub fn use_value_alignment(mut self, value_alignment: u32) -> Self {
+        self.value_alignment = value_alignment;
+        self.writer = self.writer.use_value_alignment(value_alignment);
+        self
+    }
+
+    #[must_use]
+    pub fn use_prefix_extractor(mut self, extractor: Option<PrefixExtractor>) -> Self {
+        self.prefix_extractor.clone_from(&extractor);
+        self.writer = self.writer.use_prefix_extractor(extractor);
+        self
+    }
+
+    #[must_use]
+    pub fn use_bloom_hasher(mut self, hasher: Option<BloomHasher>) -> Self {
+        self.bloom_hasher.clone_from(&hasher);
+        self.writer = self.writer.use_bloom_hasher(hasher);
+        self
+    }
+
+    #[must_use]
+    pub fn use_prefix_len(mut self, prefix_len: Option<usize>) -> Self {
+        self.prefix_len = prefix_len;
+        self
+    }
+
+    /// Uses a prebuilt Bloom filter, if only a single segment is written,
+    /// see [`Writer::use_bloom_union`].
+    #[must_use]
+    pub fn use_bloom_union(mut self, filter: Option<BloomFilter>) -> Self {
+        self.writer = self.writer.use_bloom_union(filter);
+        self
+    }
+
+    /// Returns the Bloom filter of the current segment that has been built so far.
+    #[must_use]
+    pub fn bloom_checkpoint(&self) -> BloomCheckpoint {
+        self.writer.bloom_checkpoint()
+    }
+
+    /// Continues building the Bloom filter of a previous writer's segment.
+    ///
+    /// Only the current segment's filter is affected, the following
+    /// segments use the multi writer's construction policy.
+    pub fn resume_bloom(&mut self, checkpoint: BloomCheckpoint) {
+        self.writer.resume_bloom(checkpoint);
+    }
+
     fn get_next_segment_id(&mut self) -> u64 {
         self.current_segment_id = self
             .segment_id_generator
@@ -103,12 +201,21 @@ impl MultiWriter {
             folder: self.opts.folder.clone(),
             data_block_size: self.opts.data_block_size,
             index_block_size: self.opts.index_block_size,
+            target_item_count: self.opts.target_item_count,
         })?
-        .use_compression(self.compression);
+        .use_compression(self.compression)
+        .use_checksum_type(self.checksum_type);
 
-        new_writer = new_writer.use_bloom_policy(self.bloom_policy);
+        new_writer = new_writer
+            .use_bloom_policy(self.bloom_policy)
+            .use_index_start_keys(self.index_start_keys)
+            .use_value_alignment(self.value_alignment)
+            .use_prefix_extractor(self.prefix_extractor.clone())
+            .use_bloom_hasher(self.bloom_hasher.clone());
 
-        let mut old_writer = std::mem::replace(&mut self.writer, new_writer);
+        // IMPORTANT: The prebuilt filter contains all keys, so it would be too large
+        // for the first segment, and the following segments do not get it anyway
+        let mut old_writer = std::mem::replace(&mut self.writer, new_writer).use_bloom_union(None);
 
         if let Some(result) = old_writer.finish()? {
             self.results.push(result);
@@ -122,9 +229,22 @@ impl MultiWriter {
         let is_next_key = self.current_key.as_ref() < Some(&item.key.user_key);
 
         if is_next_key {
+            let is_next_prefix = match (self.prefix_len, &self.current_key) {
+                (Some(prefix_len), Some(current_key)) => {
+                    !shares_prefix(current_key, &item.key.user_key, prefix_len)
+                }
+                _ => false,
+            };
+
             self.current_key = Some(item.key.user_key.clone());
 
-            if *self.writer.meta.file_pos >= self.target_size {
+            let is_full = *self.writer.meta.file_pos >= self.target_size
+                || self
+                    .opts
+                    .target_item_count
+                    .is_some_and(|max| self.writer.item_count() as u64 >= max);
+
+            if is_next_prefix || is_full {
                 self.rotate()?;
             }
         }
@@ -134,6 +254,22 @@ impl MultiWriter {
         Ok(())
     }
 
+    /// Deletes the files of all segments written so far, after a failed write.
+    pub fn discard(self) {
+        for result in &self.results {
+            let path = self.opts.folder.join(result.metadata.id.to_string());
+
+            if let Err(e) = std::fs::remove_file(&path) {
+                log::warn!(
+                    "Failed to delete discarded segment {}: {e:?}",
+                    path.display(),
+                );
+            }
+        }
+
+        self.writer.discard();
+    }
+
     /// Finishes the last segment, making sure all data is written durably
     ///
     /// Returns the metadata of created segments
@@ -198,4 +334,54 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn segment_multi_writer_target_item_count() -> crate::Result<()> {
+        use super::{MultiWriter, Options};
+        use crate::{InternalValue, ValueType};
+        use std::sync::{atomic::AtomicU64, Arc};
+
+        let folder = tempfile::tempdir()?;
+
+        let mut writer = MultiWriter::new(
+            Arc::new(AtomicU64::default()),
+            u64::MAX,
+            Options {
+                folder: folder.path().into(),
+                data_block_size: 4_096,
+                index_block_size: 4_096,
+                target_item_count: Some(10),
+                segment_id: 0,
+            },
+        )?;
+
+        for x in 0..35u64 {
+            writer.write(InternalValue::from_components(
+                x.to_be_bytes(),
+                "abc",
+                0,
+                ValueType::Value,
+            ))?;
+        }
+
+        // NOTE: Versions of the same key are never split up
+        for seqno in (0..5).rev() {
+            writer.write(InternalValue::from_components(
+                35u64.to_be_bytes(),
+                "abc",
+                seqno,
+                ValueType::Value,
+            ))?;
+        }
+
+        let item_counts = writer
+            .finish()?
+            .into_iter()
+            .map(|result| result.metadata.item_count)
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec![10, 10, 10, 10], item_counts);
+
+        Ok(())
+    }
 }
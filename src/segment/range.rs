@@ -8,8 +8,10 @@ use super::block_index::BlockIndexImpl;
 use super::id::GlobalSegmentId;
 use super::reader::Reader;
 use super::value_block::CachePolicy;
+use super::Segment;
 use crate::cache::Cache;
 use crate::descriptor_table::FileDescriptorTable;
+use crate::merge::SeekIterator;
 use crate::value::InternalValue;
 use crate::value::UserKey;
 use crate::Slice;
@@ -29,6 +31,14 @@ pub struct Range {
 
     has_entered_lo: bool,
     has_entered_hi: bool,
+
+    /// Keeps the segment alive while the range is open
+    ///
+    /// A compaction may remove the segment from the level manifest at any time.
+    /// Its file is only unlinked (and its file descriptors only closed)
+    /// once the last reference is dropped, so the range can keep reading.
+    #[allow(unused)]
+    segment: Option<Segment>,
 }
 
 impl Range {
@@ -60,9 +70,18 @@ impl Range {
 
             has_entered_lo: false,
             has_entered_hi: false,
+
+            segment: None,
         }
     }
 
+    /// Pins the segment for as long as the range is alive
+    #[must_use]
+    pub(crate) fn pin(mut self, segment: Segment) -> Self {
+        self.segment = Some(segment);
+        self
+    }
+
     /// Sets the cache policy
     #[must_use]
     pub fn cache_policy(mut self, policy: CachePolicy) -> Self {
@@ -70,6 +89,20 @@ impl Range {
         self
     }
 
+    /// Sets whether blocks read from disk are verified using their checksum
+    #[must_use]
+    pub fn verify_checksums(mut self, verify_checksums: bool) -> Self {
+        self.reader = self.reader.verify_checksums(verify_checksums);
+        self
+    }
+
+    /// Sets the amount of blocks to read ahead when scanning forwards
+    #[must_use]
+    pub fn readahead(mut self, blocks: usize) -> Self {
+        self.reader = self.reader.readahead(blocks);
+        self
+    }
+
     fn initialize_lo_bound(&mut self) -> crate::Result<()> {
         let start_key = match self.range.start_bound() {
             Bound::Unbounded => None,
@@ -128,6 +161,30 @@ impl Range {
     }
 }
 
+impl SeekIterator for Range {
+    fn seek(&mut self, key: &[u8]) -> crate::Result<()> {
+        let is_ahead = match self.range.start_bound() {
+            Bound::Included(start) | Bound::Excluded(start) => key > &**start,
+            Bound::Unbounded => true,
+        };
+
+        if !is_ahead {
+            return Ok(());
+        }
+
+        self.range.0 = Bound::Included(key.into());
+        self.has_entered_lo = false;
+
+        // NOTE: If no block was read yet, the lo block can still be looked up
+        // in the block index, otherwise the reader skips ahead item by item
+        if !self.reader.lo_initialized && !self.reader.hi_initialized {
+            self.lo_initialized = false;
+        }
+
+        Ok(())
+    }
+}
+
 impl Iterator for Range {
     type Item = crate::Result<InternalValue>;
 
@@ -189,6 +246,15 @@ impl Iterator for Range {
 
 impl DoubleEndedIterator for Range {
     fn next_back(&mut self) -> Option<Self::Item> {
+        // IMPORTANT: The reader detects that both ends have met by comparing
+        // block offsets, so the lo block needs to be known before reading backwards,
+        // otherwise a later call to `next` moves the lo block past the hi cursor
+        if !self.lo_initialized {
+            if let Err(e) = self.initialize_lo_bound() {
+                return Some(Err(e));
+            }
+        }
+
         if !self.hi_initialized {
             if let Err(e) = self.initialize_hi_bound() {
                 return Some(Err(e));
@@ -277,6 +343,7 @@ mod tests {
             folder: folder.clone(),
             data_block_size: 1_000, // NOTE: Block size 1 to for each item to be its own block
             index_block_size: 4_096,
+            target_item_count: None,
         })?;
 
         let items = chars.iter().map(|&key| {
@@ -347,7 +414,7 @@ mod tests {
             // NOTE: Reverse
             let expected_range = (start_char..=b'z').rev().collect::<Vec<_>>();
 
-            let iter = Range::new(
+            let mut iter = Range::new(
                 trailer.offsets.index_block_ptr,
                 table.clone(),
                 (0, 0).into(),
@@ -356,12 +423,16 @@ mod tests {
                 (Bound::Included(key), Bound::Unbounded),
             );
             let items = iter
+                .by_ref()
                 .rev()
                 .flatten()
                 .map(|x| x.key.user_key.first().copied().expect("is ok"))
                 .collect::<Vec<_>>();
 
             assert_eq!(items, expected_range);
+
+            // NOTE: Both ends have met, so there is nothing left to read forwards
+            assert!(iter.next().is_none());
         }
 
         Ok(())
@@ -377,6 +448,7 @@ mod tests {
             folder: folder.clone(),
             data_block_size: 4_096,
             index_block_size: 4_096,
+            target_item_count: None,
         })?;
 
         let items = (0u64..ITEM_COUNT).map(|i| {
@@ -578,6 +650,7 @@ mod tests {
                 folder: folder.clone(),
                 data_block_size,
                 index_block_size: 4_096,
+                target_item_count: None,
             })?;
 
             let items = (0u64..ITEM_COUNT).map(|i| {
@@ -682,6 +755,7 @@ mod tests {
             folder: folder.clone(),
             data_block_size: 250,
             index_block_size: 4_096,
+            target_item_count: None,
         })?;
 
         let items = chars.iter().map(|&key| {
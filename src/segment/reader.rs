@@ -11,7 +11,7 @@ use crate::{
     cache::Cache, descriptor_table::FileDescriptorTable, segment::block::header::Header,
     value::InternalValue, GlobalSegmentId, UserKey,
 };
-use std::sync::Arc;
+use std::{collections::VecDeque, sync::Arc};
 
 pub struct Reader {
     segment_id: GlobalSegmentId,
@@ -35,6 +35,15 @@ pub struct Reader {
     end_key: Option<UserKey>,
 
     cache_policy: CachePolicy,
+
+    /// Whether blocks read from disk are verified using their checksum
+    verify_checksums: bool,
+
+    /// Amount of blocks to read ahead when scanning forwards
+    readahead_blocks: usize,
+
+    /// Blocks that were read ahead, but not consumed yet
+    readahead: VecDeque<(BlockOffset, Arc<ValueBlock>)>,
 }
 
 impl Reader {
@@ -65,6 +74,10 @@ impl Reader {
             hi_initialized: false,
 
             cache_policy: CachePolicy::Write,
+            verify_checksums: false,
+
+            readahead_blocks: 0,
+            readahead: VecDeque::new(),
 
             start_key: None,
             end_key: None,
@@ -88,6 +101,39 @@ impl Reader {
         self
     }
 
+    /// Sets whether blocks read from disk are verified using their checksum.
+    #[must_use]
+    pub fn verify_checksums(mut self, verify_checksums: bool) -> Self {
+        self.verify_checksums = verify_checksums;
+        self
+    }
+
+    /// Sets the amount of blocks to read ahead when scanning forwards.
+    ///
+    /// Read-ahead blocks are held by the reader until they are consumed,
+    /// so they do not pollute the block cache.
+    #[must_use]
+    pub fn readahead(mut self, blocks: usize) -> Self {
+        self.readahead_blocks = blocks;
+        self
+    }
+
+    fn consume_block(&self, block: Arc<ValueBlock>) -> (u64, BlockOffset, ValueBlockConsumer) {
+        // TODO: we only need to truncate items from blocks that are not the first and last block
+        // TODO: because any block inbetween must (trivially) only contain relevant items
+
+        // Truncate as many items as possible
+        (
+            block.header.data_length.into(),
+            block.header.previous_block_offset,
+            ValueBlockConsumer::with_bounds(
+                block,
+                self.start_key.as_deref(),
+                self.end_key.as_deref(),
+            ),
+        )
+    }
+
     fn load_data_block(
         &self,
         offset: BlockOffset,
@@ -98,23 +144,93 @@ impl Reader {
             self.segment_id,
             offset,
             self.cache_policy,
+            self.verify_checksums,
         )?;
 
-        // TODO: we only need to truncate items from blocks that are not the first and last block
-        // TODO: because any block inbetween must (trivially) only contain relevant items
+        Ok(block.map(|block| self.consume_block(block)))
+    }
 
-        // Truncate as many items as possible
-        block.map_or(Ok(None), |block| {
-            Ok(Some((
-                block.header.data_length.into(),
-                block.header.previous_block_offset,
-                ValueBlockConsumer::with_bounds(
-                    block,
-                    self.start_key.as_deref(),
-                    self.end_key.as_deref(),
-                ),
-            )))
-        })
+    /// Reads the block at the given offset and the blocks following it from disk,
+    /// stopping at the hi block, so the read-ahead window is filled.
+    fn fill_readahead(&mut self, mut offset: BlockOffset) -> crate::Result<()> {
+        use std::io::{Seek, SeekFrom};
+
+        let segment_id = self.segment_id;
+
+        let file_guard = self
+            .descriptor_table
+            .access(&segment_id)?
+            .ok_or(())
+            .map_err(|()| {
+                log::error!("Failed to get file guard for segment {segment_id:?}");
+            })
+            .expect("should acquire file handle");
+
+        let mut file = file_guard.file.lock().expect("lock is poisoned");
+        file.seek(SeekFrom::Start(*offset))?;
+
+        // NOTE: + 1 because the requested block is loaded as well
+        for _ in 0..=self.readahead_blocks {
+            if offset >= self.data_block_boundary || Some(offset) == self.hi_block_offset {
+                break;
+            }
+
+            log::trace!("reading ahead value block from disk: {segment_id:?}/{offset:?}");
+
+            let block = if self.verify_checksums {
+                ValueBlock::from_reader_verified(&mut *file, segment_id.segment_id(), offset)?
+            } else {
+                ValueBlock::from_reader(&mut *file)?
+            };
+
+            let next_offset = BlockOffset(
+                *offset + Header::serialized_len() as u64 + u64::from(block.header.data_length),
+            );
+
+            self.readahead.push_back((offset, Arc::new(block)));
+
+            offset = next_offset;
+        }
+
+        drop(file);
+        drop(file_guard);
+
+        Ok(())
+    }
+
+    /// Loads the next block of a forward scan.
+    ///
+    /// Because the scan accesses blocks sequentially, the following blocks
+    /// are read ahead in a single IO pass, if read-ahead is enabled.
+    fn load_next_data_block(
+        &mut self,
+        offset: BlockOffset,
+    ) -> crate::Result<Option<(u64, BlockOffset, ValueBlockConsumer)>> {
+        if self.readahead_blocks == 0 {
+            return self.load_data_block(offset);
+        }
+
+        if self.readahead.front().map(|(x, _)| *x) != Some(offset) {
+            // NOTE: The window is outdated, so start over
+            self.readahead.clear();
+
+            if let Some(block) = self.block_cache.get_data_block(self.segment_id, offset) {
+                return Ok(Some(self.consume_block(block)));
+            }
+
+            self.fill_readahead(offset)?;
+        }
+
+        let Some((_, block)) = self.readahead.pop_front() else {
+            return Ok(None);
+        };
+
+        if self.cache_policy == CachePolicy::Write {
+            self.block_cache
+                .insert_data_block(self.segment_id, offset, block.clone());
+        }
+
+        Ok(Some(self.consume_block(block)))
     }
 
     fn initialize_lo(&mut self) -> crate::Result<()> {
@@ -187,7 +303,7 @@ impl Iterator for Reader {
 
         // TODO: when loading the next data block, we unnecessarily do binary search through it
         // (ValueBlock::with_bounds), but we may be able to skip it sometimes
-        match fail_iter!(self.load_data_block(next_block_offset)) {
+        match fail_iter!(self.load_next_data_block(next_block_offset)) {
             Some((size, _, items)) => {
                 self.lo_block_items = Some(items);
                 self.lo_block_size = size;
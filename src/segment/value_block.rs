@@ -9,6 +9,7 @@ use crate::{
 };
 use std::sync::Arc;
 
+/// Controls whether blocks that are read from disk are inserted into the block cache
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum CachePolicy {
     /// Read cached blocks, but do not change cache
@@ -41,6 +42,7 @@ impl ValueBlock {
         segment_id: GlobalSegmentId,
         offset: BlockOffset,
         cache_policy: CachePolicy,
+        verify_checksum: bool,
     ) -> crate::Result<Option<Arc<Self>>> {
         Ok(
             if let Some(block) = block_cache.get_data_block(segment_id, offset) {
@@ -61,16 +63,20 @@ impl ValueBlock {
                     .expect("should acquire file handle");
                 // TODO: ^ use inspect instead: 1.76
 
-                let block = Self::from_file(
-                    &mut *file_guard.file.lock().expect("lock is poisoned"),
-                    offset,
-                )
+                let mut file = file_guard.file.lock().expect("lock is poisoned");
+
+                let block = if verify_checksum {
+                    Self::from_file_verified(&mut *file, segment_id.segment_id(), offset)
+                } else {
+                    Self::from_file(&mut *file, offset)
+                }
                 .map_err(|e| {
                     log::error!("Failed to load value block {segment_id:?}/{offset:?}: {e:?}");
                     e
                 })?;
                 // TODO: ^ inspect_err instead: 1.76
 
+                drop(file);
                 drop(file_guard);
 
                 let block = Arc::new(block);
@@ -90,7 +96,11 @@ mod tests {
     use super::*;
     use crate::{
         segment::{
-            block::{checksum::Checksum, header::Header as BlockHeader, ItemSize},
+            block::{
+                checksum::{Checksum, ChecksumType},
+                header::Header as BlockHeader,
+                ItemSize,
+            },
             meta::CompressionType,
         },
         ValueType,
@@ -122,10 +132,12 @@ mod tests {
             header: BlockHeader {
                 compression: CompressionType::None,
                 checksum: Checksum::from_raw(0),
+                checksum_type: ChecksumType::Xxh3,
                 data_length: 0,
                 previous_block_offset: BlockOffset(0),
                 uncompressed_length: 0,
             },
+            value_alignment: 1,
         };
 
         assert_eq!(block.get_latest(b"a"), None);
@@ -90,7 +90,11 @@ impl DoubleEndedIterator for ValueBlockConsumer {
 #[allow(clippy::expect_used)]
 mod tests {
     use super::*;
-    use crate::segment::block::{checksum::Checksum, header::Header, offset::BlockOffset};
+    use crate::segment::block::{
+        checksum::{Checksum, ChecksumType},
+        header::Header,
+        offset::BlockOffset,
+    };
     use test_log::test;
 
     macro_rules! iter_closed {
@@ -108,11 +112,13 @@ mod tests {
             header: Header {
                 compression: crate::segment::meta::CompressionType::None,
                 checksum: Checksum::from_raw(0),
+                checksum_type: ChecksumType::Xxh3,
                 data_length: 0,
                 previous_block_offset: BlockOffset(0),
                 uncompressed_length: 0,
             },
             items: items.into_boxed_slice(),
+            value_alignment: 1,
         }
     }
 
@@ -0,0 +1,133 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::BloomConstructionPolicy;
+use crate::{
+    bloom::CompositeHash,
+    coding::{Decode, DecodeError, Encode, EncodeError},
+};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Write};
+
+const TAG_BITS_PER_KEY: u8 = 0;
+const TAG_FP_RATE: u8 = 1;
+
+/// In-progress Bloom filter of a segment writer
+///
+/// A Bloom filter can only be sized once the segment's key count is known,
+/// so a writer buffers the key hashes until it is finished.
+///
+/// A checkpoint contains the construction policy and all hashes buffered so far,
+/// so a resumed writer can continue building the filter, instead of having
+/// to hash all keys that were written before the checkpoint again.
+#[derive(Clone, Debug)]
+pub struct BloomCheckpoint {
+    pub(crate) policy: BloomConstructionPolicy,
+    pub(crate) hashes: Vec<CompositeHash>,
+}
+
+impl BloomCheckpoint {
+    /// Returns the amount of keys in the checkpoint.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Returns `true` if no keys were registered before the checkpoint.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+}
+
+impl Encode for BloomCheckpoint {
+    fn encode_into<W: Write>(&self, writer: &mut W) -> Result<(), EncodeError> {
+        match self.policy {
+            BloomConstructionPolicy::BitsPerKey(bpk) => {
+                writer.write_u8(TAG_BITS_PER_KEY)?;
+                writer.write_u8(bpk)?;
+            }
+            BloomConstructionPolicy::FpRate(fpr) => {
+                writer.write_u8(TAG_FP_RATE)?;
+                writer.write_f32::<BigEndian>(fpr)?;
+            }
+        }
+
+        writer.write_u64::<BigEndian>(self.hashes.len() as u64)?;
+
+        for (h1, h2) in &self.hashes {
+            writer.write_u64::<BigEndian>(*h1)?;
+            writer.write_u64::<BigEndian>(*h2)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Decode for BloomCheckpoint {
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let policy = match reader.read_u8()? {
+            TAG_BITS_PER_KEY => BloomConstructionPolicy::BitsPerKey(reader.read_u8()?),
+            TAG_FP_RATE => BloomConstructionPolicy::FpRate(reader.read_f32::<BigEndian>()?),
+            x => return Err(DecodeError::InvalidTag(("BloomConstructionPolicy", x))),
+        };
+
+        let len = reader.read_u64::<BigEndian>()?;
+
+        // NOTE: Do not trust the length for preallocation, the checkpoint may be corrupt
+        let mut hashes = Vec::new();
+
+        for _ in 0..len {
+            let h1 = reader.read_u64::<BigEndian>()?;
+            let h2 = reader.read_u64::<BigEndian>()?;
+            hashes.push((h1, h2));
+        }
+
+        Ok(Self { policy, hashes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bloom::BloomFilter;
+    use test_log::test;
+
+    #[test]
+    fn bloom_checkpoint_round_trip() -> crate::Result<()> {
+        let hashes = (0u64..100)
+            .map(|x| BloomFilter::get_hash(&x.to_be_bytes()))
+            .collect::<Vec<_>>();
+
+        for policy in [
+            BloomConstructionPolicy::BitsPerKey(7),
+            BloomConstructionPolicy::FpRate(0.001),
+        ] {
+            let checkpoint = BloomCheckpoint {
+                policy,
+                hashes: hashes.clone(),
+            };
+
+            let bytes = checkpoint.encode_into_vec();
+            let copy = BloomCheckpoint::decode_from(&mut &bytes[..])?;
+
+            assert_eq!(100, copy.len());
+            assert_eq!(hashes, copy.hashes);
+            assert_eq!(policy.build(100).len(), copy.policy.build(100).len(),);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn bloom_checkpoint_truncated() {
+        let checkpoint = BloomCheckpoint {
+            policy: BloomConstructionPolicy::default(),
+            hashes: vec![(1, 2), (3, 4)],
+        };
+
+        let bytes = checkpoint.encode_into_vec();
+        assert!(BloomCheckpoint::decode_from(&mut &bytes[..bytes.len() - 1]).is_err());
+    }
+}
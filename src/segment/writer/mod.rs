@@ -2,10 +2,16 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
+mod bloom_checkpoint;
 mod meta;
 
+pub use bloom_checkpoint::BloomCheckpoint;
+
 use super::{
-    block::{header::Header as BlockHeader, offset::BlockOffset},
+    block::{
+        checksum::ChecksumType, header::Header as BlockHeader, offset::BlockOffset,
+        MAX_VALUE_ALIGNMENT,
+    },
     block_index::writer::Writer as IndexWriter,
     file_offsets::FileOffsets,
     meta::{CompressionType, Metadata},
@@ -13,9 +19,11 @@ use super::{
     value_block::ValueBlock,
 };
 use crate::{
-    bloom::BloomFilter,
+    bloom::{BloomFilter, CompositeHash},
     coding::Encode,
+    config::{BloomHasher, PrefixExtractor},
     file::fsync_directory,
+    range_tombstone::RangeTombstone,
     segment::block::ItemSize,
     value::{InternalValue, UserKey},
     SegmentId,
@@ -31,11 +39,23 @@ pub struct Writer {
     pub(crate) opts: Options,
 
     /// Compression to use
-    compression: CompressionType,
+    pub(crate) compression: CompressionType,
+
+    /// Checksum algorithm to use
+    checksum_type: ChecksumType,
+
+    /// Alignment of values within data blocks
+    value_alignment: u32,
 
     /// Segment file
     segment_file_path: PathBuf,
 
+    /// File the segment is written to, until it is complete
+    ///
+    /// Unfinished segment files are deleted on recovery (see `Tree::recover_levels`),
+    /// so a torn segment file can never be mistaken for a complete one.
+    tmp_file_path: PathBuf,
+
     /// Writer of data blocks
     block_writer: BufWriter<File>,
 
@@ -59,6 +79,24 @@ pub struct Writer {
     ///
     /// using enhanced double hashing, so we got two u64s
     bloom_hash_buffer: Vec<(u64, u64)>,
+
+    /// Custom hasher of the keys for the bloom filter, see [`crate::Config::bloom_hasher`]
+    bloom_hasher: Option<BloomHasher>,
+
+    /// Prebuilt Bloom filter that contains all written keys, see [`Writer::use_bloom_union`]
+    bloom_union: Option<BloomFilter>,
+
+    /// Extracts the key prefixes for the prefix filter
+    prefix_extractor: Option<PrefixExtractor>,
+
+    /// Hashes for prefix filter
+    prefix_hash_buffer: Vec<CompositeHash>,
+
+    /// Last prefix that was hashed, keys are sorted so every prefix is only hashed once
+    last_prefix: Option<UserKey>,
+
+    /// Range tombstones that were written
+    range_tombstones: Vec<RangeTombstone>,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -89,12 +127,30 @@ impl BloomConstructionPolicy {
             Self::FpRate(_) => true,
         }
     }
+
+    /// Returns the false positive rate of a filter built for `n` items.
+    #[must_use]
+    pub fn fp_rate(&self, n: usize) -> f32 {
+        match self {
+            Self::BitsPerKey(bpk) => {
+                let bpk = usize::from(*bpk);
+                crate::bloom::optimal_params(n, n * bpk / 8).fp_rate
+            }
+            Self::FpRate(fpr) => *fpr,
+        }
+    }
 }
 
 pub struct Options {
     pub folder: PathBuf,
     pub data_block_size: u32,
     pub index_block_size: u32,
+
+    /// Maximum amount of items per segment, see [`crate::segment::multi_writer::MultiWriter`]
+    ///
+    /// Only used by the multi writer, a single segment writer ignores it.
+    pub target_item_count: Option<u64>,
+
     pub segment_id: SegmentId,
 }
 
@@ -102,8 +158,9 @@ impl Writer {
     /// Sets up a new `Writer` at the given folder
     pub fn new(opts: Options) -> crate::Result<Self> {
         let segment_file_path = opts.folder.join(opts.segment_id.to_string());
+        let tmp_file_path = opts.folder.join(format!("tmp_{}", opts.segment_id));
 
-        let block_writer = File::create(&segment_file_path)?;
+        let block_writer = File::create(&tmp_file_path)?;
         let block_writer = BufWriter::with_capacity(u16::MAX.into(), block_writer);
 
         let index_writer = IndexWriter::new(opts.index_block_size)?;
@@ -115,8 +172,11 @@ impl Writer {
             meta: meta::Metadata::default(),
 
             compression: CompressionType::None,
+            checksum_type: ChecksumType::default(),
+            value_alignment: 1,
 
             segment_file_path,
+            tmp_file_path,
 
             block_writer,
             index_writer,
@@ -131,6 +191,14 @@ impl Writer {
             bloom_policy: BloomConstructionPolicy::default(),
 
             bloom_hash_buffer: Vec::new(),
+            bloom_hasher: None,
+            bloom_union: None,
+
+            prefix_extractor: None,
+            prefix_hash_buffer: Vec::new(),
+            last_prefix: None,
+
+            range_tombstones: Vec::new(),
         })
     }
 
@@ -141,24 +209,112 @@ impl Writer {
         self
     }
 
+    #[must_use]
+    pub(crate) fn use_checksum_type(mut self, checksum_type: ChecksumType) -> Self {
+        self.checksum_type = checksum_type;
+        self.index_writer = self.index_writer.use_checksum_type(checksum_type);
+        self
+    }
+
+    /// Pads the values within data blocks, so they start at a multiple of `value_alignment`
+    /// bytes, relative to the start of the uncompressed block data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the alignment is not a power of two, or exceeds [`MAX_VALUE_ALIGNMENT`].
+    #[must_use]
+    pub(crate) fn use_value_alignment(mut self, value_alignment: u32) -> Self {
+        assert!(
+            value_alignment.is_power_of_two() && value_alignment <= MAX_VALUE_ALIGNMENT,
+            "invalid value alignment",
+        );
+
+        self.value_alignment = value_alignment;
+        self
+    }
+
     #[must_use]
     pub(crate) fn use_bloom_policy(mut self, bloom_policy: BloomConstructionPolicy) -> Self {
         self.bloom_policy = bloom_policy;
         self
     }
 
+    /// Uses a prebuilt Bloom filter instead of building one from the written keys.
+    ///
+    /// The filter needs to contain every key that is written, e.g. the
+    /// [`BloomFilter::union`] of the filters of all compacted segments.
+    /// It is only used if its false positive rate is not worse than the
+    /// rate of a rebuilt filter, otherwise the filter is built as usual.
+    #[must_use]
+    pub(crate) fn use_bloom_union(mut self, filter: Option<BloomFilter>) -> Self {
+        self.bloom_union = filter;
+        self
+    }
+
+    #[must_use]
+    pub(crate) fn use_prefix_extractor(mut self, extractor: Option<PrefixExtractor>) -> Self {
+        self.prefix_extractor = extractor;
+        self
+    }
+
+    #[must_use]
+    pub(crate) fn use_bloom_hasher(mut self, hasher: Option<BloomHasher>) -> Self {
+        self.bloom_hasher = hasher;
+        self
+    }
+
+    /// Returns the Bloom filter that has been built so far.
+    #[must_use]
+    pub fn bloom_checkpoint(&self) -> BloomCheckpoint {
+        BloomCheckpoint {
+            policy: self.bloom_policy,
+            hashes: self.bloom_hash_buffer.clone(),
+        }
+    }
+
+    /// Continues building the Bloom filter of a previous writer.
+    ///
+    /// The checkpoint's construction policy replaces the writer's policy,
+    /// and the resulting filter contains all keys of the checkpoint.
+    pub fn resume_bloom(&mut self, checkpoint: BloomCheckpoint) {
+        let mut hashes = checkpoint.hashes;
+        hashes.append(&mut self.bloom_hash_buffer);
+
+        self.bloom_policy = checkpoint.policy;
+        self.bloom_hash_buffer = hashes;
+
+        // NOTE: The prebuilt filter is missing the checkpoint's keys
+        self.bloom_union = None;
+
+        // IMPORTANT: The checkpoint does not contain the prefixes of its keys,
+        // so a prefix filter would wrongly rule out some prefixes
+        self.prefix_extractor = None;
+        self.prefix_hash_buffer.clear();
+    }
+
+    #[must_use]
+    pub(crate) fn use_index_start_keys(mut self, index_start_keys: bool) -> Self {
+        self.index_writer = self.index_writer.use_start_keys(index_start_keys);
+        self
+    }
+
     /// Writes a compressed block to disk.
     ///
     /// This is triggered when a `Writer::write` causes the buffer to grow to the configured `block_size`.
     ///
     /// Should only be called when the block has items in it.
     pub(crate) fn spill_block(&mut self) -> crate::Result<()> {
-        let Some(last) = self.chunk.last() else {
+        let (Some(first), Some(last)) = (self.chunk.first(), self.chunk.last()) else {
             return Ok(());
         };
 
-        let (header, data) =
-            ValueBlock::to_bytes_compressed(&self.chunk, self.prev_pos.0, self.compression)?;
+        let (header, data) = ValueBlock::to_bytes_compressed_aligned(
+            &self.chunk,
+            self.prev_pos.0,
+            self.compression,
+            self.checksum_type,
+            self.value_alignment,
+        )?;
 
         self.meta.uncompressed_size += u64::from(header.uncompressed_length);
 
@@ -169,8 +325,11 @@ impl Writer {
 
         let bytes_written = (BlockHeader::serialized_len() + data.len()) as u64;
 
-        self.index_writer
-            .register_block(last.key.user_key.clone(), self.meta.file_pos)?;
+        self.index_writer.register_block(
+            first.key.user_key.clone(),
+            last.key.user_key.clone(),
+            self.meta.file_pos,
+        )?;
 
         // Adjust metadata
         self.meta.file_pos += bytes_written;
@@ -203,6 +362,18 @@ impl Writer {
         Ok(())
     }
 
+    /// Returns the amount of items written so far, including the unflushed block.
+    #[must_use]
+    pub fn item_count(&self) -> usize {
+        self.meta.item_count + self.chunk.len()
+    }
+
+    /// Returns the amount of range tombstones written so far.
+    #[must_use]
+    pub(crate) fn range_tombstone_count(&self) -> usize {
+        self.range_tombstones.len()
+    }
+
     /// Writes an item.
     ///
     /// # Note
@@ -211,7 +382,9 @@ impl Writer {
     /// sorted as described by the [`UserKey`], otherwise the block layout will
     /// be non-sense.
     pub fn write(&mut self, item: InternalValue) -> crate::Result<()> {
-        if item.is_tombstone() {
+        if let Some(rt) = RangeTombstone::from_item(&item) {
+            self.range_tombstones.push(rt);
+        } else if item.is_tombstone() {
             self.meta.tombstone_count += 1;
         }
 
@@ -224,8 +397,19 @@ impl Writer {
             // because there may be multiple versions
             // of the same key
             if self.bloom_policy.is_active() {
-                self.bloom_hash_buffer
-                    .push(BloomFilter::get_hash(&item.key.user_key));
+                self.bloom_hash_buffer.push(match &self.bloom_hasher {
+                    Some(hasher) => hasher(&item.key.user_key),
+                    None => BloomFilter::get_hash(&item.key.user_key),
+                });
+
+                if let Some(extractor) = &self.prefix_extractor {
+                    let prefix = extractor(&item.key.user_key);
+
+                    if !prefix.is_empty() && self.last_prefix.as_deref() != Some(prefix) {
+                        self.prefix_hash_buffer.push(BloomFilter::get_hash(prefix));
+                        self.last_prefix = Some(prefix.into());
+                    }
+                }
             }
         }
 
@@ -235,7 +419,11 @@ impl Writer {
             self.meta.first_key = Some(item.key.user_key.clone());
         }
 
-        self.chunk_size += item.size();
+        // NOTE: The block size bounds the uncompressed size, so compression
+        // does not change where blocks are cut
+        //
+        // Padding counts towards the block size, so assume the worst case
+        self.chunk_size += item.size() + self.value_alignment as usize - 1;
         self.chunk.push(item);
 
         if self.chunk_size >= self.opts.data_block_size as usize {
@@ -248,15 +436,70 @@ impl Writer {
         Ok(())
     }
 
+    /// Writes a Bloom filter of the given hashes, returning its position.
+    ///
+    /// If there are no hashes, no filter is written, and the position is 0.
+    fn write_bloom_filter(
+        &mut self,
+        hashes: Vec<CompositeHash>,
+        custom_hash: bool,
+    ) -> crate::Result<BlockOffset> {
+        if hashes.is_empty() {
+            return Ok(BlockOffset(0));
+        }
+
+        let bloom_ptr = self.block_writer.stream_position()?;
+        let n = hashes.len();
+
+        log::trace!(
+            "Constructing Bloom filter with {n} entries: {:?}",
+            self.bloom_policy,
+        );
+
+        let start = std::time::Instant::now();
+
+        let mut filter = self.bloom_policy.build(n);
+
+        if custom_hash {
+            filter = filter.with_custom_hash();
+        }
+
+        filter.extend_with_hashes(hashes);
+
+        log::trace!("Built Bloom filter in {:?}", start.elapsed());
+
+        filter.encode_into(&mut self.block_writer)?;
+
+        Ok(BlockOffset(bloom_ptr))
+    }
+
     // TODO: should take mut self to avoid double finish
 
+    /// Deletes the unfinished segment file.
+    pub fn discard(self) {
+        let Self {
+            block_writer,
+            tmp_file_path,
+            ..
+        } = self;
+
+        drop(block_writer);
+
+        if let Err(e) = std::fs::remove_file(&tmp_file_path) {
+            log::warn!(
+                "Failed to delete unfinished segment {}: {e:?}",
+                tmp_file_path.display(),
+            );
+        }
+    }
+
     /// Finishes the segment, making sure all data is written durably
     pub fn finish(&mut self) -> crate::Result<Option<SegmentFileTrailer>> {
         self.spill_block()?;
 
         // No items written! Just delete segment file and return nothing
         if self.meta.item_count == 0 {
-            std::fs::remove_file(&self.segment_file_path)?;
+            std::fs::remove_file(&self.tmp_file_path)?;
             return Ok(None);
         }
 
@@ -270,32 +513,24 @@ impl Writer {
         self.meta.index_block_count = self.index_writer.block_count;
 
         // Write bloom filter
-        let bloom_ptr = {
-            if self.bloom_hash_buffer.is_empty() {
-                BlockOffset(0)
-            } else {
-                let bloom_ptr = self.block_writer.stream_position()?;
-                let n = self.bloom_hash_buffer.len();
-
-                log::trace!(
-                    "Constructing Bloom filter with {n} entries: {:?}",
-                    self.bloom_policy,
-                );
-
-                let start = std::time::Instant::now();
-
-                let mut filter = self.bloom_policy.build(n);
-
-                for hash in std::mem::take(&mut self.bloom_hash_buffer) {
-                    filter.set_with_hash(hash);
-                }
-
-                log::trace!("Built Bloom filter in {:?}", start.elapsed());
+        let hashes = std::mem::take(&mut self.bloom_hash_buffer);
+        let bloom_ptr = match self.bloom_union.take() {
+            // NOTE: The prebuilt filter may have been built using a different hash
+            Some(filter)
+                if !hashes.is_empty()
+                    && filter.has_custom_hash() == self.bloom_hasher.is_some()
+                    && filter.estimated_fp_rate() <= self.bloom_policy.fp_rate(hashes.len()) =>
+            {
+                log::trace!("Using prebuilt Bloom filter");
 
+                let bloom_ptr = self.block_writer.stream_position()?;
                 filter.encode_into(&mut self.block_writer)?;
-
                 BlockOffset(bloom_ptr)
             }
+            _ => {
+                let custom_hash = self.bloom_hasher.is_some();
+                self.write_bloom_filter(hashes, custom_hash)?
+            }
         };
         log::trace!("bloom_ptr={bloom_ptr}");
 
@@ -303,12 +538,19 @@ impl Writer {
         let rf_ptr = BlockOffset(0);
         log::trace!("rf_ptr={rf_ptr}");
 
-        // TODO: #2 https://github.com/fjall-rs/lsm-tree/issues/2 - Write range tombstones
-        let range_tombstones_ptr = BlockOffset(0);
+        // Write range tombstones
+        let range_tombstones_ptr = if self.range_tombstones.is_empty() {
+            BlockOffset(0)
+        } else {
+            let ptr = self.block_writer.stream_position()?;
+            crate::range_tombstone::encode_list(&mut self.block_writer, &self.range_tombstones)?;
+            BlockOffset(ptr)
+        };
         log::trace!("range_tombstones_ptr={range_tombstones_ptr}");
 
-        // TODO:
-        let pfx_ptr = BlockOffset(0);
+        // Write prefix filter
+        let hashes = std::mem::take(&mut self.prefix_hash_buffer);
+        let pfx_ptr = self.write_bloom_filter(hashes, false)?;
         log::trace!("pfx_ptr={pfx_ptr}");
 
         // Write metadata
@@ -336,6 +578,9 @@ impl Writer {
         self.block_writer.flush()?;
         self.block_writer.get_mut().sync_all()?;
 
+        // IMPORTANT: Only make the segment file visible under its final name once it is complete
+        std::fs::rename(&self.tmp_file_path, &self.segment_file_path)?;
+
         // IMPORTANT: fsync folder on Unix
         fsync_directory(&self.opts.folder)?;
 
@@ -372,6 +617,7 @@ mod tests {
             folder,
             data_block_size: 4_096,
             index_block_size: 4_096,
+            target_item_count: None,
             segment_id,
         })?;
 
@@ -408,6 +654,119 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[cfg(feature = "lz4")]
+    fn segment_writer_uncompressed_block_size() -> crate::Result<()> {
+        use crate::coding::Decode;
+        use crate::segment::{block::header::Header, meta::CompressionType};
+        use std::io::{Seek, SeekFrom};
+
+        const BLOCK_SIZE: u32 = 4_096;
+
+        let folder = tempfile::tempdir()?.into_path();
+
+        let mut writer = Writer::new(Options {
+            folder: folder.clone(),
+            data_block_size: BLOCK_SIZE,
+            index_block_size: 4_096,
+            target_item_count: None,
+            segment_id: 1,
+        })?
+        .use_compression(CompressionType::Lz4);
+
+        // NOTE: Values are highly compressible
+        let mut max_item_size = 0;
+
+        for x in 0..1_000u64 {
+            let item =
+                InternalValue::from_components(x.to_be_bytes(), [0; 200], 0, ValueType::Value);
+            max_item_size = max_item_size.max(item.size());
+            writer.write(item)?;
+        }
+
+        let trailer = writer.finish()?.expect("should exist");
+
+        let mut file = std::fs::File::open(folder.join("1"))?;
+        let mut offset = 0;
+        let mut block_count = 0;
+
+        while offset < *trailer.offsets.index_block_ptr {
+            file.seek(SeekFrom::Start(offset))?;
+            let header = Header::decode_from(&mut file)?;
+
+            assert!(header.uncompressed_length as usize <= BLOCK_SIZE as usize + max_item_size);
+            assert!(header.data_length < header.uncompressed_length / 4);
+
+            offset += (Header::serialized_len() as u64) + u64::from(header.data_length);
+            block_count += 1;
+        }
+
+        assert_eq!(trailer.metadata.data_block_count as usize, block_count);
+
+        Ok(())
+    }
+
+    #[test]
+    fn segment_writer_resume_bloom() -> crate::Result<()> {
+        use crate::coding::Decode;
+
+        const ITEM_COUNT: u64 = 100;
+
+        let folder = tempfile::tempdir()?.into_path();
+
+        let create_writer = |segment_id| {
+            Writer::new(Options {
+                folder: folder.clone(),
+                data_block_size: 4_096,
+                index_block_size: 4_096,
+                target_item_count: None,
+                segment_id,
+            })
+        };
+
+        let item =
+            |i: u64| InternalValue::from_components(i.to_be_bytes(), *b"abc", 0, ValueType::Value);
+
+        let mut writer =
+            create_writer(1)?.use_bloom_policy(BloomConstructionPolicy::FpRate(0.0001));
+
+        for i in 0..(ITEM_COUNT / 2) {
+            writer.write(item(i))?;
+        }
+
+        let checkpoint = writer.bloom_checkpoint().encode_into_vec();
+        let checkpoint = BloomCheckpoint::decode_from(&mut &checkpoint[..])?;
+        assert_eq!(ITEM_COUNT as usize / 2, checkpoint.len());
+        drop(writer);
+
+        let mut writer = create_writer(2)?;
+        writer.resume_bloom(checkpoint);
+
+        for i in (ITEM_COUNT / 2)..ITEM_COUNT {
+            writer.write(item(i))?;
+        }
+
+        let trailer = writer.finish()?.expect("should exist");
+
+        let filter = crate::Segment::load_bloom(&folder.join("2"), trailer.offsets.bloom_ptr)?
+            .expect("should have filter");
+
+        // NOTE: The filter contains the keys from before the checkpoint, too
+        for i in 0..ITEM_COUNT {
+            assert!(filter.contains(&i.to_be_bytes()));
+        }
+        assert!(!filter.contains(b"asdasdasd"));
+
+        assert_eq!(
+            BloomConstructionPolicy::FpRate(0.0001)
+                .build(ITEM_COUNT as usize)
+                .len(),
+            filter.len(),
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn segment_writer_zero_bpk() -> crate::Result<()> {
         const ITEM_COUNT: u64 = 100;
@@ -420,6 +779,7 @@ mod tests {
             folder,
             data_block_size: 4_096,
             index_block_size: 4_096,
+            target_item_count: None,
             segment_id,
         })?
         .use_bloom_policy(BloomConstructionPolicy::BitsPerKey(0));
@@ -458,6 +818,7 @@ mod tests {
             folder: folder.clone(),
             data_block_size: 4_096,
             index_block_size: 4_096,
+            target_item_count: None,
             segment_id,
         })?;
 
@@ -528,6 +889,7 @@ mod tests {
             folder: folder.clone(),
             data_block_size: 4_096,
             index_block_size: 4_096,
+            target_item_count: None,
             segment_id,
         })?;
 
@@ -6,15 +6,62 @@ use crate::{
     value::{SeqNo, UserKey, UserValue},
     AbstractTree, AnyTree, KvPair,
 };
-use std::ops::RangeBounds;
+use std::{
+    collections::BTreeMap,
+    ops::RangeBounds,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+/// Keeps track of the seqnos of all open snapshots of a tree
+///
+/// Flushes and compactions consult the tracker, so they do not
+/// garbage collect versions that an open snapshot can still read.
+#[derive(Clone, Default)]
+pub struct SnapshotTracker(Arc<Mutex<BTreeMap<SeqNo, usize>>>);
+
+impl SnapshotTracker {
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<SeqNo, usize>> {
+        self.0.lock().expect("lock is poisoned")
+    }
+
+    pub fn open(&self, seqno: SeqNo) {
+        *self.lock().entry(seqno).or_default() += 1;
+    }
+
+    pub fn close(&self, seqno: SeqNo) {
+        let mut lock = self.lock();
+
+        if let Some(count) = lock.get_mut(&seqno) {
+            *count -= 1;
+
+            if *count == 0 {
+                lock.remove(&seqno);
+            }
+        }
+    }
+
+    /// Returns the seqno of the oldest open snapshot.
+    pub fn oldest(&self) -> Option<SeqNo> {
+        self.lock().keys().next().copied()
+    }
+
+    /// Returns the amount of open snapshots.
+    pub fn len(&self) -> usize {
+        self.lock().values().sum()
+    }
+}
 
 /// A snapshot captures a read-only point-in-time view of the tree at the time the snapshot was created
 ///
 /// As long as the snapshot is open, old versions of objects will not be evicted as to
 /// keep the snapshot consistent. Thus, snapshots should only be kept around for as little as possible.
 ///
+/// The snapshot is registered with the tree when it is created (or cloned), and
+/// unregistered when it is dropped. Flushes and compactions that start while the
+/// snapshot is open keep every version the snapshot can read, regardless of the
+/// seqno threshold they are given.
+///
 /// Snapshots do not persist across restarts.
-#[derive(Clone)]
 pub struct Snapshot {
     tree: AnyTree,
 
@@ -22,13 +69,34 @@ pub struct Snapshot {
     pub seqno: SeqNo,
 }
 
+impl Clone for Snapshot {
+    fn clone(&self) -> Self {
+        Self::new(self.tree.clone(), self.seqno)
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        log::trace!("Closing snapshot with seqno: {}", self.seqno);
+        Self::tracker(&self.tree).close(self.seqno);
+    }
+}
+
 impl Snapshot {
     /// Creates a snapshot
     pub(crate) fn new(tree: AnyTree, seqno: SeqNo) -> Self {
         log::trace!("Opening snapshot with seqno: {seqno}");
+        Self::tracker(&tree).open(seqno);
         Self { tree, seqno }
     }
 
+    fn tracker(tree: &AnyTree) -> &SnapshotTracker {
+        match tree {
+            AnyTree::Standard(tree) => &tree.open_snapshots,
+            AnyTree::Blob(tree) => &tree.index.open_snapshots,
+        }
+    }
+
     /// Retrieves an item from the snapshot.
     ///
     /// # Examples
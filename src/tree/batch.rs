@@ -0,0 +1,117 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::Tree;
+use crate::{value::InternalValue, AbstractTree, HashSet, SeqNo, UserKey, UserValue, ValueType};
+
+/// A set of writes that is applied to the tree at once, see [`Tree::batch`]
+///
+/// All writes of a batch share the same seqno, so they belong to the
+/// same logical point in time.
+#[must_use = "a batch does nothing unless committed"]
+pub struct Batch {
+    tree: Tree,
+    entries: Vec<(UserKey, UserValue, ValueType)>,
+}
+
+impl Batch {
+    pub(crate) fn new(tree: Tree) -> Self {
+        Self {
+            tree,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Inserts a key-value pair.
+    pub fn insert<K: Into<UserKey>, V: Into<UserValue>>(&mut self, key: K, value: V) {
+        self.entries
+            .push((key.into(), value.into(), ValueType::Value));
+    }
+
+    /// Removes an item by adding a tombstone.
+    pub fn remove<K: Into<UserKey>>(&mut self, key: K) {
+        self.entries
+            .push((key.into(), UserValue::empty(), ValueType::Tombstone));
+    }
+
+    /// Removes an item by adding a weak tombstone, see [`crate::AbstractTree::remove_weak`].
+    pub fn remove_weak<K: Into<UserKey>>(&mut self, key: K) {
+        self.entries
+            .push((key.into(), UserValue::empty(), ValueType::WeakTombstone));
+    }
+
+    /// Returns the amount of writes in the batch.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the batch contains no writes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Applies all writes to the active memtable, using the given seqno.
+    ///
+    /// If a key is written more than once, the last write wins.
+    ///
+    /// The writes are applied while holding the active memtable's write lock,
+    /// so point reads, and reads that are opened, either see none or all
+    /// of the batch. An iterator that was opened before the commit and reads
+    /// without a seqno may observe the writes as they are applied; use a
+    /// snapshot seqno for a consistent view.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err(TreeClosing)` if the tree is closing and rejects writes,
+    /// see [`crate::Config::closing_write_policy`], or `Err(DeleteInProgress)`
+    /// if `seqno` is lower than the seqno of a running [`Tree::delete_sorted`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the active memtable lock is poisoned.
+    pub fn commit(self, seqno: SeqNo) -> crate::Result<()> {
+        let Self { tree, entries } = self;
+
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        // NOTE: Writes share the same seqno, so older writes to the same key
+        // would not be shadowed, but conflict with the newer ones
+        let mut seen = HashSet::default();
+
+        let items = entries
+            .into_iter()
+            .rev()
+            .filter(|(key, _, _)| seen.insert(key.clone()))
+            .map(|(key, value, value_type)| {
+                InternalValue::from_components(key, value, seqno, value_type)
+            })
+            .collect::<Vec<_>>();
+
+        // IMPORTANT: Check the write while holding the memtable lock, so the batch
+        // cannot slip in after closing (or a sorted delete) has rotated the memtable
+        let memtable_lock = tree.lock_active_memtable();
+        tree.check_write(seqno)?;
+
+        let mut should_seal = false;
+
+        for item in items {
+            let key = item.key.user_key.clone();
+            memtable_lock.insert(item);
+            should_seal |= tree.should_seal_memtable(&memtable_lock, &key);
+        }
+
+        let full_memtable = should_seal.then(|| memtable_lock.clone());
+        drop(memtable_lock);
+
+        if let Some(memtable) = full_memtable {
+            tree.rotate_full_memtable(&memtable);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::Tree;
+use crate::{
+    file::SEGMENTS_FOLDER,
+    segment::{
+        multi_writer::MultiWriter,
+        writer::{BloomConstructionPolicy, Options},
+    },
+    AbstractTree, InternalValue, SeqNo, UserKey,
+};
+use std::{sync::atomic::Ordering, time::Instant};
+
+impl Tree {
+    /// Deletes a sorted stream of keys.
+    ///
+    /// Instead of going through the memtable, the tombstones are written
+    /// directly into new segments, which are registered in L0,
+    /// so compaction can apply them later.
+    ///
+    /// Because memtables are read before segments, all memtables (active and sealed)
+    /// are flushed first, so `seqno` needs to be higher than the seqnos of all previous writes.
+    /// While the delete is running, fallible writes with a lower seqno are rejected,
+    /// because they would end up in a memtable that shadows the tombstones.
+    /// Infallible writes cannot be rejected, so they must not use a lower seqno either.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// for x in 0..100u64 {
+    ///     tree.insert(x.to_be_bytes(), "abc", x);
+    /// }
+    ///
+    /// tree.delete_sorted((0..50u64).map(|x| x.to_be_bytes()), 100)?;
+    /// assert_eq!(50, tree.len(None, None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or [`crate::Error::UnsortedIngest`]
+    /// if the keys are not sorted in ascending order (without duplicates).
+    pub fn delete_sorted<K: Into<UserKey>>(
+        &self,
+        keys: impl Iterator<Item = K>,
+        seqno: SeqNo,
+    ) -> crate::Result<()> {
+        let start = Instant::now();
+
+        // IMPORTANT: A background flush could register a memtable after the tombstones,
+        // and other sorted deletes need to be serialized because of the delete seqno
+        let _flush_lock = self.lock_flush();
+
+        // NOTE: The delete seqno is set before rotating the memtable, which waits for
+        // running writes, so all following writes are checked against it
+        self.delete_seqno.store(seqno, Ordering::Release);
+        let result = self.write_tombstone_segments(keys, seqno);
+        self.delete_seqno.store(0, Ordering::Release);
+
+        let (count, segment_count) = result?;
+
+        log::info!(
+            "Deleted {count} items into {segment_count} segments in {:?}",
+            start.elapsed(),
+        );
+
+        Ok(())
+    }
+
+    /// Flushes all memtables, then writes the tombstones into new L0 segments.
+    ///
+    /// Returns the amount of deleted keys and written segments.
+    fn write_tombstone_segments<K: Into<UserKey>>(
+        &self,
+        keys: impl Iterator<Item = K>,
+        seqno: SeqNo,
+    ) -> crate::Result<(usize, usize)> {
+        // NOTE: Seal the active memtable, so all memtables are flushed oldest first
+        self.rotate_memtable();
+        self.flush_sealed_memtables(0)?;
+
+        let folder = self.config.path.join(SEGMENTS_FOLDER);
+        log::debug!("Writing tombstone segments to {}", folder.display());
+
+        let mut writer = MultiWriter::new(
+            self.segment_id_counter.clone(),
+            /* 64 MiB */ 64 * 1_024 * 1_024,
+            Options {
+                folder: folder.clone(),
+                data_block_size: self.config.data_block_size,
+                index_block_size: self.config.index_block_size,
+                target_item_count: None,
+
+                // NOTE: The multi writer hands out its own segment IDs
+                segment_id: 0,
+            },
+        )?
+        .use_compression(self.config.compression_for_level(0))
+        .use_checksum_type(self.config.checksum_type)
+        .use_index_start_keys(self.config.index_start_keys)
+        .use_value_alignment(self.config.value_alignment)
+        .use_prefix_extractor(self.config.bloom_prefix_extractor.clone())
+        .use_bloom_hasher(self.config.bloom_hasher.clone());
+
+        // NOTE: Tombstone segments are registered in L0
+        if self.config.use_bloom_filter(0) {
+            writer = writer.use_bloom_policy(BloomConstructionPolicy::FpRate(0.00001));
+        } else {
+            writer = writer.use_bloom_policy(BloomConstructionPolicy::BitsPerKey(0));
+        }
+
+        let mut count = 0;
+        let mut last_key: Option<UserKey> = None;
+
+        for key in keys {
+            let key = key.into();
+
+            // NOTE: Nothing has been registered yet, so the tree is left unchanged
+            if last_key.as_ref().is_some_and(|last_key| key <= *last_key) {
+                writer.discard();
+                return Err(crate::Error::UnsortedIngest);
+            }
+            last_key = Some(key.clone());
+
+            writer.write(InternalValue::new_tombstone(key, seqno))?;
+
+            count += 1;
+        }
+
+        let segments = writer
+            .finish()?
+            .iter()
+            .map(|trailer| self.consume_trailer(&folder, trailer))
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        self.register_segments(&segments)?;
+
+        Ok((count, segments.len()))
+    }
+}
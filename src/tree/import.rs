@@ -0,0 +1,179 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::Tree;
+use crate::{file::SEGMENTS_FOLDER, segment::meta::SegmentId, Segment};
+
+/// How thoroughly imported segments are checked, see [`Tree::import_segment`]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum ImportValidation {
+    /// Only checks the segment's declared key range
+    ///
+    /// The key range needs to be well-formed, and may not overlap
+    /// with other segments in the destination level (unless it is L0).
+    #[default]
+    KeyRange,
+
+    /// Additionally scans the entire segment, checking that its items are sorted
+    /// and within the declared key range
+    Full,
+}
+
+/// Scans a segment, checking its items are sorted and inside its key range.
+fn validate_items(tree: &Tree, segment: &Segment) -> crate::Result<()> {
+    let segment_id = segment.id();
+    let key_range = &segment.metadata.key_range;
+
+    let mut prev_key = None;
+    let mut item_count = 0;
+
+    for item in segment.scan(tree.config.path.join(SEGMENTS_FOLDER))? {
+        let item = item?;
+
+        if !key_range.contains_key(&item.key.user_key) {
+            log::error!(
+                "Segment {segment_id} contains key {:?} outside of its key range {key_range}",
+                item.key.user_key,
+            );
+            return Err(crate::Error::OverlapViolation(segment_id));
+        }
+
+        if prev_key.as_ref().is_some_and(|prev| *prev >= item.key) {
+            log::error!("Segment {segment_id} is not sorted at key {:?}", item.key);
+            return Err(crate::Error::OverlapViolation(segment_id));
+        }
+
+        prev_key = Some(item.key);
+        item_count += 1;
+    }
+
+    if item_count != segment.metadata.item_count {
+        log::error!(
+            "Segment {segment_id} contains {item_count} items, but declares {}",
+            segment.metadata.item_count,
+        );
+        return Err(crate::Error::OverlapViolation(segment_id));
+    }
+
+    Ok(())
+}
+
+impl Tree {
+    /// Imports a segment that was written outside of the tree into level `dest_level`.
+    ///
+    /// The segment file needs to exist in the tree's segments folder,
+    /// named after its segment ID, which should have been handed out by
+    /// [`AbstractTree::get_next_segment_id`](crate::AbstractTree::get_next_segment_id).
+    ///
+    /// Before the segment is made visible, it is validated according to `validation`,
+    /// so a buggy producer cannot silently break reads.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or [`Error::OverlapViolation`](crate::Error::OverlapViolation)
+    /// if the segment is not sorted, exceeds its key range, or overlaps
+    /// with another segment in the (disjoint) destination level.
+    ///
+    /// Will return [`Error::InvalidLevel`](crate::Error::InvalidLevel) if the destination level does not exist.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the level manifest lock is poisoned.
+    pub fn import_segment(
+        &self,
+        segment_id: SegmentId,
+        dest_level: u8,
+        validation: ImportValidation,
+    ) -> crate::Result<Segment> {
+        let segment_file_path = self
+            .config
+            .path
+            .join(SEGMENTS_FOLDER)
+            .join(segment_id.to_string());
+
+        // NOTE: Checked again when the segment is registered, but fails early
+        // before the segment is recovered and validated
+        if dest_level >= self.levels.read().expect("lock is poisoned").depth() {
+            return Err(crate::Error::InvalidLevel(dest_level));
+        }
+
+        log::debug!(
+            "Importing segment {} into L{dest_level}",
+            segment_file_path.display(),
+        );
+
+        let segment = Segment::recover(
+            &segment_file_path,
+            self.id,
+            self.config.cache.clone(),
+            self.config.descriptor_table.clone(),
+            self.config.preloads_block_index(dest_level),
+            self.config.verify_checksums,
+        )?;
+
+        if segment.id() != segment_id {
+            log::error!(
+                "Segment file {} declares a different segment ID: {}",
+                segment_file_path.display(),
+                segment.id(),
+            );
+            return Err(crate::Error::Unrecoverable);
+        }
+
+        let key_range = &segment.metadata.key_range;
+
+        if key_range.min() > key_range.max() {
+            log::error!("Segment {segment_id} has an invalid key range {key_range}");
+            return Err(crate::Error::OverlapViolation(segment_id));
+        }
+
+        if validation == ImportValidation::Full {
+            validate_items(self, &segment)?;
+        }
+
+        let mut levels = self.levels.write().expect("lock is poisoned");
+
+        // NOTE: Hidden segments are still part of their level, so they are checked as well
+        let level = levels
+            .levels
+            .get(usize::from(dest_level))
+            .ok_or(crate::Error::InvalidLevel(dest_level))?;
+
+        // NOTE: L0 may contain overlapping segments, every other level is kept disjoint
+        if dest_level > 0 {
+            if let Some(other) = level
+                .iter()
+                .find(|other| other.metadata.key_range.overlaps_with_key_range(key_range))
+            {
+                log::error!(
+                    "Segment {segment_id} ({key_range}) overlaps with segment {} ({}) in L{dest_level}",
+                    other.id(),
+                    other.metadata.key_range,
+                );
+                return Err(crate::Error::OverlapViolation(segment_id));
+            }
+        }
+
+        levels.atomic_swap(|recipe| {
+            recipe
+                .get_mut(usize::from(dest_level))
+                .expect("destination level should exist")
+                .insert(segment.clone());
+        })?;
+
+        // IMPORTANT: Register the file descriptor before releasing the lock,
+        // otherwise a read could access the segment before it has a file handle
+        self.config
+            .descriptor_table
+            .insert(&segment_file_path, segment.global_id());
+
+        drop(levels);
+
+        // NOTE: Make sure the IDs of following segments do not collide
+        self.segment_id_counter
+            .fetch_max(segment_id + 1, std::sync::atomic::Ordering::Relaxed);
+
+        Ok(segment)
+    }
+}
@@ -17,6 +17,7 @@ pub struct Ingestion<'a> {
     folder: PathBuf,
     tree: &'a Tree,
     writer: MultiWriter,
+    last_key: Option<UserKey>,
 }
 
 impl<'a> Ingestion<'a> {
@@ -37,15 +38,22 @@ impl<'a> Ingestion<'a> {
                 folder: folder.clone(),
                 data_block_size: tree.config.data_block_size,
                 index_block_size: tree.config.index_block_size,
+                target_item_count: None,
                 segment_id: 0, /* TODO: unused */
             },
         )?
-        .use_compression(tree.config.compression);
+        .use_compression(tree.config.compression_for_level(6))
+        .use_checksum_type(tree.config.checksum_type)
+        .use_index_start_keys(tree.config.index_start_keys)
+        .use_value_alignment(tree.config.value_alignment)
+        .use_prefix_extractor(tree.config.bloom_prefix_extractor.clone())
+        .use_bloom_hasher(tree.config.bloom_hasher.clone());
 
         {
             use crate::segment::writer::BloomConstructionPolicy;
 
-            if tree.config.bloom_bits_per_key >= 0 {
+            // NOTE: Ingested segments are moved down into L6, see `finish`
+            if tree.config.use_bloom_filter(6) {
                 writer = writer.use_bloom_policy(BloomConstructionPolicy::BitsPerKey(
                     tree.config.bloom_bits_per_key.unsigned_abs(),
                 ));
@@ -58,10 +66,25 @@ impl<'a> Ingestion<'a> {
             folder,
             tree,
             writer,
+            last_key: None,
         })
     }
 
+    /// Writes the next item.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err(UnsortedIngest)` if the key is not greater than the previous key.
     pub fn write(&mut self, key: UserKey, value: UserValue) -> crate::Result<()> {
+        if self
+            .last_key
+            .as_ref()
+            .is_some_and(|last_key| key <= *last_key)
+        {
+            return Err(crate::Error::UnsortedIngest);
+        }
+        self.last_key = Some(key.clone());
+
         self.writer.write(crate::InternalValue::from_components(
             key,
             value,
@@ -70,6 +93,11 @@ impl<'a> Ingestion<'a> {
         ))
     }
 
+    /// Deletes the segments written so far, after the ingestion failed.
+    pub fn discard(self) {
+        self.writer.discard();
+    }
+
     pub fn finish(self) -> crate::Result<()> {
         use crate::{
             compaction::MoveDown, segment::block_index::two_level_index::TwoLevelBlockIndex,
@@ -110,6 +138,17 @@ impl<'a> Ingestion<'a> {
                         &segment_file_path,
                         trailer.offsets.bloom_ptr,
                     )?,
+                    prefix_filter: Segment::load_bloom(
+                        &segment_file_path,
+                        trailer.offsets.pfx_ptr,
+                    )?,
+
+                    range_tombstones: Segment::load_range_tombstones(
+                        &segment_file_path,
+                        trailer.offsets.range_tombstones_ptr,
+                    )?,
+
+                    verify_checksums: self.tree.config.verify_checksums,
 
                     path: segment_file_path,
                     is_deleted: AtomicBool::default(),
@@ -3,10 +3,45 @@
 // (found in the LICENSE-* files in the repository)
 
 use crate::{
-    config::Config, file::LEVELS_MANIFEST_FILE, level_manifest::LevelManifest, memtable::Memtable,
-    segment::meta::SegmentId, stop_signal::StopSignal,
+    compaction::stats::DroppedBlobsListener, config::Config, file::LEVELS_MANIFEST_FILE,
+    hot_keys::HotKeys, level_manifest::LevelManifest, memtable::Memtable,
+    read_stats::ReadLevelStats, segment::meta::SegmentId, snapshot::SnapshotTracker,
+    stop_signal::StopSignal, Segment, SeqNo,
 };
-use std::sync::{atomic::AtomicU64, Arc, RwLock};
+use std::sync::{
+    atomic::{AtomicU64, AtomicUsize, Ordering},
+    Arc, Mutex, MutexGuard, OnceLock, RwLock,
+};
+
+/// Amount of lock stripes used by [`KeyLocks`]
+const KEY_LOCK_STRIPES: usize = 64;
+
+/// Striped locks that serialize conditional writes to the same key
+///
+/// Keys are hashed onto a fixed amount of mutexes, so different keys may
+/// share a lock; the same key always maps to the same lock.
+pub struct KeyLocks(Box<[Mutex<()>]>);
+
+impl Default for KeyLocks {
+    fn default() -> Self {
+        Self((0..KEY_LOCK_STRIPES).map(|_| Mutex::default()).collect())
+    }
+}
+
+impl KeyLocks {
+    /// Locks the stripe the key belongs to.
+    pub fn lock(&self, key: &[u8]) -> MutexGuard<'_, ()> {
+        // NOTE: Truncation is fine, we only need a stripe index
+        #[allow(clippy::cast_possible_truncation)]
+        let idx = xxhash_rust::xxh3::xxh3_64(key) as usize % self.0.len();
+
+        self.0
+            .get(idx)
+            .expect("stripe index should be in bounds")
+            .lock()
+            .expect("lock is poisoned")
+    }
+}
 
 /// Unique tree ID
 ///
@@ -22,24 +57,56 @@ pub type MemtableId = u64;
 ///
 /// Memtable IDs are monotonically increasing, so we don't really
 /// need a search tree; also there are only a handful of them at most.
+///
+/// Memtables are stored in the order they were sealed (oldest first),
+/// which is also their seqno order.
 #[derive(Default)]
-pub struct SealedMemtables(Vec<(MemtableId, Arc<Memtable>)>);
+pub struct SealedMemtables {
+    memtables: Vec<(MemtableId, Arc<Memtable>)>,
+
+    /// Segments of memtables that have been flushed, but cannot be registered
+    /// yet, because an older memtable is still being flushed
+    flushed: Vec<Segment>,
+}
 
 impl SealedMemtables {
     pub fn add(&mut self, id: MemtableId, memtable: Arc<Memtable>) {
-        self.0.push((id, memtable));
+        self.memtables.push((id, memtable));
     }
 
     pub fn remove(&mut self, id_to_remove: MemtableId) {
-        self.0.retain(|(id, _)| *id != id_to_remove);
+        self.memtables.retain(|(id, _)| *id != id_to_remove);
+        self.flushed.retain(|segment| segment.id() != id_to_remove);
     }
 
     pub fn iter(&self) -> impl DoubleEndedIterator<Item = &(MemtableId, Arc<Memtable>)> {
-        self.0.iter()
+        self.memtables.iter()
     }
 
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.memtables.len()
+    }
+
+    pub fn contains(&self, id: MemtableId) -> bool {
+        self.memtables.iter().any(|(x, _)| *x == id)
+    }
+
+    /// Stores the flushed segment of a sealed memtable until it can be registered.
+    pub fn mark_flushed(&mut self, segment: Segment) {
+        self.flushed.retain(|x| x.id() != segment.id());
+        self.flushed.push(segment);
+    }
+
+    /// Returns the segments of the oldest sealed memtables that have all been flushed,
+    /// oldest first.
+    ///
+    /// Stops at the first memtable that has not been flushed yet, so the result
+    /// never contains data that is newer than some unflushed memtable.
+    pub fn flushed_prefix(&self) -> Vec<Segment> {
+        self.memtables
+            .iter()
+            .map_while(|(id, _)| self.flushed.iter().find(|x| x.id() == *id).cloned())
+            .collect()
     }
 }
 
@@ -76,6 +143,53 @@ pub struct TreeInner {
     pub(crate) stop_signal: StopSignal,
 
     pub(crate) major_compaction_lock: RwLock<()>,
+
+    /// Amount of compactions that are currently running
+    pub(crate) active_compactions: AtomicUsize,
+
+    /// Read sampler, if hot key tracking is enabled
+    pub(crate) hot_keys: Option<HotKeys>,
+
+    /// Counts which level point reads are answered by
+    pub(crate) read_stats: ReadLevelStats,
+
+    /// Serializes compare-and-swap writes
+    pub(crate) key_locks: KeyLocks,
+
+    /// Seqnos of open snapshots, which must not be garbage collected
+    pub(crate) open_snapshots: SnapshotTracker,
+
+    /// Held while sealed memtables are flushed in the background, see [`Config::background_flush`]
+    pub(crate) flush_lock: Mutex<()>,
+
+    /// Seqno of a running sorted delete, writes below it are rejected (0 if none is running)
+    pub(crate) delete_seqno: AtomicU64,
+
+    /// Set by the blob tree, so compaction can report the blobs of range-deleted values
+    pub(crate) on_blobs_dropped: OnceLock<DroppedBlobsListener>,
+}
+
+/// Counts a running compaction until dropped
+pub struct CompactionSlot<'a>(&'a AtomicUsize);
+
+impl<'a> CompactionSlot<'a> {
+    /// Registers a new compaction, if less than `max` compactions are running.
+    pub fn acquire(active: &'a AtomicUsize, max: Option<usize>) -> Option<Self> {
+        let max = max.unwrap_or(usize::MAX);
+
+        active
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < max).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| Self(active))
+    }
+}
+
+impl Drop for CompactionSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
 }
 
 impl TreeInner {
@@ -85,16 +199,44 @@ impl TreeInner {
 
         Ok(Self {
             id: get_next_tree_id(),
+            hot_keys: config.hot_key_sample_rate.map(HotKeys::new),
             segment_id_counter: Arc::new(AtomicU64::default()),
+            active_memtable: Arc::new(RwLock::new(Arc::new(Memtable::from_config(&config)))),
+            read_stats: ReadLevelStats::new(config.level_count),
             config,
-            active_memtable: Arc::default(),
             sealed_memtables: Arc::default(),
             levels: Arc::new(RwLock::new(levels)),
             stop_signal: StopSignal::default(),
             major_compaction_lock: RwLock::default(),
+            active_compactions: AtomicUsize::default(),
+            key_locks: KeyLocks::default(),
+            open_snapshots: SnapshotTracker::default(),
+            flush_lock: Mutex::default(),
+            delete_seqno: AtomicU64::default(),
+            on_blobs_dropped: OnceLock::new(),
         })
     }
 
+    /// Returns an error if a fallible write with the given seqno is rejected.
+    ///
+    /// Writes are rejected if the tree is closing, see [`Config::closing_write_policy`],
+    /// or if the write is older than a running [`crate::Tree::delete_sorted`].
+    pub(crate) fn check_write(&self, seqno: SeqNo) -> crate::Result<()> {
+        if self.stop_signal.is_stopped()
+            && self.config.closing_write_policy == crate::ClosingWritePolicy::Reject
+        {
+            return Err(crate::Error::TreeClosing);
+        }
+
+        let delete_seqno = self.delete_seqno.load(Ordering::Acquire);
+
+        if seqno < delete_seqno {
+            return Err(crate::Error::DeleteInProgress(delete_seqno));
+        }
+
+        Ok(())
+    }
+
     pub fn get_next_segment_id(&self) -> SegmentId {
         self.segment_id_counter
             .fetch_add(1, std::sync::atomic::Ordering::Relaxed)
@@ -107,5 +249,12 @@ impl Drop for TreeInner {
 
         log::trace!("Sending stop signal to compactors");
         self.stop_signal.send();
+
+        // NOTE: The cache may be shared with other trees, so purge our blocks
+        // instead of waiting for them to be evicted
+        //
+        // File handles are closed once the segments themselves are dropped
+        log::trace!("Evicting tree {} from block cache", self.id);
+        self.config.cache.remove_tree(self.id);
     }
 }
@@ -2,42 +2,56 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
+pub mod batch;
+pub mod delete_sorted;
+pub mod import;
 pub(crate) mod ingest;
 pub mod inner;
+pub mod nth_key;
+pub mod partition;
+pub mod range_tombstones;
+pub mod warm_cache;
 
 use crate::{
+    bloom::{BloomFilter, BloomStats, CompositeHash},
     cache::Cache,
     coding::{Decode, Encode},
-    compaction::CompactionStrategy,
-    config::Config,
+    compaction::{CompactionProgress, CompactionStrategy, ProgressCallback},
+    config::{Config, SealedMemtableVisibility},
     descriptor_table::FileDescriptorTable,
-    level_manifest::LevelManifest,
+    export::ExportFormat,
+    hot_keys::HotKeys,
+    level_manifest::{snapshot::ManifestSnapshot, LevelManifest},
     manifest::Manifest,
     memtable::Memtable,
+    read_stats::{ReadLevelHistogram, ReadLevelStats},
     segment::{
         block_index::{full_index::FullBlockIndex, BlockIndexImpl},
         meta::TableType,
+        value_block::CachePolicy,
         Segment, SegmentInner,
     },
+    snapshot::SnapshotTracker,
     value::InternalValue,
     version::Version,
     AbstractTree, KvPair, SegmentId, SeqNo, Snapshot, UserKey, UserValue, ValueType,
 };
-use inner::{MemtableId, SealedMemtables, TreeId, TreeInner};
+use batch::Batch;
+use inner::{CompactionSlot, KeyLocks, MemtableId, SealedMemtables, TreeId, TreeInner};
 use std::{
     io::Cursor,
     ops::RangeBounds,
     path::Path,
     sync::atomic::AtomicBool,
-    sync::{atomic::AtomicU64, Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize},
+        Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
+    time::Duration,
 };
 
 fn ignore_tombstone_value(item: InternalValue) -> Option<InternalValue> {
-    if item.is_tombstone() {
-        None
-    } else {
-        Some(item)
-    }
+    item.into_visible(crate::time::unix_timestamp())
 }
 
 /// A log-structured merge tree (LSM-tree/LSMT)
@@ -68,18 +82,12 @@ impl AbstractTree for Tree {
 
         let start = Instant::now();
         let mut count = 0;
-        let mut last_key = None;
 
         for (key, value) in iter {
-            if let Some(last_key) = &last_key {
-                assert!(
-                    key > last_key,
-                    "next key in bulk ingest was not greater than last key",
-                );
+            if let Err(e) = writer.write(key, value) {
+                writer.discard();
+                return Err(e);
             }
-            last_key = Some(key.clone());
-
-            writer.write(key, value)?;
 
             count += 1;
         }
@@ -103,7 +111,67 @@ impl AbstractTree for Tree {
             .expect("lock is poisoned");
 
         log::info!("Starting major compaction");
-        self.inner_compact(strategy, seqno_threshold)
+        self.inner_compact(strategy, seqno_threshold, None)
+    }
+
+    fn approximate_size_in_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> crate::Result<u64> {
+        use std::ops::Bound::{self, Excluded, Included, Unbounded};
+
+        let lo: Bound<UserKey> = match range.start_bound() {
+            Included(x) => Included(x.as_ref().into()),
+            Excluded(x) => Excluded(x.as_ref().into()),
+            Unbounded => Unbounded,
+        };
+
+        let hi: Bound<UserKey> = match range.end_bound() {
+            Included(x) => Included(x.as_ref().into()),
+            Excluded(x) => Excluded(x.as_ref().into()),
+            Unbounded => Unbounded,
+        };
+
+        let bounds = (lo, hi);
+
+        let levels = self.levels.read().expect("lock is poisoned");
+
+        levels
+            .iter()
+            .map(|segment| segment.approximate_size_in_range(&bounds))
+            .sum()
+    }
+
+    fn compact_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        target_size: u64,
+        seqno_threshold: SeqNo,
+    ) -> crate::Result<()> {
+        use std::ops::Bound::{self, Excluded, Included, Unbounded};
+
+        let lo: Bound<UserKey> = match range.start_bound() {
+            Included(x) => Included(x.as_ref().into()),
+            Excluded(x) => Excluded(x.as_ref().into()),
+            Unbounded => Unbounded,
+        };
+
+        let hi: Bound<UserKey> = match range.end_bound() {
+            Included(x) => Included(x.as_ref().into()),
+            Excluded(x) => Excluded(x.as_ref().into()),
+            Unbounded => Unbounded,
+        };
+
+        let strategy = Arc::new(crate::compaction::range::Strategy::new(
+            (lo, hi),
+            target_size,
+        ));
+
+        // IMPORTANT: Write lock so we can be the only compaction going on
+        let _lock = self.lock_major_compaction();
+
+        log::info!("Starting range compaction");
+        self.inner_compact(strategy, seqno_threshold, None)
     }
 
     fn l0_run_count(&self) -> usize {
@@ -154,7 +222,20 @@ impl AbstractTree for Tree {
 
         for level in &level_manifest.levels {
             for segment in &level.segments {
-                sum += segment.verify()?;
+                sum += segment.count_corrupted_blocks()?;
+
+                if let Some(stats) = segment.bloom_stats() {
+                    log::debug!("Bloom filter of segment {}: {stats:?}", segment.id());
+
+                    if stats.is_degraded() {
+                        log::warn!(
+                            "Bloom filter of segment {} is over-full ({:.0}% of bits set, estimated FP rate {})",
+                            segment.id(),
+                            stats.fill_ratio() * 100.0,
+                            stats.estimated_fp_rate,
+                        );
+                    }
+                }
             }
         }
 
@@ -200,13 +281,20 @@ impl AbstractTree for Tree {
             folder,
             data_block_size: self.config.data_block_size,
             index_block_size: self.config.index_block_size,
+            target_item_count: None,
         })?
-        .use_compression(self.config.compression);
+        .use_compression(self.config.compression_for_level(0))
+        .use_checksum_type(self.config.checksum_type)
+        .use_index_start_keys(self.config.index_start_keys)
+        .use_value_alignment(self.config.value_alignment)
+        .use_prefix_extractor(self.config.bloom_prefix_extractor.clone())
+        .use_bloom_hasher(self.config.bloom_hasher.clone());
 
         {
             use crate::segment::writer::BloomConstructionPolicy;
 
-            if self.config.bloom_bits_per_key >= 0 {
+            // NOTE: Memtables are always flushed into L0
+            if self.config.use_bloom_filter(0) {
                 segment_writer =
                     segment_writer.use_bloom_policy(BloomConstructionPolicy::FpRate(0.00001));
             } else {
@@ -216,7 +304,8 @@ impl AbstractTree for Tree {
         }
 
         let iter = memtable.iter().map(Ok);
-        let compaction_filter = CompactionStream::new(iter, seqno_threshold);
+        let compaction_filter = CompactionStream::new(iter, seqno_threshold)
+            .with_snapshot_watermark(self.open_snapshots.oldest());
 
         for item in compaction_filter {
             segment_writer.write(item?)?;
@@ -240,8 +329,36 @@ impl AbstractTree for Tree {
         let mut sealed_memtables = self.sealed_memtables.write().expect("lock is poisoned");
         log::trace!("register: Acquired sealed memtables write lock");
 
+        // IMPORTANT: Sealed memtables need to be persisted in seqno order,
+        // otherwise a crash could leave a newer memtable on disk, without an older one
+        //
+        // So flushed memtables are held back until all older memtables have been flushed
+        let mut segments_to_register = vec![];
+
+        for segment in segments {
+            if sealed_memtables.contains(segment.id()) {
+                sealed_memtables.mark_flushed(segment.clone());
+            } else {
+                segments_to_register.push(segment.clone());
+            }
+        }
+
+        let flushed_memtables = sealed_memtables.flushed_prefix();
+
+        if flushed_memtables.len() < segments.len() - segments_to_register.len() {
+            log::debug!("register: Holding back segments until older memtables are flushed");
+        }
+
+        if flushed_memtables.is_empty() && segments_to_register.is_empty() {
+            return Ok(());
+        }
+
         original_levels.atomic_swap(|recipe| {
-            for segment in segments.iter().cloned() {
+            for segment in flushed_memtables
+                .iter()
+                .chain(&segments_to_register)
+                .cloned()
+            {
                 recipe
                     .first_mut()
                     .expect("first level should exist")
@@ -251,7 +368,7 @@ impl AbstractTree for Tree {
 
         // eprintln!("{original_levels}");
 
-        for segment in segments {
+        for segment in &flushed_memtables {
             log::trace!("releasing sealed memtable {}", segment.id());
             sealed_memtables.remove(segment.id());
         }
@@ -264,7 +381,8 @@ impl AbstractTree for Tree {
     }
 
     fn clear_active_memtable(&self) {
-        *self.active_memtable.write().expect("lock is poisoned") = Arc::new(Memtable::default());
+        *self.active_memtable.write().expect("lock is poisoned") =
+            Arc::new(Memtable::from_config(&self.config));
     }
 
     fn set_active_memtable(&self, memtable: Memtable) {
@@ -291,7 +409,7 @@ impl AbstractTree for Tree {
             .read()
             .expect("lock is poisoned");
 
-        self.inner_compact(strategy, seqno_threshold)
+        self.inner_compact(strategy, seqno_threshold, None)
     }
 
     fn get_next_segment_id(&self) -> SegmentId {
@@ -317,25 +435,31 @@ impl AbstractTree for Tree {
     }
 
     fn rotate_memtable(&self) -> Option<(MemtableId, Arc<Memtable>)> {
-        log::trace!("rotate: acquiring active memtable write lock");
-        let mut active_memtable = self.lock_active_memtable();
+        self.rotate_memtable_if(|memtable| !memtable.is_empty())
+    }
 
-        log::trace!("rotate: acquiring sealed memtables write lock");
-        let mut sealed_memtables = self.lock_sealed_memtables();
+    fn close(&self) -> crate::Result<()> {
+        log::debug!("Closing tree {}", self.id);
 
-        if active_memtable.is_empty() {
-            return None;
-        }
+        // NOTE: Interrupts compactions and rejects following fallible writes,
+        // unless the closing write policy buffers them
+        self.stop_signal.send();
 
-        let yanked_memtable = std::mem::take(&mut *active_memtable);
-        let yanked_memtable = yanked_memtable;
+        // IMPORTANT: Wait for running compactions to wind down
+        let _lock = self.lock_major_compaction();
 
-        let tmp_memtable_id = self.get_next_segment_id();
-        sealed_memtables.add(tmp_memtable_id, yanked_memtable.clone());
+        // IMPORTANT: Wait for a running background flush
+        let _flush_lock = self.lock_flush();
 
-        log::trace!("rotate: added memtable id={tmp_memtable_id} to sealed memtables");
+        // NOTE: Seal the active memtable, so all memtables are flushed oldest first
+        self.rotate_memtable();
+        self.flush_sealed_memtables(0)?;
 
-        Some((tmp_memtable_id, yanked_memtable))
+        Ok(())
+    }
+
+    fn is_closing(&self) -> bool {
+        self.stop_signal.is_stopped()
     }
 
     fn segment_count(&self) -> usize {
@@ -351,6 +475,16 @@ impl AbstractTree for Tree {
             .map(|x| x.len())
     }
 
+    fn level_segment_counts(&self) -> Vec<usize> {
+        self.levels
+            .read()
+            .expect("lock is poisoned")
+            .levels
+            .iter()
+            .map(|x| x.len())
+            .collect()
+    }
+
     #[allow(clippy::significant_drop_tightening)]
     fn approximate_len(&self) -> usize {
         // NOTE: Mind lock order L -> M -> S
@@ -358,18 +492,25 @@ impl AbstractTree for Tree {
         let memtable = self.active_memtable.read().expect("lock is poisoned");
         let sealed = self.sealed_memtables.read().expect("lock is poisoned");
 
-        let segments_item_count = levels.iter().map(|x| x.metadata.item_count).sum::<u64>();
+        let segments_item_count = levels
+            .iter()
+            .map(|x| x.metadata.item_count)
+            .fold(0, u64::saturating_add);
         let memtable_count = memtable.len() as u64;
         let sealed_count = sealed.iter().map(|(_, mt)| mt.len()).sum::<usize>() as u64;
 
-        (memtable_count + sealed_count + segments_item_count)
+        (memtable_count + sealed_count)
+            .saturating_add(segments_item_count)
             .try_into()
             .expect("should not be too large")
     }
 
     fn disk_space(&self) -> u64 {
         let levels = self.levels.read().expect("lock is poisoned");
-        levels.iter().map(|x| x.metadata.file_size).sum()
+        levels
+            .iter()
+            .map(|x| x.metadata.file_size)
+            .fold(0, u64::saturating_add)
     }
 
     fn get_highest_memtable_seqno(&self) -> Option<SeqNo> {
@@ -410,9 +551,10 @@ impl AbstractTree for Tree {
         key: K,
         seqno: Option<SeqNo>,
     ) -> crate::Result<Option<UserValue>> {
-        Ok(self
-            .get_internal_entry(key.as_ref(), seqno)?
-            .map(|x| x.value))
+        let key = key.as_ref();
+        self.record_read(key);
+
+        Ok(self.get_internal_entry(key, seqno)?.map(|x| x.value))
     }
 
     fn range<K: AsRef<[u8]>, R: RangeBounds<K>>(
@@ -421,6 +563,7 @@ impl AbstractTree for Tree {
         seqno: Option<SeqNo>,
         index: Option<Arc<Memtable>>,
     ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static> {
+        self.record_range_read(&range);
         Box::new(self.create_range(&range, seqno, index))
     }
 
@@ -430,6 +573,7 @@ impl AbstractTree for Tree {
         seqno: Option<SeqNo>,
         index: Option<Arc<Memtable>>,
     ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static> {
+        self.record_read(prefix.as_ref());
         Box::new(self.create_prefix(prefix, seqno, index))
     }
 
@@ -448,6 +592,58 @@ impl AbstractTree for Tree {
         self.append_entry(value)
     }
 
+    fn drop_range<K: Into<UserKey>>(&self, start: K, end: K, seqno: SeqNo) -> (u32, u32) {
+        let (start, end) = (start.into(), end.into());
+
+        // NOTE: The range tombstone is keyed by its start key, so an empty
+        // (or inverted) range would still shadow the start key
+        if start >= end {
+            return (0, self.active_memtable_size());
+        }
+
+        let value = InternalValue::new_range_tombstone(start, end, seqno);
+        self.append_entry(value)
+    }
+
+    fn delete_prefix<K: AsRef<[u8]>>(&self, prefix: K, seqno: SeqNo) -> crate::Result<()> {
+        use crate::range::prefix_to_range;
+        use std::ops::Bound::{Excluded, Included};
+
+        let (start, end) = prefix_to_range(prefix.as_ref());
+
+        // NOTE: Keys cannot be empty, so the empty prefix starts at the lowest possible key
+        let start = match start {
+            Included(start) => start,
+            _ => UserKey::from([0]),
+        };
+
+        // NOTE: An empty end key makes the range tombstone unbounded
+        let end = match end {
+            Excluded(end) => end,
+            _ => UserKey::empty(),
+        };
+
+        let value = InternalValue::new_range_tombstone(start, end, seqno);
+        self.try_append_entry(value)?;
+
+        Ok(())
+    }
+
+    fn try_insert<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        value: V,
+        seqno: SeqNo,
+    ) -> crate::Result<(u32, u32)> {
+        let value = InternalValue::from_components(key, value, seqno, ValueType::Value);
+        self.try_append_entry(value)
+    }
+
+    fn try_remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> crate::Result<(u32, u32)> {
+        let value = InternalValue::new_tombstone(key, seqno);
+        self.try_append_entry(value)
+    }
+
     fn remove_weak<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> (u32, u32) {
         let value = InternalValue::new_weak_tombstone(key, seqno);
         self.append_entry(value)
@@ -490,30 +686,74 @@ impl Tree {
         self.active_memtable.read().expect("lock is poisoned")
     }
 
+    /// Waits for running compactions, and blocks new ones until the guard is dropped.
+    pub(crate) fn lock_major_compaction(&self) -> RwLockWriteGuard<'_, ()> {
+        self.major_compaction_lock
+            .write()
+            .expect("lock is poisoned")
+    }
+
+    /// Waits for a running background flush, and blocks new ones until the guard is dropped.
+    pub(crate) fn lock_flush(&self) -> MutexGuard<'_, ()> {
+        self.flush_lock.lock().expect("lock is poisoned")
+    }
+
     pub(crate) fn consume_writer(
         &self,
         segment_id: SegmentId,
         mut writer: crate::segment::writer::Writer,
     ) -> crate::Result<Option<Segment>> {
         let segment_folder = writer.opts.folder.clone();
-        let segment_file_path = segment_folder.join(segment_id.to_string());
 
         let Some(trailer) = writer.finish()? else {
             return Ok(None);
         };
 
+        debug_assert_eq!(segment_id, trailer.metadata.id);
+
         log::debug!("Finalized segment write at {segment_folder:?}");
 
-        let block_index =
-            FullBlockIndex::from_file(&segment_file_path, &trailer.metadata, &trailer.offsets)?;
-        let block_index = Arc::new(BlockIndexImpl::Full(block_index));
+        let created_segment = self.consume_trailer(&segment_folder, &trailer)?;
+
+        log::debug!("Flushed segment to {segment_folder:?}");
+
+        Ok(Some(created_segment))
+    }
+
+    /// Loads a segment that has just been written into the given folder, to be registered in L0.
+    fn consume_trailer(
+        &self,
+        segment_folder: &Path,
+        trailer: &crate::segment::trailer::SegmentFileTrailer,
+    ) -> crate::Result<Segment> {
+        use crate::segment::block_index::two_level_index::TwoLevelBlockIndex;
+
+        let segment_id = trailer.metadata.id;
+        let segment_file_path = segment_folder.join(segment_id.to_string());
+
+        let block_index = if self.config.preloads_block_index(0) {
+            let block_index =
+                FullBlockIndex::from_file(&segment_file_path, &trailer.metadata, &trailer.offsets)?;
+            BlockIndexImpl::Full(block_index)
+        } else {
+            let block_index = TwoLevelBlockIndex::from_file(
+                &segment_file_path,
+                &trailer.metadata,
+                trailer.offsets.tli_ptr,
+                (self.id, segment_id).into(),
+                self.config.descriptor_table.clone(),
+                self.config.cache.clone(),
+            )?;
+            BlockIndexImpl::TwoLevel(block_index)
+        };
+        let block_index = Arc::new(block_index);
 
         let created_segment: Segment = SegmentInner {
             path: segment_file_path.to_path_buf(),
 
             tree_id: self.id,
 
-            metadata: trailer.metadata,
+            metadata: trailer.metadata.clone(),
             offsets: trailer.offsets,
 
             descriptor_table: self.config.descriptor_table.clone(),
@@ -521,6 +761,14 @@ impl Tree {
             cache: self.config.cache.clone(),
 
             bloom_filter: Segment::load_bloom(&segment_file_path, trailer.offsets.bloom_ptr)?,
+            prefix_filter: Segment::load_bloom(&segment_file_path, trailer.offsets.pfx_ptr)?,
+
+            range_tombstones: Segment::load_range_tombstones(
+                &segment_file_path,
+                trailer.offsets.range_tombstones_ptr,
+            )?,
+
+            verify_checksums: self.config.verify_checksums,
 
             is_deleted: AtomicBool::default(),
         }
@@ -530,9 +778,7 @@ impl Tree {
             .descriptor_table
             .insert(segment_file_path, created_segment.global_id());
 
-        log::debug!("Flushed segment to {segment_folder:?}");
-
-        Ok(Some(created_segment))
+        Ok(created_segment)
     }
 
     /// Synchronously flushes the active memtable to a disk segment.
@@ -562,90 +808,1036 @@ impl Tree {
         Ok(Some(segment))
     }
 
-    /// Returns `true` if there are some segments that are being compacted.
-    #[doc(hidden)]
-    #[must_use]
-    pub fn is_compacting(&self) -> bool {
-        let levels = self.levels.read().expect("lock is poisoned");
-        levels.is_compacting()
-    }
-
-    /// Write-locks the sealed memtables for exclusive access
-    fn lock_sealed_memtables(&self) -> RwLockWriteGuard<'_, SealedMemtables> {
-        self.sealed_memtables.write().expect("lock is poisoned")
-    }
+    /// Flushes all sealed memtables (oldest first) to segments, and returns the new segments.
+    ///
+    /// Memtables are sealed when they reach [`Config::max_memtable_size`]
+    /// or [`Config::max_memtable_entries`], so this should be called whenever
+    /// [`AbstractTree::sealed_memtable_count`] is not zero.
+    ///
+    /// Should not be called concurrently with itself.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sealed memtables lock is poisoned.
+    pub fn flush_sealed_memtables(&self, seqno_threshold: SeqNo) -> crate::Result<Vec<Segment>> {
+        let sealed = self
+            .sealed_memtables
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
 
-    // TODO: maybe not needed anyway
-    /// Used for [`BlobTree`] lookup
-    pub(crate) fn get_internal_entry_with_memtable(
-        &self,
-        memtable_lock: &Memtable,
-        key: &[u8],
-        seqno: Option<SeqNo>,
-    ) -> crate::Result<Option<InternalValue>> {
-        if let Some(entry) = memtable_lock.get(key, seqno) {
-            return Ok(ignore_tombstone_value(entry));
-        };
+        let mut segments = Vec::with_capacity(sealed.len());
 
-        // Now look in sealed memtables
-        if let Some(entry) = self.get_internal_entry_from_sealed_memtables(key, seqno) {
-            return Ok(ignore_tombstone_value(entry));
+        for (segment_id, memtable) in sealed {
+            if let Some(segment) = self.flush_memtable(segment_id, &memtable, seqno_threshold)? {
+                self.register_segments(std::slice::from_ref(&segment))?;
+                segments.push(segment);
+            }
         }
 
-        self.get_internal_entry_from_segments(key, seqno)
+        Ok(segments)
     }
 
-    fn get_internal_entry_from_sealed_memtables(
+    /// Flushes the active memtable, and returns the seqno up to which the tree is durable.
+    ///
+    /// This is the recovery point when using the tree as the store behind
+    /// an external write-ahead or replication log: every write with a seqno
+    /// lower than or equal to the returned seqno is fsynced to disk and survives a crash,
+    /// so the external log can be truncated up to (and including) it.
+    ///
+    /// The guarantee only applies to writes that have finished before the call,
+    /// and assumes seqnos are handed out in increasing order, so a write with a
+    /// lower seqno is never applied after a write with a higher seqno.
+    ///
+    /// If older sealed memtables are still flushed by other threads, the flushed
+    /// memtable is held back until they are done, so the returned seqno
+    /// may not cover the writes of the active memtable.
+    ///
+    /// Returns `None` if no data has been persisted yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// assert_eq!(None, tree.flush_and_checkpoint(0)?);
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("b", "abc", 1);
+    /// assert_eq!(Some(1), tree.flush_and_checkpoint(0)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn flush_and_checkpoint(&self, seqno_threshold: SeqNo) -> crate::Result<Option<SeqNo>> {
+        self.flush_active_memtable(seqno_threshold)?;
+        Ok(self.get_highest_persisted_seqno())
+    }
+
+    /// Retrieves an item from the tree, using the given cache policy for any blocks read from disk.
+    ///
+    /// [`AbstractTree::get`] uses [`CachePolicy::Write`], so blocks are inserted into the cache.
+    /// With [`CachePolicy::Read`], cached blocks are still used, but blocks loaded
+    /// from disk are not inserted. This is useful for one-off lookups (e.g. during a scan
+    /// or a consistency check) that should not evict the hot working set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, CachePolicy, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let item = tree.get_with_cache_policy("a", None, CachePolicy::Read)?;
+    /// assert_eq!(Some("abc".as_bytes().into()), item);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn get_with_cache_policy<K: AsRef<[u8]>>(
         &self,
-        key: &[u8],
+        key: K,
         seqno: Option<SeqNo>,
-    ) -> Option<InternalValue> {
-        let memtable_lock = self.sealed_memtables.read().expect("lock is poisoned");
-
-        for (_, memtable) in memtable_lock.iter().rev() {
-            if let Some(entry) = memtable.get(key, seqno) {
-                return Some(entry);
-            }
-        }
+        cache_policy: CachePolicy,
+    ) -> crate::Result<Option<UserValue>> {
+        let key = key.as_ref();
+        self.record_read(key);
 
-        None
+        Ok(self
+            .get_internal_entry_with_cache_policy(key, seqno, cache_policy)?
+            .map(|x| x.value))
     }
 
-    fn get_internal_entry_from_segments(
+    /// Returns the seqno and type of the newest version of a key.
+    ///
+    /// Unlike [`AbstractTree::get`], tombstones are returned as well, so a deleted key
+    /// still reports the seqno it was deleted at (also if it was deleted by [`AbstractTree::drop_range`]).
+    /// Expired values are reported as tombstones. This is useful for optimistic
+    /// concurrency control: if the seqno has changed since the key was read,
+    /// another write to the key has happened in the meantime.
+    ///
+    /// The lookup stops at the newest version and skips segments using their Bloom filters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree, ValueType};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// assert_eq!(None, tree.latest_seqno("a")?);
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    /// assert_eq!(Some((0, ValueType::Value)), tree.latest_seqno("a")?);
+    ///
+    /// tree.remove("a", 1);
+    /// assert_eq!(Some((1, ValueType::Tombstone)), tree.latest_seqno("a")?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn latest_seqno<K: AsRef<[u8]>>(
         &self,
-        key: &[u8],
-        seqno: Option<SeqNo>,
-    ) -> crate::Result<Option<InternalValue>> {
-        // NOTE: Create key hash for hash sharing
-        // https://fjall-rs.github.io/post/bloom-filter-hash-sharing/
-        let key_hash = crate::bloom::BloomFilter::get_hash(key);
+        key: K,
+    ) -> crate::Result<Option<(SeqNo, ValueType)>> {
+        let key = key.as_ref();
 
-        let level_manifest = self.levels.read().expect("lock is poisoned");
+        // NOTE: Expired values are reported as tombstones, and live ones as values
+        let now = crate::time::unix_timestamp();
+        let version = |entry: InternalValue| {
+            let entry = entry.expire(now);
 
-        for level in &level_manifest.levels {
-            // NOTE: Based on benchmarking, binary search is only worth it with ~4 segments
-            if level.len() >= 4 {
-                if let Some(level) = level.as_disjoint() {
-                    if let Some(segment) = level.get_segment_containing_key(key) {
-                        if let Some(item) = segment.get(key, seqno, key_hash)? {
-                            return Ok(ignore_tombstone_value(item));
-                        }
-                    }
+            let value_type = match entry.key.value_type {
+                ValueType::ExpiringValue => ValueType::Value,
+                ValueType::RangeTombstone => ValueType::Tombstone,
+                value_type => value_type,
+            };
 
-                    // NOTE: Go to next level
-                    continue;
-                }
-            }
+            (entry.key.seqno, value_type)
+        };
 
-            // NOTE: Fallback to linear search
-            for segment in &level.segments {
-                if !segment.is_key_in_key_range(key) {
-                    continue;
-                }
+        let memtable_lock = self.read_lock_active_memtable();
 
-                if let Some(item) = segment.get(key, seqno, key_hash)? {
-                    return Ok(ignore_tombstone_value(item));
-                }
+        let entry = match memtable_lock
+            .get(key, None)
+            .or_else(|| self.get_internal_entry_from_sealed_memtables(key, None))
+        {
+            Some(entry) => Some(entry),
+            None => self
+                .get_internal_entry_from_segments(key, None, CachePolicy::Write)?
+                .map(|(_, entry)| entry),
+        };
+
+        let Some(entry) = entry else {
+            return Ok(None);
+        };
+
+        // NOTE: A key that is deleted by a range tombstone reports the deletion
+        if let Some(seqno) = self.get_covering_range_tombstone(&memtable_lock, &entry, None) {
+            return Ok(Some((seqno, ValueType::Tombstone)));
+        }
+
+        Ok(Some(version(entry)))
+    }
+
+    /// Returns the amount of open snapshots, see [`Snapshot`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// let snapshot = tree.snapshot(0);
+    /// assert_eq!(1, tree.open_snapshot_count());
+    ///
+    /// drop(snapshot);
+    /// assert_eq!(0, tree.open_snapshot_count());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn open_snapshot_count(&self) -> usize {
+        self.open_snapshots.len()
+    }
+
+    /// Returns the bloom filter statistics of every segment, in level order.
+    ///
+    /// Segments without a bloom filter are skipped. Use [`BloomStats::is_degraded`]
+    /// to find filters that are over-full, e.g. because they were sized wrongly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let stats = tree.bloom_stats();
+    /// assert_eq!(1, stats.len());
+    /// assert_eq!(1, stats[0].1.key_count);
+    /// assert!(!stats[0].1.is_degraded());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the level manifest lock is poisoned.
+    #[must_use]
+    pub fn bloom_stats(&self) -> Vec<(SegmentId, BloomStats)> {
+        self.levels
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .filter_map(|segment| Some((segment.id(), segment.bloom_stats()?)))
+            .collect()
+    }
+
+    /// Inserts a key-value pair that expires after the given time-to-live.
+    ///
+    /// Once expired, the value is invisible to reads, as if it had been deleted;
+    /// it also keeps shadowing older versions of the key. Compactions turn
+    /// expired values into tombstones, which are dropped in the last level, as usual.
+    ///
+    /// Expiry is based on the wall clock.
+    ///
+    /// Returns the added item's size and new size of the memtable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    /// use std::time::Duration;
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert_with_ttl("a", "abc", 0, Duration::from_secs(60));
+    /// assert_eq!(Some("abc".as_bytes().into()), tree.get("a", None)?);
+    ///
+    /// tree.insert_with_ttl("b", "abc", 1, Duration::ZERO);
+    /// assert!(!tree.contains_key("b", None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    pub fn insert_with_ttl<K: Into<UserKey>, V: AsRef<[u8]>>(
+        &self,
+        key: K,
+        value: V,
+        seqno: SeqNo,
+        ttl: Duration,
+    ) -> (u32, u32) {
+        let expires_at = crate::time::unix_timestamp() + ttl;
+        let value = InternalValue::new_expiring(key, value, seqno, expires_at);
+        self.append_entry(value)
+    }
+
+    /// Starts a batch of writes that are committed atomically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// let mut batch = tree.batch();
+    /// batch.insert("a", "abc");
+    /// batch.insert("b", "def");
+    /// batch.remove("c");
+    /// batch.commit(0)?;
+    ///
+    /// assert_eq!(2, tree.len(None, None)?);
+    /// assert_eq!(Some((0, lsm_tree::ValueType::Value)), tree.latest_seqno("b")?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    pub fn batch(&self) -> Batch {
+        Batch::new(self.clone())
+    }
+
+    /// Writes a value, but only if the newest version of the key has the expected seqno.
+    ///
+    /// `expected_seqno` is compared against the seqno of the key's current value;
+    /// `None` means the key must not exist. A deleted key does not exist, so its
+    /// tombstone will never match `Some(_)`.
+    ///
+    /// Returns `true` if the value was written.
+    ///
+    /// # Locking
+    ///
+    /// The check and the write happen while holding a lock stripe for the key,
+    /// so concurrent `compare_and_swap` calls on the same key are serialized.
+    /// Stripes are shared among keys by hash, so unrelated keys may contend.
+    ///
+    /// Plain writes ([`AbstractTree::insert`], [`AbstractTree::remove`], ...) do not
+    /// take the lock: if those are used on the same key concurrently, the swap
+    /// is no longer atomic with respect to them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// assert!(tree.compare_and_swap("a", None, "abc", 0)?);
+    /// assert!(!tree.compare_and_swap("a", None, "def", 1)?);
+    ///
+    /// assert!(tree.compare_and_swap("a", Some(0), "def", 1)?);
+    /// assert_eq!(Some("def".as_bytes().into()), tree.get("a", None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or [`crate::Error::TreeClosing`]
+    /// if the tree is closing.
+    pub fn compare_and_swap<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        expected_seqno: Option<SeqNo>,
+        value: V,
+        seqno: SeqNo,
+    ) -> crate::Result<bool> {
+        let key = key.into();
+
+        let _guard = self.key_locks.lock(&key);
+
+        let current_seqno = self
+            .latest_seqno(&key)?
+            .filter(|(_, value_type)| *value_type == ValueType::Value)
+            .map(|(seqno, _)| seqno);
+
+        if current_seqno != expected_seqno {
+            return Ok(false);
+        }
+
+        self.try_insert(key, value, seqno)?;
+
+        Ok(true)
+    }
+
+    /// Returns an iterator over every stored item version, in merge order,
+    /// tagged with the memtable or segment it was read from.
+    ///
+    /// This is meant for repair and inspection tools: all versions, tombstones
+    /// and shadowed items are returned as-is, without any MVCC collapsing.
+    /// Use the regular iterators for reading data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{debug::Source, AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "old", 0);
+    /// tree.flush_active_memtable(0)?;
+    /// tree.insert("a", "new", 1);
+    ///
+    /// let items = tree.debug_merge_iter().collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(2, items.len());
+    /// assert_eq!(Source::ActiveMemtable, items[0].0);
+    /// assert!(matches!(items[1].0, Source::Segment { level: 0, .. }));
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if a lock is poisoned.
+    #[must_use]
+    pub fn debug_merge_iter(&self) -> crate::debug::DebugMergeIter {
+        use crate::debug::DebugMergeIter;
+
+        // NOTE: Mind lock order L -> M -> S
+        let level_manifest = self.levels.read().expect("lock is poisoned");
+        let active = self.read_lock_active_memtable();
+        let sealed = self.sealed_memtables.read().expect("lock is poisoned");
+
+        let segments = level_manifest
+            .levels
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, level)| {
+                // NOTE: Level count is u8
+                #[allow(clippy::cast_possible_truncation)]
+                let idx = idx as u8;
+
+                level
+                    .segments
+                    .iter()
+                    .map(move |segment| (idx, segment.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        let iter = DebugMergeIter::new(active.clone(), sealed.iter().cloned().collect(), &segments);
+
+        drop(sealed);
+        drop(active);
+        drop(level_manifest);
+
+        iter
+    }
+
+    /// Returns an iterator over the items of a single level, or `None` if the level does not exist.
+    ///
+    /// Only the newest version of every key in the level is returned, including tombstones,
+    /// regardless of what is stored in the memtables or other levels.
+    /// Combined with [`Tree::debug_merge_iter`], this helps
+    /// finding out which level shadows a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "old", 0);
+    /// tree.flush_active_memtable(0)?;
+    /// tree.major_compact(u64::MAX, 0)?;
+    ///
+    /// tree.insert("a", "new", 1);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let items = tree.iter_level(6).expect("level should exist").collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(1, items.len());
+    /// assert_eq!(&*items[0].value, b"old");
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the level manifest lock is poisoned.
+    #[must_use]
+    pub fn iter_level(
+        &self,
+        level: u8,
+    ) -> Option<Box<dyn DoubleEndedIterator<Item = crate::Result<InternalValue>> + 'static>> {
+        use crate::{
+            merge::{BoxedIterator, Merger},
+            mvcc_stream::MvccStream,
+        };
+
+        let level_manifest = self.levels.read().expect("lock is poisoned");
+
+        // NOTE: Inspecting a level should not evict the working set
+        //
+        // Segment readers pin their segment, so they do not need to be referenced
+        let iters = level_manifest
+            .levels
+            .get(usize::from(level))?
+            .segments
+            .iter()
+            .map(|segment| -> BoxedIterator<'static> {
+                Box::new(segment.iter().cache_policy(CachePolicy::Read))
+            })
+            .collect::<Vec<_>>();
+
+        drop(level_manifest);
+
+        Some(Box::new(MvccStream::new(Merger::new(iters))))
+    }
+
+    /// Returns `(level, target_bytes, actual_bytes)` for every level starting from L1,
+    /// as seen by the given leveled compaction strategy.
+    ///
+    /// Levels whose actual size exceeds their target will be compacted.
+    /// L0 is compacted based on its segment count, so it is not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{compaction::Leveled, Config};
+    ///
+    /// let tree = Config::new(folder).level_ratio(8.0).open()?;
+    /// let strategy = Leveled::default();
+    ///
+    /// let targets = tree.level_size_targets(&strategy);
+    /// assert_eq!(6, targets.len());
+    /// assert_eq!(targets[0].1 * 8, targets[1].1);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the level manifest lock is poisoned.
+    #[must_use]
+    pub fn level_size_targets(&self, strategy: &crate::compaction::Leveled) -> Vec<(u8, u64, u64)> {
+        let levels = self.levels.read().expect("lock is poisoned");
+        strategy.level_size_targets(&levels, &self.config)
+    }
+
+    /// Estimates how many bytes the given leveled compaction strategy still needs
+    /// to compact to bring every level under its size target.
+    ///
+    /// A high and growing debt means compaction is not keeping up with writes.
+    /// See [`Tree::level_size_targets`] for the size targets that are used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the level manifest lock is poisoned.
+    #[must_use]
+    pub fn estimate_compaction_debt(&self, strategy: &crate::compaction::Leveled) -> u64 {
+        let levels = self.levels.read().expect("lock is poisoned");
+        strategy.estimate_compaction_debt(&levels, &self.config)
+    }
+
+    /// Returns how the memory budget is currently split up between the tree's components.
+    ///
+    /// Returns `None` if no memory budget is configured, see [`Config::memory_budget`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).memory_budget(64_000_000).open()?;
+    /// let budget = tree.effective_config().unwrap();
+    /// assert_eq!(48_000_000, budget.block_cache);
+    ///
+    /// if u64::from(tree.active_memtable_size()) >= budget.write_buffer {
+    ///     tree.flush_active_memtable(0)?;
+    /// }
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn effective_config(&self) -> Option<crate::MemoryBudget> {
+        use crate::MemoryBudget;
+
+        let total = self.config.memory_budget?;
+
+        Some(MemoryBudget::new(
+            total,
+            self.config.cache.capacity(),
+            self.bloom_filter_size() as u64,
+        ))
+    }
+
+    /// Computes a digest over all live items in the given range, as seen by `seqno`.
+    ///
+    /// Two replicas that hold the same items (including their sequence numbers)
+    /// compute the same digest, so divergence can be detected without
+    /// transferring the items themselves. The range is streamed, so memory
+    /// usage is constant.
+    ///
+    /// The digest is the 64-bit XXH3 hash (seed 0) of the concatenation
+    /// of every item, in ascending key order, encoded as:
+    ///
+    /// - key length (u32, big endian)
+    /// - key
+    /// - value length (u32, big endian)
+    /// - value
+    /// - sequence number (u64, big endian)
+    ///
+    /// Deleted keys are skipped, so an empty range hashes the empty input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// let digest = tree.range_digest::<&str, _>(.., None)?;
+    ///
+    /// tree.insert("b", "abc", 1);
+    /// assert_ne!(digest, tree.range_digest::<&str, _>(.., None)?);
+    /// assert_eq!(digest, tree.range_digest("a"..="a", None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn range_digest<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: Option<SeqNo>,
+    ) -> crate::Result<u64> {
+        let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+
+        for item in self.create_internal_range(&range, seqno, None) {
+            let item = item?;
+
+            // NOTE: Keys are limited to 65536 bytes, and values to 2^32 bytes
+            #[allow(clippy::cast_possible_truncation)]
+            let key_len = item.key.user_key.len() as u32;
+
+            #[allow(clippy::cast_possible_truncation)]
+            let value_len = item.value.len() as u32;
+
+            hasher.update(&key_len.to_be_bytes());
+            hasher.update(&item.key.user_key);
+            hasher.update(&value_len.to_be_bytes());
+            hasher.update(&item.value);
+            hasher.update(&item.key.seqno.to_be_bytes());
+        }
+
+        Ok(hasher.digest())
+    }
+
+    /// Runs a compaction like [`AbstractTree::compact`], reporting its progress.
+    ///
+    /// While segments are merged, `progress` is called every 100,000 input items,
+    /// and once more when all items have been merged, with cumulative counters.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the compaction lock is poisoned.
+    pub fn compact_with_progress<F: Fn(&CompactionProgress) + Send + Sync + 'static>(
+        &self,
+        strategy: Arc<dyn CompactionStrategy>,
+        seqno_threshold: SeqNo,
+        progress: F,
+    ) -> crate::Result<()> {
+        // NOTE: See AbstractTree::compact
+        let _lock = self
+            .0
+            .major_compaction_lock
+            .read()
+            .expect("lock is poisoned");
+
+        self.inner_compact(strategy, seqno_threshold, Some(Arc::new(progress)))
+    }
+
+    /// Runs a major compaction like [`AbstractTree::major_compact`], reporting its progress.
+    ///
+    /// See [`Tree::compact_with_progress`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    /// tree.insert("b", "abc", 1);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// tree.major_compact_with_progress(u64::MAX, 0, |progress| {
+    ///     println!("compacted {:.1}%, ETA {:?}", progress.ratio() * 100.0, progress.eta());
+    /// })?;
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn major_compact_with_progress<F: Fn(&CompactionProgress) + Send + Sync + 'static>(
+        &self,
+        target_size: u64,
+        seqno_threshold: SeqNo,
+        progress: F,
+    ) -> crate::Result<()> {
+        let strategy = Arc::new(crate::compaction::major::Strategy::new(target_size));
+
+        // IMPORTANT: Write lock so we can be the only compaction going on
+        let _lock = self.lock_major_compaction();
+
+        log::info!("Starting major compaction");
+        self.inner_compact(strategy, seqno_threshold, Some(Arc::new(progress)))
+    }
+
+    /// Reclaims the space held by deleted and overwritten data.
+    ///
+    /// Flushes all memtables, then rewrites every segment into the last level
+    /// using a major compaction without seqno threshold, so tombstones and the
+    /// versions they (or newer values) cover are evicted. Versions that an open
+    /// [`Snapshot`] can read are kept. Last, the tree's blocks are evicted from
+    /// the block cache, as all of them belong to segments that no longer exist.
+    ///
+    /// This is **very expensive**, as the whole tree is read and rewritten, and
+    /// the space of the old segments is only freed once the compaction is done.
+    /// It is meant to be called after deleting a large part of the tree,
+    /// not periodically.
+    ///
+    /// Old versions are dropped regardless of the seqno they are read at, so this
+    /// should not be used if reads use seqnos that are not held by a [`Snapshot`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    /// tree.remove("a", 1);
+    ///
+    /// tree.shrink_to_fit()?;
+    /// assert_eq!(0, tree.segment_count());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn shrink_to_fit(&self) -> crate::Result<()> {
+        // NOTE: Same as the default target size of leveled compaction
+        const TARGET_SIZE: u64 = /* 64 MiB */ 64 * 1_024 * 1_024;
+
+        log::info!("Shrinking tree {} to fit", self.id);
+
+        self.rotate_memtable();
+        self.flush_sealed_memtables(SeqNo::MAX)?;
+
+        self.major_compact(TARGET_SIZE, SeqNo::MAX)?;
+
+        self.config.cache.remove_tree(self.id);
+
+        Ok(())
+    }
+
+    /// Exports all live key-value pairs into a single sorted file at `path`.
+    ///
+    /// Unlike the segment layout of the tree, the file is self-contained and
+    /// only contains the latest version of every key (tombstones are dropped),
+    /// so it can be consumed by other systems. See [`ExportFormat`] for the file layout.
+    ///
+    /// Returns the amount of exported items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, ExportFormat, Tree};
+    ///
+    /// let tree = Config::new(folder.path().join("tree")).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("b", "def", 1);
+    /// tree.remove("a", 2);
+    ///
+    /// let count = tree.export_sorted(folder.path().join("export"), ExportFormat::Native)?;
+    /// assert_eq!(1, count);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn export_sorted<P: AsRef<Path>>(
+        &self,
+        path: P,
+        format: ExportFormat,
+    ) -> crate::Result<u64> {
+        crate::export::export_sorted(self.iter(None, None), path.as_ref(), format)
+    }
+
+    /// Returns the `top_n` most read keys with their estimated read count, most read first.
+    ///
+    /// Range and prefix scans are counted by their start key.
+    ///
+    /// Returns an empty list if hot key tracking is disabled, see [`Config::track_hot_keys`].
+    /// At most 128 keys are tracked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).track_hot_keys(1.0).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.get("a", None)?;
+    /// tree.get("a", None)?;
+    /// tree.get("b", None)?;
+    ///
+    /// let hot_keys = tree.hot_keys(1);
+    /// assert_eq!(&*hot_keys[0].0, b"a");
+    /// assert_eq!(hot_keys[0].1, 2);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn hot_keys(&self, top_n: usize) -> Vec<(UserKey, u64)> {
+        self.hot_keys
+            .as_ref()
+            .map(|hot_keys| hot_keys.top(top_n))
+            .unwrap_or_default()
+    }
+
+    /// Returns how many point reads were answered by the memtables
+    /// and by each level, since the tree was opened.
+    ///
+    /// A tombstone answers a read just like a value does.
+    /// Reads of keys that are not found in any memtable or level are counted as misses.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.get("a", None)?;
+    ///
+    /// tree.flush_active_memtable(0)?;
+    /// tree.get("a", None)?;
+    /// tree.get("b", None)?;
+    ///
+    /// let histogram = tree.read_level_histogram();
+    /// assert_eq!(1, histogram.memtable);
+    /// assert_eq!(1, histogram.levels[0]);
+    /// assert_eq!(1, histogram.misses);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn read_level_histogram(&self) -> ReadLevelHistogram {
+        self.read_stats.histogram()
+    }
+
+    /// Returns an owned copy of the tree's level layout: the segments of
+    /// every level with their IDs, key ranges, seqno ranges and sizes.
+    ///
+    /// The level manifest is only locked while copying, so the snapshot
+    /// may be inspected (or serialized, using the `serde` feature) at leisure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("b", "abc", 1);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let snapshot = tree.manifest_snapshot();
+    /// assert_eq!(1, snapshot.segment_count());
+    ///
+    /// let segment = &snapshot.levels[0].segments[0];
+    /// assert_eq!((0, 1), segment.seqnos);
+    /// assert_eq!(2, segment.item_count);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the level manifest lock is poisoned.
+    #[must_use]
+    pub fn manifest_snapshot(&self) -> ManifestSnapshot {
+        let level_manifest = self.levels.read().expect("lock is poisoned");
+        ManifestSnapshot::from(&*level_manifest)
+    }
+
+    /// Samples a read for hot key tracking.
+    pub(crate) fn record_read(&self, key: &[u8]) {
+        if let Some(hot_keys) = &self.hot_keys {
+            hot_keys.record(key);
+        }
+    }
+
+    /// Samples a range read for hot key tracking, using its start key.
+    pub(crate) fn record_range_read<K: AsRef<[u8]>, R: RangeBounds<K>>(&self, range: &R) {
+        use std::ops::Bound::{Excluded, Included, Unbounded};
+
+        if let Some(hot_keys) = &self.hot_keys {
+            match range.start_bound() {
+                Included(key) | Excluded(key) => hot_keys.record(key.as_ref()),
+                Unbounded => hot_keys.record(&[]),
+            }
+        }
+    }
+
+    /// Returns `true` if there are some segments that are being compacted.
+    #[doc(hidden)]
+    #[must_use]
+    pub fn is_compacting(&self) -> bool {
+        let levels = self.levels.read().expect("lock is poisoned");
+        levels.is_compacting()
+    }
+
+    /// Returns `true` if reads see data in memtables, see [`Config::sealed_memtable_visibility`].
+    fn memtables_visible(&self) -> bool {
+        self.config.sealed_memtable_visibility == SealedMemtableVisibility::Immediate
+    }
+
+    /// Write-locks the sealed memtables for exclusive access
+    fn lock_sealed_memtables(&self) -> RwLockWriteGuard<'_, SealedMemtables> {
+        self.sealed_memtables.write().expect("lock is poisoned")
+    }
+
+    // TODO: maybe not needed anyway
+    /// Used for [`BlobTree`] lookup
+    pub(crate) fn get_internal_entry_with_memtable(
+        &self,
+        memtable_lock: &Memtable,
+        key: &[u8],
+        seqno: Option<SeqNo>,
+    ) -> crate::Result<Option<InternalValue>> {
+        let entry = if self.memtables_visible() {
+            memtable_lock
+                .get(key, seqno)
+                .or_else(|| self.get_internal_entry_from_sealed_memtables(key, seqno))
+        } else {
+            None
+        };
+
+        let entry = match entry {
+            Some(entry) => Some(entry),
+            None => self
+                .get_internal_entry_from_segments(key, seqno, CachePolicy::Write)?
+                .map(|(_, item)| item),
+        };
+
+        Ok(entry
+            .and_then(ignore_tombstone_value)
+            .filter(|item| !self.is_range_deleted(memtable_lock, item, seqno)))
+    }
+
+    fn get_internal_entry_from_sealed_memtables(
+        &self,
+        key: &[u8],
+        seqno: Option<SeqNo>,
+    ) -> Option<InternalValue> {
+        let memtable_lock = self.sealed_memtables.read().expect("lock is poisoned");
+
+        for (_, memtable) in memtable_lock.iter().rev() {
+            if let Some(entry) = memtable.get(key, seqno) {
+                return Some(entry);
+            }
+        }
+
+        None
+    }
+
+    fn get_internal_entry_from_segments(
+        &self,
+        key: &[u8],
+        seqno: Option<SeqNo>,
+        cache_policy: CachePolicy,
+    ) -> crate::Result<Option<(usize, InternalValue)>> {
+        // NOTE: Create key hash for hash sharing
+        // https://fjall-rs.github.io/post/bloom-filter-hash-sharing/
+        let key_hash = crate::bloom::KeyHash::new(key, self.config.bloom_hasher.as_ref());
+
+        let level_manifest = self.levels.read().expect("lock is poisoned");
+
+        for (level_idx, level) in level_manifest.levels.iter().enumerate() {
+            // NOTE: Based on benchmarking, binary search is only worth it with ~4 segments
+            if level.len() >= 4 {
+                if let Some(level) = level.as_disjoint() {
+                    if let Some(segment) = level.get_segment_containing_key(key) {
+                        if let Some(item) = segment.get(key, seqno, &key_hash, cache_policy)? {
+                            return Ok(Some((level_idx, item)));
+                        }
+                    }
+
+                    // NOTE: Go to next level
+                    continue;
+                }
+            }
+
+            // NOTE: Fallback to linear search
+            for segment in &level.segments {
+                if !segment.is_key_in_key_range(key) {
+                    continue;
+                }
+
+                if let Some(item) = segment.get(key, seqno, &key_hash, cache_policy)? {
+                    return Ok(Some((level_idx, item)));
+                }
             }
         }
 
@@ -657,35 +1849,72 @@ impl Tree {
         &self,
         key: &[u8],
         seqno: Option<SeqNo>,
+    ) -> crate::Result<Option<InternalValue>> {
+        self.get_internal_entry_with_cache_policy(key, seqno, CachePolicy::Write)
+    }
+
+    fn get_internal_entry_with_cache_policy(
+        &self,
+        key: &[u8],
+        seqno: Option<SeqNo>,
+        cache_policy: CachePolicy,
     ) -> crate::Result<Option<InternalValue>> {
         // TODO: consolidate memtable & sealed behind single RwLock
 
-        let memtable_lock = self.active_memtable.read().expect("lock is poisoned");
+        let active_memtable = self
+            .active_memtable
+            .read()
+            .expect("lock is poisoned")
+            .clone();
+
+        if self.memtables_visible() {
+            if let Some(entry) = active_memtable.get(key, seqno) {
+                self.read_stats.record_memtable();
+                return Ok(ignore_tombstone_value(entry)
+                    .filter(|item| !self.is_range_deleted(&active_memtable, item, seqno)));
+            };
+
+            // Now look in sealed memtables
+            if let Some(entry) = self.get_internal_entry_from_sealed_memtables(key, seqno) {
+                self.read_stats.record_memtable();
+                return Ok(ignore_tombstone_value(entry)
+                    .filter(|item| !self.is_range_deleted(&active_memtable, item, seqno)));
+            }
+        }
 
-        if let Some(entry) = memtable_lock.get(key, seqno) {
-            return Ok(ignore_tombstone_value(entry));
+        // Now look in segments... this may involve disk I/O
+        let Some((level_idx, item)) =
+            self.get_internal_entry_from_segments(key, seqno, cache_policy)?
+        else {
+            self.read_stats.record_miss();
+            return Ok(None);
         };
 
-        drop(memtable_lock);
-
-        // Now look in sealed memtables
-        if let Some(entry) = self.get_internal_entry_from_sealed_memtables(key, seqno) {
-            return Ok(ignore_tombstone_value(entry));
-        }
+        self.read_stats.record_level(level_idx);
 
-        // Now look in segments... this may involve disk I/O
-        self.get_internal_entry_from_segments(key, seqno)
+        Ok(ignore_tombstone_value(item)
+            .filter(|item| !self.is_range_deleted(&active_memtable, item, seqno)))
     }
 
     fn inner_compact(
         &self,
         strategy: Arc<dyn CompactionStrategy>,
         seqno_threshold: SeqNo,
+        progress: Option<ProgressCallback>,
     ) -> crate::Result<()> {
         use crate::compaction::worker::{do_compaction, Options};
 
+        let Some(_slot) = CompactionSlot::acquire(
+            &self.active_compactions,
+            self.config.max_concurrent_compactions,
+        ) else {
+            log::debug!("Too many compactions are running, skipping compaction");
+            return Ok(());
+        };
+
         let mut opts = Options::from_tree(self, strategy);
         opts.eviction_seqno = seqno_threshold;
+        opts.progress = progress;
 
         do_compaction(&opts)?;
 
@@ -710,6 +1939,18 @@ impl Tree {
         range: &'a R,
         seqno: Option<SeqNo>,
         ephemeral: Option<Arc<Memtable>>,
+    ) -> impl DoubleEndedIterator<Item = crate::Result<InternalValue>> + 'static {
+        self.create_filtered_range(range, seqno, ephemeral, None)
+    }
+
+    /// Like [`Tree::create_internal_range`], but skips segments whose prefix filter
+    /// does not contain the prefix hash, if given.
+    fn create_filtered_range<'a, K: AsRef<[u8]> + 'a, R: RangeBounds<K> + 'a>(
+        &'a self,
+        range: &'a R,
+        seqno: Option<SeqNo>,
+        ephemeral: Option<Arc<Memtable>>,
+        prefix_hash: Option<CompositeHash>,
     ) -> impl DoubleEndedIterator<Item = crate::Result<InternalValue>> + 'static {
         use crate::range::{IterState, TreeIter};
         use std::ops::Bound::{self, Excluded, Included, Unbounded};
@@ -744,14 +1985,87 @@ impl Tree {
             .expect("lock is poisoned");
         log::trace!("range read: acquired sealed memtable read lock");
 
+        let iter_state = if self.memtables_visible() {
+            IterState {
+                active: active.clone(),
+                sealed: sealed.iter().map(|(_, mt)| mt.clone()).collect(),
+                ephemeral,
+            }
+        } else {
+            // NOTE: Memtables are not visible, so just pass an empty one
+            IterState {
+                active: Arc::new(Memtable::from_config(&self.config)),
+                sealed: Vec::new(),
+                ephemeral,
+            }
+        };
+
+        TreeIter::create_range(
+            iter_state,
+            bounds,
+            seqno,
+            level_manifest,
+            self.config.scan_readahead_blocks,
+            prefix_hash,
+        )
+    }
+
+    /// Returns an iterator over the disk segments only, ignoring all memtables.
+    ///
+    /// This is a view of the durable state of the tree, as of the last flush:
+    /// writes that have not been flushed yet are not visible.
+    ///
+    /// Like [`AbstractTree::iter`], only the latest version of every key is returned,
+    /// and deleted keys are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    /// tree.insert("b", "abc", 1);
+    ///
+    /// assert_eq!(1, tree.segment_iter().count());
+    /// assert_eq!(2, tree.iter(None, None).count());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the level manifest lock is poisoned.
+    #[must_use]
+    pub fn segment_iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = crate::Result<InternalValue>> + 'static {
+        use crate::range::{IterState, TreeIter};
+        use std::ops::Bound::Unbounded;
+
+        log::trace!("segment iter: acquiring levels manifest read lock");
+        let level_manifest =
+            guardian::ArcRwLockReadGuardian::take(self.levels.clone()).expect("lock is poisoned");
+        log::trace!("segment iter: acquired level manifest read lock");
+
+        // NOTE: Memtables are not read, so just pass an empty one
         let iter_state = IterState {
-            active: active.clone(),
-            sealed: sealed.iter().map(|(_, mt)| mt.clone()).collect(),
-            ephemeral,
-            levels: level_manifest.levels.clone(),
+            active: Arc::new(Memtable::from_config(&self.config)),
+            sealed: Vec::new(),
+            ephemeral: None,
         };
 
-        TreeIter::create_range(iter_state, bounds, seqno, level_manifest)
+        TreeIter::create_range(
+            iter_state,
+            (Unbounded, Unbounded),
+            None,
+            level_manifest,
+            self.config.scan_readahead_blocks,
+            None,
+        )
     }
 
     #[doc(hidden)]
@@ -777,18 +2091,173 @@ impl Tree {
     ) -> impl DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static {
         use crate::range::prefix_to_range;
 
-        let range = prefix_to_range(prefix.as_ref());
-        self.create_range(&range, seqno, ephemeral)
+        let prefix = prefix.as_ref();
+        let range = prefix_to_range(prefix);
+
+        // NOTE: An empty prefix means the scanned prefix is not covered by the extractor
+        let prefix_hash = self
+            .config
+            .bloom_prefix_extractor
+            .as_ref()
+            .map(|extractor| extractor(prefix))
+            .filter(|prefix| !prefix.is_empty())
+            .map(BloomFilter::get_hash);
+
+        self.create_filtered_range(&range, seqno, ephemeral, prefix_hash)
+            .map(|item| match item {
+                Ok(kv) => Ok((kv.key.user_key, kv.value)),
+                Err(e) => Err(e),
+            })
     }
 
     /// Adds an item to the active memtable.
     ///
     /// Returns the added item's size and new size of the memtable.
+    ///
+    /// If the memtable is full afterwards, it is sealed.
+    ///
+    /// The item is accepted even if the tree is closing, see [`Config::closing_write_policy`].
     #[doc(hidden)]
     #[must_use]
     pub fn append_entry(&self, value: InternalValue) -> (u32, u32) {
-        let memtable_lock = self.active_memtable.read().expect("lock is poisoned");
-        memtable_lock.insert(value)
+        let memtable_lock = self.read_lock_active_memtable();
+        self.insert_into_memtable(memtable_lock, value)
+    }
+
+    /// Adds an item to the active memtable, unless the tree rejects the write.
+    ///
+    /// Returns the added item's size and new size of the memtable.
+    ///
+    /// If the memtable is full afterwards, it is sealed.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err(TreeClosing)` if the tree is closing, see [`Config::closing_write_policy`],
+    /// or `Err(DeleteInProgress)` if the item is older than a running [`Tree::delete_sorted`].
+    #[doc(hidden)]
+    pub fn try_append_entry(&self, value: InternalValue) -> crate::Result<(u32, u32)> {
+        // IMPORTANT: Check the write while holding the memtable lock, so the write
+        // cannot slip in after closing (or a sorted delete) has rotated the memtable
+        let memtable_lock = self.read_lock_active_memtable();
+        self.check_write(value.key.seqno)?;
+
+        Ok(self.insert_into_memtable(memtable_lock, value))
+    }
+
+    /// Inserts an item into the locked active memtable, sealing it if it is full afterwards.
+    fn insert_into_memtable(
+        &self,
+        memtable_lock: RwLockReadGuard<'_, Arc<Memtable>>,
+        value: InternalValue,
+    ) -> (u32, u32) {
+        let key = value.key.user_key.clone();
+        let result = memtable_lock.insert(value);
+        let full_memtable = self
+            .should_seal_memtable(&memtable_lock, &key)
+            .then(|| memtable_lock.clone());
+        drop(memtable_lock);
+
+        if let Some(memtable) = full_memtable {
+            self.rotate_full_memtable(&memtable);
+        }
+
+        result
+    }
+
+    /// Returns `true` if the flush policy decides the memtable should be sealed.
+    ///
+    /// Uses [`Config::flush_policy`], or the memtable limits if no policy is set.
+    fn should_seal_memtable(&self, memtable: &Memtable, key: &[u8]) -> bool {
+        use crate::flush_policy::{DefaultFlushPolicy, FlushPolicy, MemtableStats};
+
+        let stats = MemtableStats {
+            size: memtable.size(),
+            len: memtable.len(),
+            key,
+        };
+
+        match &self.config.flush_policy {
+            Some(policy) => policy.should_flush(&stats),
+            None => DefaultFlushPolicy {
+                max_size: self.config.max_memtable_size,
+                max_entries: self.config.max_memtable_entries,
+            }
+            .should_flush(&stats),
+        }
+    }
+
+    /// Seals the given memtable, if it is still the active memtable.
+    fn rotate_full_memtable(&self, memtable: &Arc<Memtable>) {
+        // NOTE: Concurrent writers may have sealed the memtable already,
+        // in which case the new active memtable should not be sealed
+        let rotated = self
+            .rotate_memtable_if(|active| Arc::ptr_eq(active, memtable))
+            .is_some();
+
+        // NOTE: Blob trees separate values while flushing, so the index tree cannot flush itself
+        if rotated
+            && self.config.background_flush
+            && self.config.tree_type == crate::TreeType::Standard
+        {
+            self.spawn_background_flush();
+        }
+    }
+
+    /// Flushes all sealed memtables on a background thread, see [`Config::background_flush`].
+    fn spawn_background_flush(&self) {
+        let tree = self.clone();
+
+        std::thread::spawn(move || loop {
+            // NOTE: If another flush is running, it picks up the new sealed memtable
+            let Ok(flush_lock) = tree.flush_lock.try_lock() else {
+                return;
+            };
+
+            if tree.stop_signal.is_stopped() {
+                return;
+            }
+
+            if let Err(e) = tree.flush_sealed_memtables(0) {
+                log::error!("Background flush failed: {e:?}");
+                return;
+            }
+
+            drop(flush_lock);
+
+            // IMPORTANT: A memtable may have been sealed after the flush collected the sealed memtables,
+            // while its writer could not acquire the flush lock
+            if tree.sealed_memtable_count() == 0 {
+                return;
+            }
+        });
+    }
+
+    /// Seals the active memtable if the predicate returns `true`.
+    fn rotate_memtable_if(
+        &self,
+        predicate: impl FnOnce(&Arc<Memtable>) -> bool,
+    ) -> Option<(MemtableId, Arc<Memtable>)> {
+        log::trace!("rotate: acquiring active memtable write lock");
+        let mut active_memtable = self.lock_active_memtable();
+
+        log::trace!("rotate: acquiring sealed memtables write lock");
+        let mut sealed_memtables = self.lock_sealed_memtables();
+
+        if !predicate(&active_memtable) {
+            return None;
+        }
+
+        let yanked_memtable = std::mem::replace(
+            &mut *active_memtable,
+            Arc::new(Memtable::from_config(&self.config)),
+        );
+
+        let tmp_memtable_id = self.get_next_segment_id();
+        sealed_memtables.add(tmp_memtable_id, yanked_memtable.clone());
+
+        log::trace!("rotate: added memtable id={tmp_memtable_id} to sealed memtables");
+
+        Some((tmp_memtable_id, yanked_memtable))
     }
 
     /// Recovers previous state, by loading the level manifest and segments.
@@ -822,6 +2291,8 @@ impl Tree {
             tree_id,
             &config.cache,
             &config.descriptor_table,
+            config.verify_checksums,
+            config.preload_block_index_levels,
         )?;
         levels.update_metadata();
 
@@ -830,12 +2301,20 @@ impl Tree {
         let inner = TreeInner {
             id: tree_id,
             segment_id_counter: Arc::new(AtomicU64::new(highest_segment_id + 1)),
-            active_memtable: Arc::default(),
+            active_memtable: Arc::new(RwLock::new(Arc::new(Memtable::from_config(&config)))),
             sealed_memtables: Arc::default(),
             levels: Arc::new(RwLock::new(levels)),
             stop_signal: StopSignal::default(),
+            hot_keys: config.hot_key_sample_rate.map(HotKeys::new),
+            read_stats: ReadLevelStats::new(config.level_count),
             config,
             major_compaction_lock: RwLock::default(),
+            active_compactions: AtomicUsize::default(),
+            key_locks: KeyLocks::default(),
+            open_snapshots: SnapshotTracker::default(),
+            flush_lock: Mutex::default(),
+            delete_seqno: AtomicU64::default(),
+            on_blobs_dropped: std::sync::OnceLock::new(),
         };
 
         Ok(Self(Arc::new(inner)))
@@ -883,6 +2362,8 @@ impl Tree {
         tree_id: TreeId,
         cache: &Arc<Cache>,
         descriptor_table: &Arc<FileDescriptorTable>,
+        verify_checksums: bool,
+        preload_block_index_levels: u8,
     ) -> crate::Result<LevelManifest> {
         use crate::{
             file::fsync_directory,
@@ -893,6 +2374,12 @@ impl Tree {
         let tree_path = tree_path.as_ref();
 
         let level_manifest_path = tree_path.join(LEVELS_MANIFEST_FILE);
+        let segment_base_folder = tree_path.join(SEGMENTS_FOLDER);
+
+        // IMPORTANT: Needs to happen before reading the level manifest,
+        // otherwise the segments of a completed write would be deleted as orphans
+        LevelManifest::reconcile_interrupted_write(&level_manifest_path, &segment_base_folder)?;
+
         log::info!("Recovering manifest at {level_manifest_path:?}");
 
         let segment_id_map = LevelManifest::recover_ids(&level_manifest_path)?;
@@ -908,8 +2395,6 @@ impl Tree {
 
         let mut segments = vec![];
 
-        let segment_base_folder = tree_path.join(SEGMENTS_FOLDER);
-
         if !segment_base_folder.try_exists()? {
             std::fs::create_dir_all(&segment_base_folder)?;
             fsync_directory(&segment_base_folder)?;
@@ -951,7 +2436,8 @@ impl Tree {
                     tree_id,
                     cache.clone(),
                     descriptor_table.clone(),
-                    level_idx == 0 || level_idx == 1,
+                    level_idx < preload_block_index_levels,
+                    verify_checksums,
                 )?;
 
                 descriptor_table.insert(&segment_file_path, segment.global_id());
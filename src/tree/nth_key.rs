@@ -0,0 +1,102 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::Tree;
+use crate::{segment::value_block::CachePolicy, Segment, UserKey};
+
+impl Tree {
+    /// Returns the key at (roughly) rank `n` in sorted order.
+    ///
+    /// This is useful for picking split points or sampling a sorted keyspace,
+    /// without scanning the entire tree.
+    ///
+    /// The rank is estimated using the block indexes of the disk segments:
+    /// every data block is assumed to hold an equal share of its segment's items,
+    /// which locates the block containing rank `n`. Then, that block is scanned for the final position.
+    ///
+    /// Note that memtables are not considered, and that ranks count
+    /// all stored items (including older versions and tombstones), so the result
+    /// is only exact at block granularity.
+    ///
+    /// Returns `None` if `n` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// for x in 0..100u64 {
+    ///     tree.insert(x.to_be_bytes(), "abc", x);
+    /// }
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// assert_eq!(&*tree.nth_key(0)?.unwrap(), 0u64.to_be_bytes());
+    /// assert!(tree.nth_key(100)?.is_none());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the level manifest lock is poisoned.
+    pub fn nth_key(&self, n: u64) -> crate::Result<Option<UserKey>> {
+        let segments: Vec<Segment> = self
+            .levels
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .cloned()
+            .collect();
+
+        // Estimate the item count of every data block, in key order
+        let mut blocks = Vec::new();
+
+        for segment in &segments {
+            let handles = segment.data_block_handles(CachePolicy::Read)?;
+            let item_count = u128::from(segment.metadata.item_count);
+            let block_count = handles.len() as u128;
+
+            for (idx, handle) in handles.into_iter().enumerate() {
+                let idx = idx as u128;
+
+                // NOTE: Spread the remainder, so the shares sum up to the segment's item count
+                #[allow(clippy::cast_possible_truncation)]
+                let share = ((item_count * (idx + 1)) / block_count
+                    - (item_count * idx) / block_count) as u64;
+
+                blocks.push((handle, segment, share));
+            }
+        }
+
+        blocks.sort_by(|(a, ..), (b, ..)| a.end_key.cmp(&b.end_key));
+
+        let mut rank = n;
+
+        for (handle, segment, share) in blocks {
+            if rank >= share {
+                rank -= share;
+                continue;
+            }
+
+            let Some(block) = segment.load_data_block(handle.offset, CachePolicy::Read)? else {
+                return Ok(None);
+            };
+
+            // NOTE: The share is just an estimate, so scale the rank to the actual block size
+            #[allow(clippy::cast_possible_truncation)]
+            let idx = (u128::from(rank) * block.items.len() as u128 / u128::from(share)) as usize;
+
+            return Ok(block.items.get(idx).map(|item| item.key.user_key.clone()));
+        }
+
+        Ok(None)
+    }
+}
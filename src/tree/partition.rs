@@ -0,0 +1,116 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::Tree;
+use crate::{AbstractTree, KvPair, SeqNo};
+
+/// Statistics of a logical partition, see [`Tree::partition_stats`]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct PartitionStats {
+    /// Amount of visible items
+    pub item_count: u64,
+
+    /// Sum of the key sizes in bytes, including the partition prefix
+    pub key_bytes: u64,
+
+    /// Sum of the value sizes in bytes
+    pub value_bytes: u64,
+}
+
+impl Tree {
+    fn assert_partition(&self, partition: &[u8]) {
+        let prefix_len = self
+            .config
+            .partition_prefix_len
+            .expect("partitions should be configured");
+
+        assert_eq!(
+            prefix_len,
+            partition.len(),
+            "partition should be exactly as long as the partition prefix",
+        );
+    }
+
+    /// Scans all items of a logical partition, see [`crate::Config::partition_prefix_len`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if partitions are not configured, or the partition is not
+    /// exactly as long as the configured partition prefix.
+    pub fn scan_partition<K: AsRef<[u8]>>(
+        &self,
+        partition: K,
+        seqno: Option<SeqNo>,
+    ) -> impl DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static {
+        self.assert_partition(partition.as_ref());
+        self.prefix(partition, seqno, None)
+    }
+
+    /// Deletes all items of a logical partition, see [`crate::Config::partition_prefix_len`].
+    ///
+    /// The partition is deleted by a range tombstone, see [`AbstractTree::delete_prefix`].
+    /// The space is reclaimed as the partition's segments are compacted.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if partitions are not configured, or the partition is not
+    /// exactly as long as the configured partition prefix.
+    pub fn drop_partition<K: AsRef<[u8]>>(&self, partition: K, seqno: SeqNo) -> crate::Result<()> {
+        self.assert_partition(partition.as_ref());
+        self.delete_prefix(partition, seqno)
+    }
+
+    /// Returns the statistics of a logical partition, see [`crate::Config::partition_prefix_len`].
+    ///
+    /// This scans the entire partition.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).partition_prefix_len(2).open()?;
+    ///
+    /// tree.insert("p1a", "abc", 0);
+    /// tree.insert("p2a", "abc", 1);
+    ///
+    /// let stats = tree.partition_stats("p1", None)?;
+    /// assert_eq!(1, stats.item_count);
+    /// assert_eq!(3, stats.key_bytes);
+    /// assert_eq!(3, stats.value_bytes);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if partitions are not configured, or the partition is not
+    /// exactly as long as the configured partition prefix.
+    pub fn partition_stats<K: AsRef<[u8]>>(
+        &self,
+        partition: K,
+        seqno: Option<SeqNo>,
+    ) -> crate::Result<PartitionStats> {
+        let mut stats = PartitionStats::default();
+
+        for kv in self.scan_partition(partition, seqno) {
+            let (key, value) = kv?;
+
+            stats.item_count += 1;
+            stats.key_bytes += key.len() as u64;
+            stats.value_bytes += value.len() as u64;
+        }
+
+        Ok(stats)
+    }
+}
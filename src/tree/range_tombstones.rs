@@ -0,0 +1,63 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::Tree;
+use crate::{memtable::Memtable, range_tombstone::newest_covering, InternalValue, SeqNo};
+
+impl Tree {
+    /// Returns `true` if the item is deleted by a range tombstone that is visible at `seqno`.
+    ///
+    /// Range tombstones are not stored next to the keys they delete,
+    /// so the range tombstones of all memtables and segments need to be checked.
+    pub(super) fn is_range_deleted(
+        &self,
+        active_memtable: &Memtable,
+        item: &InternalValue,
+        seqno: Option<SeqNo>,
+    ) -> bool {
+        self.get_covering_range_tombstone(active_memtable, item, seqno)
+            .is_some()
+    }
+
+    /// Returns the seqno of the newest range tombstone that is visible at `seqno`
+    /// and deletes the item, if any.
+    pub(super) fn get_covering_range_tombstone(
+        &self,
+        active_memtable: &Memtable,
+        item: &InternalValue,
+        seqno: Option<SeqNo>,
+    ) -> Option<SeqNo> {
+        let key = &item.key.user_key;
+
+        let mut newest = None;
+
+        if self.memtables_visible() {
+            newest =
+                newest.max(active_memtable.covering_range_tombstone(key, item.key.seqno, seqno));
+
+            let sealed = self.sealed_memtables.read().expect("lock is poisoned");
+
+            for (_, memtable) in sealed.iter() {
+                newest = newest.max(memtable.covering_range_tombstone(key, item.key.seqno, seqno));
+            }
+        }
+
+        let range_tombstones = self
+            .levels
+            .read()
+            .expect("lock is poisoned")
+            .range_tombstones();
+
+        if !range_tombstones.is_empty() {
+            newest = newest.max(newest_covering(
+                &range_tombstones,
+                key,
+                item.key.seqno,
+                seqno,
+            ));
+        }
+
+        newest
+    }
+}
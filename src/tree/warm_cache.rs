@@ -0,0 +1,124 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::Tree;
+use crate::{segment::value_block::CachePolicy, CacheWarmup, Segment, UserKey};
+use std::ops::RangeBounds;
+
+impl Tree {
+    /// Loads the data blocks of all disk segments that overlap with the given range into the cache.
+    ///
+    /// This avoids latency spikes caused by a cold cache, before serving a workload
+    /// on a known key range. The index blocks of the segments are cached, too.
+    ///
+    /// Warming stops once the cache's capacity has been reached, so blocks
+    /// that were just loaded are not evicted again.
+    ///
+    /// Returns how many blocks (and bytes) have been loaded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// for x in 0..100u64 {
+    ///     tree.insert(x.to_be_bytes(), "abc", x);
+    /// }
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let warmup = tree.warm_cache(0u64.to_be_bytes()..10u64.to_be_bytes())?;
+    /// assert!(warmup.blocks > 0);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the level manifest lock is poisoned.
+    pub fn warm_cache<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> crate::Result<CacheWarmup> {
+        use std::ops::Bound::{self, Excluded, Included, Unbounded};
+
+        let lo: Bound<UserKey> = match range.start_bound() {
+            Included(x) => Included(x.as_ref().into()),
+            Excluded(x) => Excluded(x.as_ref().into()),
+            Unbounded => Unbounded,
+        };
+
+        let hi: Bound<UserKey> = match range.end_bound() {
+            Included(x) => Included(x.as_ref().into()),
+            Excluded(x) => Excluded(x.as_ref().into()),
+            Unbounded => Unbounded,
+        };
+
+        let bounds = (lo, hi);
+
+        let segments: Vec<Segment> = self
+            .levels
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .filter(|segment| segment.check_key_range_overlap(&bounds))
+            .cloned()
+            .collect();
+
+        let capacity = self.config.cache.capacity();
+        let mut warmup = CacheWarmup::default();
+
+        for segment in &segments {
+            let handles = segment.data_block_handles(CachePolicy::Write)?;
+
+            // NOTE: A block contains keys in [previous block's end key, end key],
+            // because versions of a key may span multiple blocks
+            let mut start_key: Option<&UserKey> = None;
+
+            for handle in &handles {
+                let is_after_range = start_key.is_some_and(|key| match &bounds.1 {
+                    Included(hi) => key > hi,
+                    Excluded(hi) => key >= hi,
+                    Unbounded => false,
+                });
+
+                if is_after_range {
+                    break;
+                }
+
+                let is_before_range = match &bounds.0 {
+                    Included(lo) => handle.end_key < *lo,
+                    Excluded(lo) => handle.end_key <= *lo,
+                    Unbounded => false,
+                };
+
+                start_key = Some(&handle.end_key);
+
+                if is_before_range {
+                    continue;
+                }
+
+                if warmup.bytes >= capacity {
+                    return Ok(warmup);
+                }
+
+                let Some(block) = segment.load_data_block(handle.offset, CachePolicy::Write)?
+                else {
+                    continue;
+                };
+
+                warmup.blocks += 1;
+                warmup.bytes += u64::from(block.header.uncompressed_length);
+            }
+        }
+
+        Ok(warmup)
+    }
+}
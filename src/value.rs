@@ -5,10 +5,13 @@
 use crate::{
     coding::{Decode, DecodeError, Encode, EncodeError},
     key::InternalKey,
-    segment::block::ItemSize,
+    segment::block::{AlignedItem, ItemSize},
     Slice,
 };
-use std::io::{Read, Write};
+use std::{
+    io::{Cursor, Read, Write},
+    time::Duration,
+};
 use varint_rs::{VarintReader, VarintWriter};
 
 /// User defined key
@@ -40,6 +43,16 @@ pub enum ValueType {
 
     /// "Weak" deletion (a.k.a. `SingleDelete` in `RocksDB`)
     WeakTombstone,
+
+    /// Existing value that expires at some point in time, see [`crate::Tree::insert_with_ttl`]
+    ///
+    /// The expiry timestamp (unix milliseconds, big endian) is stored in front of the value.
+    ExpiringValue,
+
+    /// Deletion of a key range, see [`crate::AbstractTree::drop_range`]
+    ///
+    /// The key is the (inclusive) start of the range, the value is the (exclusive) end.
+    RangeTombstone,
 }
 
 impl TryFrom<u8> for ValueType {
@@ -50,6 +63,8 @@ impl TryFrom<u8> for ValueType {
             0 => Ok(Self::Value),
             1 => Ok(Self::Tombstone),
             2 => Ok(Self::WeakTombstone),
+            3 => Ok(Self::ExpiringValue),
+            4 => Ok(Self::RangeTombstone),
             _ => Err(()),
         }
     }
@@ -61,6 +76,8 @@ impl From<ValueType> for u8 {
             ValueType::Value => 0,
             ValueType::Tombstone => 1,
             ValueType::WeakTombstone => 2,
+            ValueType::ExpiringValue => 3,
+            ValueType::RangeTombstone => 4,
         }
     }
 }
@@ -131,13 +148,119 @@ impl InternalValue {
         Self::new(key, vec![])
     }
 
+    /// Creates a new range tombstone, deleting all keys in `[start, end)`.
+    ///
+    /// An empty end key deletes all keys from `start` onwards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start key is empty, or a key length is greater than 2^16.
+    pub fn new_range_tombstone<K: Into<UserKey>>(start: K, end: K, seqno: SeqNo) -> Self {
+        let end = end.into();
+
+        assert!(
+            end.len() <= u16::MAX.into(),
+            "keys can be 65535 bytes in length",
+        );
+
+        let key = InternalKey::new(start, seqno, ValueType::RangeTombstone);
+        Self::new(key, end)
+    }
+
+    /// Creates a new value that expires at the given unix timestamp.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key length is empty or greater than 2^16, or the value length is greater than 2^32.
+    pub fn new_expiring<K: Into<UserKey>, V: AsRef<[u8]>>(
+        key: K,
+        value: V,
+        seqno: SeqNo,
+        expires_at: Duration,
+    ) -> Self {
+        // NOTE: Milliseconds fit into 64 bits for the next couple million years
+        #[allow(clippy::cast_possible_truncation)]
+        let expires_at = expires_at.as_millis() as u64;
+
+        let value = value.as_ref();
+        let mut bytes = Vec::with_capacity(EXPIRY_LEN + value.len());
+        bytes.extend_from_slice(&expires_at.to_be_bytes());
+        bytes.extend_from_slice(value);
+
+        Self::from_components(key, bytes, seqno, ValueType::ExpiringValue)
+    }
+
     #[doc(hidden)]
     #[must_use]
     pub fn is_tombstone(&self) -> bool {
         self.key.is_tombstone()
     }
+
+    /// Returns the expiry timestamp (unix milliseconds), if the value expires.
+    #[must_use]
+    pub(crate) fn expires_at(&self) -> Option<u64> {
+        if self.key.value_type != ValueType::ExpiringValue {
+            return None;
+        }
+
+        let bytes = self.value.get(..EXPIRY_LEN)?;
+        let mut buf = [0; EXPIRY_LEN];
+        buf.copy_from_slice(bytes);
+
+        Some(u64::from_be_bytes(buf))
+    }
+
+    /// Returns `true` if the value has expired at the given unix timestamp.
+    #[must_use]
+    pub(crate) fn is_expired(&self, now: Duration) -> bool {
+        self.expires_at()
+            .is_some_and(|expires_at| u128::from(expires_at) <= now.as_millis())
+    }
+
+    /// Turns the value into a tombstone, if it has expired.
+    ///
+    /// The tombstone keeps the seqno, so it still shadows older versions of the key.
+    #[must_use]
+    pub(crate) fn expire(self, now: Duration) -> Self {
+        if self.is_expired(now) {
+            Self::new_tombstone(self.key.user_key, self.key.seqno)
+        } else {
+            self
+        }
+    }
+
+    /// Prepares a value for reading.
+    ///
+    /// Tombstones and expired values are removed, and expiring values
+    /// are returned as regular values, without their expiry timestamp.
+    #[must_use]
+    pub(crate) fn into_visible(self, now: Duration) -> Option<Self> {
+        if self.is_tombstone() || self.is_expired(now) {
+            return None;
+        }
+
+        if self.key.value_type == ValueType::ExpiringValue {
+            let value = if self.value.len() >= EXPIRY_LEN {
+                self.value.slice(EXPIRY_LEN..)
+            } else {
+                UserValue::empty()
+            };
+
+            return Some(Self::from_components(
+                self.key.user_key,
+                value,
+                self.key.seqno,
+                ValueType::Value,
+            ));
+        }
+
+        Some(self)
+    }
 }
 
+/// Length of the expiry timestamp in front of a [`ValueType::ExpiringValue`]
+const EXPIRY_LEN: usize = std::mem::size_of::<u64>();
+
 impl ItemSize for InternalValue {
     fn size(&self) -> usize {
         std::mem::size_of::<SeqNo>()
@@ -167,7 +290,7 @@ impl Encode for InternalValue {
         self.key.encode_into(writer)?;
 
         // NOTE: Only write value len + value if we are actually a value
-        if !self.is_tombstone() {
+        if self.key.has_value() {
             // NOTE: We know values are limited to 32-bit length
             #[allow(clippy::cast_possible_truncation)]
             writer.write_u32_varint(self.value.len() as u32)?;
@@ -182,18 +305,60 @@ impl Decode for InternalValue {
     fn decode_from<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
         let key = InternalKey::decode_from(reader)?;
 
-        if key.is_tombstone() {
+        if key.has_value() {
+            // NOTE: Only read value if we are actually a value
+
+            let value_len = reader.read_u32_varint()?;
+            let value = UserValue::from_reader(reader, value_len as usize)?;
+
+            Ok(Self { key, value })
+        } else {
             Ok(Self {
                 key,
                 value: UserValue::empty(),
             })
-        } else {
-            // NOTE: Only read value if we are actually a value
+        }
+    }
+}
+
+impl AlignedItem for InternalValue {
+    fn encode_aligned(&self, buf: &mut Vec<u8>, alignment: usize) -> Result<(), EncodeError> {
+        self.key.encode_into(buf)?;
+
+        if self.key.has_value() {
+            // NOTE: We know values are limited to 32-bit length
+            #[allow(clippy::cast_possible_truncation)]
+            buf.write_u32_varint(self.value.len() as u32)?;
+
+            // NOTE: Pad with zeroes, so the value starts at the next aligned offset
+            let padding = (alignment - buf.len() % alignment) % alignment;
+            buf.resize(buf.len() + padding, 0);
+
+            buf.write_all(&self.value)?;
+        }
+
+        Ok(())
+    }
+
+    fn decode_aligned(reader: &mut Cursor<Vec<u8>>, alignment: usize) -> Result<Self, DecodeError> {
+        let key = InternalKey::decode_from(reader)?;
 
+        if key.has_value() {
             let value_len = reader.read_u32_varint()?;
+
+            // NOTE: The padding is not stored, but follows from the position in the block
+            let alignment = alignment as u64;
+            let position = reader.position();
+            reader.set_position(position + (alignment - position % alignment) % alignment);
+
             let value = UserValue::from_reader(reader, value_len as usize)?;
 
             Ok(Self { key, value })
+        } else {
+            Ok(Self {
+                key,
+                value: UserValue::empty(),
+            })
         }
     }
 }
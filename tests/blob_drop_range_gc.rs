@@ -0,0 +1,92 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn blob_drop_range_compaction_marks_blobs_stale() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .compression(lsm_tree::CompressionType::None)
+        .open_as_blob_tree()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let big_value = b"neptune!".repeat(2_000);
+
+    for key in ["a", "b", "c", "d", "e"] {
+        tree.insert(key, &big_value, seqno.next());
+    }
+    tree.insert("f", "small", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.drop_range("b", "e", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    assert_eq!(3, tree.len(None, None)?);
+    assert!(!tree.contains_key("c", None)?);
+
+    // NOTE: The blobs are still referenced by the index tree until compaction drops the keys
+    assert_eq!(0, tree.blobs.manifest.stale_bytes());
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+
+    assert_eq!(
+        3 * big_value.len() as u64,
+        tree.blobs.manifest.stale_bytes()
+    );
+
+    let strategy = value_log::StaleThresholdStrategy::new(0.5);
+    tree.apply_gc_strategy(&strategy, seqno.next())?;
+    tree.gc_drop_stale()?;
+
+    assert_eq!(0, tree.blobs.manifest.stale_bytes());
+    assert_eq!(
+        2 * big_value.len() as u64,
+        tree.blobs.manifest.total_bytes()
+    );
+    assert_eq!(&*tree.get("a", None)?.unwrap(), big_value);
+    assert_eq!(&*tree.get("e", None)?.unwrap(), big_value);
+    assert_eq!(&*tree.get("f", None)?.unwrap(), b"small");
+
+    Ok(())
+}
+
+#[test]
+fn blob_drop_range_compaction_snapshot() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .compression(lsm_tree::CompressionType::None)
+        .open_as_blob_tree()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let big_value = b"neptune!".repeat(2_000);
+
+    for key in ["a", "b", "c"] {
+        tree.insert(key, &big_value, seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    let snapshot = tree.snapshot(seqno.get());
+
+    tree.drop_range("a", "z", seqno.next());
+    tree.flush_active_memtable(0)?;
+    assert!(tree.is_empty(None, None)?);
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+
+    // NOTE: Snapshot can still see the blobs, so they are not stale yet
+    assert_eq!(0, tree.blobs.manifest.stale_bytes());
+    assert_eq!(3, snapshot.len()?);
+    assert_eq!(&*snapshot.get("b")?.unwrap(), big_value);
+
+    drop(snapshot);
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+
+    assert_eq!(
+        3 * big_value.len() as u64,
+        tree.blobs.manifest.stale_bytes()
+    );
+
+    Ok(())
+}
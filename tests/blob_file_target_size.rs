@@ -0,0 +1,56 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+const KEYS: [&str; 6] = ["a", "b", "c", "d", "e", "f"];
+
+fn write_and_overwrite(tree: &lsm_tree::BlobTree) -> lsm_tree::Result<()> {
+    let seqno = SequenceNumberCounter::default();
+
+    for key in KEYS {
+        tree.insert(key, "neptune".repeat(10_000), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Only the blobs that were written first become stale
+    tree.insert("a", "a", seqno.next());
+    tree.insert("b", "b", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.gc_scan_stats(seqno.get(), 1_000)?;
+    tree.gc_drop_stale()?;
+
+    for key in KEYS {
+        assert!(tree.get(key, None)?.is_some());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blob_file_target_size_small() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .blob_file_target_size(100_000)
+        .open_as_blob_tree()?;
+
+    write_and_overwrite(&tree)?;
+
+    // NOTE: Every blob file holds two blobs, so the first file is fully stale
+    // and can be dropped without rewriting anything
+    assert_eq!(2, tree.blob_file_count());
+
+    Ok(())
+}
+
+#[test]
+fn blob_file_target_size_large() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+
+    write_and_overwrite(&tree)?;
+
+    // NOTE: All blobs are in one blob file, which is only partially stale
+    assert_eq!(1, tree.blob_file_count());
+
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use test_log::test;
+
+const ITEM_COUNT: usize = 100;
+
+fn value(key: &str, version: usize) -> Vec<u8> {
+    format!("{key}:{version}:").repeat(2_000).into_bytes()
+}
+
+fn is_valid(key: &[u8], value: &[u8]) -> bool {
+    value.starts_with(key) && value.len() > 1_000
+}
+
+#[test]
+fn blob_gc_keeps_blob_files_for_open_iterator() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    let seqno = SequenceNumberCounter::default();
+
+    tree.insert("a", value("a", 0), seqno.next());
+    tree.insert("b", value("b", 0), seqno.next());
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.blobs.segment_count());
+
+    let mut iter = tree.iter(None, None);
+    let (key, value) = iter.next().unwrap()?;
+    assert!(is_valid(&key, &value));
+
+    // NOTE: Relocate "a" into a new blob file, making the old one stale
+    tree.insert("b", "b", seqno.next());
+    tree.gc_scan_stats(seqno.get(), 1_000)?;
+
+    let strategy = value_log::SpaceAmpStrategy::new(1.0);
+    tree.apply_gc_strategy(&strategy, seqno.next())?;
+
+    // NOTE: The iterator still holds the old blob file
+    assert_eq!(2, tree.blobs.segment_count());
+
+    let (key, value) = iter.next().unwrap()?;
+    assert!(is_valid(&key, &value));
+    assert!(iter.next().is_none());
+
+    assert_eq!(0, tree.gc_drop_stale()?);
+    drop(iter);
+
+    assert!(tree.gc_drop_stale()? > 0);
+    assert_eq!(1, tree.blobs.segment_count());
+
+    assert!(is_valid(b"a", &tree.get("a", None)?.unwrap()));
+    assert_eq!(&*tree.get("b", None)?.unwrap(), b"b");
+
+    Ok(())
+}
+
+#[test]
+fn blob_gc_concurrent_reads() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .blob_file_target_size(100_000)
+        .open_as_blob_tree()?;
+
+    let seqno = SequenceNumberCounter::default();
+
+    let keys = (0..ITEM_COUNT)
+        .map(|x| format!("{x:0>4}"))
+        .collect::<Vec<_>>();
+
+    for key in &keys {
+        tree.insert(key.as_str(), value(key, 0), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let readers = (0..4)
+        .map(|idx| {
+            let tree = tree.clone();
+            let keys = keys.clone();
+            let stop = stop.clone();
+
+            std::thread::spawn(move || -> lsm_tree::Result<usize> {
+                let mut reads = 0;
+
+                while !stop.load(Ordering::Relaxed) {
+                    if idx % 2 == 0 {
+                        for key in &keys {
+                            let value = tree.get(key, None)?.expect("should exist");
+                            assert!(is_valid(key.as_bytes(), &value));
+                            reads += 1;
+                        }
+                    } else {
+                        let mut count = 0;
+
+                        for kv in tree.iter(None, None) {
+                            let (key, value) = kv?;
+                            assert!(is_valid(&key, &value));
+                            count += 1;
+
+                            // NOTE: Read slowly, so GC runs while the iterator is alive
+                            if count % 10 == 0 {
+                                std::thread::yield_now();
+                            }
+                        }
+
+                        assert_eq!(ITEM_COUNT, count);
+                        reads += count;
+                    }
+                }
+
+                Ok(reads)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for version in 1..=20 {
+        // NOTE: Overwrite some keys, so blob files become fragmented
+        for key in keys.iter().skip(version % 3).step_by(3) {
+            tree.insert(key.as_str(), value(key, version), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+
+        tree.gc_scan_stats(seqno.get(), seqno.get())?;
+
+        let strategy = value_log::StaleThresholdStrategy::new(0.1);
+        tree.apply_gc_strategy(&strategy, seqno.next())?;
+        tree.gc_drop_stale()?;
+    }
+
+    stop.store(true, Ordering::Relaxed);
+
+    for reader in readers {
+        let reads = reader.join().expect("reader should not panic")?;
+        assert!(reads > 0);
+    }
+
+    // NOTE: Once all readers are done, all stale blob files can be dropped
+    tree.gc_scan_stats(seqno.get(), seqno.get())?;
+    tree.gc_drop_stale()?;
+
+    assert!(tree
+        .blobs
+        .manifest
+        .segments
+        .read()
+        .expect("lock is poisoned")
+        .values()
+        .all(|x| !x.is_stale()));
+
+    for key in &keys {
+        let value = tree.get(key, None)?.expect("should exist");
+        assert!(is_valid(key.as_bytes(), &value));
+    }
+
+    Ok(())
+}
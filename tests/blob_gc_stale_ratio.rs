@@ -0,0 +1,52 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn blob_gc_stale_ratio() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+
+    let seqno = SequenceNumberCounter::default();
+
+    for key in ["a", "b", "c", "d"] {
+        tree.insert(key, key.repeat(10_000), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.blobs.segment_count());
+
+    let stats = tree.gc_stats();
+    assert_eq!(1, stats.segment_count);
+    assert_eq!(4, stats.total_blobs);
+    assert_eq!(0, stats.stale_blobs);
+
+    // NOTE: 1 of 4 blobs is stale, which is below the threshold
+    tree.insert("a", "a", seqno.next());
+    assert_eq!(0, tree.gc(0.5, seqno.next(), 1_000)?);
+    assert_eq!(1, tree.blobs.segment_count());
+
+    let stats = tree.gc_stats();
+    assert_eq!(1, stats.stale_blobs);
+    assert_eq!(10_000, stats.stale_bytes);
+    assert_eq!(0, stats.stale_segment_count);
+
+    // NOTE: Now 3 of 4 blobs are stale
+    tree.insert("b", "b", seqno.next());
+    tree.insert("c", "c", seqno.next());
+    tree.gc(0.5, seqno.next(), 1_000)?;
+
+    let stats = tree.gc_stats();
+    assert_eq!(1, stats.segment_count);
+    assert_eq!(1, stats.total_blobs);
+    assert_eq!(0, stats.stale_blobs);
+
+    assert_eq!(&*tree.get("a", None)?.unwrap(), b"a");
+    assert_eq!(&*tree.get("b", None)?.unwrap(), b"b");
+    assert_eq!(&*tree.get("c", None)?.unwrap(), b"c");
+    assert_eq!(
+        &*tree.get("d", None)?.unwrap(),
+        "d".repeat(10_000).as_bytes()
+    );
+
+    Ok(())
+}
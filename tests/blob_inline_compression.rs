@@ -0,0 +1,91 @@
+#![cfg(any(feature = "lz4", feature = "miniz"))]
+
+use lsm_tree::{AbstractTree, BlobTree, CompressionType, Config, SequenceNumberCounter};
+use std::path::Path;
+use test_log::test;
+
+const ITEM_COUNT: usize = 100;
+
+fn value(x: usize) -> Vec<u8> {
+    format!("{x:0>4}").repeat(500).into_bytes()
+}
+
+fn open(path: &Path, compression: CompressionType) -> lsm_tree::Result<BlobTree> {
+    Config::new(path)
+        .inline_value_compression(compression)
+        .open_as_blob_tree()
+}
+
+fn check(tree: &BlobTree) -> lsm_tree::Result<()> {
+    for x in 0..ITEM_COUNT {
+        let item = tree.get(format!("{x:0>4}"), None)?.expect("should exist");
+        assert_eq!(&*item, value(x));
+    }
+
+    for (idx, kv) in tree.iter(None, None).enumerate() {
+        let (_, item) = kv?;
+        assert_eq!(&*item, value(idx));
+    }
+
+    Ok(())
+}
+
+fn write(path: &Path, compression: CompressionType) -> lsm_tree::Result<u64> {
+    let tree = open(path, compression)?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(format!("{x:0>4}"), value(x), seqno.next());
+    }
+    check(&tree)?;
+
+    tree.flush_active_memtable(0)?;
+    assert_eq!(0, tree.blob_file_count());
+    check(&tree)?;
+
+    tree.major_compact(u64::MAX, 0)?;
+    check(&tree)?;
+
+    Ok(tree.disk_space())
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn blob_tree_inline_compression() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let uncompressed_size = write(folder.path(), CompressionType::None)?;
+
+    let folder = tempfile::tempdir()?;
+    let compressed_size = write(folder.path(), CompressionType::Lz4)?;
+    assert!(
+        compressed_size * 2 < uncompressed_size,
+        "{compressed_size} vs {uncompressed_size}",
+    );
+
+    // NOTE: Compressed values can be read without the option being set
+    let tree = open(folder.path(), CompressionType::None)?;
+    check(&tree)?;
+
+    Ok(())
+}
+
+#[cfg(feature = "miniz")]
+#[test]
+fn blob_tree_inline_compression_mixed() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    write(folder.path(), CompressionType::None)?;
+
+    let tree = open(folder.path(), CompressionType::Miniz(6))?;
+    let seqno = SequenceNumberCounter::new(ITEM_COUNT as u64);
+
+    for x in (0..ITEM_COUNT).step_by(2) {
+        tree.insert(format!("{x:0>4}"), value(x), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    check(&tree)?;
+
+    tree.major_compact(u64::MAX, 0)?;
+    check(&tree)?;
+
+    Ok(())
+}
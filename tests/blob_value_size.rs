@@ -0,0 +1,28 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn blob_value_size() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .blob_file_separation_threshold(1_024)
+        .open_as_blob_tree()?;
+    let seqno = SequenceNumberCounter::default();
+
+    tree.insert("big", "a".repeat(50_000), seqno.next());
+    tree.insert("small", "a".repeat(100), seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    assert_eq!(1, tree.blob_file_count());
+
+    let snapshot_seqno = seqno.get();
+    tree.insert("big", "a".repeat(10), seqno.next());
+
+    assert_eq!(Some(10), tree.value_size("big", None)?);
+    assert_eq!(Some(50_000), tree.value_size("big", Some(snapshot_seqno))?);
+    assert_eq!(Some(100), tree.value_size("small", None)?);
+    assert_eq!(None, tree.value_size("missing", None)?);
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use lsm_tree::{
+    merge::{BoxedSeekIterator, Merger, SeekIterator},
+    segment::range::Range,
+    AbstractTree, Config, SequenceNumberCounter, Tree,
+};
+use std::{
+    io::{Seek, SeekFrom, Write},
+    path::Path,
+};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+const SEGMENT_COUNT: u64 = 3;
+
+/// Creates overlapping segments, every segment contains every third key
+fn create_tree(path: &Path) -> lsm_tree::Result<Tree> {
+    let tree = Config::new(path).data_block_size(1_024).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for segment in 0..SEGMENT_COUNT {
+        for x in (segment..ITEM_COUNT).step_by(SEGMENT_COUNT as usize) {
+            tree.insert(x.to_be_bytes(), x.to_string(), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    Ok(tree)
+}
+
+fn segment_ranges(tree: &Tree) -> Vec<Range> {
+    let levels = tree.levels.read().expect("lock is poisoned");
+    levels.iter().map(|x| x.iter()).collect()
+}
+
+fn keys<I: SeekIterator>(merger: Merger<I>) -> lsm_tree::Result<Vec<u64>> {
+    merger
+        .map(|item| {
+            let item = item?;
+            let key: [u8; 8] = (*item.key.user_key).try_into().expect("should be u64");
+            Ok(u64::from_be_bytes(key))
+        })
+        .collect()
+}
+
+#[test]
+fn merge_seek_before_iteration() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = create_tree(folder.path())?;
+    assert_eq!(SEGMENT_COUNT as usize, tree.segment_count());
+
+    for target in [0, 1, 500, 998, 999, 1_000, u64::MAX] {
+        let mut merger = Merger::new(segment_ranges(&tree));
+        merger.seek(&target.to_be_bytes())?;
+        assert_eq!((target..ITEM_COUNT).collect::<Vec<_>>(), keys(merger)?);
+
+        // NOTE: Reading from the back stops at the seek key as well
+        let mut merger = Merger::new(segment_ranges(&tree));
+        merger.seek(&target.to_be_bytes())?;
+        assert_eq!(
+            (target..ITEM_COUNT).rev().collect::<Vec<_>>(),
+            keys_rev(merger)?
+        );
+    }
+
+    Ok(())
+}
+
+fn keys_rev<I: SeekIterator>(merger: Merger<I>) -> lsm_tree::Result<Vec<u64>> {
+    merger
+        .rev()
+        .map(|item| {
+            let item = item?;
+            let key: [u8; 8] = (*item.key.user_key).try_into().expect("should be u64");
+            Ok(u64::from_be_bytes(key))
+        })
+        .collect()
+}
+
+#[test]
+fn merge_seek_while_iterating() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = create_tree(folder.path())?;
+
+    let mut merger = Merger::new(segment_ranges(&tree));
+
+    for x in 0..10u64 {
+        let item = merger.next().expect("should exist")?;
+        assert_eq!(&x.to_be_bytes(), &*item.key.user_key);
+    }
+
+    merger.seek(&500u64.to_be_bytes())?;
+
+    for x in 500..510u64 {
+        let item = merger.next().expect("should exist")?;
+        assert_eq!(&x.to_be_bytes(), &*item.key.user_key);
+    }
+
+    // NOTE: Seeking never moves backwards
+    merger.seek(&100u64.to_be_bytes())?;
+    merger.seek(&510u64.to_be_bytes())?;
+    assert_eq!((510..ITEM_COUNT).collect::<Vec<_>>(), keys(merger)?);
+
+    Ok(())
+}
+
+#[test]
+fn merge_seek_boxed() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = create_tree(folder.path())?;
+
+    let iters = segment_ranges(&tree)
+        .into_iter()
+        .map(|x| Box::new(x) as BoxedSeekIterator<'static>)
+        .collect();
+
+    let mut merger = Merger::new(iters);
+    merger.seek(&750u64.to_be_bytes())?;
+    assert_eq!((750..ITEM_COUNT).collect::<Vec<_>>(), keys(merger)?);
+
+    Ok(())
+}
+
+#[test]
+fn merge_seek_uses_block_index() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = create_tree(folder.path())?;
+        let levels = tree.levels.read().expect("lock is poisoned");
+
+        for segment in levels.iter() {
+            assert!(segment.metadata.data_block_count > 2);
+
+            // NOTE: Overwrite the header of the first data block, so reading it fails
+            let mut file = std::fs::OpenOptions::new()
+                .write(true)
+                .open(&segment.path)?;
+            file.seek(SeekFrom::Start(0))?;
+            file.write_all(&[0xFF; 4])?;
+            file.sync_all()?;
+        }
+    }
+
+    // NOTE: Reopen, so the corrupted blocks are not cached
+    let tree = Config::new(&folder).data_block_size(1_024).open()?;
+
+    let merger = Merger::new(segment_ranges(&tree));
+    assert!(keys(merger).is_err());
+
+    // NOTE: The skipped blocks are never read
+    let mut merger = Merger::new(segment_ranges(&tree));
+    merger.seek(&900u64.to_be_bytes())?;
+    assert_eq!((900..ITEM_COUNT).collect::<Vec<_>>(), keys(merger)?);
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter, ValueType};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+#[test]
+fn segment_raw_iter() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).data_block_size(1_024).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "old", seqno.next());
+        tree.insert(x.to_be_bytes(), "new", seqno.next());
+    }
+    tree.remove_weak(0u64.to_be_bytes(), seqno.next());
+    tree.remove(1u64.to_be_bytes(), seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    let segment = tree
+        .levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .next()
+        .cloned()
+        .unwrap();
+    assert!(segment.metadata.data_block_count > 1);
+
+    let items = segment.raw_iter()?.collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(segment.metadata.item_count, items.len() as u64);
+    assert_eq!(2 * ITEM_COUNT + 2, items.len() as u64);
+
+    // NOTE: Items are sorted by key, then by descending seqno
+    assert!(items.windows(2).all(|w| w[0].key < w[1].key));
+
+    assert_eq!(ValueType::WeakTombstone, items[0].key.value_type);
+    assert_eq!(&*items[1].value, b"new");
+    assert_eq!(&*items[2].value, b"old");
+    assert_eq!(ValueType::Tombstone, items[3].key.value_type);
+
+    // NOTE: Reads through the tree only see the newest version
+    assert_eq!(ITEM_COUNT as usize - 2, tree.len(None, None)?);
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use lsm_tree::{
+    segment::writer::{Options, Writer},
+    AbstractTree, Config, InternalValue, SequenceNumberCounter, ValueType,
+};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+#[test]
+fn segment_writer_crash_mid_write() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let segments_folder = folder.path().join("segments");
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), "a".repeat(100), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+        assert_eq!(1, tree.segment_count());
+    }
+
+    // NOTE: Simulate a crash while writing a segment: some blocks are written,
+    // but the writer is never finished
+    let segment_id = 1_000;
+    {
+        let mut writer = Writer::new(Options {
+            folder: segments_folder.clone(),
+            data_block_size: 1_024,
+            index_block_size: 1_024,
+            target_item_count: None,
+            segment_id,
+        })?;
+
+        for x in 0..ITEM_COUNT {
+            writer.write(InternalValue::from_components(
+                x.to_be_bytes(),
+                "b".repeat(100),
+                seqno.next(),
+                ValueType::Value,
+            ))?;
+        }
+    }
+
+    // NOTE: The torn file does not use the segment's final name
+    assert!(!segments_folder.join(segment_id.to_string()).try_exists()?);
+    let tmp_file = segments_folder.join(format!("tmp_{segment_id}"));
+    assert!(std::fs::metadata(&tmp_file)?.len() > 0);
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert_eq!(1, tree.segment_count());
+        assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+        assert_eq!(
+            &*tree.get(0u64.to_be_bytes(), None)?.expect("should exist"),
+            "a".repeat(100).as_bytes(),
+        );
+    }
+
+    assert!(!tmp_file.try_exists()?);
+
+    Ok(())
+}
+
+#[test]
+fn segment_writer_finish_renames() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let segment_id = 5;
+
+    let mut writer = Writer::new(Options {
+        folder: folder.path().into(),
+        data_block_size: 4_096,
+        index_block_size: 4_096,
+        target_item_count: None,
+        segment_id,
+    })?;
+
+    writer.write(InternalValue::from_components(
+        "a",
+        "a",
+        0,
+        ValueType::Value,
+    ))?;
+
+    let tmp_file = folder.path().join(format!("tmp_{segment_id}"));
+    let segment_file = folder.path().join(segment_id.to_string());
+    assert!(tmp_file.try_exists()?);
+    assert!(!segment_file.try_exists()?);
+
+    writer.finish()?.expect("should exist");
+    assert!(!tmp_file.try_exists()?);
+    assert!(segment_file.try_exists()?);
+
+    // NOTE: Empty segments are not written at all
+    let mut writer = Writer::new(Options {
+        folder: folder.path().into(),
+        data_block_size: 4_096,
+        index_block_size: 4_096,
+        target_item_count: None,
+        segment_id: segment_id + 1,
+    })?;
+    assert!(writer.finish()?.is_none());
+    assert_eq!(1, std::fs::read_dir(folder.path())?.count());
+
+    Ok(())
+}
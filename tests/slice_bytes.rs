@@ -0,0 +1,40 @@
+#![cfg(feature = "bytes")]
+
+use bytes::Bytes;
+use lsm_tree::{AbstractTree, Config, Slice};
+use test_log::test;
+
+#[test]
+fn slice_bytes_zero_copy() {
+    let bytes = Bytes::from(b"neptune".repeat(100));
+    let ptr = bytes.as_ptr();
+
+    let slice = Slice::from(bytes);
+    assert_eq!(ptr, slice.as_ptr());
+
+    let bytes = Bytes::from(slice);
+    assert_eq!(ptr, bytes.as_ptr());
+}
+
+#[test]
+fn tree_bytes_zero_copy() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    let key = Bytes::from_static(b"a");
+    let value = Bytes::from(b"neptune".repeat(100));
+    let ptr = value.as_ptr();
+
+    tree.insert(key, value, 0);
+
+    // NOTE: The memtable holds the inserted buffer, so reading it does not copy
+    let item = Bytes::from(tree.get("a", None)?.expect("should exist"));
+    assert_eq!(ptr, item.as_ptr());
+
+    tree.flush_active_memtable(0)?;
+
+    let item = Bytes::from(tree.get("a", None)?.expect("should exist"));
+    assert_eq!(&*item, b"neptune".repeat(100));
+
+    Ok(())
+}
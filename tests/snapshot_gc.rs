@@ -0,0 +1,63 @@
+use lsm_tree::{AbstractTree, Config, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn snapshot_gc_keeps_snapshot_versions() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    tree.insert("a", "old", seqno.next());
+    tree.insert("b", "old", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    let snapshot = tree.snapshot(seqno.get());
+    assert_eq!(1, tree.open_snapshot_count());
+
+    tree.insert("a", "new", seqno.next());
+    tree.remove("b", seqno.next());
+
+    // NOTE: Even though the threshold allows it, the snapshot's versions are kept
+    tree.flush_active_memtable(SeqNo::MAX)?;
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+
+    assert_eq!(Some("old".as_bytes().into()), snapshot.get("a")?);
+    assert_eq!(Some("old".as_bytes().into()), snapshot.get("b")?);
+    assert_eq!(2, snapshot.iter().count());
+    assert_eq!(1, snapshot.range("a"..="a").count());
+    assert_eq!(1, snapshot.prefix("b").count());
+
+    assert_eq!(Some("new".as_bytes().into()), tree.get("a", None)?);
+    assert_eq!(None, tree.get("b", None)?);
+
+    // NOTE: Once the snapshot is closed, old versions are dropped
+    drop(snapshot);
+    assert_eq!(0, tree.open_snapshot_count());
+
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+
+    assert_eq!(None, tree.get("a", Some(2))?);
+    assert_eq!(Some("new".as_bytes().into()), tree.get("a", None)?);
+
+    Ok(())
+}
+
+#[test]
+fn snapshot_gc_clone_registers() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    let snapshot = tree.snapshot(5);
+    let cloned = snapshot.clone();
+    let other = tree.snapshot(2);
+    assert_eq!(3, tree.open_snapshot_count());
+
+    drop(snapshot);
+    assert_eq!(2, tree.open_snapshot_count());
+
+    drop(other);
+    drop(cloned);
+    assert_eq!(0, tree.open_snapshot_count());
+
+    Ok(())
+}
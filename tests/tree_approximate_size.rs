@@ -0,0 +1,55 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 10_000;
+
+#[test]
+fn tree_approximate_size_in_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).data_block_size(1_024).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a".repeat(50), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(64_000, 0)?;
+    assert!(tree.segment_count() > 5);
+
+    let total = tree.approximate_size_in_range::<&[u8], _>(..)?;
+    assert!(total > 0);
+
+    // NOTE: An unbounded range covers all data blocks
+    assert!(total <= tree.disk_space());
+
+    for (lo, hi) in [(0, 1_000), (2_500, 7_500), (9_000, ITEM_COUNT)] {
+        let expected = total * (hi - lo) / ITEM_COUNT;
+        let size = tree.approximate_size_in_range(lo.to_be_bytes()..hi.to_be_bytes())?;
+
+        assert!(size >= expected / 2, "{size} < {expected} / 2");
+        assert!(size <= expected * 2, "{size} > {expected} * 2");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_approximate_size_in_range_disjoint() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    assert_eq!(0, tree.approximate_size_in_range::<&str, _>(..)?);
+
+    tree.insert("b", "abc", 0);
+    tree.insert("c", "abc", 1);
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Memtables are not counted
+    tree.insert("x", "abc", 2);
+
+    assert_eq!(0, tree.approximate_size_in_range("d"..="z")?);
+    assert_eq!(0, tree.approximate_size_in_range("0".."a")?);
+    assert!(tree.approximate_size_in_range("a"..="b")? > 0);
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter, Tree};
+use std::time::{Duration, Instant};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 10_000;
+
+fn wait_for_flushes(tree: &Tree) {
+    let start = Instant::now();
+
+    while tree.sealed_memtable_count() > 0 {
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "background flush did not finish",
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn tree_background_flush() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .max_memtable_size(16_000)
+        .background_flush(true)
+        .open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a", seqno.next());
+
+        // NOTE: Sealed memtables are readable until they are flushed
+        assert!(tree.contains_key(x.to_be_bytes(), None)?);
+    }
+
+    wait_for_flushes(&tree);
+    assert!(tree.segment_count() > 1);
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+
+    drop(tree);
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert!(tree.segment_count() > 1);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_background_flush_disabled() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).max_memtable_size(16_000).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a", seqno.next());
+    }
+
+    assert!(tree.sealed_memtable_count() > 0);
+    assert_eq!(0, tree.segment_count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_background_flush_close() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .max_memtable_size(16_000)
+        .background_flush(true)
+        .open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a", seqno.next());
+    }
+
+    // NOTE: Closing waits for a running background flush
+    tree.close()?;
+    assert_eq!(0, tree.sealed_memtable_count());
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+
+    Ok(())
+}
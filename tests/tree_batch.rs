@@ -0,0 +1,104 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter, ValueType};
+use test_log::test;
+
+#[test]
+fn tree_batch_commit() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    tree.insert("c", "old", seqno.next());
+    tree.insert("d", "old", seqno.next());
+
+    let mut batch = tree.batch();
+    batch.insert("a", "new");
+    batch.insert("b", "new");
+    batch.remove("c");
+    batch.remove_weak("d");
+    assert_eq!(4, batch.len());
+
+    let batch_seqno = seqno.next();
+    batch.commit(batch_seqno)?;
+
+    assert_eq!(
+        Some((batch_seqno, ValueType::Value)),
+        tree.latest_seqno("a")?
+    );
+    assert_eq!(
+        Some((batch_seqno, ValueType::Value)),
+        tree.latest_seqno("b")?
+    );
+    assert_eq!(
+        Some((batch_seqno, ValueType::Tombstone)),
+        tree.latest_seqno("c")?
+    );
+    assert_eq!(
+        Some((batch_seqno, ValueType::WeakTombstone)),
+        tree.latest_seqno("d")?
+    );
+    assert_eq!(2, tree.len(None, None)?);
+
+    // NOTE: A snapshot below the batch's seqno sees none of it
+    assert_eq!(2, tree.len(Some(batch_seqno), None)?);
+    assert!(!tree.contains_key("a", Some(batch_seqno))?);
+    assert!(tree.contains_key("c", Some(batch_seqno))?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_batch_last_write_wins() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    let mut batch = tree.batch();
+    batch.insert("a", "1");
+    batch.remove("a");
+    batch.insert("a", "2");
+    batch.insert("b", "1");
+    batch.remove("b");
+    batch.commit(0)?;
+
+    assert_eq!(Some("2".as_bytes().into()), tree.get("a", None)?);
+    assert!(!tree.contains_key("b", None)?);
+    assert_eq!(2, tree.debug_merge_iter().count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_batch_seals_memtable() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).max_memtable_entries(10).open()?;
+
+    let mut batch = tree.batch();
+    for x in 0..100u64 {
+        batch.insert(x.to_be_bytes(), "a");
+    }
+    batch.commit(0)?;
+
+    // NOTE: The batch is never split across memtables
+    assert_eq!(1, tree.sealed_memtable_count());
+    assert_eq!(0, tree.active_memtable_size());
+    assert_eq!(100, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_batch_closing() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    // NOTE: Empty batches are a no-op
+    tree.batch().commit(0)?;
+
+    tree.close()?;
+
+    let mut batch = tree.batch();
+    batch.insert("a", "abc");
+    assert!(matches!(batch.commit(0), Err(lsm_tree::Error::TreeClosing)));
+    assert!(tree.is_empty(None, None)?);
+
+    Ok(())
+}
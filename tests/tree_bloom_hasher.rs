@@ -0,0 +1,90 @@
+use lsm_tree::{AbstractTree, BloomHasher, Config, SequenceNumberCounter};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 100;
+
+fn counting_hasher(calls: Arc<AtomicUsize>) -> BloomHasher {
+    Arc::new(move |key: &[u8]| {
+        calls.fetch_add(1, Ordering::Relaxed);
+
+        let mut buf = [0; 8];
+        buf.copy_from_slice(key);
+        let x = u64::from_be_bytes(buf);
+
+        (x.wrapping_mul(0x9E37_79B9_7F4A_7C15), x.rotate_left(32) | 1)
+    })
+}
+
+#[test]
+fn tree_bloom_hasher() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        // NOTE: Segment built using the built-in hash
+        let tree = Config::new(&folder).open()?;
+        tree.insert(u64::MAX.to_be_bytes(), "old", seqno.next());
+        tree.flush_active_memtable(0)?;
+    }
+
+    let calls = Arc::new(AtomicUsize::default());
+
+    {
+        let tree = Config::new(&folder)
+            .bloom_hasher(counting_hasher(calls.clone()))
+            .open()?;
+
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), "a", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+        assert_eq!(2, tree.segment_count());
+
+        // NOTE: Every key was hashed once for the new segment's filter
+        assert_eq!(ITEM_COUNT as usize, calls.load(Ordering::Relaxed));
+
+        for x in 0..ITEM_COUNT {
+            assert!(tree.contains_key(x.to_be_bytes(), None)?);
+        }
+        assert!(tree.contains_key(u64::MAX.to_be_bytes(), None)?);
+        assert!(!tree.contains_key(ITEM_COUNT.to_be_bytes(), None)?);
+
+        // NOTE: Keys are hashed at most once per point read
+        assert!(calls.load(Ordering::Relaxed) <= 3 * ITEM_COUNT as usize + 2);
+
+        let filter_hashes = tree
+            .levels
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .filter_map(|segment| segment.bloom_filter.as_ref())
+            .map(lsm_tree::bloom::BloomFilter::has_custom_hash)
+            .filter(|&x| x)
+            .count();
+        assert_eq!(1, filter_hashes);
+
+        tree.major_compact(u64::MAX, seqno.next())?;
+        assert_eq!(1, tree.segment_count());
+
+        for x in 0..ITEM_COUNT {
+            assert!(tree.contains_key(x.to_be_bytes(), None)?);
+        }
+        assert!(tree.contains_key(u64::MAX.to_be_bytes(), None)?);
+    }
+
+    {
+        // NOTE: Without the hasher, filters with a custom hash are skipped
+        let tree = Config::new(&folder).open()?;
+
+        for x in 0..ITEM_COUNT {
+            assert!(tree.contains_key(x.to_be_bytes(), None)?);
+        }
+        assert!(!tree.contains_key(ITEM_COUNT.to_be_bytes(), None)?);
+    }
+
+    Ok(())
+}
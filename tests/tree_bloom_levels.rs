@@ -0,0 +1,69 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+fn fill(tree: &impl AbstractTree, seqno: &SequenceNumberCounter) {
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a", seqno.next());
+    }
+}
+
+#[test]
+fn tree_bloom_levels() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).bloom_levels(0..=0).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    fill(&tree, &seqno);
+    tree.flush_active_memtable(0)?;
+    assert!(tree.bloom_filter_size() > 0);
+
+    // NOTE: Major compaction writes into the last level
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(Some(1), tree.level_segment_count(6));
+    assert_eq!(0, tree.bloom_filter_size());
+
+    for x in 0..ITEM_COUNT {
+        assert!(tree.contains_key(x.to_be_bytes(), None)?);
+    }
+    assert!(!tree.contains_key(ITEM_COUNT.to_be_bytes(), None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_bloom_levels_skip_l0() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).bloom_levels(1..=6).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    fill(&tree, &seqno);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(0, tree.bloom_filter_size());
+    assert!(tree.contains_key(5u64.to_be_bytes(), None)?);
+
+    tree.major_compact(u64::MAX, 0)?;
+    assert!(tree.bloom_filter_size() > 0);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_bloom_levels() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .bloom_levels(1..=6)
+        .open_as_blob_tree()?;
+    let seqno = SequenceNumberCounter::default();
+
+    fill(&tree, &seqno);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(0, tree.bloom_filter_size());
+    assert!(tree.contains_key(5u64.to_be_bytes(), None)?);
+
+    Ok(())
+}
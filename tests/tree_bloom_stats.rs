@@ -0,0 +1,57 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_bloom_stats() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    assert!(tree.bloom_stats().is_empty());
+
+    for count in [100u64, 1_000] {
+        for x in 0..count {
+            tree.insert(x.to_be_bytes(), "a", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let stats = tree.bloom_stats();
+    assert_eq!(2, stats.len());
+
+    for (segment_id, stats) in &stats {
+        let segment = tree
+            .levels
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .find(|x| x.id() == *segment_id)
+            .cloned()
+            .expect("segment should exist");
+
+        assert_eq!(Some(*stats), segment.bloom_stats());
+        assert_eq!(segment.metadata.key_count, stats.key_count);
+        assert_eq!(segment.bloom_filter_size() * 8, stats.bits);
+        assert!(stats.set_bits > 0 && stats.set_bits < stats.bits);
+        assert!(stats.estimated_fp_rate < 0.001);
+        assert!(!stats.is_degraded());
+    }
+
+    assert_eq!(0, tree.verify()?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_bloom_stats_disabled() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).bloom_bits_per_key(-1).open()?;
+
+    tree.insert("a", "a", 0);
+    tree.flush_active_memtable(0)?;
+
+    assert_eq!(1, tree.segment_count());
+    assert!(tree.bloom_stats().is_empty());
+
+    Ok(())
+}
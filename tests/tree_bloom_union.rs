@@ -0,0 +1,109 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter, Tree};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+fn bloom_filters(tree: &Tree) -> Vec<lsm_tree::bloom::BloomFilter> {
+    tree.levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .map(|segment| {
+            segment
+                .bloom_filter
+                .clone()
+                .expect("should have bloom filter")
+        })
+        .collect()
+}
+
+#[test]
+fn tree_bloom_union_compatible_inputs() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    // NOTE: Same keys, so the filters are identical
+    for _ in 0..2 {
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), "a", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let inputs = bloom_filters(&tree);
+    assert_eq!(2, inputs.len());
+    assert_eq!(inputs[0], inputs[1]);
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+
+    // NOTE: The combined filter is as good as a rebuilt one, so it is used as-is
+    let outputs = bloom_filters(&tree);
+    assert_eq!(1, outputs.len());
+    assert_eq!(inputs[0], outputs[0]);
+
+    for x in 0..ITEM_COUNT {
+        assert!(tree.contains_key(x.to_be_bytes(), None)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_bloom_union_incompatible_inputs() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for count in [ITEM_COUNT, ITEM_COUNT / 2] {
+        for x in 0..count {
+            tree.insert(x.to_be_bytes(), "a", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let inputs = bloom_filters(&tree);
+    assert!(inputs[0].union(&inputs[1]).is_err());
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+
+    // NOTE: The filter is rebuilt using the last level's policy
+    let outputs = bloom_filters(&tree);
+    assert_eq!(1, outputs.len());
+    assert!(outputs.iter().all(|x| !inputs.contains(x)));
+
+    for x in 0..ITEM_COUNT {
+        assert!(tree.contains_key(x.to_be_bytes(), None)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_bloom_union_multiple_outputs() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for _ in 0..2 {
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), "a", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let inputs = bloom_filters(&tree);
+
+    // NOTE: Every output segment only holds some keys, so the filters are rebuilt
+    tree.major_compact(1_024, seqno.get())?;
+
+    let outputs = bloom_filters(&tree);
+    assert!(outputs.len() > 1);
+    assert!(outputs.iter().all(|x| x.len() < inputs[0].len()));
+
+    for x in 0..ITEM_COUNT {
+        assert!(tree.contains_key(x.to_be_bytes(), None)?);
+    }
+
+    Ok(())
+}
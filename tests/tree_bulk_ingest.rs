@@ -149,3 +149,57 @@ fn blob_tree_copy() -> lsm_tree::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn tree_bulk_ingest_unsorted() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    let items = [("a", "1"), ("c", "2"), ("b", "3")];
+
+    let result = tree.ingest(items.into_iter().map(|(k, v)| (k.into(), v.into())));
+    assert!(matches!(result, Err(lsm_tree::Error::UnsortedIngest)));
+
+    // NOTE: Nothing is left behind, so the ingestion can be retried
+    assert_eq!(0, tree.segment_count());
+    assert_eq!(
+        0,
+        std::fs::read_dir(folder.path().join("segments"))?.count()
+    );
+
+    tree.ingest((0..100u64).map(|x| (x.to_be_bytes().into(), "a".into())))?;
+    assert_eq!(100, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_bulk_ingest_duplicate_keys() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    let items = [("a", "1"), ("b", "2"), ("b", "3")];
+
+    let result = tree.ingest(items.into_iter().map(|(k, v)| (k.into(), v.into())));
+    assert!(matches!(result, Err(lsm_tree::Error::UnsortedIngest)));
+    assert!(tree.is_empty(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_bulk_ingest_unsorted() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+
+    let result = tree.ingest(
+        [2u64, 1]
+            .into_iter()
+            .map(|x| (x.to_be_bytes().into(), "a".repeat(10_000).into())),
+    );
+    assert!(matches!(result, Err(lsm_tree::Error::UnsortedIngest)));
+    assert_eq!(0, tree.segment_count());
+    assert!(tree.is_empty(None, None)?);
+
+    Ok(())
+}
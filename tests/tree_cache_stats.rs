@@ -0,0 +1,52 @@
+use lsm_tree::{AbstractTree, Cache, Config};
+use std::sync::Arc;
+use test_log::test;
+
+#[test]
+fn tree_cache_stats_hits_misses() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let cache = Arc::new(Cache::with_capacity_bytes(1_000_000));
+    let tree = Config::new(&folder).use_cache(cache.clone()).open()?;
+
+    assert_eq!(0.0, cache.stats().hit_ratio());
+
+    for x in 0..100u64 {
+        tree.insert(x.to_be_bytes(), "abc", x);
+    }
+    tree.flush_active_memtable(0)?;
+
+    let before = cache.stats();
+
+    // NOTE: The first read loads the block, the following ones hit the cache
+    for _ in 0..10 {
+        assert!(tree.get(0u64.to_be_bytes(), None)?.is_some());
+    }
+
+    let after = cache.stats();
+    assert_eq!(before.misses + 1, after.misses);
+    assert_eq!(before.inserts + 1, after.inserts);
+    assert!(after.hits >= before.hits + 9);
+
+    Ok(())
+}
+
+#[test]
+fn tree_cache_stats_no_capacity() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let cache = Arc::new(Cache::with_capacity_bytes(0));
+    let tree = Config::new(&folder).use_cache(cache.clone()).open()?;
+
+    tree.insert("a", "abc", 0);
+    tree.flush_active_memtable(0)?;
+
+    for _ in 0..10 {
+        assert!(tree.get("a", None)?.is_some());
+    }
+
+    let stats = cache.stats();
+    assert_eq!(0, stats.hits);
+    assert_eq!(0, stats.inserts);
+    assert!(stats.misses >= 10);
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use lsm_tree::{AbstractTree, ChecksumType, Config, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: usize = 1_000;
+
+#[test]
+fn tree_checksum_type_mixed() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder)
+            .checksum_type(ChecksumType::Crc32)
+            .open()?;
+
+        for x in 0..ITEM_COUNT as u64 {
+            tree.insert(x.to_be_bytes(), "crc", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+
+        assert_eq!(0, tree.verify()?);
+    }
+
+    {
+        let tree = Config::new(&folder)
+            .checksum_type(ChecksumType::Xxh3)
+            .open()?;
+        assert_eq!(ITEM_COUNT, tree.len(None, None)?);
+
+        for x in (0..ITEM_COUNT as u64).step_by(2) {
+            tree.insert(x.to_be_bytes(), "xxh3", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: Every block self-describes its checksum type
+        assert_eq!(2, tree.segment_count());
+        assert_eq!(0, tree.verify()?);
+
+        tree.major_compact(u64::MAX, 0)?;
+        assert_eq!(1, tree.segment_count());
+        assert_eq!(0, tree.verify()?);
+
+        assert_eq!(ITEM_COUNT, tree.len(None, None)?);
+        assert_eq!(&*tree.get(0u64.to_be_bytes(), None)?.unwrap(), b"xxh3");
+        assert_eq!(&*tree.get(1u64.to_be_bytes(), None)?.unwrap(), b"crc");
+    }
+
+    Ok(())
+}
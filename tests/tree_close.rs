@@ -0,0 +1,144 @@
+use lsm_tree::{AbstractTree, ClosingWritePolicy, Config, Error, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_close() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+        let seqno = SequenceNumberCounter::default();
+
+        tree.try_insert("a", "a", seqno.next())?;
+        tree.try_insert("b", "b", seqno.next())?;
+        tree.rotate_memtable();
+        tree.try_insert("c", "c", seqno.next())?;
+        tree.try_remove("a", seqno.next())?;
+        assert_eq!(1, tree.sealed_memtable_count());
+        assert!(!tree.is_closing());
+
+        tree.close()?;
+        assert!(tree.is_closing());
+
+        assert_eq!(0, tree.sealed_memtable_count());
+        assert_eq!(0, tree.active_memtable_size());
+        assert_eq!(2, tree.segment_count());
+
+        assert!(matches!(
+            tree.try_insert("d", "d", seqno.next()),
+            Err(Error::TreeClosing)
+        ));
+        assert!(matches!(
+            tree.try_remove("b", seqno.next()),
+            Err(Error::TreeClosing)
+        ));
+        assert_eq!(2, tree.len(None, None)?);
+    }
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert_eq!(2, tree.len(None, None)?);
+        assert!(!tree.contains_key("a", None)?);
+        assert!(tree.contains_key("c", None)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_close() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open_as_blob_tree()?;
+        let seqno = SequenceNumberCounter::default();
+
+        tree.try_insert("a", "a".repeat(10_000), seqno.next())?;
+        tree.try_insert("b", "b", seqno.next())?;
+
+        tree.close()?;
+        assert_eq!(1, tree.segment_count());
+        assert_eq!(1, tree.blob_file_count());
+
+        assert!(matches!(
+            tree.try_insert("c", "c", seqno.next()),
+            Err(Error::TreeClosing)
+        ));
+    }
+
+    {
+        let tree = Config::new(&folder).open_as_blob_tree()?;
+        assert_eq!(2, tree.len(None, None)?);
+        assert_eq!(
+            &*tree.get("a", None)?.unwrap(),
+            "a".repeat(10_000).as_bytes()
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_close_insert_buffers() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+        let seqno = SequenceNumberCounter::default();
+
+        tree.close()?;
+
+        // NOTE: Infallible writes cannot be rejected, so they are buffered
+        tree.insert("a", "a", seqno.next());
+        assert!(tree.contains_key("a", None)?);
+
+        assert!(matches!(
+            tree.delete_prefix("a", seqno.next()),
+            Err(Error::TreeClosing)
+        ));
+        assert!(tree.contains_key("a", None)?);
+
+        tree.close()?;
+        assert_eq!(1, tree.segment_count());
+    }
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert!(tree.contains_key("a", None)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_close_buffer_writes() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder)
+            .closing_write_policy(ClosingWritePolicy::Buffer)
+            .open()?;
+        let seqno = SequenceNumberCounter::default();
+
+        tree.insert("a", "a", seqno.next());
+        tree.close()?;
+        assert_eq!(1, tree.segment_count());
+
+        tree.insert("b", "b", seqno.next());
+        tree.try_insert("c", "c", seqno.next())?;
+        tree.remove("a", seqno.next());
+        assert_eq!(2, tree.len(None, None)?);
+
+        // NOTE: Buffered writes are flushed by closing again
+        tree.close()?;
+        assert_eq!(2, tree.segment_count());
+    }
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert_eq!(2, tree.len(None, None)?);
+        assert!(!tree.contains_key("a", None)?);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use lsm_tree::{AbstractTree, Config, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+#[test]
+fn tree_compact_range_evicts_tombstones() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).data_block_size(1_024).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a".repeat(100), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(16_000, 0)?;
+
+    let segment_count = tree.segment_count();
+    assert!(segment_count > 5);
+
+    for x in 100..200u64 {
+        tree.remove(x.to_be_bytes(), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.level_segment_count(0).unwrap_or_default());
+
+    tree.compact_range(
+        100u64.to_be_bytes()..200u64.to_be_bytes(),
+        16_000,
+        SeqNo::MAX,
+    )?;
+
+    // NOTE: The tombstones are gone, and segments outside the range were not rewritten
+    assert_eq!(0, tree.level_segment_count(0).unwrap_or_default());
+    assert!(tree.segment_count() <= segment_count);
+    assert_eq!(ITEM_COUNT as usize - 100, tree.len(None, None)?);
+    assert_eq!(
+        0,
+        tree.levels
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .map(|segment| segment.metadata.tombstone_count)
+            .sum::<u64>(),
+    );
+
+    for x in 0..ITEM_COUNT {
+        assert_eq!(
+            !(100..200).contains(&x),
+            tree.contains_key(x.to_be_bytes(), None)?
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_compact_range_includes_overlapping() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    // NOTE: Only the first segment overlaps the range, but it overlaps the other segments
+    tree.insert("a", "old", seqno.next());
+    tree.insert("m", "old", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("m", "new", seqno.next());
+    tree.insert("z", "new", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("y", "old", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.compact_range("a"..="b", u64::MAX, SeqNo::MAX)?;
+
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(Some("new".as_bytes().into()), tree.get("m", None)?);
+    assert_eq!(4, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_compact_range_keeps_snapshot() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    tree.insert("a", "old", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    let snapshot = tree.snapshot(seqno.get());
+
+    tree.remove("a", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.compact_range::<&str, _>(.., u64::MAX, SeqNo::MAX)?;
+    assert_eq!(None, tree.get("a", None)?);
+    assert_eq!(Some("old".as_bytes().into()), snapshot.get("a")?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_compact_range_empty() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "abc", 0);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(0, tree.level_segment_count(6).unwrap_or_default());
+
+    tree.compact_range("b"..="c", u64::MAX, SeqNo::MAX)?;
+    assert_eq!(1, tree.level_segment_count(0).unwrap_or_default());
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use lsm_tree::{
+    compaction::{Choice, CompactionStrategy, Leveled},
+    level_manifest::LevelManifest,
+    AbstractTree, Config, SequenceNumberCounter, Tree,
+};
+use std::sync::Arc;
+use test_log::test;
+
+const TENANTS: [&str; 4] = ["t0:", "t1:", "t2:", "t3:"];
+const ITEM_COUNT: usize = 250;
+
+/// Drops all segments that only contain keys of the given prefix
+struct DropPrefix(&'static [u8]);
+
+impl CompactionStrategy for DropPrefix {
+    fn get_name(&self) -> &'static str {
+        "DropPrefix"
+    }
+
+    fn choose(&self, levels: &LevelManifest, _: &Config) -> Choice {
+        Choice::Drop(
+            levels
+                .iter()
+                .filter(|x| {
+                    let key_range = &x.metadata.key_range;
+                    key_range.min().starts_with(self.0) && key_range.max().starts_with(self.0)
+                })
+                .map(|x| x.metadata.id)
+                .collect(),
+        )
+    }
+}
+
+fn fill(tree: &Tree, seqno: &SequenceNumberCounter) -> lsm_tree::Result<()> {
+    // NOTE: Every flush contains all tenants
+    for batch in 0..4 {
+        for x in 0..ITEM_COUNT {
+            for tenant in TENANTS {
+                let key = format!("{tenant}{:0>8}", batch * ITEM_COUNT + x);
+                tree.insert(key, "a".repeat(32), seqno.next());
+            }
+        }
+        tree.flush_active_memtable(0)?;
+    }
+    Ok(())
+}
+
+fn segment_prefixes(tree: &Tree) -> Vec<(u8, Vec<u8>, Vec<u8>)> {
+    let levels = tree.levels.read().expect("lock is poisoned");
+
+    levels
+        .levels
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, level)| {
+            level.iter().map(move |x| {
+                let key_range = &x.metadata.key_range;
+
+                #[allow(clippy::cast_possible_truncation)]
+                (
+                    idx as u8,
+                    key_range.min()[..3].to_vec(),
+                    key_range.max()[..3].to_vec(),
+                )
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn tree_compaction_prefix_split() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    fill(&tree, &seqno)?;
+    assert_eq!(4, tree.level_segment_count(0).unwrap_or_default());
+
+    let strategy = Leveled {
+        l0_threshold: 4,
+        target_size: 100_000,
+        prefix_len: Some(3),
+        ..Default::default()
+    };
+    tree.compact(Arc::new(strategy), 0)?;
+
+    let segments = segment_prefixes(&tree);
+    assert_eq!(4, segments.len());
+
+    for (level, min, max) in &segments {
+        assert_eq!(1, *level);
+        assert_eq!(min, max, "segment spans more than one prefix");
+    }
+    assert_eq!(ITEM_COUNT * 4 * 4, tree.len(None, None)?);
+
+    // NOTE: Dropping a tenant does not need to rewrite any segment
+    tree.compact(Arc::new(DropPrefix(b"t1:")), 0)?;
+    assert_eq!(3, tree.segment_count());
+    assert_eq!(0, tree.prefix("t1:", None, None).count());
+    assert_eq!(ITEM_COUNT * 4, tree.prefix("t2:", None, None).count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_compaction_prefix_split_target_size() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    fill(&tree, &seqno)?;
+
+    // NOTE: Outputs are still split by size inside of a prefix
+    let strategy = Leveled {
+        l0_threshold: 4,
+        target_size: 8_192,
+        prefix_len: Some(3),
+        ..Default::default()
+    };
+    tree.compact(Arc::new(strategy), 0)?;
+
+    let segments = segment_prefixes(&tree);
+    assert!(segments.len() > TENANTS.len());
+
+    for (_, min, max) in &segments {
+        assert_eq!(min, max, "segment spans more than one prefix");
+    }
+
+    for tenant in TENANTS {
+        assert_eq!(ITEM_COUNT * 4, tree.prefix(tenant, None, None).count());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_compaction_no_prefix_split() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    fill(&tree, &seqno)?;
+
+    let strategy = Leveled {
+        l0_threshold: 4,
+        target_size: 100_000,
+        ..Default::default()
+    };
+    tree.compact(Arc::new(strategy), 0)?;
+
+    // NOTE: Without a prefix length, segments are only split by size
+    assert!(segment_prefixes(&tree)
+        .iter()
+        .any(|(level, min, max)| *level == 1 && min != max));
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use lsm_tree::{compaction::CompactionProgress, AbstractTree, Config, SequenceNumberCounter};
+use std::sync::{Arc, Mutex};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 100_000;
+
+#[test]
+fn tree_compaction_progress() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for round in 0..3 {
+        for x in 0..ITEM_COUNT {
+            // NOTE: Overwrite half of the keys of the previous round
+            let key = x + round * ITEM_COUNT / 2;
+            tree.insert(key.to_be_bytes(), "abc", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let total_bytes = tree.disk_space();
+
+    let updates = Arc::new(Mutex::new(Vec::<CompactionProgress>::new()));
+
+    {
+        let updates = updates.clone();
+
+        tree.major_compact_with_progress(u64::MAX, seqno.get(), move |progress| {
+            updates.lock().unwrap().push(progress.clone());
+        })?;
+    }
+
+    assert_eq!(1, tree.segment_count());
+
+    let updates = updates.lock().unwrap();
+    assert!(updates.len() >= 2);
+
+    for window in updates.windows(2) {
+        assert!(window[0].items_read <= window[1].items_read);
+        assert!(window[0].bytes_read <= window[1].bytes_read);
+        assert!(window[0].elapsed <= window[1].elapsed);
+    }
+
+    for progress in updates.iter() {
+        assert_eq!(3 * ITEM_COUNT, progress.total_items);
+        assert_eq!(total_bytes, progress.total_bytes);
+        assert!(progress.items_written <= progress.items_read);
+    }
+
+    let last = updates.last().unwrap();
+    assert_eq!(last.total_items, last.items_read);
+    assert_eq!(last.total_bytes, last.bytes_read);
+    assert_eq!(2 * ITEM_COUNT, last.items_written);
+    assert_eq!(1.0, last.ratio());
+
+    Ok(())
+}
+
+#[test]
+fn tree_compaction_progress_no_merge() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    let updates = Arc::new(Mutex::new(0));
+
+    {
+        let updates = updates.clone();
+
+        tree.compact_with_progress(
+            Arc::new(lsm_tree::compaction::Leveled::default()),
+            0,
+            move |_| {
+                *updates.lock().unwrap() += 1;
+            },
+        )?;
+    }
+
+    // NOTE: Nothing to compact
+    assert_eq!(0, *updates.lock().unwrap());
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use lsm_tree::{AbstractTree, Config};
+use std::{
+    io::{Seek, SeekFrom, Write},
+    time::{Duration, Instant},
+};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+#[test]
+fn tree_compaction_retry_corruption_fails_fast() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).data_block_size(1_024).open()?;
+
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), "a", x);
+        }
+        tree.flush_active_memtable(0)?;
+
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), "b", ITEM_COUNT + x);
+        }
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: Overwrite the header of the first data block of the first segment
+        let levels = tree.levels.read().expect("lock is poisoned");
+        let segment = levels.iter().next().expect("should exist");
+
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&segment.path)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&[0xFF; 4])?;
+        file.sync_all()?;
+    }
+
+    let tree = Config::new(&folder)
+        .compaction_retries(5, Duration::from_secs(10))
+        .open()?;
+
+    let start = Instant::now();
+    tree.major_compact(u64::MAX, 0)?;
+
+    // NOTE: Corruption is never retried, and the input segments stay in place
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(2, tree.segment_count());
+
+    // NOTE: The partially written segment was deleted
+    assert_eq!(
+        2,
+        std::fs::read_dir(folder.path().join("segments"))?.count()
+    );
+
+    Ok(())
+}
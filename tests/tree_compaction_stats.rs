@@ -0,0 +1,74 @@
+use lsm_tree::{compaction::CompactionStats, AbstractTree, Config, SequenceNumberCounter};
+use std::sync::{Arc, Mutex};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 10_000;
+
+#[test]
+fn tree_compaction_stats() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let stats = Arc::new(Mutex::new(Vec::<CompactionStats>::new()));
+
+    let tree = {
+        let stats = stats.clone();
+
+        Config::new(&folder)
+            .on_compaction_complete(Arc::new(move |x| {
+                stats.lock().unwrap().push(x);
+            }))
+            .open()?
+    };
+    let seqno = SequenceNumberCounter::default();
+
+    for _ in 0..3 {
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), "abc", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let input_bytes = tree.disk_space();
+    tree.major_compact(64 * 1_024, seqno.get())?;
+
+    let stats = stats.lock().unwrap();
+    let [stats] = stats.as_slice() else {
+        panic!("expected exactly one compaction, got {stats:?}");
+    };
+
+    assert_eq!(3, stats.input_segment_count);
+    assert_eq!(input_bytes, stats.input_bytes);
+    assert_eq!(tree.segment_count(), stats.output_segment_count);
+    assert!(stats.output_segment_count > 1);
+    assert_eq!(tree.disk_space(), stats.output_bytes);
+    assert!(stats.output_bytes < stats.input_bytes);
+    assert_eq!(6, stats.dest_level);
+
+    Ok(())
+}
+
+#[test]
+fn tree_compaction_stats_not_called_for_moves() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let calls = Arc::new(Mutex::new(0));
+
+    let tree = {
+        let calls = calls.clone();
+
+        Config::new(&folder)
+            .on_compaction_complete(Arc::new(move |_| {
+                *calls.lock().unwrap() += 1;
+            }))
+            .open()?
+    };
+
+    tree.insert("a", "abc", 0);
+    tree.flush_active_memtable(0)?;
+
+    tree.compact(Arc::new(lsm_tree::compaction::MoveDown(0, 6)), 0)?;
+    assert_eq!(Some(1), tree.level_segment_count(6));
+    assert_eq!(0, *calls.lock().unwrap());
+
+    Ok(())
+}
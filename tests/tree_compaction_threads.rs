@@ -0,0 +1,138 @@
+use lsm_tree::{
+    compaction::{Choice, CompactionStrategy, PullDown},
+    AbstractTree, Config, SequenceNumberCounter, Tree,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+/// Chooses L0->L1 and L2->L3, and a L2->L2 compaction that overlaps the latter
+struct TwoJobs;
+
+impl CompactionStrategy for TwoJobs {
+    fn get_name(&self) -> &'static str {
+        "TwoJobs"
+    }
+
+    fn choose(&self, levels: &lsm_tree::level_manifest::LevelManifest, config: &Config) -> Choice {
+        PullDown(0, 1).choose(levels, config)
+    }
+
+    fn choose_many(
+        &self,
+        levels: &lsm_tree::level_manifest::LevelManifest,
+        config: &Config,
+    ) -> Vec<Choice> {
+        vec![
+            PullDown(0, 1).choose(levels, config),
+            PullDown(2, 3).choose(levels, config),
+            PullDown(2, 2).choose(levels, config),
+        ]
+    }
+}
+
+/// Sets up a segment in L2 and another one in L0, so L0->L1 and L2->L3 are independent compactions.
+fn setup(config: Config) -> lsm_tree::Result<Tree> {
+    let tree = config.open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.compact(Arc::new(PullDown(0, 2)), 0)?;
+    assert_eq!(Some(1), tree.level_segment_count(2));
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "b", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(Some(1), tree.level_segment_count(0));
+
+    Ok(tree)
+}
+
+#[test]
+fn tree_compaction_threads() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = setup(Config::new(&folder).compaction_threads(4))?;
+
+    let running = Arc::new(AtomicUsize::default());
+    let overlapped = Arc::new(AtomicBool::default());
+
+    // NOTE: Every job waits (for a bit) until the other job is running as well
+    tree.compact_with_progress(Arc::new(TwoJobs), 0, {
+        let running = running.clone();
+        let overlapped = overlapped.clone();
+
+        move |_| {
+            running.fetch_add(1, Ordering::SeqCst);
+
+            let start = Instant::now();
+            while start.elapsed() < Duration::from_secs(10) {
+                if running.load(Ordering::SeqCst) >= 2 {
+                    overlapped.store(true, Ordering::SeqCst);
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+    })?;
+
+    assert!(
+        overlapped.load(Ordering::SeqCst),
+        "jobs should run in parallel"
+    );
+
+    // NOTE: The L2->L2 job overlaps the L2->L3 job, so it was not run
+    assert_eq!(2, running.load(Ordering::SeqCst));
+
+    assert_eq!(Some(0), tree.level_segment_count(0));
+    assert_eq!(Some(1), tree.level_segment_count(1));
+    assert_eq!(Some(0), tree.level_segment_count(2));
+    assert_eq!(Some(1), tree.level_segment_count(3));
+
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+    assert_eq!(&*tree.get(0u64.to_be_bytes(), None)?.unwrap(), b"b");
+
+    Ok(())
+}
+
+#[test]
+fn tree_compaction_threads_leveled() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).compaction_threads(4).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let strategy = Arc::new(lsm_tree::compaction::Leveled {
+        l0_threshold: 2,
+        target_size: 1,
+        ..Default::default()
+    });
+
+    for batch in 0..10u64 {
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), batch.to_be_bytes(), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+        tree.compact(strategy.clone(), 0)?;
+    }
+
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+
+    for x in 0..ITEM_COUNT {
+        assert_eq!(
+            &*tree.get(x.to_be_bytes(), None)?.unwrap(),
+            9u64.to_be_bytes()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+#[test]
+fn tree_compare_and_swap_basic() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    assert!(tree.compare_and_swap("a", None, "v0", 0)?);
+    assert!(!tree.compare_and_swap("a", None, "v1", 1)?);
+    assert!(!tree.compare_and_swap("a", Some(5), "v1", 1)?);
+    assert_eq!(Some("v0".as_bytes().into()), tree.get("a", None)?);
+
+    // NOTE: The check also sees versions that are already on disk
+    tree.flush_active_memtable(0)?;
+    assert!(tree.compare_and_swap("a", Some(0), "v1", 1)?);
+    assert_eq!(Some("v1".as_bytes().into()), tree.get("a", None)?);
+
+    // NOTE: Deleted keys do not exist anymore
+    tree.remove("a", 2);
+    assert!(!tree.compare_and_swap("a", Some(1), "v2", 3)?);
+    assert!(!tree.compare_and_swap("a", Some(2), "v2", 3)?);
+    assert!(tree.compare_and_swap("a", None, "v2", 3)?);
+    assert_eq!(Some("v2".as_bytes().into()), tree.get("a", None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_compare_and_swap_drop_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    assert!(tree.compare_and_swap("b", None, "v0", 0)?);
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Range-deleted keys do not exist anymore
+    tree.drop_range("a", "c", 1);
+    assert!(!tree.compare_and_swap("b", Some(0), "v1", 2)?);
+    assert!(tree.get("b", None)?.is_none());
+
+    assert!(tree.compare_and_swap("b", None, "v1", 2)?);
+    assert_eq!(Some("v1".as_bytes().into()), tree.get("b", None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_compare_and_swap_closing() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    tree.close()?;
+    assert!(matches!(
+        tree.compare_and_swap("a", None, "v0", 0),
+        Err(lsm_tree::Error::TreeClosing)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn tree_compare_and_swap_concurrent_counter() -> lsm_tree::Result<()> {
+    const THREADS: usize = 4;
+    const INCREMENTS: u64 = 250;
+
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = Arc::new(SequenceNumberCounter::default());
+
+    tree.insert("counter", 0u64.to_be_bytes(), seqno.next());
+
+    std::thread::scope(|s| -> lsm_tree::Result<()> {
+        let handles = (0..THREADS)
+            .map(|_| {
+                s.spawn(|| -> lsm_tree::Result<()> {
+                    let mut done = 0;
+
+                    while done < INCREMENTS {
+                        let (current, _) = tree.latest_seqno("counter")?.expect("should exist");
+                        let value = tree.get("counter", None)?.expect("should exist");
+
+                        // NOTE: Make sure the value belongs to the seqno
+                        if tree.latest_seqno("counter")?.map(|(x, _)| x) != Some(current) {
+                            continue;
+                        }
+
+                        let count = u64::from_be_bytes((*value).try_into().expect("should be u64"));
+
+                        if tree.compare_and_swap(
+                            "counter",
+                            Some(current),
+                            (count + 1).to_be_bytes(),
+                            seqno.next(),
+                        )? {
+                            done += 1;
+                        }
+                    }
+
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            handle.join().expect("thread should not panic")?;
+        }
+
+        Ok(())
+    })?;
+
+    let value = tree.get("counter", None)?.expect("should exist");
+    assert_eq!(
+        THREADS as u64 * INCREMENTS,
+        u64::from_be_bytes((*value).try_into().expect("should be u64"))
+    );
+
+    Ok(())
+}
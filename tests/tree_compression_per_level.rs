@@ -0,0 +1,60 @@
+#![cfg(feature = "lz4")]
+
+use lsm_tree::{AbstractTree, CompressionType, Config, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+fn level_compression(tree: &lsm_tree::Tree, level: usize) -> Vec<CompressionType> {
+    tree.levels.read().expect("lock is poisoned").levels[level]
+        .segments
+        .iter()
+        .map(|segment| segment.metadata.compression)
+        .collect()
+}
+
+#[test]
+fn tree_compression_per_level() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .compression(CompressionType::Lz4)
+        .compression_per_level(vec![CompressionType::None, CompressionType::Lz4])
+        .open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a".repeat(100), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(vec![CompressionType::None], level_compression(&tree, 0));
+
+    // NOTE: The last compression applies to all deeper levels
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(vec![CompressionType::Lz4], level_compression(&tree, 6));
+
+    for x in 0..ITEM_COUNT {
+        assert!(tree.get(x.to_be_bytes(), None)?.is_some());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_compression_per_level_fallback() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .compression(CompressionType::Lz4)
+        .open()?;
+
+    tree.insert("a", "a", 0);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(vec![CompressionType::Lz4], level_compression(&tree, 0));
+
+    Ok(())
+}
+
+#[test]
+#[should_panic = "compression_per_level may not be empty"]
+fn tree_compression_per_level_empty() {
+    let _ = Config::new("").compression_per_level(vec![]);
+}
@@ -0,0 +1,103 @@
+use lsm_tree::{compaction::PullDown, AbstractTree, Config, SequenceNumberCounter, Tree};
+use std::sync::{mpsc, Arc, Mutex};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+/// Sets up a segment in L2 and another one in L0, so L0->L1 and L2->L3 are disjoint compactions.
+fn setup(config: Config) -> lsm_tree::Result<Tree> {
+    let tree = config.open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.compact(Arc::new(PullDown(0, 2)), 0)?;
+    assert_eq!(Some(1), tree.level_segment_count(2));
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "b", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(Some(1), tree.level_segment_count(0));
+
+    Ok(tree)
+}
+
+/// Runs a L2->L3 compaction that blocks until the returned sender is used.
+fn start_blocked_compaction(
+    tree: &Tree,
+) -> (
+    std::thread::JoinHandle<lsm_tree::Result<()>>,
+    mpsc::Sender<()>,
+) {
+    let (entered_tx, entered_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+
+    let entered_tx = Mutex::new(Some(entered_tx));
+    let release_rx = Mutex::new(release_rx);
+
+    let handle = {
+        let tree = tree.clone();
+
+        std::thread::spawn(move || {
+            tree.compact_with_progress(Arc::new(PullDown(2, 3)), 0, move |_| {
+                if let Some(entered_tx) = entered_tx.lock().unwrap().take() {
+                    entered_tx.send(()).unwrap();
+                    release_rx.lock().unwrap().recv().unwrap();
+                }
+            })
+        })
+    };
+
+    entered_rx.recv().unwrap();
+
+    (handle, release_tx)
+}
+
+#[test]
+fn tree_concurrent_compactions() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = setup(Config::new(&folder))?;
+
+    let (handle, release) = start_blocked_compaction(&tree);
+
+    // NOTE: Runs while the L2->L3 compaction is still going on
+    tree.compact(Arc::new(PullDown(0, 1)), 0)?;
+    assert_eq!(Some(0), tree.level_segment_count(0));
+    assert_eq!(Some(1), tree.level_segment_count(1));
+
+    release.send(()).unwrap();
+    handle.join().unwrap()?;
+
+    assert_eq!(Some(0), tree.level_segment_count(2));
+    assert_eq!(Some(1), tree.level_segment_count(3));
+    assert_eq!(&*tree.get(0u64.to_be_bytes(), None)?.unwrap(), b"b");
+
+    Ok(())
+}
+
+#[test]
+fn tree_max_concurrent_compactions() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = setup(Config::new(&folder).max_concurrent_compactions(1))?;
+
+    let (handle, release) = start_blocked_compaction(&tree);
+
+    // NOTE: Limit is reached, so the compaction does nothing
+    tree.compact(Arc::new(PullDown(0, 1)), 0)?;
+    assert_eq!(Some(1), tree.level_segment_count(0));
+    assert_eq!(Some(0), tree.level_segment_count(1));
+
+    release.send(()).unwrap();
+    handle.join().unwrap()?;
+
+    assert_eq!(Some(1), tree.level_segment_count(3));
+
+    tree.compact(Arc::new(PullDown(0, 1)), 0)?;
+    assert_eq!(Some(0), tree.level_segment_count(0));
+    assert_eq!(Some(1), tree.level_segment_count(1));
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use lsm_tree::{debug::Source, AbstractTree, Config, SeqNo, SequenceNumberCounter, ValueType};
+use test_log::test;
+
+#[test]
+fn tree_debug_merge_iter() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    tree.insert("a", "a0", seqno.next());
+    tree.insert("b", "b0", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("a", "a1", seqno.next());
+    tree.remove("b", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("a", "a2", seqno.next());
+    tree.insert("c", "c0", seqno.next());
+    let (sealed_id, _) = tree.rotate_memtable().expect("should rotate");
+
+    tree.insert("a", "a3", seqno.next());
+
+    let items = tree
+        .debug_merge_iter()
+        .map(|item| {
+            let (source, item) = item?;
+            Ok((
+                source,
+                item.key.user_key,
+                item.key.seqno,
+                item.key.value_type,
+            ))
+        })
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+
+    let keys = items
+        .iter()
+        .map(|(_, key, seqno, value_type)| (&**key, *seqno, *value_type))
+        .collect::<Vec<(&[u8], SeqNo, ValueType)>>();
+
+    // NOTE: All versions and tombstones are returned, sorted by key, then seqno descending
+    assert_eq!(
+        vec![
+            (b"a" as &[u8], 6, ValueType::Value),
+            (b"a", 4, ValueType::Value),
+            (b"a", 2, ValueType::Value),
+            (b"a", 0, ValueType::Value),
+            (b"b", 3, ValueType::Tombstone),
+            (b"b", 1, ValueType::Value),
+            (b"c", 5, ValueType::Value),
+        ],
+        keys,
+    );
+
+    assert_eq!(Source::ActiveMemtable, items[0].0);
+    assert_eq!(Source::SealedMemtable(sealed_id), items[1].0);
+    assert_eq!(Source::SealedMemtable(sealed_id), items[6].0);
+
+    let Source::Segment {
+        level: 0,
+        id: new_segment,
+    } = items[2].0
+    else {
+        panic!("should be segment: {:?}", items[2].0);
+    };
+    let Source::Segment {
+        level: 0,
+        id: old_segment,
+    } = items[3].0
+    else {
+        panic!("should be segment: {:?}", items[3].0);
+    };
+    assert!(old_segment < new_segment);
+    assert_eq!(
+        Source::Segment {
+            level: 0,
+            id: new_segment
+        },
+        items[4].0
+    );
+    assert_eq!(
+        Source::Segment {
+            level: 0,
+            id: old_segment
+        },
+        items[5].0
+    );
+
+    // NOTE: Regular reads are not affected
+    assert_eq!(2, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_debug_merge_iter_levels() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    tree.insert("a", "a0", seqno.next());
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 0)?;
+
+    tree.insert("a", "a1", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    let sources = tree
+        .debug_merge_iter()
+        .map(|item| item.map(|(source, _)| source))
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+
+    assert_eq!(2, sources.len());
+    assert!(matches!(sources[0], Source::Segment { level: 0, .. }));
+    assert!(matches!(sources[1], Source::Segment { level: 6, .. }));
+
+    Ok(())
+}
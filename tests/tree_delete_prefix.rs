@@ -0,0 +1,96 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_delete_prefix() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..100u64 {
+        tree.insert(format!("a#{x:0>3}"), "", seqno.next());
+        tree.insert(format!("b#{x:0>3}"), "", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    for x in 100..200u64 {
+        tree.insert(format!("a#{x:0>3}"), "", seqno.next());
+    }
+
+    let snapshot = tree.snapshot(seqno.get());
+
+    assert_eq!(300, tree.approximate_len());
+    tree.delete_prefix("a#", seqno.next())?;
+
+    // NOTE: A single range tombstone is written
+    assert_eq!(301, tree.approximate_len());
+
+    assert_eq!(0, tree.prefix("a#", None, None).count());
+    assert_eq!(100, tree.prefix("b#", None, None).count());
+    assert_eq!(200, snapshot.prefix("a#").count());
+    drop(snapshot);
+
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(100, tree.len(None, None)?);
+
+    // NOTE: The range tombstone has been dropped as well
+    assert_eq!(100, tree.approximate_len());
+
+    Ok(())
+}
+
+#[test]
+fn tree_delete_prefix_max_byte() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    tree.insert([255, 255], "", seqno.next());
+    tree.insert([255, 255, 0], "", seqno.next());
+    tree.insert([255, 254], "", seqno.next());
+    tree.flush_active_memtable(0)?;
+    assert_eq!(3, tree.approximate_len());
+
+    // NOTE: Unbounded prefixes are deleted by a single range tombstone as well
+    tree.delete_prefix([255, 255], seqno.next())?;
+    assert_eq!(4, tree.approximate_len());
+    assert_eq!(1, tree.len(None, None)?);
+
+    tree.delete_prefix([], seqno.next())?;
+    assert_eq!(5, tree.approximate_len());
+    assert!(tree.is_empty(None, None)?);
+
+    tree.insert([0], "", seqno.next());
+    assert_eq!(1, tree.len(None, None)?);
+
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(1, tree.len(None, None)?);
+    assert_eq!(1, tree.approximate_len());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_delete_prefix() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(folder).open_as_blob_tree()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let big_value = b"neptune!".repeat(2_000);
+
+    tree.insert("a#1", &big_value, seqno.next());
+    tree.insert("a#2", "small", seqno.next());
+    tree.insert("b#1", &big_value, seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.delete_prefix("a#", seqno.next())?;
+    assert_eq!(1, tree.len(None, None)?);
+    assert_eq!(&*tree.get("b#1", None)?.unwrap(), big_value);
+
+    Ok(())
+}
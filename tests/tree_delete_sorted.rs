@@ -0,0 +1,154 @@
+use lsm_tree::{AbstractTree, Config, Error, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+#[test]
+fn tree_delete_sorted() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), "a", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: Some keys only live in the memtable
+        for x in ITEM_COUNT..(ITEM_COUNT + 10) {
+            tree.insert(x.to_be_bytes(), "a", seqno.next());
+        }
+
+        tree.delete_sorted(
+            (0..(ITEM_COUNT + 10)).step_by(2).map(u64::to_be_bytes),
+            seqno.next(),
+        )?;
+
+        // NOTE: The memtable was flushed, and the tombstones were written into a new segment
+        assert_eq!(0, tree.active_memtable_size());
+        assert_eq!(Some(3), tree.level_segment_count(0));
+
+        assert_eq!(505, tree.len(None, None)?);
+        assert!(tree.get(0u64.to_be_bytes(), None)?.is_none());
+        assert!(tree.get(1u64.to_be_bytes(), None)?.is_some());
+        assert!(tree.get(ITEM_COUNT.to_be_bytes(), None)?.is_none());
+
+        // NOTE: Older snapshots still see the deleted items
+        assert_eq!(
+            (ITEM_COUNT + 10) as usize,
+            tree.len(Some(seqno.get() - 1), None)?,
+        );
+
+        // NOTE: Newer writes are not affected by the tombstones
+        tree.insert(0u64.to_be_bytes(), "b", seqno.next());
+        assert_eq!(
+            &*tree.get(0u64.to_be_bytes(), None)?.expect("should exist"),
+            b"b"
+        );
+    }
+
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(505, tree.len(None, None)?);
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(505, tree.len(None, None)?);
+    assert!(tree.get(2u64.to_be_bytes(), None)?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn tree_delete_sorted_empty() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    tree.insert("a", "a", 0);
+
+    tree.delete_sorted(std::iter::empty::<&str>(), 1)?;
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(1, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_delete_sorted_unsorted() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    tree.insert("a", "a", 0);
+    tree.insert("b", "b", 1);
+
+    assert!(matches!(
+        tree.delete_sorted(["b", "a"].into_iter(), 2),
+        Err(Error::UnsortedIngest),
+    ));
+    assert!(matches!(
+        tree.delete_sorted(["a", "a"].into_iter(), 2),
+        Err(Error::UnsortedIngest),
+    ));
+
+    // NOTE: Only the flushed memtable was registered, the tombstone segments were discarded
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(
+        1,
+        std::fs::read_dir(folder.path().join("segments"))?.count()
+    );
+    assert_eq!(2, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_delete_sorted_sealed_memtable() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    tree.insert("a", "a", 0);
+    tree.insert("b", "b", 1);
+    tree.rotate_memtable();
+    tree.insert("c", "c", 2);
+    assert_eq!(1, tree.sealed_memtable_count());
+
+    tree.delete_sorted(["a", "c"].into_iter(), 3)?;
+
+    // NOTE: The sealed memtable was flushed before the tombstones, so it cannot shadow them
+    assert_eq!(0, tree.sealed_memtable_count());
+    assert_eq!(Some(3), tree.level_segment_count(0));
+
+    assert!(tree.get("a", None)?.is_none());
+    assert!(tree.get("b", None)?.is_some());
+    assert!(tree.get("c", None)?.is_none());
+    assert_eq!(1, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_delete_sorted_rejects_older_writes() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    tree.insert("a", "a", 0);
+
+    let keys = ["a", "b"].into_iter().inspect(|_| {
+        assert!(matches!(
+            tree.try_insert("b", "b", 1),
+            Err(Error::DeleteInProgress(5)),
+        ));
+        assert!(tree.try_insert("c", "c", 6).is_ok());
+    });
+    tree.delete_sorted(keys, 5)?;
+
+    // NOTE: Once the delete is done, writes are not checked anymore
+    tree.try_insert("d", "d", 2)?;
+
+    assert!(tree.get("a", None)?.is_none());
+    assert!(tree.get("b", None)?.is_none());
+    assert!(tree.get("c", None)?.is_some());
+    assert!(tree.get("d", None)?.is_some());
+
+    Ok(())
+}
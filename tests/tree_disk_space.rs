@@ -0,0 +1,99 @@
+use lsm_tree::{AbstractTree, Config};
+use std::path::Path;
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+/// Checks that the disk space is close to the actual size of the segment files,
+/// which additionally contain the block index, filters and trailer
+fn assert_disk_space(disk_space: u64, actual: u64) {
+    assert!(disk_space <= actual, "{disk_space} > {actual}");
+    assert!(
+        disk_space * 10 >= actual * 9,
+        "{disk_space} is far below {actual}"
+    );
+}
+
+/// Sums the sizes of the files in the folder, ignoring subfolders
+fn folder_size(path: &Path) -> lsm_tree::Result<u64> {
+    let mut size = 0;
+
+    for dirent in std::fs::read_dir(path)? {
+        let metadata = dirent?.metadata()?;
+
+        if metadata.is_file() {
+            size += metadata.len();
+        }
+    }
+
+    Ok(size)
+}
+
+#[test]
+fn tree_disk_space_and_shape() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    assert_eq!(0, tree.disk_space());
+    assert_eq!(vec![0; 7], tree.level_segment_counts());
+
+    for batch in 0..3 {
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), "a".repeat(100), batch * ITEM_COUNT + x);
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    assert_eq!(vec![3, 0, 0, 0, 0, 0, 0], tree.level_segment_counts());
+    assert_eq!(3, tree.segment_count());
+
+    let data_size = tree
+        .levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .map(|x| x.metadata.file_size)
+        .sum::<u64>();
+    assert_eq!(data_size, tree.disk_space());
+    assert_disk_space(
+        tree.disk_space(),
+        folder_size(&folder.path().join("segments"))?,
+    );
+
+    tree.major_compact(u64::MAX, 3 * ITEM_COUNT)?;
+
+    assert_eq!(vec![0, 0, 0, 0, 0, 0, 1], tree.level_segment_counts());
+    assert_eq!(1, tree.segment_count());
+    assert_disk_space(
+        tree.disk_space(),
+        folder_size(&folder.path().join("segments"))?,
+    );
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_disk_space_and_shape() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .blob_file_separation_threshold(1_024)
+        .open_as_blob_tree()?;
+
+    assert_eq!(0, tree.disk_space());
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a".repeat(2_000), x);
+    }
+    tree.flush_active_memtable(0)?;
+
+    assert_eq!(vec![1, 0, 0, 0, 0, 0, 0], tree.level_segment_counts());
+    assert!(tree.blob_file_count() > 0);
+
+    // NOTE: The blob files are much larger than the index
+    let index_size = folder_size(&folder.path().join("segments"))?;
+    let blobs_size = folder_size(&folder.path().join("blobs").join("segments"))?;
+    assert!(blobs_size > ITEM_COUNT * 2_000);
+    assert_disk_space(tree.disk_space(), index_size + blobs_size);
+
+    Ok(())
+}
@@ -0,0 +1,265 @@
+use lsm_tree::{compaction::Leveled, AbstractTree, Config, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+const ITEM_COUNT: u64 = 100;
+
+fn key(x: u64) -> String {
+    format!("{x:0>3}")
+}
+
+/// Asserts the state after dropping [25, 75) and reinserting 50
+fn assert_dropped<T: AbstractTree>(tree: &T) -> lsm_tree::Result<()> {
+    assert_eq!(51, tree.len(None, None)?);
+    assert_eq!(51, tree.iter(None, None).rev().count());
+
+    assert!(tree.get(key(24), None)?.is_some());
+    assert!(tree.get(key(25), None)?.is_none());
+    assert!(tree.get(key(45), None)?.is_none());
+    assert!(tree.get(key(74), None)?.is_none());
+    assert!(tree.get(key(75), None)?.is_some());
+    assert_eq!(&*tree.get(key(50), None)?.unwrap(), b"newest");
+
+    assert_eq!(1, tree.range(key(30)..key(70), None, None).count());
+    assert_eq!(0, tree.prefix("04", None, None).count());
+    assert_eq!(1, tree.prefix("05", None, None).count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_drop_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(key(x), "old", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Some keys are newer versions in the memtable
+    for x in 40..60 {
+        tree.insert(key(x), "new", seqno.next());
+    }
+
+    let snapshot = tree.snapshot(seqno.get());
+
+    tree.drop_range(key(25), key(75), seqno.next());
+
+    // NOTE: Newer writes are not deleted
+    tree.insert(key(50), "newest", seqno.next());
+
+    assert_dropped(&tree)?;
+
+    // NOTE: Older snapshots still read the deleted items
+    assert_eq!(ITEM_COUNT as usize, snapshot.len()?);
+    assert_eq!(&*snapshot.get(key(45))?.unwrap(), b"new");
+    drop(snapshot);
+
+    tree.flush_active_memtable(0)?;
+    assert_dropped(&tree)?;
+
+    tree.major_compact(u64::MAX, 0)?;
+    assert_dropped(&tree)?;
+
+    drop(tree);
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert_dropped(&tree)?;
+
+        // NOTE: Covered items and the range tombstone are removed physically
+        tree.major_compact(u64::MAX, seqno.get())?;
+        assert_dropped(&tree)?;
+        assert_eq!(51, tree.approximate_len());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_drop_range_start_key_overwrite() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..10 {
+        tree.insert(key(x), "old", seqno.next());
+    }
+
+    tree.drop_range(key(0), key(10), seqno.next());
+
+    // NOTE: Overwriting the start key must not drop the range tombstone
+    tree.insert(key(0), "new", seqno.next());
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, seqno.get())?;
+
+    assert_eq!(1, tree.len(None, None)?);
+    assert_eq!(&*tree.get(key(0), None)?.unwrap(), b"new");
+
+    Ok(())
+}
+
+#[test]
+fn tree_drop_range_nested() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(key(x), "old", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    tree.drop_range(key(0), key(ITEM_COUNT), seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: The newer range tombstone is contained in the older one,
+    // which must still delete the remaining keys
+    tree.drop_range(key(0), key(10), seqno.next());
+    tree.flush_active_memtable(0)?;
+    assert_eq!(3, tree.segment_count());
+
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(0, tree.len(None, None)?);
+
+    tree.insert(key(5), "new", seqno.next());
+    tree.major_compact(u64::MAX, seqno.get())?;
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(1, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_drop_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let big_value = b"neptune!".repeat(2_000);
+
+    tree.insert("a", &big_value, seqno.next());
+    tree.insert("b", &big_value, seqno.next());
+    tree.insert("c", "small", seqno.next());
+    tree.insert("d", &big_value, seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.drop_range("b", "d", seqno.next());
+    assert_eq!(2, tree.len(None, None)?);
+    assert!(tree.get("b", None)?.is_none());
+
+    tree.flush_active_memtable(0)?;
+    assert_eq!(2, tree.len(None, None)?);
+    assert!(tree.get("c", None)?.is_none());
+    assert_eq!(&*tree.get("d", None)?.unwrap(), big_value);
+
+    Ok(())
+}
+
+#[test]
+fn tree_drop_range_empty() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..10 {
+        tree.insert(key(x), "old", seqno.next());
+    }
+
+    // NOTE: Inverted and empty ranges do not delete anything, not even the start key
+    let (item_size, _) = tree.drop_range(key(5), key(2), seqno.next());
+    assert_eq!(0, item_size);
+    tree.drop_range(key(5), key(5), seqno.next());
+
+    assert_eq!(10, tree.len(None, None)?);
+    assert!(tree.get(key(5), None)?.is_some());
+
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(10, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_drop_range_compaction_below_tombstone() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert(key(5), "old", 1);
+    tree.flush_active_memtable(0)?;
+
+    tree.drop_range(key(0), key(10), 7);
+    tree.flush_active_memtable(0)?;
+
+    assert_eq!(&*tree.get(key(5), Some(6))?.unwrap(), b"old");
+    assert!(tree.get(key(5), None)?.is_none());
+
+    // NOTE: The range tombstone is newer than the eviction seqno,
+    // so reads between both seqnos still need the deleted version
+    tree.major_compact(u64::MAX, 5)?;
+
+    assert_eq!(&*tree.get(key(5), Some(6))?.unwrap(), b"old");
+    assert!(tree.get(key(5), None)?.is_none());
+
+    tree.major_compact(u64::MAX, 8)?;
+    assert!(tree.get(key(5), None)?.is_none());
+    assert_eq!(0, tree.approximate_len());
+
+    Ok(())
+}
+
+#[test]
+fn tree_drop_range_last_level() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).level_count(2).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let strategy = Arc::new(Leveled {
+        l0_threshold: 1,
+        target_size: 1,
+        ..Default::default()
+    });
+
+    tree.insert("x", "old", seqno.next());
+    tree.insert("y", "old", seqno.next());
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 0)?;
+
+    for key in ["a", "b", "c"] {
+        tree.insert(key, "old", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    tree.drop_range("a", "c", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: The range tombstone does not overlap the other segment of the last level,
+    // so it is dropped without a full compaction
+    tree.compact(strategy.clone(), seqno.get())?;
+    assert_eq!(2, tree.segment_count());
+    assert_eq!(3, tree.len(None, None)?);
+    assert_eq!(3, tree.approximate_len());
+
+    tree.drop_range("a", "xa", seqno.next());
+    tree.insert("0", "new", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: The range tombstone overlaps both segments of the last level,
+    // so they are compacted with it
+    tree.compact(strategy, seqno.get())?;
+    assert_eq!(2, tree.len(None, None)?);
+    assert_eq!(2, tree.approximate_len());
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use lsm_tree::{descriptor_table::FileDescriptorTable, AbstractTree, Cache, Config};
+use std::sync::Arc;
+use test_log::test;
+
+const ITEM_COUNT: usize = 1_000;
+
+fn fill(tree: &impl AbstractTree, value_size: usize) -> lsm_tree::Result<()> {
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), "a".repeat(value_size), x);
+    }
+
+    let (id, memtable) = tree.rotate_memtable().expect("should have data");
+    let segment = tree
+        .flush_memtable(id, &memtable, 0)?
+        .expect("should flush");
+    tree.register_segments(&[segment])?;
+
+    // NOTE: Read everything, so all blocks are cached and file handles are opened
+    for x in 0..ITEM_COUNT as u64 {
+        assert!(tree.get(x.to_be_bytes(), None)?.is_some());
+    }
+    Ok(())
+}
+
+#[test]
+fn tree_drop_evicts_shared_cache() -> lsm_tree::Result<()> {
+    let cache = Arc::new(Cache::with_capacity_bytes(64 * 1_024 * 1_024));
+    let descriptor_table = Arc::new(FileDescriptorTable::new(512, 1));
+
+    let folder1 = tempfile::tempdir()?;
+    let tree1 = Config::new(&folder1)
+        .use_cache(cache.clone())
+        .descriptor_table(descriptor_table.clone())
+        .open()?;
+
+    let folder2 = tempfile::tempdir()?;
+    let tree2 = Config::new(&folder2)
+        .use_cache(cache.clone())
+        .descriptor_table(descriptor_table.clone())
+        .open_as_blob_tree()?;
+
+    fill(&tree1, 100)?;
+    let tree1_blocks = cache.len();
+    assert!(tree1_blocks > 0);
+
+    // NOTE: Large values, so blobs are cached as well
+    fill(&tree2, 5_000)?;
+    let tree2_blocks = cache.len() - tree1_blocks;
+    assert!(tree2_blocks > 0);
+    assert_eq!(2, descriptor_table.len());
+
+    drop(tree1);
+    assert_eq!(tree2_blocks, cache.len());
+    assert_eq!(1, descriptor_table.len());
+
+    // NOTE: The remaining tree is unaffected
+    assert_eq!(ITEM_COUNT, tree2.len(None, None)?);
+
+    drop(tree2);
+    assert!(cache.is_empty());
+    assert_eq!(0, cache.size());
+    assert!(descriptor_table.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn tree_drop_iter_outlives_tree() -> lsm_tree::Result<()> {
+    let cache = Arc::new(Cache::with_capacity_bytes(64 * 1_024 * 1_024));
+    let descriptor_table = Arc::new(FileDescriptorTable::new(512, 1));
+
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .use_cache(cache.clone())
+        .descriptor_table(descriptor_table.clone())
+        .open()?;
+
+    fill(&tree, 100)?;
+
+    let iter = tree.iter(None, None);
+    drop(tree);
+    assert!(cache.is_empty());
+
+    // NOTE: The iterator still pins the segment, so it can keep reading from disk
+    assert_eq!(1, descriptor_table.len());
+    assert_eq!(ITEM_COUNT, iter.count());
+
+    assert!(descriptor_table.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use lsm_tree::{
+    compaction::{Choice, CompactionStrategy, Fifo, Leveled, SizeTiered, Universal},
+    AbstractTree, Config, SequenceNumberCounter, Tree,
+};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 100;
+
+fn assert_empty<T: AbstractTree>(tree: &T) -> lsm_tree::Result<()> {
+    for seqno in [None, Some(u64::MAX)] {
+        assert!(tree.get("a", seqno)?.is_none());
+        assert!(tree.get(5u64.to_be_bytes(), seqno)?.is_none());
+        assert!(!tree.contains_key("a", seqno)?);
+
+        assert!(tree.first_key_value(seqno, None)?.is_none());
+        assert!(tree.last_key_value(seqno, None)?.is_none());
+
+        assert_eq!(0, tree.len(seqno, None)?);
+        assert!(tree.is_empty(seqno, None)?);
+
+        assert_eq!(0, tree.iter(seqno, None).count());
+        assert_eq!(0, tree.iter(seqno, None).rev().count());
+        assert_eq!(0, tree.keys(seqno, None).count());
+        assert_eq!(0, tree.values(seqno, None).count());
+
+        assert_eq!(0, tree.range::<&[u8], _>(.., seqno, None).count());
+        assert_eq!(0, tree.range::<&[u8], _>(.., seqno, None).rev().count());
+        assert_eq!(0, tree.range("a".."z", seqno, None).count());
+        assert_eq!(0, tree.range("a".."z", seqno, None).rev().count());
+
+        assert_eq!(0, tree.prefix("", seqno, None).count());
+        assert_eq!(0, tree.prefix("", seqno, None).rev().count());
+        assert_eq!(0, tree.prefix("a", seqno, None).count());
+        assert_eq!(0, tree.prefix("a", seqno, None).rev().count());
+    }
+
+    let snapshot = tree.snapshot(u64::MAX);
+    assert!(snapshot.get("a")?.is_none());
+    assert!(snapshot.first_key_value()?.is_none());
+    assert!(snapshot.last_key_value()?.is_none());
+    assert_eq!(0, snapshot.len()?);
+    assert_eq!(0, snapshot.iter().count());
+
+    Ok(())
+}
+
+fn assert_does_nothing(tree: &Tree) {
+    let strategies: [Box<dyn CompactionStrategy>; 4] = [
+        Box::new(Leveled::default()),
+        Box::new(SizeTiered::default()),
+        Box::new(Fifo::new(u64::MAX, None)),
+        Box::new(Universal::default()),
+    ];
+
+    let levels = tree.levels.read().expect("lock is poisoned");
+
+    for strategy in strategies {
+        assert_eq!(
+            Choice::DoNothing,
+            strategy.choose(&levels, &tree.config),
+            "{} should do nothing",
+            strategy.get_name(),
+        );
+    }
+}
+
+fn insert_and_remove<T: AbstractTree>(tree: &T) {
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a", seqno.next());
+    }
+    for x in 0..ITEM_COUNT {
+        tree.remove(x.to_be_bytes(), seqno.next());
+    }
+}
+
+#[test]
+fn tree_empty_fresh() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    assert_empty(&tree)?;
+    assert_eq!(0, tree.approximate_len());
+    assert_eq!(0, tree.segment_count());
+    assert_eq!(None, tree.get_highest_seqno());
+    assert_does_nothing(&tree);
+
+    // NOTE: Compacting and flushing an empty tree are no-ops
+    tree.major_compact(u64::MAX, 0)?;
+    assert!(tree.flush_active_memtable(0)?.is_none());
+    assert_eq!(0, tree.segment_count());
+
+    assert_empty(&tree)?;
+
+    Ok(())
+}
+
+#[test]
+fn tree_empty_fresh_reopen() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert_empty(&tree)?;
+    }
+
+    let tree = Config::new(&folder).open()?;
+    assert_empty(&tree)?;
+    assert_does_nothing(&tree);
+
+    Ok(())
+}
+
+#[test]
+fn tree_empty_tombstones_memtable() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    insert_and_remove(&tree);
+    assert_empty(&tree)?;
+    assert_does_nothing(&tree);
+
+    Ok(())
+}
+
+#[test]
+fn tree_empty_tombstones_segment() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    insert_and_remove(&tree);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.segment_count());
+
+    assert_empty(&tree)?;
+    assert_does_nothing(&tree);
+
+    Ok(())
+}
+
+#[test]
+fn tree_empty_tombstones_shadowing_segment() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Tombstones in the memtable shadow the values in the segment
+    for x in 0..ITEM_COUNT {
+        tree.remove(x.to_be_bytes(), seqno.next());
+    }
+    assert_empty(&tree)?;
+
+    tree.flush_active_memtable(0)?;
+    assert_empty(&tree)?;
+
+    // NOTE: Major compaction drops all tombstones and shadowed values
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(0, tree.segment_count());
+    assert_empty(&tree)?;
+    assert_does_nothing(&tree);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_empty() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+
+    assert_empty(&tree)?;
+
+    insert_and_remove(&tree);
+    assert_empty(&tree)?;
+
+    tree.flush_active_memtable(0)?;
+    assert_empty(&tree)?;
+
+    Ok(())
+}
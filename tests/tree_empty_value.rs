@@ -0,0 +1,127 @@
+use lsm_tree::{AbstractTree, AnyTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+fn check(tree: &AnyTree) -> lsm_tree::Result<()> {
+    // NOTE: An empty value is still a value...
+    assert_eq!(Some(b"" as &[u8]), tree.get("empty", None)?.as_deref());
+    assert!(tree.contains_key("empty", None)?);
+    assert_eq!(Some(0), tree.size_of("empty", None)?);
+
+    // NOTE: ...but a tombstone is not
+    assert!(tree.get("deleted", None)?.is_none());
+    assert!(!tree.contains_key("deleted", None)?);
+    assert!(tree.size_of("deleted", None)?.is_none());
+
+    assert_eq!(
+        Some(b"" as &[u8]),
+        tree.get("overwritten", None)?.as_deref()
+    );
+
+    let items = tree
+        .iter(None, None)
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(2, items.len());
+    assert_eq!(
+        (&*items[0].0, &*items[0].1),
+        (b"empty" as &[u8], b"" as &[u8])
+    );
+    assert_eq!(
+        (&*items[1].0, &*items[1].1),
+        (b"overwritten" as &[u8], b"" as &[u8])
+    );
+
+    assert_eq!(2, tree.iter(None, None).rev().count());
+    assert_eq!(2, tree.len(None, None)?);
+    assert_eq!(1, tree.prefix("empty", None, None).count());
+
+    Ok(())
+}
+
+fn fill(tree: &AnyTree, seqno: &SequenceNumberCounter) {
+    tree.insert("empty", [], seqno.next());
+
+    tree.insert("deleted", [], seqno.next());
+    tree.remove("deleted", seqno.next());
+
+    tree.insert("overwritten", "abc", seqno.next());
+    tree.insert("overwritten", [], seqno.next());
+}
+
+fn flush(tree: &AnyTree) -> lsm_tree::Result<()> {
+    match tree {
+        AnyTree::Standard(tree) => tree.flush_active_memtable(0)?,
+        AnyTree::Blob(tree) => tree.flush_active_memtable(0)?,
+    };
+    Ok(())
+}
+
+#[test]
+fn tree_empty_value() -> lsm_tree::Result<()> {
+    // NOTE: (blob tree, separation threshold)
+    // A threshold of 0 moves even empty values into blob files
+    for (blob, threshold) in [(false, 0), (true, 4_096), (true, 0)] {
+        let folder = tempfile::tempdir()?;
+        let seqno = SequenceNumberCounter::default();
+
+        let open = || -> lsm_tree::Result<AnyTree> {
+            let config = Config::new(&folder).blob_file_separation_threshold(threshold);
+
+            Ok(if blob {
+                AnyTree::Blob(config.open_as_blob_tree()?)
+            } else {
+                AnyTree::Standard(config.open()?)
+            })
+        };
+
+        {
+            let tree = open()?;
+            fill(&tree, &seqno);
+            check(&tree)?;
+
+            flush(&tree)?;
+            check(&tree)?;
+
+            if let AnyTree::Blob(tree) = &tree {
+                assert_eq!(threshold == 0, tree.blobs.segment_count() > 0);
+            }
+
+            // NOTE: Evicts the tombstone, but keeps the empty value
+            tree.major_compact(u64::MAX, seqno.get())?;
+            check(&tree)?;
+        }
+
+        let tree = open()?;
+        check(&tree)?;
+
+        // NOTE: Empty values survive being merged with newer data
+        tree.insert("other", "abc", seqno.next());
+        flush(&tree)?;
+        tree.major_compact(u64::MAX, seqno.get())?;
+        assert_eq!(Some(b"" as &[u8]), tree.get("empty", None)?.as_deref());
+        assert!(tree.get("deleted", None)?.is_none());
+        assert_eq!(3, tree.len(None, None)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_empty_value_weak_tombstone() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", [], 0);
+    tree.insert("a", [], 1);
+    tree.remove_weak("a", 2);
+    assert!(tree.get("a", None)?.is_none());
+    assert_eq!(Some(b"" as &[u8]), tree.get("a", Some(2))?.as_deref());
+
+    tree.flush_active_memtable(0)?;
+    assert!(tree.get("a", None)?.is_none());
+    assert_eq!(Some(b"" as &[u8]), tree.get("a", Some(2))?.as_deref());
+
+    tree.major_compact(u64::MAX, 3)?;
+    assert!(tree.get("a", None)?.is_none());
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use lsm_tree::{AbstractTree, Config, ExportFormat, SequenceNumberCounter};
+use std::path::Path;
+use test_log::test;
+
+fn read_export(path: &Path) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let bytes = std::fs::read(path).unwrap();
+
+    let (payload, checksum) = bytes.split_at(bytes.len() - 8);
+    assert_eq!(
+        xxhash_rust::xxh3::xxh3_64(payload),
+        u64::from_be_bytes(checksum.try_into().unwrap())
+    );
+
+    assert_eq!(b"LSMX", &payload[0..4]);
+    assert_eq!(1, payload[4]);
+
+    let (entries, item_count) = payload.split_at(payload.len() - 8);
+    let item_count = u64::from_be_bytes(item_count.try_into().unwrap());
+
+    let mut items = vec![];
+    let mut pos = 5;
+
+    while pos < entries.len() {
+        let key_len = u16::from_be_bytes(entries[pos..pos + 2].try_into().unwrap()) as usize;
+        pos += 2;
+        let key = entries[pos..pos + key_len].to_vec();
+        pos += key_len;
+
+        let value_len = u32::from_be_bytes(entries[pos..pos + 4].try_into().unwrap()) as usize;
+        pos += 4;
+        let value = entries[pos..pos + value_len].to_vec();
+        pos += value_len;
+
+        items.push((key, value));
+    }
+
+    assert_eq!(item_count, items.len() as u64);
+
+    items
+}
+
+#[test]
+fn tree_export_sorted() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let export_path = folder.path().join("export.lsmx");
+
+    let tree = Config::new(folder.path().join("tree")).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    tree.insert("c", "old", seqno.next());
+    tree.insert("a", "a", seqno.next());
+    tree.insert("d", "d", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("c", "new", seqno.next());
+    tree.remove("d", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("b", "b", seqno.next());
+
+    let count = tree.export_sorted(&export_path, ExportFormat::Native)?;
+    assert_eq!(3, count);
+
+    assert_eq!(
+        vec![
+            (b"a".to_vec(), b"a".to_vec()),
+            (b"b".to_vec(), b"b".to_vec()),
+            (b"c".to_vec(), b"new".to_vec()),
+        ],
+        read_export(&export_path),
+    );
+
+    assert!(!folder.path().join("export.lsmx.tmp").try_exists()?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_export_sorted_empty() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let export_path = folder.path().join("export.lsmx");
+
+    let tree = Config::new(folder.path().join("tree")).open()?;
+
+    assert_eq!(
+        0,
+        tree.export_sorted(&export_path, ExportFormat::default())?
+    );
+    assert!(read_export(&export_path).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_export_sorted() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let export_path = folder.path().join("export.lsmx");
+
+    let tree = Config::new(folder.path().join("tree")).open_as_blob_tree()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let big_value = b"neptune!".repeat(2_000);
+
+    tree.insert("a", &big_value, seqno.next());
+    tree.insert("b", "small", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    assert_eq!(2, tree.export_sorted(&export_path, ExportFormat::Native)?);
+
+    assert_eq!(
+        vec![
+            (b"a".to_vec(), big_value),
+            (b"b".to_vec(), b"small".to_vec()),
+        ],
+        read_export(&export_path),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use lsm_tree::{compaction::Fifo, AbstractTree, Config, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+const BATCH_SIZE: u64 = 100;
+
+#[test]
+fn tree_fifo_drops_oldest_segments() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for batch in 0..4u64 {
+        for x in (batch * BATCH_SIZE)..((batch + 1) * BATCH_SIZE) {
+            tree.insert(x.to_be_bytes(), "a".repeat(100), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+    assert_eq!(4, tree.segment_count());
+
+    let segment_size = tree.disk_space() / 4;
+    let segment_ids_before = tree
+        .levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .map(|x| x.id())
+        .collect::<Vec<_>>();
+
+    // NOTE: Keep room for roughly two segments
+    let strategy = Arc::new(Fifo::new(segment_size * 2 + segment_size / 2, None));
+    tree.compact(strategy.clone(), 0)?;
+    assert_eq!(2, tree.segment_count());
+
+    // NOTE: Segments are only dropped, never rewritten
+    for segment in tree.levels.read().expect("lock is poisoned").iter() {
+        assert!(segment_ids_before.contains(&segment.id()));
+    }
+
+    // NOTE: The oldest data is gone, the newest data is still there
+    assert!(tree.get(0u64.to_be_bytes(), None)?.is_none());
+    assert!(tree
+        .get((2 * BATCH_SIZE - 1).to_be_bytes(), None)?
+        .is_none());
+    assert!(tree.get((2 * BATCH_SIZE).to_be_bytes(), None)?.is_some());
+    assert!(tree
+        .get((4 * BATCH_SIZE - 1).to_be_bytes(), None)?
+        .is_some());
+    assert_eq!(2 * BATCH_SIZE as usize, tree.len(None, None)?);
+
+    // NOTE: Below the limit, nothing happens
+    tree.compact(strategy, 0)?;
+    assert_eq!(2, tree.segment_count());
+
+    Ok(())
+}
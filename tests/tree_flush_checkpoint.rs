@@ -0,0 +1,75 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_flush_and_checkpoint() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert_eq!(None, tree.flush_and_checkpoint(0)?);
+
+        for x in 0..10u64 {
+            tree.insert(x.to_be_bytes(), "a", seqno.next());
+        }
+        assert_eq!(Some(9), tree.flush_and_checkpoint(0)?);
+
+        // NOTE: Nothing new to flush
+        assert_eq!(Some(9), tree.flush_and_checkpoint(0)?);
+
+        tree.insert("a", "a", seqno.next());
+        assert_eq!(Some(10), tree.flush_and_checkpoint(0)?);
+    }
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert_eq!(Some(10), tree.get_highest_persisted_seqno());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_flush_and_checkpoint_pending_sealed_memtable() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "a", seqno.next());
+    assert_eq!(Some(0), tree.flush_and_checkpoint(0)?);
+
+    // NOTE: Another thread is flushing this memtable
+    tree.insert("b", "b", seqno.next());
+    let (old_id, old_memtable) = tree.rotate_memtable().expect("should rotate");
+
+    tree.insert("c", "c", seqno.next());
+
+    // NOTE: The older memtable is not durable yet, so seqno 1 and 2 are not either
+    assert_eq!(Some(0), tree.flush_and_checkpoint(0)?);
+
+    let old_segment = tree
+        .flush_memtable(old_id, &old_memtable, 0)?
+        .expect("should flush");
+    tree.register_segments(&[old_segment])?;
+
+    assert_eq!(Some(2), tree.flush_and_checkpoint(0)?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_flush_and_checkpoint() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    assert_eq!(None, tree.flush_and_checkpoint(0)?);
+
+    tree.insert("a", "a".repeat(10_000), 0);
+    tree.insert("b", "b", 1);
+    assert_eq!(Some(1), tree.flush_and_checkpoint(0)?);
+    assert_eq!(1, tree.blob_file_count());
+
+    Ok(())
+}
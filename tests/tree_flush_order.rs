@@ -0,0 +1,135 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_flush_order_out_of_order_is_held_back() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        tree.insert("a", "old", seqno.next());
+        let (old_id, old_memtable) = tree.rotate_memtable().expect("should rotate");
+
+        tree.insert("a", "new", seqno.next());
+        tree.insert("b", "new", seqno.next());
+        let (new_id, new_memtable) = tree.rotate_memtable().expect("should rotate");
+
+        // NOTE: Flush newer memtable first
+        let new_segment = tree
+            .flush_memtable(new_id, &new_memtable, 0)?
+            .expect("should flush");
+        tree.register_segments(&[new_segment])?;
+
+        // Newer memtable must not reach disk before the older one
+        assert_eq!(0, tree.segment_count());
+        assert_eq!(2, tree.sealed_memtable_count());
+        assert_eq!(&*tree.get("a", None)?.unwrap(), b"new");
+        assert_eq!(&*tree.get("b", None)?.unwrap(), b"new");
+
+        let old_segment = tree
+            .flush_memtable(old_id, &old_memtable, 0)?
+            .expect("should flush");
+        tree.register_segments(&[old_segment])?;
+
+        assert_eq!(2, tree.segment_count());
+        assert_eq!(0, tree.sealed_memtable_count());
+        assert_eq!(&*tree.get("a", None)?.unwrap(), b"new");
+    }
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert_eq!(2, tree.segment_count());
+        assert_eq!(&*tree.get("a", None)?.unwrap(), b"new");
+        assert_eq!(&*tree.get("b", None)?.unwrap(), b"new");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_flush_order_crash_leaves_seqno_prefix() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        tree.insert("a", "a", seqno.next());
+        let (_old_id, _old_memtable) = tree.rotate_memtable().expect("should rotate");
+
+        tree.insert("b", "b", seqno.next());
+        let (new_id, new_memtable) = tree.rotate_memtable().expect("should rotate");
+
+        let new_segment = tree
+            .flush_memtable(new_id, &new_memtable, 0)?
+            .expect("should flush");
+        tree.register_segments(&[new_segment])?;
+
+        // NOTE: Simulate crash before the older memtable is flushed
+    }
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        // The newer memtable was never committed, so nothing is exposed
+        assert_eq!(0, tree.segment_count());
+        assert!(tree.get("b", None)?.is_none());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_flush_order_in_order() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder).open()?;
+
+    let mut sealed = vec![];
+
+    for key in ["a", "b", "c"] {
+        tree.insert(key, key, seqno.next());
+        sealed.push(tree.rotate_memtable().expect("should rotate"));
+    }
+
+    for (idx, (id, memtable)) in sealed.into_iter().enumerate() {
+        let segment = tree
+            .flush_memtable(id, &memtable, 0)?
+            .expect("should flush");
+        tree.register_segments(&[segment])?;
+
+        assert_eq!(idx + 1, tree.segment_count());
+        assert_eq!(2 - idx, tree.sealed_memtable_count());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_flush_order_close() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        tree.insert("a", "old", seqno.next());
+        tree.rotate_memtable().expect("should rotate");
+
+        tree.insert("a", "new", seqno.next());
+        tree.close()?;
+
+        assert_eq!(0, tree.sealed_memtable_count());
+        assert_eq!(2, tree.segment_count());
+    }
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert_eq!(&*tree.get("a", None)?.unwrap(), b"new");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use lsm_tree::{
+    AbstractTree, Config, DefaultFlushPolicy, FlushPolicy, MemtableStats, SequenceNumberCounter,
+};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use test_log::test;
+
+struct SentinelPolicy;
+
+impl FlushPolicy for SentinelPolicy {
+    fn should_flush(&self, stats: &MemtableStats) -> bool {
+        stats.key == b"$flush"
+    }
+}
+
+struct PressurePolicy {
+    pressure: AtomicBool,
+    limits: DefaultFlushPolicy,
+}
+
+impl FlushPolicy for PressurePolicy {
+    fn should_flush(&self, stats: &MemtableStats) -> bool {
+        self.pressure.load(Ordering::Relaxed) || self.limits.should_flush(stats)
+    }
+}
+
+#[test]
+fn tree_flush_policy_sentinel_key() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .flush_policy(Arc::new(SentinelPolicy))
+        .open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..100u64 {
+        tree.insert(x.to_be_bytes(), "a", seqno.next());
+    }
+    assert_eq!(0, tree.sealed_memtable_count());
+
+    tree.insert("$flush", "", seqno.next());
+    assert_eq!(1, tree.sealed_memtable_count());
+    assert_eq!(0, tree.active_memtable_size());
+
+    // NOTE: Tombstones are writes, too
+    tree.remove("$flush", seqno.next());
+    assert_eq!(2, tree.sealed_memtable_count());
+
+    let segments = tree.flush_sealed_memtables(0)?;
+    assert_eq!(2, segments.len());
+    assert_eq!(100, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_flush_policy_overrides_limits() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .max_memtable_entries(1)
+        .flush_policy(Arc::new(SentinelPolicy))
+        .open()?;
+
+    for x in 0..10u64 {
+        tree.insert(x.to_be_bytes(), "a", x);
+    }
+    assert_eq!(0, tree.sealed_memtable_count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_flush_policy_external_pressure() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let policy = Arc::new(PressurePolicy {
+        pressure: AtomicBool::new(false),
+        limits: DefaultFlushPolicy {
+            max_size: None,
+            max_entries: Some(10),
+        },
+    });
+
+    let tree = Config::new(&folder)
+        .flush_policy(policy.clone())
+        .open_as_blob_tree()?;
+
+    for x in 0..5u64 {
+        tree.insert(x.to_be_bytes(), "a", x);
+    }
+    assert_eq!(0, tree.sealed_memtable_count());
+
+    policy.pressure.store(true, Ordering::Relaxed);
+    tree.insert(5u64.to_be_bytes(), "a", 5);
+    assert_eq!(1, tree.sealed_memtable_count());
+
+    policy.pressure.store(false, Ordering::Relaxed);
+    for x in 6..16u64 {
+        tree.insert(x.to_be_bytes(), "a", x);
+    }
+    assert_eq!(2, tree.sealed_memtable_count());
+
+    assert_eq!(2, tree.flush_sealed_memtables(0)?.len());
+    assert_eq!(16, tree.len(None, None)?);
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use lsm_tree::{AbstractTree, Cache, CachePolicy, Config, SequenceNumberCounter};
+use std::{path::Path, sync::Arc};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 100;
+
+fn open(path: &Path, cache: Arc<Cache>) -> lsm_tree::Result<lsm_tree::Tree> {
+    Config::new(path)
+        .data_block_size(1_024)
+        .use_cache(cache)
+        .open()
+}
+
+#[test]
+fn tree_get_cache_policy() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = open(folder.path(), Arc::new(Cache::with_capacity_bytes(0)))?;
+        let seqno = SequenceNumberCounter::default();
+
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), "a".repeat(200), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let cache = Arc::new(Cache::with_capacity_bytes(1_024 * 1_024));
+    let tree = open(folder.path(), cache.clone())?;
+
+    for x in 0..ITEM_COUNT {
+        let item = tree.get_with_cache_policy(x.to_be_bytes(), None, CachePolicy::Read)?;
+        assert!(item.is_some());
+
+        let item = tree.get_with_cache_policy(x.to_be_bytes(), Some(1_000), CachePolicy::Read)?;
+        assert!(item.is_some());
+    }
+
+    // NOTE: Read-only lookups do not populate the cache
+    assert!(cache.is_empty());
+
+    // NOTE: The default policy populates the cache
+    assert!(tree.get(0u64.to_be_bytes(), None)?.is_some());
+    assert!(!cache.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn tree_get_cache_policy_two_level_index() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = open(folder.path(), Arc::new(Cache::with_capacity_bytes(0)))?;
+        let seqno = SequenceNumberCounter::default();
+
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), "a".repeat(200), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: The last level uses a two-level block index
+        tree.major_compact(u64::MAX, 0)?;
+        assert_eq!(Some(0), tree.level_segment_count(0));
+    }
+
+    let cache = Arc::new(Cache::with_capacity_bytes(1_024 * 1_024));
+    let tree = open(folder.path(), cache.clone())?;
+
+    for x in 0..ITEM_COUNT {
+        let item = tree.get_with_cache_policy(x.to_be_bytes(), None, CachePolicy::Read)?;
+        assert!(item.is_some());
+    }
+    assert!(cache.is_empty());
+
+    Ok(())
+}
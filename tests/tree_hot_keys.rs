@@ -0,0 +1,87 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_hot_keys_disabled() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    tree.insert("a", "a", 0);
+
+    for _ in 0..100 {
+        tree.get("a", None)?;
+    }
+
+    assert!(tree.hot_keys(10).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn tree_hot_keys() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).track_hot_keys(1.0).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..1_000u64 {
+        tree.insert(x.to_be_bytes(), "a", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    for x in 0..1_000u64 {
+        tree.get(x.to_be_bytes(), None)?;
+        tree.get(7u64.to_be_bytes(), None)?;
+
+        if x % 2 == 0 {
+            tree.range(3u64.to_be_bytes().., None, None)
+                .next()
+                .transpose()?;
+        }
+        if x % 4 == 0 {
+            tree.prefix("pre", None, None).next().transpose()?;
+        }
+    }
+
+    let hot_keys = tree.hot_keys(3);
+    assert_eq!(3, hot_keys.len());
+
+    assert_eq!(&*hot_keys[0].0, 7u64.to_be_bytes());
+    assert!(hot_keys[0].1 >= 1_000);
+
+    assert_eq!(&*hot_keys[1].0, 3u64.to_be_bytes());
+    assert!(hot_keys[1].1 >= 500);
+
+    assert_eq!(&*hot_keys[2].0, b"pre");
+    assert!(hot_keys[2].1 >= 250);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_hot_keys() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .track_hot_keys(0.5)
+        .open_as_blob_tree()?;
+
+    let big_value = b"neptune!".repeat(2_000);
+    tree.insert("big", &big_value, 0);
+    tree.insert("small", "small", 1);
+    tree.flush_active_memtable(0)?;
+
+    for _ in 0..100 {
+        tree.get("big", None)?;
+    }
+    for _ in 0..10 {
+        tree.get("small", None)?;
+    }
+
+    let hot_keys = tree.hot_keys(10);
+    assert_eq!(2, hot_keys.len());
+    assert_eq!(&*hot_keys[0].0, b"big");
+    assert_eq!(&*hot_keys[1].0, b"small");
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use lsm_tree::{
+    segment::writer::{Options, Writer},
+    AbstractTree, Config, Error, ImportValidation, InternalValue, SegmentId, Tree, ValueType,
+};
+use test_log::test;
+
+fn write_external_segment(tree: &Tree, keys: &[&str]) -> lsm_tree::Result<SegmentId> {
+    let segment_id = tree.get_next_segment_id();
+
+    let mut writer = Writer::new(Options {
+        folder: tree.tree_config().path.join("segments"),
+        data_block_size: 4_096,
+        index_block_size: 4_096,
+        target_item_count: None,
+        segment_id,
+    })?;
+
+    for key in keys {
+        writer.write(InternalValue::from_components(
+            *key,
+            *key,
+            0,
+            ValueType::Value,
+        ))?;
+    }
+
+    writer.finish()?.expect("should write segment");
+
+    Ok(segment_id)
+}
+
+#[test]
+fn tree_import_segment() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        let segment_id = write_external_segment(&tree, &["a", "b", "c"])?;
+        tree.import_segment(segment_id, 1, ImportValidation::Full)?;
+
+        assert_eq!(Some(1), tree.level_segment_count(1));
+        assert_eq!(3, tree.len(None, None)?);
+        assert_eq!(&*tree.get("b", None)?.unwrap(), b"b");
+
+        // NOTE: Disjoint from the first segment
+        let segment_id = write_external_segment(&tree, &["d", "e"])?;
+        tree.import_segment(segment_id, 1, ImportValidation::KeyRange)?;
+
+        assert_eq!(Some(2), tree.level_segment_count(1));
+        assert_eq!(5, tree.len(None, None)?);
+
+        // NOTE: Following segments should not collide with imported ones
+        assert!(tree.get_next_segment_id() > segment_id);
+    }
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert_eq!(Some(2), tree.level_segment_count(1));
+        assert_eq!(5, tree.len(None, None)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_import_segment_overlap() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    let segment_id = write_external_segment(&tree, &["a", "c"])?;
+    tree.import_segment(segment_id, 1, ImportValidation::KeyRange)?;
+
+    let segment_id = write_external_segment(&tree, &["b", "d"])?;
+    assert!(matches!(
+        tree.import_segment(segment_id, 1, ImportValidation::KeyRange),
+        Err(Error::OverlapViolation(id)) if id == segment_id,
+    ));
+    assert_eq!(Some(1), tree.level_segment_count(1));
+    assert!(!tree.contains_key("d", None)?);
+
+    // NOTE: L0 may contain overlapping segments
+    tree.import_segment(segment_id, 0, ImportValidation::KeyRange)?;
+    assert_eq!(Some(1), tree.level_segment_count(0));
+    assert!(tree.contains_key("d", None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_import_segment_unsorted() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    // NOTE: Declared key range is [c, b], which cannot be right
+    let segment_id = write_external_segment(&tree, &["c", "a", "b"])?;
+    assert!(matches!(
+        tree.import_segment(segment_id, 1, ImportValidation::KeyRange),
+        Err(Error::OverlapViolation(_)),
+    ));
+
+    // NOTE: Declared key range is [a, b], but "c" is out of range and out of order,
+    // which only a full scan can detect
+    let segment_id = write_external_segment(&tree, &["a", "c", "b"])?;
+    assert!(matches!(
+        tree.import_segment(segment_id, 1, ImportValidation::Full),
+        Err(Error::OverlapViolation(_)),
+    ));
+
+    assert_eq!(0, tree.segment_count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_import_segment_invalid_level() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).level_count(7).open()?;
+
+    let segment_id = write_external_segment(&tree, &["a", "b"])?;
+    assert!(matches!(
+        tree.import_segment(segment_id, 7, ImportValidation::KeyRange),
+        Err(Error::InvalidLevel(7)),
+    ));
+    assert_eq!(0, tree.segment_count());
+
+    tree.import_segment(segment_id, 6, ImportValidation::KeyRange)?;
+    assert_eq!(Some(1), tree.level_segment_count(6));
+
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use lsm_tree::{AbstractTree, Cache, Config, SequenceNumberCounter};
+use std::{path::Path, sync::Arc};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 100;
+
+fn key(x: u64) -> String {
+    format!("{:0>4}", x * 10)
+}
+
+fn open(
+    path: &Path,
+    index_start_keys: bool,
+    cache: Arc<Cache>,
+) -> lsm_tree::Result<lsm_tree::Tree> {
+    Config::new(path)
+        .data_block_size(1_024)
+        .bloom_bits_per_key(-1)
+        .index_start_keys(index_start_keys)
+        .use_cache(cache)
+        .open()
+}
+
+fn fill(tree: &lsm_tree::Tree) -> lsm_tree::Result<()> {
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(key(x), "a".repeat(200), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    Ok(())
+}
+
+/// Returns how many lookups of keys in between two written keys had to load a data block.
+fn count_block_loads(path: &Path, index_start_keys: bool) -> lsm_tree::Result<usize> {
+    let mut block_loads = 0;
+
+    // NOTE: Keys after the last key are filtered by the segment's key range
+    for x in 0..(ITEM_COUNT - 1) {
+        let cache = Arc::new(Cache::with_capacity_bytes(1_024 * 1_024));
+        let tree = open(path, index_start_keys, cache.clone())?;
+
+        assert!(tree.get(format!("{:0>4}", x * 10 + 5), None)?.is_none());
+
+        if !cache.is_empty() {
+            block_loads += 1;
+        }
+    }
+
+    Ok(block_loads)
+}
+
+#[test]
+fn tree_index_start_keys_negative_lookups() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let cache = Arc::new(Cache::with_capacity_bytes(1_024 * 1_024));
+
+    {
+        let tree = open(folder.path(), false, cache.clone())?;
+        fill(&tree)?;
+    }
+
+    // NOTE: Without start keys, every lookup needs to load a block
+    assert_eq!(
+        ITEM_COUNT as usize - 1,
+        count_block_loads(folder.path(), false)?
+    );
+
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = open(folder.path(), true, cache)?;
+        fill(&tree)?;
+    }
+
+    // NOTE: Keys that fall in between two blocks are answered by the index
+    let block_loads = count_block_loads(folder.path(), true)?;
+    assert!(block_loads < ITEM_COUNT as usize - 1, "{block_loads}");
+
+    Ok(())
+}
+
+#[test]
+fn tree_index_start_keys_reads() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let cache = Arc::new(Cache::with_capacity_bytes(1_024 * 1_024));
+
+    let tree = open(folder.path(), true, cache)?;
+    fill(&tree)?;
+
+    let check = |tree: &lsm_tree::Tree| -> lsm_tree::Result<()> {
+        for x in 0..ITEM_COUNT {
+            assert!(tree.get(key(x), None)?.is_some());
+            assert!(tree.get(format!("{:0>4}", x * 10 + 5), None)?.is_none());
+        }
+        assert!(tree.get("", None)?.is_none());
+
+        assert_eq!(ITEM_COUNT as usize, tree.iter(None, None).count());
+        assert_eq!(ITEM_COUNT as usize, tree.iter(None, None).rev().count());
+        assert_eq!(10, tree.range(key(10)..key(20), None, None).count());
+
+        Ok(())
+    };
+
+    // NOTE: L0 uses a full block index
+    check(&tree)?;
+
+    // NOTE: The last level uses a two-level block index
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(Some(0), tree.level_segment_count(0));
+    check(&tree)?;
+    drop(tree);
+
+    let tree = open(folder.path(), true, Arc::new(Cache::with_capacity_bytes(0)))?;
+    check(&tree)?;
+
+    Ok(())
+}
+
+#[test]
+fn tree_index_start_keys_mixed_segments() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let cache = Arc::new(Cache::with_capacity_bytes(1_024 * 1_024));
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = open(folder.path(), false, cache.clone())?;
+
+        for x in 0..ITEM_COUNT {
+            tree.insert(key(x), "old".repeat(50), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let tree = open(folder.path(), true, cache)?;
+
+    for x in (0..ITEM_COUNT).step_by(2) {
+        tree.insert(key(x), "new".repeat(50), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(2, tree.segment_count());
+
+    for x in 0..ITEM_COUNT {
+        let value = tree.get(key(x), None)?.expect("should exist");
+        let expected = if x % 2 == 0 { "new" } else { "old" };
+        assert_eq!(&*value, expected.repeat(50).as_bytes());
+    }
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+
+    Ok(())
+}
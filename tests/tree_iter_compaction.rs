@@ -0,0 +1,158 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use test_log::test;
+
+const ITEM_COUNT: usize = 1_000;
+
+fn segment_file_count(folder: &std::path::Path) -> lsm_tree::Result<usize> {
+    Ok(std::fs::read_dir(folder.join("segments"))?.count())
+}
+
+#[test]
+fn tree_iter_survives_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).data_block_size(1_024).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for batch in 0..4u64 {
+        for x in (batch..ITEM_COUNT as u64).step_by(4) {
+            tree.insert(x.to_be_bytes(), "old", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+    assert_eq!(4, tree.segment_count());
+    assert_eq!(4, segment_file_count(folder.path())?);
+
+    let mut forwards = tree.iter(None, None);
+    let mut backwards = tree.iter(None, None).rev();
+    assert!(forwards.next().is_some());
+    assert!(backwards.next().is_some());
+
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), "new", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(1, tree.segment_count());
+
+    // NOTE: Superseded segments stay on disk while iterators pin them
+    assert_eq!(5, segment_file_count(folder.path())?);
+
+    let mut count = 1;
+    for item in forwards.by_ref() {
+        let (_, value) = item?;
+        assert_eq!(&*value, b"old");
+        count += 1;
+    }
+    assert_eq!(ITEM_COUNT, count);
+
+    let mut count = 1;
+    for item in backwards.by_ref() {
+        let (_, value) = item?;
+        assert_eq!(&*value, b"old");
+        count += 1;
+    }
+    assert_eq!(ITEM_COUNT, count);
+
+    drop(forwards);
+    drop(backwards);
+    assert_eq!(1, segment_file_count(folder.path())?);
+
+    assert!(tree
+        .iter(None, None)
+        .all(|item| &*item.expect("should read").1 == b"new"));
+
+    Ok(())
+}
+
+#[test]
+fn tree_iter_compaction_stress() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).data_block_size(1_024).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), 0u64.to_be_bytes(), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    let stop = Arc::new(AtomicBool::default());
+
+    let readers = (0..4)
+        .map(|_| {
+            let tree = tree.clone();
+            let seqno = seqno.clone();
+            let stop = stop.clone();
+
+            std::thread::spawn(move || -> lsm_tree::Result<usize> {
+                let mut rounds = 0;
+
+                while !stop.load(Ordering::Relaxed) {
+                    let snapshot_seqno = seqno.get();
+                    let mut iter = tree.iter(Some(snapshot_seqno), None);
+
+                    let mut prev_key = None;
+                    let mut version = None;
+                    let mut count = 0;
+
+                    while let Some(item) = iter.next() {
+                        let (key, value) = item?;
+
+                        // NOTE: Keys stay sorted and unique
+                        assert!(prev_key.as_ref() < Some(&key));
+                        prev_key = Some(key);
+
+                        // NOTE: Rounds overwrite all keys, and every round is flushed at once,
+                        // so a snapshot sees (at most) two consecutive versions
+                        let value = u64::from_be_bytes((*value).try_into().expect("should be u64"));
+                        let lowest = *version.get_or_insert(value);
+                        assert!(value == lowest || value == lowest + 1 || value + 1 == lowest);
+
+                        count += 1;
+
+                        if count % 100 == 0 {
+                            std::thread::yield_now();
+                        }
+                    }
+
+                    assert_eq!(ITEM_COUNT, count);
+                    rounds += 1;
+                }
+
+                Ok(rounds)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for round in 1..=20u64 {
+        let next_seqno = seqno.next();
+        for x in 0..ITEM_COUNT as u64 {
+            tree.insert(x.to_be_bytes(), round.to_be_bytes(), next_seqno);
+        }
+        tree.flush_active_memtable(0)?;
+
+        if round % 2 == 0 {
+            tree.major_compact(u64::MAX, 0)?;
+        } else {
+            tree.compact(Arc::new(lsm_tree::compaction::Leveled::default()), 0)?;
+        }
+    }
+
+    stop.store(true, Ordering::Relaxed);
+
+    for reader in readers {
+        let rounds = reader.join().expect("should join")?;
+        assert!(rounds > 0);
+    }
+
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(1, segment_file_count(folder.path())?);
+    assert_eq!(ITEM_COUNT, tree.len(None, None)?);
+
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use lsm_tree::{AbstractTree, Config, KvPair, SequenceNumberCounter, Tree};
+use std::collections::BTreeMap;
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+type Model = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// Consumes the iterator from both ends, choosing the end by `pattern`,
+/// and returns the items in key order
+fn consume_interleaved(
+    mut iter: impl DoubleEndedIterator<Item = lsm_tree::Result<KvPair>>,
+    mut pattern: impl FnMut() -> bool,
+) -> lsm_tree::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+    let mut front = vec![];
+    let mut back = vec![];
+
+    loop {
+        let item = if pattern() {
+            iter.next().map(|x| x.map(|kv| (kv, true)))
+        } else {
+            iter.next_back().map(|x| x.map(|kv| (kv, false)))
+        };
+
+        let Some(item) = item else {
+            break;
+        };
+        let ((key, value), is_front) = item?;
+
+        if is_front {
+            front.push((key.to_vec(), value.to_vec()));
+        } else {
+            back.push((key.to_vec(), value.to_vec()));
+        }
+    }
+
+    // NOTE: Once both ends have met, there is nothing left to read from either end
+    assert!(iter.next().is_none());
+    assert!(iter.next_back().is_none());
+
+    back.reverse();
+    front.extend(back);
+    Ok(front)
+}
+
+fn patterns() -> Vec<Box<dyn FnMut() -> bool>> {
+    let mut toggle = false;
+    let mut counter = 0u64;
+    let mut rng = 0x2545_F491_4F6C_DD1Du64;
+
+    vec![
+        Box::new(|| true),
+        Box::new(|| false),
+        Box::new(move || {
+            toggle = !toggle;
+            toggle
+        }),
+        Box::new(move || {
+            counter += 1;
+            counter % 7 < 3
+        }),
+        Box::new(move || {
+            // NOTE: xorshift, so the pattern is deterministic
+            rng ^= rng << 13;
+            rng ^= rng >> 7;
+            rng ^= rng << 17;
+            rng % 2 == 0
+        }),
+    ]
+}
+
+fn assert_iter_matches(tree: &Tree, model: &Model) -> lsm_tree::Result<()> {
+    let expected = model
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect::<Vec<_>>();
+
+    for pattern in patterns() {
+        assert_eq!(
+            expected,
+            consume_interleaved(tree.iter(None, None), pattern)?
+        );
+    }
+
+    let lo = 100u64.to_be_bytes();
+    let hi = 900u64.to_be_bytes();
+    let expected_range = model
+        .range(lo.to_vec()..hi.to_vec())
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect::<Vec<_>>();
+
+    for pattern in patterns() {
+        assert_eq!(
+            expected_range,
+            consume_interleaved(tree.range(lo..hi, None, None), pattern)?,
+        );
+    }
+
+    Ok(())
+}
+
+fn insert(tree: &Tree, model: &mut Model, seqno: &SequenceNumberCounter, x: u64, value: &str) {
+    tree.insert(x.to_be_bytes(), value, seqno.next());
+    model.insert(x.to_be_bytes().to_vec(), value.as_bytes().to_vec());
+}
+
+fn remove(tree: &Tree, model: &mut Model, seqno: &SequenceNumberCounter, x: u64) {
+    tree.remove(x.to_be_bytes(), seqno.next());
+    model.remove(&x.to_be_bytes().to_vec());
+}
+
+#[test]
+fn tree_iter_interleaved_disjoint_segments() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).data_block_size(1_024).open()?;
+    let seqno = SequenceNumberCounter::default();
+    let mut model = Model::new();
+
+    for x in 0..ITEM_COUNT {
+        insert(&tree, &mut model, &seqno, x, "a");
+    }
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Split the data into many small, disjoint segments in the last level
+    tree.major_compact(1_024, 0)?;
+    assert!(tree.segment_count() > 5);
+    assert_iter_matches(&tree, &model)?;
+
+    // NOTE: Memtable shadows some of the segments' items
+    for x in (0..ITEM_COUNT).step_by(3) {
+        insert(&tree, &mut model, &seqno, x, "b");
+    }
+    for x in (0..ITEM_COUNT).step_by(5) {
+        remove(&tree, &mut model, &seqno, x);
+    }
+    assert_iter_matches(&tree, &model)?;
+
+    Ok(())
+}
+
+#[test]
+fn tree_iter_interleaved_overlapping_segments() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).data_block_size(1_024).open()?;
+    let seqno = SequenceNumberCounter::default();
+    let mut model = Model::new();
+
+    // NOTE: Every segment spans the whole key range, so L0 is not disjoint
+    for (step, value) in [(1, "a"), (2, "b"), (3, "c"), (7, "d")] {
+        for x in (0..ITEM_COUNT).step_by(step) {
+            insert(&tree, &mut model, &seqno, x, value);
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    for x in (0..ITEM_COUNT).step_by(11) {
+        remove(&tree, &mut model, &seqno, x);
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(5, tree.level_segment_count(0).unwrap_or_default());
+    assert_iter_matches(&tree, &model)?;
+
+    for x in (0..ITEM_COUNT).step_by(13) {
+        insert(&tree, &mut model, &seqno, x, "e");
+    }
+    assert_iter_matches(&tree, &model)?;
+
+    Ok(())
+}
+
+#[test]
+fn tree_iter_interleaved_mixed_levels() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).data_block_size(1_024).open()?;
+    let seqno = SequenceNumberCounter::default();
+    let mut model = Model::new();
+
+    for x in 0..ITEM_COUNT {
+        insert(&tree, &mut model, &seqno, x, "a");
+    }
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(2_048, 0)?;
+
+    // NOTE: Disjoint segments of L0 only cover some key ranges
+    for range in [0..100, 450..550, 900..ITEM_COUNT] {
+        for x in range {
+            insert(&tree, &mut model, &seqno, x, "b");
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    for x in (0..ITEM_COUNT).step_by(4) {
+        remove(&tree, &mut model, &seqno, x);
+    }
+    tree.rotate_memtable().expect("should have sealed memtable");
+
+    for x in (0..ITEM_COUNT).step_by(9) {
+        insert(&tree, &mut model, &seqno, x, "c");
+    }
+    assert_iter_matches(&tree, &model)?;
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter, ValueType};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 100;
+
+#[test]
+fn tree_iter_level() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for level in 0..7 {
+        assert_eq!(0, tree.iter_level(level).expect("should exist").count());
+    }
+    assert!(tree.iter_level(7).is_none());
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "old", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 0)?;
+
+    // NOTE: Two overlapping L0 segments, the newer one shadows the older one
+    for x in 0..(ITEM_COUNT / 2) {
+        tree.insert(x.to_be_bytes(), "mid", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    for x in 0..(ITEM_COUNT / 4) {
+        tree.insert(x.to_be_bytes(), "new", seqno.next());
+    }
+    tree.remove(0u64.to_be_bytes(), seqno.next());
+    tree.flush_active_memtable(0)?;
+    assert_eq!(Some(2), tree.level_segment_count(0));
+
+    // NOTE: The memtable is not part of any level
+    tree.insert(1u64.to_be_bytes(), "memtable", seqno.next());
+
+    let l0 = tree
+        .iter_level(0)
+        .expect("should exist")
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!((ITEM_COUNT / 2) as usize, l0.len());
+
+    assert_eq!(ValueType::Tombstone, l0[0].key.value_type);
+    for item in &l0[1..(ITEM_COUNT / 4) as usize] {
+        assert_eq!(&*item.value, b"new");
+    }
+    for item in &l0[(ITEM_COUNT / 4) as usize..] {
+        assert_eq!(&*item.value, b"mid");
+    }
+
+    // NOTE: Reverse iteration yields the same items
+    let mut l0_rev = tree
+        .iter_level(0)
+        .expect("should exist")
+        .rev()
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    l0_rev.reverse();
+    assert_eq!(l0, l0_rev);
+
+    let l6 = tree
+        .iter_level(6)
+        .expect("should exist")
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(ITEM_COUNT as usize, l6.len());
+    assert!(l6.iter().all(|item| &*item.value == b"old"));
+
+    for level in 1..6 {
+        assert_eq!(0, tree.iter_level(level).expect("should exist").count());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter, ValueType};
+use test_log::test;
+
+#[test]
+fn tree_latest_seqno() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    assert_eq!(None, tree.latest_seqno("a")?);
+
+    tree.insert("a", "a", seqno.next());
+    tree.insert("b", "b", seqno.next());
+    assert_eq!(Some((0, ValueType::Value)), tree.latest_seqno("a")?);
+
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(Some((0, ValueType::Value)), tree.latest_seqno("a")?);
+    assert_eq!(Some((1, ValueType::Value)), tree.latest_seqno("b")?);
+
+    tree.insert("a", "a2", seqno.next());
+    tree.flush_active_memtable(0)?;
+    assert_eq!(Some((2, ValueType::Value)), tree.latest_seqno("a")?);
+
+    tree.remove("a", seqno.next());
+    assert_eq!(Some((3, ValueType::Tombstone)), tree.latest_seqno("a")?);
+
+    tree.rotate_memtable().expect("should have sealed memtable");
+    assert_eq!(Some((3, ValueType::Tombstone)), tree.latest_seqno("a")?);
+
+    tree.remove_weak("b", seqno.next());
+    assert_eq!(Some((4, ValueType::WeakTombstone)), tree.latest_seqno("b")?);
+
+    assert_eq!(None, tree.latest_seqno("c")?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_latest_seqno_detects_conflict() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    tree.insert("a", "a", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    let read_version = tree.latest_seqno("a")?;
+
+    tree.insert("a", "b", seqno.next());
+    assert_ne!(read_version, tree.latest_seqno("a")?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_latest_seqno() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .blob_file_separation_threshold(1)
+        .open_as_blob_tree()?;
+
+    tree.insert("a", "a".repeat(100), 0);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.blob_file_count());
+    assert_eq!(Some((0, ValueType::Value)), tree.latest_seqno("a")?);
+
+    tree.remove("a", 1);
+    assert_eq!(Some((1, ValueType::Tombstone)), tree.latest_seqno("a")?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_latest_seqno_drop_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "a", 0);
+    tree.insert("b", "b", 1);
+    tree.insert("c", "c", 2);
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Covered keys report the range tombstone as their deletion
+    tree.drop_range("a", "c", 3);
+    assert_eq!(Some((3, ValueType::Tombstone)), tree.latest_seqno("a")?);
+    assert_eq!(Some((3, ValueType::Tombstone)), tree.latest_seqno("b")?);
+    assert_eq!(Some((2, ValueType::Value)), tree.latest_seqno("c")?);
+
+    tree.flush_active_memtable(0)?;
+    assert_eq!(Some((3, ValueType::Tombstone)), tree.latest_seqno("b")?);
+
+    // NOTE: Newer writes are not covered
+    tree.insert("b", "b2", 4);
+    assert_eq!(Some((4, ValueType::Value)), tree.latest_seqno("b")?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_latest_seqno_drop_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .blob_file_separation_threshold(1)
+        .open_as_blob_tree()?;
+
+    tree.insert("b", "b".repeat(100), 0);
+    tree.flush_active_memtable(0)?;
+
+    tree.drop_range("a", "c", 1);
+    assert_eq!(Some((1, ValueType::Tombstone)), tree.latest_seqno("b")?);
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use lsm_tree::{compaction::Leveled, AbstractTree, Config, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+#[test]
+fn tree_level_size_targets() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let strategy = Leveled::default();
+
+    let tree = Config::new(&folder).open()?;
+    let targets = tree.level_size_targets(&strategy);
+    assert_eq!(
+        (1..=6).collect::<Vec<_>>(),
+        targets
+            .iter()
+            .map(|(level, _, _)| *level)
+            .collect::<Vec<_>>(),
+    );
+    assert!(targets.iter().all(|(_, _, actual)| *actual == 0));
+    assert_eq!(0, tree.estimate_compaction_debt(&strategy));
+
+    let base = u64::from(strategy.target_size) * u64::from(strategy.l0_threshold);
+    assert_eq!(base, targets[0].1);
+    assert_eq!(base * 10, targets[1].1);
+    assert_eq!(base * 100, targets[2].1);
+
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).level_ratio(2.5).open()?;
+    let targets = tree.level_size_targets(&strategy);
+    assert_eq!(base, targets[0].1);
+    assert_eq!(base * 5 / 2, targets[1].1);
+    assert_eq!(base * 25 / 4, targets[2].1);
+
+    Ok(())
+}
+
+#[test]
+fn tree_level_ratio_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).level_ratio(2.0).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let strategy = Leveled {
+        l0_threshold: 2,
+        target_size: 4_096,
+        ..Default::default()
+    };
+
+    for batch in 0..20u64 {
+        for x in 0..50u64 {
+            let key = (batch * 50 + x).to_be_bytes();
+            tree.insert(key, "a".repeat(100), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+
+        for _ in 0..10 {
+            tree.compact(Arc::new(strategy.clone()), 0)?;
+        }
+    }
+
+    let targets = tree.level_size_targets(&strategy);
+
+    // NOTE: Actual sizes are the sum of the levels' segment sizes
+    let levels = tree.levels.read().expect("lock is poisoned");
+    for (level, _, actual) in &targets {
+        assert_eq!(levels.levels[usize::from(*level)].size(), *actual);
+    }
+    drop(levels);
+
+    // NOTE: Data has been pushed past L1, because the L1 target is small
+    assert!(targets.iter().skip(1).any(|(_, _, actual)| *actual > 0));
+
+    // NOTE: All levels except the last one are within their targets
+    for (level, target, actual) in &targets[..targets.len() - 1] {
+        assert!(
+            actual <= target,
+            "L{level} is over target: {actual} > {target}"
+        );
+    }
+
+    assert_eq!(0, tree.estimate_compaction_debt(&strategy));
+
+    assert_eq!(1_000, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_level_target_size_config() {
+    let config = Config::new("");
+    assert_eq!(100, config.level_target_size(1, 100, 10.0));
+    assert_eq!(1_000, config.level_target_size(2, 100, 10.0));
+    assert_eq!(10_000, config.level_target_size(3, 100, 10.0));
+    assert_eq!(u64::MAX, config.level_target_size(u8::MAX, 100, 10.0));
+
+    // NOTE: The configured level ratio takes precedence
+    let config = Config::new("").level_ratio(2.0);
+    assert_eq!(100, config.level_target_size(1, 100, 10.0));
+    assert_eq!(200, config.level_target_size(2, 100, 10.0));
+    assert_eq!(400, config.level_target_size(3, 100, 10.0));
+}
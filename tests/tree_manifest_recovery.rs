@@ -0,0 +1,137 @@
+use lsm_tree::{AbstractTree, Config};
+use std::path::Path;
+use test_log::test;
+
+fn write_segment(tree: &lsm_tree::Tree, key: &str, seqno: u64) -> lsm_tree::Result<()> {
+    tree.insert(key, key, seqno);
+    tree.flush_active_memtable(0)?;
+    Ok(())
+}
+
+fn levels_path(folder: &Path) -> std::path::PathBuf {
+    folder.join("levels")
+}
+
+fn temp_levels_path(folder: &Path) -> std::path::PathBuf {
+    folder.join("levels.tmp")
+}
+
+#[test]
+fn tree_manifest_recovery_discard_truncated() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let complete_manifest = {
+        let tree = Config::new(&folder).open()?;
+        write_segment(&tree, "a", 0)?;
+        write_segment(&tree, "b", 1)?;
+
+        let before = std::fs::read(levels_path(folder.path()))?;
+        write_segment(&tree, "c", 2)?;
+        let after = std::fs::read(levels_path(folder.path()))?;
+
+        // NOTE: Simulate crash before the rename, the new segment is orphaned
+        std::fs::write(levels_path(folder.path()), before)?;
+
+        after
+    };
+
+    // NOTE: Truncate the temporary manifest at every possible point
+    for len in 0..complete_manifest.len() {
+        std::fs::write(temp_levels_path(folder.path()), &complete_manifest[..len])?;
+
+        let tree = Config::new(&folder).open()?;
+        assert!(!temp_levels_path(folder.path()).try_exists()?);
+
+        assert_eq!(2, tree.segment_count());
+        assert!(tree.contains_key("a", None)?);
+        assert!(tree.contains_key("b", None)?);
+        assert!(!tree.contains_key("c", None)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_manifest_recovery_discard_corrupted() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+        write_segment(&tree, "a", 0)?;
+        write_segment(&tree, "b", 1)?;
+    }
+
+    let mut bytes = std::fs::read(levels_path(folder.path()))?;
+    *bytes.last_mut().expect("should not be empty") ^= 0xFF;
+    std::fs::write(temp_levels_path(folder.path()), bytes)?;
+
+    let tree = Config::new(&folder).open()?;
+    assert!(!temp_levels_path(folder.path()).try_exists()?);
+    assert_eq!(2, tree.segment_count());
+    assert_eq!(2, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_manifest_recovery_complete_write() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+        write_segment(&tree, "a", 0)?;
+        write_segment(&tree, "b", 1)?;
+
+        let before = std::fs::read(levels_path(folder.path()))?;
+        write_segment(&tree, "c", 2)?;
+
+        // NOTE: Simulate crash after the temporary manifest was fsynced, but before the rename
+        std::fs::rename(levels_path(folder.path()), temp_levels_path(folder.path()))?;
+        std::fs::write(levels_path(folder.path()), before)?;
+    }
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert!(!temp_levels_path(folder.path()).try_exists()?);
+
+        assert_eq!(3, tree.segment_count());
+        assert!(tree.contains_key("a", None)?);
+        assert!(tree.contains_key("b", None)?);
+        assert!(tree.contains_key("c", None)?);
+    }
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert_eq!(3, tree.segment_count());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_manifest_recovery_missing_segment() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let segment_count_before = {
+        let tree = Config::new(&folder).open()?;
+        write_segment(&tree, "a", 0)?;
+        write_segment(&tree, "b", 1)?;
+        tree.segment_count()
+    };
+
+    // NOTE: Temporary manifest references a segment that was never written
+    let mut bytes = vec![b'L', b'S', b'M', 2];
+    bytes.push(1); // level count
+    bytes.extend_from_slice(&1u32.to_be_bytes()); // segment count
+    bytes.extend_from_slice(&999u64.to_be_bytes()); // segment ID
+    let checksum = xxhash_rust::xxh3::xxh3_64(&bytes);
+    bytes.extend_from_slice(&checksum.to_be_bytes());
+    std::fs::write(temp_levels_path(folder.path()), bytes)?;
+
+    let tree = Config::new(&folder).open()?;
+    assert!(!temp_levels_path(folder.path()).try_exists()?);
+    assert_eq!(segment_count_before, tree.segment_count());
+    assert_eq!(2, tree.len(None, None)?);
+
+    Ok(())
+}
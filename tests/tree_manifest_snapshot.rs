@@ -0,0 +1,51 @@
+use lsm_tree::{AbstractTree, Config, KeyRange};
+use test_log::test;
+
+#[test]
+fn tree_manifest_snapshot() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(0, tree.manifest_snapshot().segment_count());
+
+    tree.insert("a", "abc", 0);
+    tree.insert("c", "abc", 1);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("b", "abc", 2);
+    tree.remove("d", 3);
+    tree.flush_active_memtable(0)?;
+
+    let snapshot = tree.manifest_snapshot();
+    assert_eq!(7, snapshot.levels.len());
+    assert_eq!(2, snapshot.segment_count());
+    assert_eq!(2, snapshot.levels[0].segments.len());
+    assert!(!snapshot.levels[0].is_disjoint);
+    assert_eq!(tree.disk_space(), snapshot.size());
+
+    // NOTE: L0 segments are ordered from newest to oldest
+    let oldest = &snapshot.levels[0].segments[1];
+    assert_eq!(KeyRange::new(("a".into(), "c".into())), oldest.key_range);
+    assert_eq!((0, 1), oldest.seqnos);
+    assert_eq!(0, oldest.tombstone_count);
+    assert!(!oldest.is_compacting);
+
+    let newest = &snapshot.levels[0].segments[0];
+    assert_eq!(KeyRange::new(("b".into(), "d".into())), newest.key_range);
+    assert_eq!((2, 3), newest.seqnos);
+    assert_eq!(1, newest.tombstone_count);
+
+    // NOTE: The snapshot is not affected by later changes to the tree
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(2, snapshot.segment_count());
+
+    let snapshot = tree.manifest_snapshot();
+    assert_eq!(1, snapshot.segment_count());
+    assert_eq!(1, snapshot.levels[6].segments.len());
+    assert_eq!(
+        snapshot.levels[6].segments[0].id,
+        snapshot.iter().next().unwrap().id,
+    );
+
+    Ok(())
+}
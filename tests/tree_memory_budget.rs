@@ -0,0 +1,54 @@
+use lsm_tree::{AbstractTree, Cache, Config, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+#[test]
+fn tree_memory_budget() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).memory_budget(4_000_000).open()?;
+    let budget = tree.effective_config().expect("should have budget");
+
+    assert_eq!(4_000_000, budget.total);
+    assert_eq!(3_000_000, budget.block_cache);
+    assert_eq!(3_000_000, tree.config.cache.capacity());
+    assert_eq!(0, budget.bloom_filters);
+    assert_eq!(1_000_000, budget.write_buffer);
+
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..10_000u64 {
+        tree.insert(x.to_be_bytes(), "a", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Bloom filters are taken from the memtable share
+    let budget = tree.effective_config().expect("should have budget");
+    assert!(budget.bloom_filters > 0);
+    assert_eq!(tree.bloom_filter_size() as u64, budget.bloom_filters);
+    assert_eq!(
+        budget.total,
+        budget.block_cache + budget.bloom_filters + budget.write_buffer,
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_memory_budget_unset() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    assert!(tree.effective_config().is_none());
+
+    // NOTE: Setting a cache afterwards replaces the memory budget
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .memory_budget(4_000_000)
+        .use_cache(Arc::new(Cache::with_capacity_bytes(1_000)))
+        .open()?;
+    assert!(tree.effective_config().is_none());
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use lsm_tree::{AbstractTree, Config, MemtableKind, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_memtable_coalesce_overwrites() -> lsm_tree::Result<()> {
+    for kind in [MemtableKind::SkipList, MemtableKind::BTree] {
+        let folder = tempfile::tempdir()?;
+
+        let tree = Config::new(&folder)
+            .memtable_kind(kind)
+            .memtable_coalesce_overwrites(true)
+            .open()?;
+
+        let seqno = SequenceNumberCounter::default();
+
+        tree.insert("a", "disk", seqno.next());
+        tree.insert("b", "disk", seqno.next());
+        tree.flush_active_memtable(0)?;
+
+        for x in 0..1_000u32 {
+            tree.insert("a", x.to_be_bytes(), seqno.next());
+        }
+        tree.remove("b", seqno.next());
+
+        // NOTE: 2 items on disk, 2 in the memtable
+        assert_eq!(4, tree.approximate_len());
+        assert!(tree.active_memtable_size() < 1_000);
+
+        assert_eq!(&*tree.get("a", None)?.unwrap(), 999u32.to_be_bytes());
+        assert!(tree.get("b", None)?.is_none());
+        assert_eq!(1, tree.len(None, None)?);
+
+        tree.flush_active_memtable(0)?;
+
+        assert_eq!(&*tree.get("a", None)?.unwrap(), 999u32.to_be_bytes());
+        assert!(tree.get("b", None)?.is_none());
+        assert_eq!(1, tree.len(None, None)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_memtable_keep_versions_by_default() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    for seqno in 0..100 {
+        tree.insert("a", "a", seqno);
+    }
+
+    assert_eq!(100, tree.approximate_len());
+    assert!(tree.get("a", Some(50))?.is_some());
+
+    Ok(())
+}
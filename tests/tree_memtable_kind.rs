@@ -0,0 +1,39 @@
+use lsm_tree::{AbstractTree, Config, MemtableKind, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: usize = 1_000;
+
+#[test]
+fn tree_memtable_kind() -> lsm_tree::Result<()> {
+    for kind in [MemtableKind::SkipList, MemtableKind::BTree] {
+        let folder = tempfile::tempdir()?;
+        let tree = Config::new(&folder).memtable_kind(kind).open()?;
+        let seqno = SequenceNumberCounter::default();
+
+        assert_eq!(kind, tree.lock_active_memtable().kind());
+
+        for x in (0..ITEM_COUNT as u64).rev() {
+            tree.insert(x.to_be_bytes(), x.to_be_bytes(), seqno.next());
+        }
+        tree.remove(0u64.to_be_bytes(), seqno.next());
+
+        assert_eq!(ITEM_COUNT - 1, tree.len(None, None)?);
+        assert_eq!(ITEM_COUNT - 1, tree.iter(None, None).rev().count());
+        assert!(tree.get(0u64.to_be_bytes(), None)?.is_none());
+        assert!(tree.get(1u64.to_be_bytes(), Some(1))?.is_none());
+        assert!(tree.get(1u64.to_be_bytes(), None)?.is_some());
+
+        let (_, memtable) = tree.rotate_memtable().expect("should have data");
+        assert_eq!(kind, memtable.kind());
+        assert_eq!(kind, tree.lock_active_memtable().kind());
+
+        tree.insert("a", "a", seqno.next());
+
+        assert_eq!(ITEM_COUNT, tree.len(None, None)?);
+
+        tree.flush_active_memtable(0)?;
+        assert_eq!(ITEM_COUNT, tree.len(None, None)?);
+    }
+
+    Ok(())
+}
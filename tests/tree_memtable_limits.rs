@@ -0,0 +1,108 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_max_memtable_entries() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).max_memtable_entries(10).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..25u64 {
+        tree.insert(x.to_be_bytes(), "", seqno.next());
+    }
+
+    assert_eq!(2, tree.sealed_memtable_count());
+    assert!(tree.active_memtable_size() > 0);
+
+    for x in 0..25u64 {
+        assert!(tree.get(x.to_be_bytes(), None)?.is_some());
+    }
+
+    // NOTE: Tombstones count as entries, too
+    for x in 0..5u64 {
+        tree.remove(x.to_be_bytes(), seqno.next());
+    }
+    assert_eq!(3, tree.sealed_memtable_count());
+    assert_eq!(0, tree.active_memtable_size());
+
+    let segments = tree.flush_sealed_memtables(0)?;
+    assert_eq!(3, segments.len());
+    assert!(segments.iter().all(|x| x.metadata.item_count == 10));
+    assert_eq!(0, tree.sealed_memtable_count());
+    assert_eq!(20, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_max_memtable_size() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).max_memtable_size(1_024).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..100u64 {
+        tree.insert(x.to_be_bytes(), "a".repeat(100), seqno.next());
+        assert!(tree.active_memtable_size() < 1_024);
+    }
+
+    assert!(tree.sealed_memtable_count() > 0);
+
+    Ok(())
+}
+
+#[test]
+fn tree_memtable_limits_first_hit_wins() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .max_memtable_size(u32::MAX)
+        .max_memtable_entries(10)
+        .open()?;
+
+    for x in 0..10u64 {
+        tree.insert(x.to_be_bytes(), "", x);
+    }
+    assert_eq!(1, tree.sealed_memtable_count());
+
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .max_memtable_size(1)
+        .max_memtable_entries(10)
+        .open()?;
+
+    tree.insert("a", "", 0);
+    assert_eq!(1, tree.sealed_memtable_count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_memtable_unlimited_by_default() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    for x in 0..1_000u64 {
+        tree.insert(x.to_be_bytes(), "", x);
+    }
+    assert_eq!(0, tree.sealed_memtable_count());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_max_memtable_entries() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .max_memtable_entries(10)
+        .open_as_blob_tree()?;
+
+    for x in 0..20u64 {
+        tree.insert(x.to_be_bytes(), "", x);
+    }
+    assert_eq!(2, tree.sealed_memtable_count());
+
+    assert_eq!(2, tree.flush_sealed_memtables(0)?.len());
+    assert_eq!(0, tree.sealed_memtable_count());
+    assert_eq!(20, tree.len(None, None)?);
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use lsm_tree::{AbstractTree, Config, SealedMemtableVisibility, ValueType};
+use test_log::test;
+
+#[test]
+fn tree_memtable_visibility_immediate() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "abc", 0);
+    tree.rotate_memtable().expect("should have sealed memtable");
+    tree.insert("b", "abc", 1);
+
+    assert!(tree.contains_key("a", None)?);
+    assert!(tree.contains_key("b", None)?);
+    assert_eq!(2, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_memtable_visibility_after_flush() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .sealed_memtable_visibility(SealedMemtableVisibility::AfterFlush)
+        .open()?;
+
+    tree.insert("a", "abc", 0);
+    assert!(!tree.contains_key("a", None)?);
+
+    tree.rotate_memtable().expect("should have sealed memtable");
+    tree.insert("b", "abc", 1);
+    assert!(!tree.contains_key("a", None)?);
+    assert!(tree.is_empty(None, None)?);
+
+    // NOTE: Conflict checks still see unflushed writes
+    assert_eq!(Some((0, ValueType::Value)), tree.latest_seqno("a")?);
+
+    tree.flush_sealed_memtables(0)?;
+    assert_eq!(Some("abc".as_bytes().into()), tree.get("a", None)?);
+    assert!(!tree.contains_key("b", None)?);
+    assert_eq!(1, tree.len(None, None)?);
+
+    // NOTE: Deletions are staged, too
+    tree.remove("a", 2);
+    assert!(tree.contains_key("a", None)?);
+
+    tree.flush_active_memtable(0)?;
+    assert!(!tree.contains_key("a", None)?);
+    assert_eq!(
+        vec![("b".as_bytes().into(), "abc".as_bytes().into())],
+        tree.iter(None, None)
+            .collect::<lsm_tree::Result<Vec<_>>>()?,
+    );
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_memtable_visibility_after_flush() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .sealed_memtable_visibility(SealedMemtableVisibility::AfterFlush)
+        .open_as_blob_tree()?;
+
+    tree.insert("a", "abc".repeat(1_000), 0);
+    assert!(!tree.contains_key("a", None)?);
+    assert!(tree.is_empty(None, None)?);
+
+    tree.flush_active_memtable(0)?;
+    assert_eq!(
+        Some("abc".repeat(1_000).as_bytes().into()),
+        tree.get("a", None)?
+    );
+    assert_eq!(1, tree.len(None, None)?);
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 10_000;
+
+fn key_at(tree: &lsm_tree::Tree, n: u64) -> lsm_tree::Result<u64> {
+    let key = tree.nth_key(n)?.expect("should exist");
+    Ok(u64::from_be_bytes(
+        (*key).try_into().expect("should be u64"),
+    ))
+}
+
+#[test]
+fn tree_nth_key() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).data_block_size(1_024).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    assert!(tree.nth_key(0)?.is_none());
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), x.to_be_bytes(), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    assert_eq!(0, key_at(&tree, 0)?);
+    assert_eq!(ITEM_COUNT - 1, key_at(&tree, ITEM_COUNT - 1)?);
+    assert!(tree.nth_key(ITEM_COUNT)?.is_none());
+
+    // NOTE: Blocks hold ~30 items each, so we are off by a block at most
+    for n in (0..ITEM_COUNT).step_by(777) {
+        assert!(key_at(&tree, n)?.abs_diff(n) < 50);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_nth_key_multiple_segments() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).data_block_size(1_024).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    // NOTE: Interleaved (overlapping) segments
+    for batch in 0..4 {
+        for x in (batch..ITEM_COUNT).step_by(4) {
+            tree.insert(x.to_be_bytes(), x.to_be_bytes(), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+    assert_eq!(4, tree.segment_count());
+
+    assert!(key_at(&tree, 0)? < 4);
+    assert!(key_at(&tree, ITEM_COUNT - 1)? >= ITEM_COUNT - 4);
+    assert!(tree.nth_key(ITEM_COUNT)?.is_none());
+
+    for n in (0..ITEM_COUNT).step_by(777) {
+        assert!(key_at(&tree, n)?.abs_diff(n) < 200);
+    }
+
+    tree.major_compact(u64::MAX, 0)?;
+
+    for n in (0..ITEM_COUNT).step_by(777) {
+        assert!(key_at(&tree, n)?.abs_diff(n) < 50);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+fn key(partition: u32, x: u64) -> Vec<u8> {
+    let mut key = partition.to_be_bytes().to_vec();
+    key.extend_from_slice(&x.to_be_bytes());
+    key
+}
+
+#[test]
+fn tree_partitions_scan_drop() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).partition_prefix_len(4).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for partition in 0..3 {
+        for x in 0..100 {
+            tree.insert(key(partition, x), "abc", seqno.next());
+        }
+    }
+    tree.flush_active_memtable(0)?;
+
+    for partition in 0..3u32 {
+        assert_eq!(
+            100,
+            tree.scan_partition(partition.to_be_bytes(), None).count()
+        );
+    }
+
+    tree.drop_partition(1u32.to_be_bytes(), seqno.next())?;
+    assert_eq!(0, tree.scan_partition(1u32.to_be_bytes(), None).count());
+    assert_eq!(100, tree.scan_partition(2u32.to_be_bytes(), None).count());
+
+    let stats = tree.partition_stats(0u32.to_be_bytes(), None)?;
+    assert_eq!(100, stats.item_count);
+    assert_eq!(100 * 12, stats.key_bytes);
+    assert_eq!(100 * 3, stats.value_bytes);
+
+    assert_eq!(
+        0,
+        tree.partition_stats(1u32.to_be_bytes(), None)?.item_count
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_partitions_compaction_splits_segments() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).partition_prefix_len(4).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for partition in 0..5 {
+        for x in 0..10 {
+            tree.insert(key(partition, x), "abc", seqno.next());
+        }
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.segment_count());
+
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(5, tree.segment_count());
+
+    tree.drop_partition(3u32.to_be_bytes(), seqno.next())?;
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, seqno.get())?;
+
+    assert_eq!(4, tree.segment_count());
+    assert_eq!(40, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+#[should_panic = "partition should be exactly as long as the partition prefix"]
+fn tree_partitions_wrong_prefix_len() {
+    let folder = tempfile::tempdir().unwrap();
+    let tree = Config::new(&folder).partition_prefix_len(4).open().unwrap();
+
+    let _ = tree.scan_partition("a", None);
+}
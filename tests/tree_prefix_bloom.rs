@@ -0,0 +1,217 @@
+use lsm_tree::{bloom::BloomFilter, AbstractTree, Config, SequenceNumberCounter, Tree};
+use std::{
+    io::{Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use test_log::test;
+
+fn tenant(key: &[u8]) -> &[u8] {
+    match key.iter().position(|&byte| byte == b'|') {
+        Some(idx) => &key[..=idx],
+        None => &[],
+    }
+}
+
+fn open(path: &Path, use_extractor: bool) -> lsm_tree::Result<Tree> {
+    let config = Config::new(path);
+
+    if use_extractor {
+        config.bloom_prefix_extractor(Arc::new(tenant)).open()
+    } else {
+        config.open()
+    }
+}
+
+/// Returns the path of the segment that starts with the given key
+fn segment_path(tree: &Tree, min_key: &[u8]) -> PathBuf {
+    let levels = tree.levels.read().expect("lock is poisoned");
+
+    let path = levels
+        .iter()
+        .find(|x| &**x.metadata.key_range.min() == min_key)
+        .map(|x| x.path.clone())
+        .expect("segment should exist");
+    path
+}
+
+/// Overwrites the header of the first data block of a segment, so reading it fails
+fn corrupt_data(path: &Path) -> lsm_tree::Result<()> {
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&[0xFF; 4])?;
+    file.sync_all()?;
+    Ok(())
+}
+
+#[test]
+fn tree_prefix_bloom_filter_contents() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = open(folder.path(), true)?;
+    let seqno = SequenceNumberCounter::default();
+
+    for tenant in ["t1|", "t2|"] {
+        for x in 0..100 {
+            tree.insert(format!("{tenant}{x:0>4}"), "a", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    // NOTE: Keys without a prefix are not added to the prefix filter
+    tree.insert("t3", "a", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    let levels = tree.levels.read().expect("lock is poisoned");
+    let segments = levels.iter().collect::<Vec<_>>();
+    assert_eq!(3, segments.len());
+
+    for segment in segments {
+        let min_key = segment.metadata.key_range.min();
+
+        if min_key.starts_with(b"t1|") {
+            let filter = segment.prefix_filter.as_ref().expect("should have filter");
+            assert!(filter.contains(b"t1|"));
+            assert!(!filter.contains(b"t2|"));
+            assert!(!filter.contains(b"t1|0000"));
+        } else if min_key.starts_with(b"t2|") {
+            let filter = segment.prefix_filter.as_ref().expect("should have filter");
+            assert!(filter.contains_hash(BloomFilter::get_hash(b"t2|")));
+            assert!(!filter.contains(b"t1|"));
+        } else {
+            assert!(segment.prefix_filter.is_none());
+        }
+
+        // NOTE: The regular Bloom filter still contains the full keys
+        let filter = segment.bloom_filter.as_ref().expect("should have filter");
+        assert!(filter.contains(min_key));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_prefix_bloom_skips_overlapping_segment() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = open(folder.path(), true)?;
+
+        for x in 0..100 {
+            tree.insert(format!("t3|{x:0>4}"), "a", 0);
+        }
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: The segment's key range overlaps with t3, but it does not contain t3
+        tree.insert("t2|0000", "a", 1);
+        tree.insert("t4|0000", "a", 1);
+        tree.flush_active_memtable(0)?;
+
+        corrupt_data(&segment_path(&tree, b"t2|0000"))?;
+    }
+
+    // NOTE: Reopen, so the corrupted block is not cached
+    let tree = open(folder.path(), true)?;
+    assert_eq!(
+        100,
+        tree.prefix("t3|", None, None)
+            .collect::<lsm_tree::Result<Vec<_>>>()?
+            .len()
+    );
+    assert_eq!(
+        10,
+        tree.prefix("t3|005", None, None)
+            .collect::<lsm_tree::Result<Vec<_>>>()?
+            .len()
+    );
+
+    // NOTE: Point reads do not use the prefix filter
+    assert!(tree.get("t3|0005", None)?.is_some());
+    assert!(tree.get("t2|0000", None).is_err());
+
+    // NOTE: Prefixes the extractor does not cover read all overlapping segments
+    assert!(tree
+        .prefix("t", None, None)
+        .collect::<lsm_tree::Result<Vec<_>>>()
+        .is_err());
+    drop(tree);
+
+    // NOTE: Without an extractor, the corrupted segment is read
+    let tree = open(folder.path(), false)?;
+    assert!(tree
+        .prefix("t3|", None, None)
+        .collect::<lsm_tree::Result<Vec<_>>>()
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn tree_prefix_bloom_skips_disjoint_segment() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = open(folder.path(), true)?;
+
+        tree.insert("t2|0000", "a", 0);
+        tree.insert("t4|0000", "a", 0);
+        tree.flush_active_memtable(0)?;
+
+        tree.insert("t5|0000", "a", 1);
+        tree.flush_active_memtable(0)?;
+        assert!(tree.levels.read().expect("lock is poisoned").is_disjoint());
+
+        corrupt_data(&segment_path(&tree, b"t2|0000"))?;
+    }
+
+    let tree = open(folder.path(), true)?;
+    assert_eq!(0, tree.prefix("t3|", None, None).count());
+    assert_eq!(0, tree.prefix("t3|", None, None).rev().count());
+    assert_eq!(
+        1,
+        tree.prefix("t5|", None, None)
+            .collect::<lsm_tree::Result<Vec<_>>>()?
+            .len()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_prefix_bloom_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = open(folder.path(), true)?;
+    let seqno = SequenceNumberCounter::default();
+
+    for tenant in ["t1|", "t2|", "t3|"] {
+        for x in 0..100 {
+            tree.insert(format!("{tenant}{x:0>4}"), "a", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(1, tree.segment_count());
+
+    {
+        let levels = tree.levels.read().expect("lock is poisoned");
+        let segment = levels.iter().next().expect("should exist");
+        let filter = segment.prefix_filter.as_ref().expect("should have filter");
+
+        for tenant in ["t1|", "t2|", "t3|"] {
+            assert!(filter.contains(tenant.as_bytes()));
+        }
+    }
+
+    for tenant in ["t1|", "t2|", "t3|"] {
+        assert_eq!(100, tree.prefix(tenant, None, None).count());
+        assert_eq!(100, tree.prefix(tenant, None, None).rev().count());
+    }
+    assert_eq!(0, tree.prefix("t0|", None, None).count());
+    assert_eq!(300, tree.prefix("t", None, None).count());
+
+    // NOTE: The prefix filter is persisted
+    drop(tree);
+    let tree = open(folder.path(), true)?;
+    assert_eq!(100, tree.prefix("t2|", None, None).count());
+
+    Ok(())
+}
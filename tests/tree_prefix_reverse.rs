@@ -0,0 +1,125 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter, Slice};
+use test_log::test;
+
+fn collect_rev<T: AbstractTree>(tree: &T, prefix: &[u8]) -> lsm_tree::Result<Vec<Slice>> {
+    tree.prefix(prefix, None, None)
+        .rev()
+        .map(|kv| kv.map(|(k, _)| k))
+        .collect()
+}
+
+fn collect<T: AbstractTree>(tree: &T, prefix: &[u8]) -> lsm_tree::Result<Vec<Slice>> {
+    tree.prefix(prefix, None, None)
+        .map(|kv| kv.map(|(k, _)| k))
+        .collect()
+}
+
+fn assert_prefixes<T: AbstractTree>(tree: &T, keys: &[&[u8]]) -> lsm_tree::Result<()> {
+    for prefix in [
+        &b"a"[..],
+        b"ab",
+        b"b",
+        &[0xFE],
+        &[0xFF],
+        &[0xFF, 0xFF],
+        &[0xFF, 0xFF, 0xFF],
+        &[0xFE, 0xFF],
+        &[],
+    ] {
+        let mut expected = keys
+            .iter()
+            .filter(|k| k.starts_with(prefix))
+            .map(|k| Slice::from(*k))
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        assert_eq!(expected, collect(tree, prefix)?, "prefix {prefix:?}");
+
+        expected.reverse();
+        assert_eq!(expected, collect_rev(tree, prefix)?, "prefix {prefix:?}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_prefix_reverse() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let keys: &[&[u8]] = &[
+        b"a",
+        b"ab",
+        b"abc",
+        b"abd",
+        b"ac",
+        b"b",
+        &[0xFE],
+        &[0xFE, 0xFF],
+        &[0xFE, 0xFF, 0x00],
+        &[0xFF],
+        &[0xFF, 0x00],
+        &[0xFF, 0xFF],
+        &[0xFF, 0xFF, 0xFF],
+        &[0xFF, 0xFF, 0xFF, 0xFF],
+    ];
+
+    // NOTE: Spread the keys over the memtable and multiple segments
+    for (idx, key) in keys.iter().enumerate() {
+        tree.insert(*key, "v", seqno.next());
+
+        if idx % 3 == 0 {
+            tree.flush_active_memtable(0)?;
+        }
+    }
+
+    assert_prefixes(&tree, keys)?;
+
+    // NOTE: Older versions and deleted keys are not returned
+    tree.insert(b"abc", "v2", seqno.next());
+    tree.remove(b"abd", seqno.next());
+    tree.remove([0xFF, 0xFF, 0xFF], seqno.next());
+
+    let keys = keys
+        .iter()
+        .copied()
+        .filter(|k| *k != b"abd" && *k != [0xFF, 0xFF, 0xFF])
+        .collect::<Vec<_>>();
+
+    assert_prefixes(&tree, &keys)?;
+
+    tree.major_compact(u64::MAX, seqno.next())?;
+    assert_prefixes(&tree, &keys)?;
+
+    Ok(())
+}
+
+#[test]
+fn tree_prefix_reverse_latest_first() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..1_000u64 {
+        let mut key = b"log#".to_vec();
+        key.extend_from_slice(&x.to_be_bytes());
+        tree.insert(key, x.to_be_bytes(), seqno.next());
+
+        if x % 100 == 0 {
+            tree.flush_active_memtable(0)?;
+        }
+    }
+    tree.insert("log$", "not a log", seqno.next());
+    tree.insert("loc", "not a log", seqno.next());
+
+    let mut iter = tree.prefix("log#", None, None).rev();
+
+    for x in (0..1_000u64).rev() {
+        let (_, value) = iter.next().expect("should exist")?;
+        assert_eq!(&*value, x.to_be_bytes());
+    }
+    assert!(iter.next().is_none());
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use lsm_tree::{
+    compaction::PullDown, segment::block_index::BlockIndexImpl, AbstractTree, Config,
+    SequenceNumberCounter, Tree,
+};
+use std::sync::Arc;
+use test_log::test;
+
+const ITEM_COUNT: u64 = 100;
+
+/// Returns, per level, whether the segments have a full (preloaded) block index
+fn full_block_index_levels(tree: &Tree) -> Vec<Vec<bool>> {
+    let levels = tree.levels.read().expect("lock is poisoned");
+
+    levels
+        .levels
+        .iter()
+        .map(|level| {
+            level
+                .segments
+                .iter()
+                .map(|segment| matches!(&*segment.block_index, BlockIndexImpl::Full(_)))
+                .collect()
+        })
+        .collect()
+}
+
+fn fill(tree: &Tree, seqno: &SequenceNumberCounter) -> lsm_tree::Result<()> {
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    Ok(())
+}
+
+#[test]
+fn tree_preload_block_index_default() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        fill(&tree, &seqno)?;
+        tree.compact(Arc::new(PullDown(0, 1)), 0)?;
+        fill(&tree, &seqno)?;
+        fill(&tree, &seqno)?;
+        tree.compact(Arc::new(PullDown(0, 2)), 0)?;
+        fill(&tree, &seqno)?;
+
+        let levels = full_block_index_levels(&tree);
+        assert_eq!(vec![true], levels[0]);
+        assert_eq!(vec![true], levels[1]);
+        assert_eq!(vec![false], levels[2]);
+    }
+
+    let tree = Config::new(&folder).open()?;
+
+    let levels = full_block_index_levels(&tree);
+    assert_eq!(vec![true], levels[0]);
+    assert_eq!(vec![true], levels[1]);
+    assert_eq!(vec![false], levels[2]);
+
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_preload_block_index_levels() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder).preload_block_index_levels(3).open()?;
+
+        fill(&tree, &seqno)?;
+        tree.compact(Arc::new(PullDown(0, 2)), 0)?;
+        fill(&tree, &seqno)?;
+        tree.compact(Arc::new(PullDown(0, 3)), 0)?;
+
+        let levels = full_block_index_levels(&tree);
+        assert_eq!(vec![true], levels[2]);
+        assert_eq!(vec![false], levels[3]);
+    }
+
+    // NOTE: Reopening with no preloaded levels
+    let tree = Config::new(&folder).preload_block_index_levels(0).open()?;
+
+    let levels = full_block_index_levels(&tree);
+    assert_eq!(vec![false], levels[2]);
+    assert_eq!(vec![false], levels[3]);
+
+    fill(&tree, &seqno)?;
+    assert_eq!(vec![false], full_block_index_levels(&tree)[0]);
+
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+    assert_eq!(&*tree.get(0u64.to_be_bytes(), None)?.unwrap(), b"a");
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use lsm_tree::{AbstractTree, Config, SeqNo, Tree};
+use test_log::test;
+use xxhash_rust::xxh3::Xxh3;
+
+const ITEM_COUNT: u64 = 1_000;
+
+fn fill(tree: &Tree) {
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), x.to_string(), x);
+    }
+    for x in (0..ITEM_COUNT).step_by(7) {
+        tree.remove(x.to_be_bytes(), ITEM_COUNT + x);
+    }
+}
+
+/// Implements the digest as documented on [`Tree::range_digest`]
+fn reference_digest(items: impl Iterator<Item = (u64, String, SeqNo)>) -> u64 {
+    let mut hasher = Xxh3::new();
+
+    for (key, value, seqno) in items {
+        let key = key.to_be_bytes();
+        hasher.update(&(key.len() as u32).to_be_bytes());
+        hasher.update(&key);
+        hasher.update(&(value.len() as u32).to_be_bytes());
+        hasher.update(value.as_bytes());
+        hasher.update(&seqno.to_be_bytes());
+    }
+
+    hasher.digest()
+}
+
+#[test]
+fn tree_range_digest_matches_reference() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    fill(&tree);
+
+    let expected = reference_digest(
+        (100..500u64)
+            .filter(|x| x % 7 != 0)
+            .map(|x| (x, x.to_string(), x)),
+    );
+    assert_eq!(
+        expected,
+        tree.range_digest(100u64.to_be_bytes()..500u64.to_be_bytes(), None)?
+    );
+
+    assert_eq!(
+        reference_digest(std::iter::empty()),
+        tree.range_digest(ITEM_COUNT.to_be_bytes().., None)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_range_digest_independent_of_layout() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let memtable_tree = Config::new(folder.path().join("a")).open()?;
+    let disk_tree = Config::new(folder.path().join("b"))
+        .data_block_size(1_024)
+        .open()?;
+
+    fill(&memtable_tree);
+    fill(&disk_tree);
+    disk_tree.flush_active_memtable(0)?;
+    disk_tree.major_compact(4_096, 0)?;
+    assert!(disk_tree.segment_count() > 1);
+
+    assert_eq!(
+        memtable_tree.range_digest::<&[u8], _>(.., None)?,
+        disk_tree.range_digest::<&[u8], _>(.., None)?,
+    );
+
+    let lo = 250u64.to_be_bytes();
+    let hi = 750u64.to_be_bytes();
+    assert_eq!(
+        memtable_tree.range_digest(lo..=hi, None)?,
+        disk_tree.range_digest(lo..=hi, None)?,
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_range_digest_detects_divergence() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let a = Config::new(folder.path().join("a")).open()?;
+    let b = Config::new(folder.path().join("b")).open()?;
+
+    fill(&a);
+    fill(&b);
+
+    let lo = 0u64.to_be_bytes();
+    let hi = 100u64.to_be_bytes();
+    let digest = a.range_digest(lo..hi, None)?;
+    assert_eq!(digest, b.range_digest(lo..hi, None)?);
+
+    // NOTE: Same value, but a different version
+    b.insert(50u64.to_be_bytes(), "50", 5_000);
+    assert_ne!(digest, b.range_digest(lo..hi, None)?);
+
+    // NOTE: Diverging outside of the range does not change the digest
+    a.insert(500u64.to_be_bytes(), "diverged", 5_000);
+    assert_eq!(digest, a.range_digest(lo..hi, None)?);
+
+    // NOTE: Comparing at a snapshot ignores newer writes
+    assert_eq!(digest, b.range_digest(lo..hi, Some(5_000))?);
+
+    b.remove(51u64.to_be_bytes(), 5_001);
+    assert_ne!(
+        b.range_digest(lo..hi, Some(5_001))?,
+        b.range_digest(lo..hi, Some(5_002))?
+    );
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_read_level_histogram() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    assert_eq!(0, tree.read_level_histogram().total());
+    assert_eq!(7, tree.read_level_histogram().levels.len());
+
+    tree.insert("a", "a", seqno.next());
+    assert!(tree.get("a", None)?.is_some());
+    assert!(tree.get("b", None)?.is_none());
+
+    tree.flush_active_memtable(0)?;
+    tree.insert("b", "b", seqno.next());
+    assert!(tree.get("a", None)?.is_some());
+    assert!(tree.get("b", None)?.is_some());
+
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 0)?;
+    assert!(tree.get("a", None)?.is_some());
+
+    // NOTE: A tombstone answers the read, even though no value is returned
+    tree.remove("c", seqno.next());
+    tree.flush_active_memtable(0)?;
+    assert!(tree.get("c", None)?.is_none());
+
+    let histogram = tree.read_level_histogram();
+    assert_eq!(2, histogram.memtable);
+    assert_eq!(vec![2, 0, 0, 0, 0, 0, 1], histogram.levels);
+    assert_eq!(1, histogram.misses);
+    assert_eq!(6, histogram.total());
+
+    Ok(())
+}
+
+#[test]
+fn tree_read_level_histogram_sealed_memtable() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "a", 0);
+    tree.rotate_memtable().expect("should have sealed memtable");
+    assert!(tree.get("a", None)?.is_some());
+
+    let histogram = tree.read_level_histogram();
+    assert_eq!(1, histogram.memtable);
+    assert_eq!(0, histogram.misses);
+
+    Ok(())
+}
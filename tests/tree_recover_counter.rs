@@ -59,3 +59,65 @@ fn tree_recover_segment_counter() -> lsm_tree::Result<()> {
 
     Ok(())
 }
+
+fn segment_ids(tree: &lsm_tree::Tree) -> Vec<u64> {
+    tree.levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .map(lsm_tree::Segment::id)
+        .collect()
+}
+
+#[test]
+fn tree_recover_segment_counter_after_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let next_id = {
+        let tree = Config::new(&folder).open()?;
+
+        for x in 0..5u64 {
+            tree.insert(x.to_be_bytes(), "a", x);
+            tree.flush_active_memtable(0)?;
+        }
+        tree.major_compact(u64::MAX, 5)?;
+
+        tree.insert("b", "b", 6);
+        tree.flush_active_memtable(0)?;
+        assert_eq!(2, tree.segment_count());
+
+        tree.0
+            .segment_id_counter
+            .load(std::sync::atomic::Ordering::Relaxed)
+    };
+
+    // NOTE: Simulate a crash after writing a segment that never made it into the level manifest
+    let orphan_path = folder.path().join("segments").join(next_id.to_string());
+    std::fs::write(&orphan_path, b"garbage")?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert!(!orphan_path.try_exists()?);
+
+        let counter = tree
+            .0
+            .segment_id_counter
+            .load(std::sync::atomic::Ordering::Relaxed);
+        assert_eq!(next_id, counter);
+
+        let ids = segment_ids(&tree);
+        assert!(ids.iter().all(|&id| id < counter));
+
+        tree.insert("c", "c", 7);
+        tree.flush_active_memtable(0)?;
+
+        let mut new_ids = segment_ids(&tree);
+        assert_eq!(3, new_ids.len());
+        new_ids.sort_unstable();
+        new_ids.dedup();
+        assert_eq!(3, new_ids.len());
+        assert_eq!(7, tree.len(None, None)?);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use lsm_tree::{AbstractTree, AnyTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: usize = 100;
+
+fn fill(tree: &AnyTree, seqno: &SequenceNumberCounter, value: &[u8]) -> lsm_tree::Result<()> {
+    for batch in 0..4 {
+        for x in (batch..ITEM_COUNT).step_by(4) {
+            tree.insert(format!("{x:0>4}"), value, seqno.next());
+        }
+
+        match tree {
+            AnyTree::Standard(tree) => tree.flush_active_memtable(0)?,
+            AnyTree::Blob(tree) => tree.flush_active_memtable(0)?,
+        };
+    }
+
+    Ok(())
+}
+
+fn check(tree: &AnyTree, value: &[u8]) -> lsm_tree::Result<()> {
+    for x in 0..ITEM_COUNT {
+        let item = tree.get(format!("{x:0>4}"), None)?.expect("should exist");
+        assert_eq!(&*item, value);
+    }
+    assert_eq!(ITEM_COUNT, tree.len(None, None)?);
+
+    Ok(())
+}
+
+fn relocate(
+    open: impl Fn(Config) -> lsm_tree::Result<AnyTree>,
+    value: &[u8],
+) -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let old_path = folder.path().join("old");
+    let new_path = folder.path().join("somewhere").join("new");
+
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = open(Config::new(&old_path))?;
+        fill(&tree, &seqno, value)?;
+        tree.major_compact(u64::MAX, 0)?;
+        fill(&tree, &seqno, value)?;
+        check(&tree, value)?;
+    }
+
+    std::fs::create_dir_all(folder.path().join("somewhere"))?;
+    std::fs::rename(&old_path, &new_path)?;
+    assert!(!old_path.try_exists()?);
+
+    let tree = open(Config::new(&new_path))?;
+    check(&tree, value)?;
+
+    // NOTE: Flushes and compactions need to write into the new folder
+    fill(&tree, &seqno, value)?;
+    tree.major_compact(u64::MAX, seqno.get())?;
+    check(&tree, value)?;
+    assert!(!old_path.try_exists()?);
+    drop(tree);
+
+    let tree = open(Config::new(&new_path))?;
+    check(&tree, value)?;
+
+    Ok(())
+}
+
+#[test]
+fn tree_relocate() -> lsm_tree::Result<()> {
+    relocate(|config| Ok(AnyTree::Standard(config.open()?)), b"abc")
+}
+
+#[test]
+fn blob_tree_relocate() -> lsm_tree::Result<()> {
+    relocate(
+        |config| Ok(AnyTree::Blob(config.open_as_blob_tree()?)),
+        &b"abc".repeat(10_000),
+    )
+}
@@ -0,0 +1,90 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: usize = 10_000;
+
+fn fill_tree(tree: &lsm_tree::Tree) -> lsm_tree::Result<()> {
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), x.to_string().repeat(10), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    Ok(())
+}
+
+#[test]
+fn tree_scan_readahead() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .data_block_size(1_024)
+        .scan_readahead_blocks(4)
+        .open()?;
+    fill_tree(&tree)?;
+
+    assert_eq!(ITEM_COUNT, tree.iter(None, None).rev().count());
+
+    for (idx, item) in tree.iter(None, None).enumerate() {
+        let (key, _) = item?;
+        assert_eq!(&*key, (idx as u64).to_be_bytes());
+    }
+
+    let range = tree
+        .range(100u64.to_be_bytes()..9_000u64.to_be_bytes(), None, None)
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(8_900, range.len());
+    assert_eq!(&*range.first().unwrap().0, 100u64.to_be_bytes());
+    assert_eq!(&*range.last().unwrap().0, 8_999u64.to_be_bytes());
+
+    Ok(())
+}
+
+#[test]
+fn tree_scan_readahead_ping_pong() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .data_block_size(1_024)
+        .scan_readahead_blocks(4)
+        .open()?;
+    fill_tree(&tree)?;
+
+    let mut iter = tree.iter(None, None);
+
+    for x in 0..(ITEM_COUNT as u64 / 2) {
+        let (key, _) = iter.next().unwrap()?;
+        assert_eq!(&*key, x.to_be_bytes());
+
+        let (key, _) = iter.next_back().unwrap()?;
+        assert_eq!(&*key, (ITEM_COUNT as u64 - 1 - x).to_be_bytes());
+    }
+
+    assert!(iter.next().is_none());
+    assert!(iter.next_back().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn tree_scan_readahead_no_cache_pollution() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).data_block_size(1_024).open()?;
+    fill_tree(&tree)?;
+
+    let folder = tempfile::tempdir()?;
+    let readahead_tree = Config::new(&folder)
+        .data_block_size(1_024)
+        .scan_readahead_blocks(8)
+        .open()?;
+    fill_tree(&readahead_tree)?;
+
+    assert_eq!(1_000, tree.iter(None, None).take(1_000).count());
+    assert_eq!(1_000, readahead_tree.iter(None, None).take(1_000).count());
+
+    // NOTE: Blocks that were read ahead, but not consumed, are not cached
+    assert_eq!(tree.config.cache.len(), readahead_tree.config.cache.len());
+
+    Ok(())
+}
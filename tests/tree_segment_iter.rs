@@ -0,0 +1,57 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_segment_iter() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    assert_eq!(0, tree.segment_iter().count());
+
+    tree.insert("a", "old", seqno.next());
+    tree.insert("b", "b", seqno.next());
+    tree.insert("c", "c", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("a", "new", seqno.next());
+    tree.remove("b", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Not flushed yet
+    tree.insert("c", "unflushed", seqno.next());
+    tree.insert("d", "unflushed", seqno.next());
+
+    // NOTE: Sealed, but not flushed yet
+    tree.rotate_memtable();
+    tree.insert("e", "unflushed", seqno.next());
+
+    let items = tree.segment_iter().collect::<lsm_tree::Result<Vec<_>>>()?;
+
+    assert_eq!(
+        vec![
+            (b"a".to_vec(), b"new".to_vec()),
+            (b"c".to_vec(), b"c".to_vec())
+        ],
+        items
+            .iter()
+            .map(|item| (item.key.user_key.to_vec(), item.value.to_vec()))
+            .collect::<Vec<_>>(),
+    );
+    assert_eq!(3, items[0].key.seqno);
+    assert_eq!(2, items[1].key.seqno);
+
+    let reversed = tree
+        .segment_iter()
+        .rev()
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(
+        items.iter().rev().map(|x| &x.key).collect::<Vec<_>>(),
+        reversed.iter().map(|x| &x.key).collect::<Vec<_>>(),
+    );
+
+    assert_eq!(4, tree.iter(None, None).count());
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use lsm_tree::{AbstractTree, Cache, Config, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+const ITEM_COUNT: u64 = 10_000;
+
+#[test]
+fn tree_shrink_to_fit() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let cache = Arc::new(Cache::with_capacity_bytes(1_000_000));
+    let tree = Config::new(&folder).use_cache(cache.clone()).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a".repeat(100), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    for x in 0..ITEM_COUNT {
+        assert!(tree.get(x.to_be_bytes(), None)?.is_some());
+    }
+    assert!(!cache.is_empty());
+
+    let disk_space_before = tree.disk_space();
+
+    for x in 0..ITEM_COUNT - 10 {
+        tree.remove(x.to_be_bytes(), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Some deletes are still unflushed
+    tree.remove((ITEM_COUNT - 1).to_be_bytes(), seqno.next());
+
+    tree.shrink_to_fit()?;
+
+    assert_eq!(0, tree.active_memtable_size());
+    assert!(tree.disk_space() < disk_space_before / 10);
+    assert!(cache.is_empty());
+    assert_eq!(9, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_shrink_to_fit_keeps_snapshot() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    tree.insert("a", "abc", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    let snapshot = tree.snapshot(seqno.get());
+    tree.remove("a", seqno.next());
+
+    tree.shrink_to_fit()?;
+    assert_eq!(None, tree.get("a", None)?);
+    assert_eq!(Some("abc".as_bytes().into()), snapshot.get("a")?);
+
+    drop(snapshot);
+    tree.shrink_to_fit()?;
+    assert_eq!(0, tree.segment_count());
+
+    Ok(())
+}
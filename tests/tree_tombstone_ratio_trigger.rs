@@ -0,0 +1,92 @@
+use lsm_tree::{compaction::Leveled, AbstractTree, Config, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+const ITEM_COUNT: usize = 100;
+
+fn tombstone_count(tree: &lsm_tree::Tree) -> u64 {
+    tree.levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .map(lsm_tree::Segment::tombstone_count)
+        .sum()
+}
+
+fn delete_heavy_tree(config: Config) -> lsm_tree::Result<lsm_tree::Tree> {
+    let tree = config.open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), "value", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 0)?;
+
+    for x in 0..(ITEM_COUNT as u64 - 20) {
+        tree.remove(x.to_be_bytes(), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    assert_eq!(2, tree.segment_count());
+    assert_eq!(20, tree.len(None, None)?);
+
+    Ok(tree)
+}
+
+#[test]
+fn tree_tombstone_ratio_trigger() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = delete_heavy_tree(Config::new(&folder).tombstone_ratio_trigger(0.5))?;
+
+    let strategy = Arc::new(Leveled::default());
+
+    // NOTE: Tombstones are pushed down level by level, until they are dropped in the last level
+    for _ in 0..10 {
+        tree.compact(strategy.clone(), u64::MAX)?;
+    }
+
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(0, tombstone_count(&tree));
+    assert_eq!(20, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_tombstone_ratio_trigger_disabled() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = delete_heavy_tree(Config::new(&folder))?;
+
+    let strategy = Arc::new(Leveled::default());
+
+    for _ in 0..10 {
+        tree.compact(strategy.clone(), u64::MAX)?;
+    }
+
+    assert_eq!(2, tree.segment_count());
+    assert_eq!(80, tombstone_count(&tree));
+    assert_eq!(20, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_tombstone_ratio_trigger_gc_watermark() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = delete_heavy_tree(Config::new(&folder).tombstone_ratio_trigger(0.5))?;
+
+    let strategy = Arc::new(Leveled::default());
+
+    // NOTE: Old versions are still visible to snapshots, so tombstones need to be kept
+    for _ in 0..10 {
+        tree.compact(strategy.clone(), 0)?;
+    }
+
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(80, tombstone_count(&tree));
+    assert_eq!(20, tree.len(None, None)?);
+    assert_eq!(ITEM_COUNT, tree.len(Some(ITEM_COUNT as u64), None)?);
+
+    Ok(())
+}
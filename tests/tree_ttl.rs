@@ -0,0 +1,132 @@
+use lsm_tree::{AbstractTree, Config, ValueType};
+use std::time::Duration;
+use test_log::test;
+
+const LONG_TTL: Duration = Duration::from_secs(3_600);
+
+#[test]
+fn tree_ttl_expired_values_are_invisible() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert_with_ttl("a", "live", 0, LONG_TTL);
+    tree.insert_with_ttl("b", "expired", 1, Duration::ZERO);
+    tree.insert_with_ttl("c", "soon", 2, Duration::from_millis(100));
+
+    assert_eq!(Some("live".as_bytes().into()), tree.get("a", None)?);
+    assert_eq!(None, tree.get("b", None)?);
+    assert_eq!(Some((1, ValueType::Tombstone)), tree.latest_seqno("b")?);
+    assert_eq!(Some((0, ValueType::Value)), tree.latest_seqno("a")?);
+
+    std::thread::sleep(Duration::from_millis(150));
+
+    assert!(!tree.contains_key("c", None)?);
+
+    // NOTE: The expiry timestamp is not part of the returned value
+    let items = tree
+        .iter(None, None)
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(
+        vec![("a".as_bytes().into(), "live".as_bytes().into())],
+        items
+    );
+    assert_eq!(1, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_ttl_survives_reopen() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+        tree.insert_with_ttl("a", "live", 0, LONG_TTL);
+        tree.insert_with_ttl("b", "expired", 1, Duration::ZERO);
+        tree.flush_active_memtable(0)?;
+    }
+
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(Some("live".as_bytes().into()), tree.get("a", None)?);
+    assert_eq!(None, tree.get("b", None)?);
+    assert_eq!(
+        Some(("a".as_bytes().into(), "live".as_bytes().into())),
+        tree.first_key_value(None, None)?,
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_ttl_does_not_resurrect_older_versions() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "old", 0);
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 0)?;
+
+    tree.insert_with_ttl("a", "new", 1, Duration::ZERO);
+    assert_eq!(None, tree.get("a", None)?);
+    assert_eq!(Some("old".as_bytes().into()), tree.get("a", Some(1))?);
+
+    tree.flush_active_memtable(0)?;
+    assert_eq!(None, tree.get("a", None)?);
+
+    // NOTE: The old version is still needed by snapshots, so the
+    // expired value is kept as tombstone, even in the last level
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(None, tree.get("a", None)?);
+    assert_eq!(Some("old".as_bytes().into()), tree.get("a", Some(1))?);
+
+    let versions = tree
+        .debug_merge_iter()
+        .map(|item| item.map(|(_, item)| (item.key.seqno, item.key.value_type)))
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(
+        vec![(1, ValueType::Tombstone), (0, ValueType::Value)],
+        versions
+    );
+
+    // NOTE: Once no snapshot needs the old version, everything is dropped
+    tree.major_compact(u64::MAX, 2)?;
+    assert_eq!(None, tree.get("a", None)?);
+    assert_eq!(0, tree.debug_merge_iter().count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_ttl_compaction_tombstones_expired_values() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert_with_ttl("a", "live", 0, LONG_TTL);
+    tree.insert_with_ttl("b", "expired", 1, Duration::ZERO);
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Flushing does not touch expiring values
+    let versions = tree
+        .debug_merge_iter()
+        .map(|item| item.map(|(_, item)| item.key.value_type))
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(
+        vec![ValueType::ExpiringValue, ValueType::ExpiringValue],
+        versions
+    );
+
+    tree.major_compact(u64::MAX, 2)?;
+
+    // NOTE: The tombstone is the only version, so it is dropped in the last level
+    let versions = tree
+        .debug_merge_iter()
+        .map(|item| item.map(|(_, item)| (item.key.user_key, item.key.value_type)))
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(
+        vec![("a".as_bytes().into(), ValueType::ExpiringValue)],
+        versions
+    );
+    assert_eq!(Some("live".as_bytes().into()), tree.get("a", None)?);
+
+    Ok(())
+}
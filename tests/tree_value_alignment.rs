@@ -0,0 +1,115 @@
+use lsm_tree::{
+    segment::{block::offset::BlockOffset, value_block::ValueBlock},
+    AbstractTree, Config, Tree,
+};
+use std::path::Path;
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+fn first_block_alignments(tree: &Tree) -> lsm_tree::Result<Vec<u32>> {
+    let levels = tree.levels.read().expect("lock is poisoned");
+
+    levels
+        .iter()
+        .map(|segment| {
+            let mut file = std::fs::File::open(&segment.path)?;
+            let block = ValueBlock::from_file(&mut file, BlockOffset(0))?;
+            Ok(block.value_alignment)
+        })
+        .collect()
+}
+
+fn assert_items(tree: &Tree, round_count: u64) -> lsm_tree::Result<()> {
+    for x in 0..ITEM_COUNT {
+        let value = tree.get(x.to_be_bytes(), None)?.expect("should exist");
+        assert_eq!(&*value, (x * round_count).to_le_bytes());
+    }
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+    assert_eq!(0, tree.verify()?);
+
+    Ok(())
+}
+
+fn write_round(path: &Path, alignment: u32, round: u64) -> lsm_tree::Result<Tree> {
+    let tree = Config::new(path)
+        .data_block_size(1_024)
+        .value_alignment(alignment)
+        .open()?;
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(
+            x.to_be_bytes(),
+            (x * round).to_le_bytes(),
+            round * ITEM_COUNT + x,
+        );
+    }
+    tree.flush_active_memtable(0)?;
+
+    Ok(tree)
+}
+
+#[test]
+fn tree_value_alignment_mixed() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = write_round(folder.path(), 8, 1)?;
+        assert_eq!(vec![8], first_block_alignments(&tree)?);
+        assert_items(&tree, 1)?;
+    }
+
+    {
+        // NOTE: Segments of different alignments are read correctly
+        let tree = write_round(folder.path(), 1, 2)?;
+
+        let mut alignments = first_block_alignments(&tree)?;
+        alignments.sort_unstable();
+        assert_eq!(vec![1, 8], alignments);
+
+        assert_items(&tree, 2)?;
+    }
+
+    {
+        let tree = Config::new(&folder).value_alignment(64).open()?;
+        tree.major_compact(u64::MAX, u64::MAX)?;
+
+        assert_eq!(vec![64], first_block_alignments(&tree)?);
+        assert_items(&tree, 2)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_value_alignment_blob_tree() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .value_alignment(16)
+        .blob_file_separation_threshold(1_024)
+        .open_as_blob_tree()?;
+
+    for x in 0..ITEM_COUNT {
+        let value = if x % 2 == 0 {
+            vec![1; 2_048]
+        } else {
+            vec![2; 16]
+        };
+        tree.insert(x.to_be_bytes(), value, x);
+    }
+    tree.flush_active_memtable(0)?;
+
+    for x in 0..ITEM_COUNT {
+        let value = tree.get(x.to_be_bytes(), None)?.expect("should exist");
+        assert_eq!(if x % 2 == 0 { 2_048 } else { 16 }, value.len());
+    }
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "invalid value alignment")]
+fn tree_value_alignment_invalid() {
+    let _ = Config::new("").value_alignment(12);
+}
@@ -0,0 +1,75 @@
+use lsm_tree::{AbstractTree, Config, Error, SequenceNumberCounter};
+use test_log::test;
+
+const VALUE: &[u8] = b"this value is going to be corrupted";
+
+fn corrupt_value(folder: &std::path::Path) -> lsm_tree::Result<()> {
+    let segment_path = std::fs::read_dir(folder.join("segments"))?
+        .next()
+        .expect("should have segment")?
+        .path();
+
+    let mut bytes = std::fs::read(&segment_path)?;
+
+    let offset = bytes
+        .windows(VALUE.len())
+        .position(|x| x == VALUE)
+        .expect("value should exist");
+
+    if let Some(byte) = bytes.get_mut(offset) {
+        *byte = b'T';
+    }
+
+    std::fs::write(&segment_path, bytes)?;
+
+    Ok(())
+}
+
+#[test]
+fn tree_verify_checksums() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder).open()?;
+        tree.insert("a", VALUE, seqno.next());
+        tree.flush_active_memtable(0)?;
+    }
+
+    corrupt_value(folder.path())?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        let segment_id = {
+            let levels = tree.levels.read().expect("lock is poisoned");
+            let segment = levels.iter().next().expect("should have segment");
+
+            assert!(matches!(
+                segment.verify(),
+                Err(Error::ChecksumMismatch { segment_id, block_offset: 0 }) if segment_id == segment.id()
+            ));
+
+            segment.id()
+        };
+
+        assert!(matches!(
+            tree.get("a", None),
+            Err(Error::ChecksumMismatch { segment_id: x, block_offset: 0 }) if x == segment_id
+        ));
+        assert!(matches!(
+            tree.iter(None, None).next(),
+            Some(Err(Error::ChecksumMismatch { .. }))
+        ));
+        assert_eq!(1, tree.verify()?);
+    }
+
+    {
+        let tree = Config::new(&folder).verify_checksums(false).open()?;
+
+        let value = tree.get("a", None)?.expect("should exist");
+        assert_ne!(VALUE, &*value);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use lsm_tree::{AbstractTree, Cache, Config, SequenceNumberCounter};
+use std::{path::Path, sync::Arc};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+fn write(path: &Path) -> lsm_tree::Result<()> {
+    let tree = Config::new(path).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a".repeat(100), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    for x in (0..ITEM_COUNT).step_by(2) {
+        tree.insert(x.to_be_bytes(), "b".repeat(100), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    Ok(())
+}
+
+#[test]
+fn tree_warm_cache_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    write(folder.path())?;
+
+    let cache = Arc::new(Cache::with_capacity_bytes(10 * 1_024 * 1_024));
+    let tree = Config::new(&folder).use_cache(cache.clone()).open()?;
+    assert!(cache.is_empty());
+
+    let range = 100u64.to_be_bytes()..200u64.to_be_bytes();
+
+    let warmup = tree.warm_cache(range.clone())?;
+    assert!(warmup.blocks > 0);
+    assert!(warmup.bytes > 0);
+
+    // NOTE: Only the range's blocks are loaded
+    let all = tree.warm_cache::<&[u8], _>(..)?;
+    assert!(warmup.blocks < all.blocks);
+
+    let cache = Arc::new(Cache::with_capacity_bytes(10 * 1_024 * 1_024));
+    let tree = Config::new(&folder).use_cache(cache.clone()).open()?;
+    tree.warm_cache(range.clone())?;
+    let cached_blocks = cache.len();
+
+    // NOTE: Reading the range does not need to load any more blocks
+    assert_eq!(100, tree.range(range, None, None).count());
+    assert_eq!(cached_blocks, cache.len());
+
+    Ok(())
+}
+
+#[test]
+fn tree_warm_cache_capacity() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    write(folder.path())?;
+
+    let cache = Arc::new(Cache::with_capacity_bytes(20_000));
+    let tree = Config::new(&folder).use_cache(cache.clone()).open()?;
+
+    let warmup = tree.warm_cache::<&[u8], _>(..)?;
+    assert!(warmup.bytes >= 20_000);
+    assert!(warmup.bytes < 30_000, "{warmup:?}");
+
+    let warmup = tree.warm_cache(1_000_000u64.to_be_bytes()..)?;
+    assert_eq!(0, warmup.blocks);
+
+    Ok(())
+}
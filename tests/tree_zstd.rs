@@ -0,0 +1,116 @@
+#![cfg(feature = "zstd")]
+
+use lsm_tree::{AbstractTree, CompressionType, Config, SequenceNumberCounter};
+use std::path::Path;
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+fn value(x: u64) -> Vec<u8> {
+    format!("{x:0>4}").repeat(100).into_bytes()
+}
+
+fn write<T: AbstractTree>(tree: &T, seqno: &SequenceNumberCounter) -> lsm_tree::Result<()> {
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), value(x), seqno.next());
+    }
+
+    let (id, memtable) = tree.rotate_memtable().expect("should have sealed memtable");
+    let segment = tree
+        .flush_memtable(id, &memtable, 0)?
+        .expect("should flush");
+    tree.register_segments(&[segment])?;
+
+    Ok(())
+}
+
+fn check<T: AbstractTree>(tree: &T) -> lsm_tree::Result<()> {
+    for x in 0..ITEM_COUNT {
+        let item = tree.get(x.to_be_bytes(), None)?.expect("should exist");
+        assert_eq!(&*item, value(x));
+    }
+    assert_eq!(ITEM_COUNT as usize, tree.iter(None, None).rev().count());
+    Ok(())
+}
+
+fn disk_space(path: &Path, compression: CompressionType) -> lsm_tree::Result<u64> {
+    let tree = Config::new(path).compression(compression).open()?;
+    write(&tree, &SequenceNumberCounter::default())?;
+    check(&tree)?;
+    Ok(tree.disk_space())
+}
+
+#[test]
+fn tree_zstd() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let uncompressed_size = disk_space(folder.path(), CompressionType::None)?;
+
+    let folder = tempfile::tempdir()?;
+    let compressed_size = disk_space(folder.path(), CompressionType::Zstd(9))?;
+    assert!(
+        compressed_size * 2 < uncompressed_size,
+        "{compressed_size} vs {uncompressed_size}",
+    );
+
+    // NOTE: The level is stored in the block headers, so reading needs no config
+    let tree = Config::new(&folder).open()?;
+    check(&tree)?;
+
+    tree.major_compact(u64::MAX, 0)?;
+    check(&tree)?;
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_zstd() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder)
+            .compression(CompressionType::Zstd(3))
+            .blob_compression(CompressionType::Zstd(-1))
+            .blob_file_separation_threshold(100)
+            .open_as_blob_tree()?;
+
+        write(&tree, &SequenceNumberCounter::default())?;
+        assert!(tree.blob_file_count() > 0);
+        check(&tree)?;
+    }
+
+    let tree = Config::new(&folder)
+        .blob_compression(CompressionType::Zstd(-1))
+        .open_as_blob_tree()?;
+    check(&tree)?;
+
+    Ok(())
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn tree_zstd_mixed_with_lz4() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder)
+            .compression(CompressionType::Lz4)
+            .open()?;
+        write(&tree, &seqno)?;
+    }
+
+    // NOTE: Lz4 segments stay readable after switching to zstd
+    let tree = Config::new(&folder)
+        .compression(CompressionType::Zstd(9))
+        .open()?;
+    check(&tree)?;
+
+    write(&tree, &seqno)?;
+    assert_eq!(2, tree.segment_count());
+    check(&tree)?;
+
+    tree.major_compact(u64::MAX, 0)?;
+    check(&tree)?;
+
+    Ok(())
+}